@@ -18,6 +18,7 @@ use crate::{address::Address, types::*};
 // https://github.com/bitcoin-core/secp256k1/blob/1e5d50fa93d71d751b95eec6a80f6732879a0071/include/secp256k1.h#L180-L181
 const SECP256K1_TAG_PUBKEY_EVEN: u8 = 0x02;
 const SECP256K1_TAG_PUBKEY_ODD: u8 = 0x03;
+const SECP256K1_TAG_PUBKEY_UNCOMPRESSED: u8 = 0x04;
 
 /// Type to be parsed from a bytes array
 pub(crate) trait Parsable: Sized {
@@ -469,6 +470,78 @@ pub(crate) fn extract_address_hash_scriptsig(input_script: &[u8]) -> Result<Addr
     })
 }
 
+/// Parses a push-only scriptSig into the data elements it pushes onto the stack.
+/// Fails with `UnsupportedInputFormat` if the script contains any other opcode.
+pub(crate) fn extract_script_sig_pushes(input_script: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+    let mut parser = BytesParser::new(input_script);
+    let mut pushes = Vec::new();
+
+    while parser.position < parser.raw_bytes.len() {
+        let opcode = parser.parse::<u8>()?;
+        let push_size = match opcode {
+            0x00 => 0,
+            0x01..=0x4b => opcode as usize,
+            x if x == OpCode::OpPushData1 as u8 => parser.parse::<u8>()? as usize,
+            x if x == OpCode::OpPushData2 as u8 => parser.parse::<u16>()? as usize,
+            x if x == OpCode::OpPushData4 as u8 => parser.parse::<u32>()? as usize,
+            x if x == OpCode::Op1Negate as u8 || (OpCode::Op1 as u8..=OpCode::Op16 as u8).contains(&x) => {
+                // small integers are pushed by the opcode itself
+                pushes.push(vec![x]);
+                continue;
+            }
+            _ => return Err(Error::UnsupportedInputFormat),
+        };
+        pushes.push(parser.read(push_size)?);
+    }
+
+    Ok(pushes)
+}
+
+fn is_public_key(bytes: &[u8]) -> bool {
+    match bytes.len() {
+        33 => bytes[0] == SECP256K1_TAG_PUBKEY_EVEN || bytes[0] == SECP256K1_TAG_PUBKEY_ODD,
+        65 => bytes[0] == SECP256K1_TAG_PUBKEY_UNCOMPRESSED,
+        _ => false,
+    }
+}
+
+/// Returns every address that an input with the given scriptSig and witness could
+/// be spending from. Since the previous output is not known, all plausible script
+/// types are returned: P2PKH, P2SH (including multisig and nested SegWit), P2WPKH
+/// and P2WSH.
+pub(crate) fn extract_address_hashes(input_script: &[u8], witness: &[Vec<u8>]) -> Result<Vec<Address>, Error> {
+    let pushes = extract_script_sig_pushes(input_script)?;
+    let mut addresses = Vec::new();
+
+    if let Some(witness_script) = witness.last() {
+        // https://github.com/bitcoin/bips/blob/master/bip-0141.mediawiki#p2wpkh
+        if witness.len() == 2 && witness_script.len() == 33 && is_public_key(witness_script) {
+            addresses.push(Address::P2WPKHv0(H160::from_slice(
+                &Hash160::hash(witness_script).to_vec(),
+            )));
+        }
+        // https://github.com/bitcoin/bips/blob/master/bip-0141.mediawiki#p2wsh
+        let mut hasher = Sha256::default();
+        hasher.input(witness_script);
+        addresses.push(Address::P2WSHv0(H256::from_slice(&hasher.result()[..])));
+    } else if let [_sig, public_key] = &pushes[..] {
+        if is_public_key(public_key) {
+            addresses.push(Address::P2PKH(H160::from_slice(&Hash160::hash(public_key).to_vec())));
+        }
+    }
+
+    // the last push is the redeem script if the input spends a P2SH output,
+    // this also covers P2WPKH-P2SH and P2WSH-P2SH where it is the witness program
+    if let Some(redeem_script) = pushes.last() {
+        addresses.push(Address::P2SH(H160::from_slice(&Hash160::hash(redeem_script).to_vec())));
+    }
+
+    if addresses.is_empty() {
+        return Err(Error::UnsupportedInputFormat);
+    }
+    Ok(addresses)
+}
+
 pub(crate) fn extract_op_return_data(output_script: &[u8]) -> Result<Vec<u8>, Error> {
     if *output_script.get(0).ok_or(Error::EndOfFile)? != OpCode::OpReturn as u8 {
         return Err(Error::MalformedOpReturnOutput);
@@ -801,6 +874,163 @@ pub(crate) mod tests {
         assert_eq!(actual, expected);
     }
 
+    fn sample_compressed_public_key() -> Vec<u8> {
+        hex::decode("02a8316d000ee36a70543b2599ee792c4208b540f813891b2fde325fbbdd98a545").unwrap()
+    }
+
+    // 2-of-3 multisig script using `sample_compressed_public_key` as the first key
+    fn sample_multisig_script() -> Vec<u8> {
+        hex::decode("522102a8316d000ee36a70543b2599ee792c4208b540f813891b2fde325fbbdd98a54521028bdceb0df9a498b304afd9aa54dab3b6f76d303998f1a5e11af2bba0e1f8c3fa21034172c29d3da8279f71adda48db8281d65b794e73cf04ea91fac4293030f0fe9153ae").unwrap()
+    }
+
+    fn p2pkh_address(hash: &str) -> Address {
+        Address::P2PKH(H160::from_slice(&hex::decode(hash).unwrap()))
+    }
+
+    fn p2sh_address(hash: &str) -> Address {
+        Address::P2SH(H160::from_slice(&hex::decode(hash).unwrap()))
+    }
+
+    fn p2wpkh_address(hash: &str) -> Address {
+        Address::P2WPKHv0(H160::from_slice(&hex::decode(hash).unwrap()))
+    }
+
+    fn p2wsh_address(hash: &str) -> Address {
+        Address::P2WSHv0(H256::from_slice(&hex::decode(hash).unwrap()))
+    }
+
+    #[test]
+    fn test_extract_script_sig_pushes() {
+        // OP_0 <1, 2> OP_PUSHDATA1 <3> OP_PUSHDATA2 <4> OP_1
+        let script_sig = vec![0x00, 0x02, 1, 2, 0x4c, 0x01, 3, 0x4d, 0x01, 0x00, 4, 0x51];
+
+        assert_eq!(
+            extract_script_sig_pushes(&script_sig).unwrap(),
+            vec![vec![], vec![1, 2], vec![3], vec![4], vec![0x51]]
+        );
+    }
+
+    #[test]
+    fn test_extract_script_sig_pushes_fails_with_non_push_opcode() {
+        assert!(matches!(
+            extract_script_sig_pushes(&[OpCode::OpDup as u8]),
+            Err(Error::UnsupportedInputFormat)
+        ));
+        assert!(matches!(extract_script_sig_pushes(&[0x02, 1]), Err(Error::EndOfFile)));
+    }
+
+    #[test]
+    fn test_extract_address_hashes_p2pkh_compressed() {
+        let raw_tx = "0100000001c15041a06deb6b3818b022fac558da4ce2097f0860c8f642105bbad9d29be02a010000006c493046022100cfd2a2d332b29adce119c55a9fadd3c073332024b7e272513e51623ca15993480221009b482d7f7b4d479aff62bdcdaea54667737d56f8d4d63dd03ec3ef651ed9a25401210325f8b039a11861659c9bf03f43fc4ea055f3a71cd60c7b1fd474ab578f9977faffffffff0290d94000000000001976a9148ed243a7be26080a1a8cf96b53270665f1b8dd2388ac4083086b000000001976a9147e7d94d0ddc21d83bfbcfc7798e4547edf0832aa88ac00000000";
+        let transaction = parse_transaction(&hex::decode(&raw_tx).unwrap()).unwrap();
+
+        let addresses = transaction.inputs[0].extract_addresses().unwrap();
+        assert!(addresses.contains(&p2pkh_address("7e7d94d0ddc21d83bfbcfc7798e4547edf0832aa")));
+    }
+
+    #[test]
+    fn test_extract_address_hashes_p2pkh_uncompressed() {
+        let public_key = hex::decode("0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8").unwrap();
+        let script_sig = [vec![0x47], vec![1; 71], vec![0x41], public_key].concat();
+
+        let addresses = extract_address_hashes(&script_sig, &[]).unwrap();
+        assert!(addresses.contains(&p2pkh_address("91b24bf9f5288532960ac687abb035127b1d28a5")));
+    }
+
+    #[test]
+    fn test_extract_address_hashes_p2sh_multisig() {
+        // OP_0 <sig> <sig> OP_PUSHDATA1 <redeem script>
+        let script_sig = [
+            vec![0x00, 0x47],
+            vec![1; 71],
+            vec![0x47],
+            vec![1; 71],
+            vec![0x4c, 0x69],
+            sample_multisig_script(),
+        ]
+        .concat();
+
+        let addresses = extract_address_hashes(&script_sig, &[]).unwrap();
+        assert_eq!(
+            addresses,
+            vec![p2sh_address("e2af687e4eb76c608506311877d3aa1d35b7b79c")]
+        );
+    }
+
+    #[test]
+    fn test_extract_address_hashes_p2sh_multisig_from_transaction() {
+        let raw_tx = "0100000001c8cc2b56525e734ff63a13bc6ad06a9e5664df8c67632253a8e36017aee3ee40000000009000483045022100ad0851c69dd756b45190b5a8e97cb4ac3c2b0fa2f2aae23aed6ca97ab33bf88302200b248593abc1259512793e7dea61036c601775ebb23640a0120b0dba2c34b79001455141042f90074d7a5bf30c72cf3a8dfd1381bdbd30407010e878f3a11269d5f74a58788505cdca22ea6eab7cfb40dc0e07aba200424ab0d79122a653ad0c7ec9896bdf51aefeffffff0120f40e00000000001976a9141d30342095961d951d306845ef98ac08474b36a088aca7270400";
+        let transaction = parse_transaction(&hex::decode(&raw_tx).unwrap()).unwrap();
+
+        let addresses = transaction.inputs[0].extract_addresses().unwrap();
+        assert!(addresses.contains(&p2sh_address("e9c3dd0c07aac76179ebc76a6c78d4d67c6c160a")));
+    }
+
+    #[test]
+    fn test_extract_address_hashes_p2wpkh() {
+        let witness = vec![vec![1; 71], sample_compressed_public_key()];
+
+        let addresses = extract_address_hashes(&[], &witness).unwrap();
+        assert!(addresses.contains(&p2wpkh_address("500a15c28ee2774ae6120758bbe8e3611450eb09")));
+        assert!(!addresses.iter().any(|address| matches!(address, Address::P2SH(_))));
+    }
+
+    #[test]
+    fn test_extract_address_hashes_p2wpkh_p2sh() {
+        let script_sig = hex::decode("160014500a15c28ee2774ae6120758bbe8e3611450eb09").unwrap();
+        let witness = vec![vec![1; 71], sample_compressed_public_key()];
+
+        let addresses = extract_address_hashes(&script_sig, &witness).unwrap();
+        // both the nested address and the inner witness program are returned
+        assert!(addresses.contains(&p2sh_address("c3c94d2db9598f652b7c7bbee0c810b027cd3eba")));
+        assert!(addresses.contains(&p2wpkh_address("500a15c28ee2774ae6120758bbe8e3611450eb09")));
+    }
+
+    #[test]
+    fn test_extract_address_hashes_p2wpkh_p2sh_testnet() {
+        // bitcoin-cli -testnet getrawtransaction "3453e52ebab8ac96159d6b19114b492a05cce05a8fdfdaf5dea266ac10601ce4" 0
+        let raw_tx = "0200000000010108ce8e8943edbbf09d070bb893e09c0de12c0cf3704fe8a9b0f8b8d1a4a7a4760000000017160014473ca3f4d726ce9c21af7cdc3fcc13264f681b04feffffff02b377413f0000000017a914fe5183ccb89d98beaa6908c7cf1bd109029482cf87142e1a00000000001976a914d0a46d39dafa3012c2a7ed4d82d644b428e4586b88ac02473044022069484377c6627ccca566d4c4ac2cb84d1b0662f5ffbd384815c5e98b072759fc022061de3b77b4543ef43bb969d3f97fbbbdcddc008438720e7026181d99c455b2410121034172c29d3da8279f71adda48db8281d65b794e73cf04ea91fac4293030f0fe91a3ee1c00";
+        let transaction = parse_transaction(&hex::decode(&raw_tx).unwrap()).unwrap();
+
+        let addresses = transaction.inputs[0].extract_addresses().unwrap();
+        // 2MsqorfMrsvXiVM8pD9bPWxGnccSWsj16XE
+        assert!(addresses.contains(&p2sh_address("068a6a2ec6be7d6e7aac1657445154c52db0cef8")));
+        assert!(addresses.contains(&p2wpkh_address("473ca3f4d726ce9c21af7cdc3fcc13264f681b04")));
+    }
+
+    #[test]
+    fn test_extract_address_hashes_p2wsh() {
+        let witness = vec![vec![], vec![1; 71], vec![1; 71], sample_multisig_script()];
+
+        let addresses = extract_address_hashes(&[], &witness).unwrap();
+        assert_eq!(
+            addresses,
+            vec![p2wsh_address(
+                "7b99879d38df888e1a5b3b912d0a1a4594428786ad2a2e190f337a34603ef305"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_extract_address_hashes_p2wsh_p2sh() {
+        let script_sig = hex::decode("2200207b99879d38df888e1a5b3b912d0a1a4594428786ad2a2e190f337a34603ef305").unwrap();
+        let witness = vec![vec![], vec![1; 71], vec![1; 71], sample_multisig_script()];
+
+        let addresses = extract_address_hashes(&script_sig, &witness).unwrap();
+        assert!(addresses.contains(&p2sh_address("5adf2b0012028e9a49c2b687bf9291531a2d6a2a")));
+        assert!(addresses.contains(&p2wsh_address(
+            "7b99879d38df888e1a5b3b912d0a1a4594428786ad2a2e190f337a34603ef305"
+        )));
+    }
+
+    #[test]
+    fn test_extract_address_hashes_fails_with_empty_input() {
+        assert!(matches!(
+            extract_address_hashes(&[], &[]),
+            Err(Error::UnsupportedInputFormat)
+        ));
+    }
+
     /*
     #[test]
     fn test_extract_address_invalid_p2pkh_fails() {
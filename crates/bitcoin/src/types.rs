@@ -6,7 +6,7 @@ use mocktopus::macros::mockable;
 use crate::{
     formatter::{Formattable, TryFormattable},
     merkle::{MerkleProof, MerkleTree},
-    parser::{extract_address_hash_scriptsig, extract_address_hash_witness},
+    parser::{extract_address_hash_scriptsig, extract_address_hash_witness, extract_address_hashes},
     utils::{log2, reverse_endianness, sha256d_le},
     Address, Error, PublicKey, Script,
 };
@@ -240,6 +240,7 @@ pub const MAX_OPRETURN_SIZE: usize = 83;
 /// Bitcoin Basic Block Headers
 // TODO: Figure out how to set a pointer to the ChainIndex mapping instead
 #[derive(Encode, Decode, Default, Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlockHeader {
     pub merkle_root: H256Le,
     pub target: U256,
@@ -283,6 +284,12 @@ impl TransactionInput {
             extract_address_hash_witness,
         )
     }
+
+    /// Returns every address this input could be spending from, including
+    /// the nested (P2SH) form of SegWit spends.
+    pub fn extract_addresses(&self) -> Result<Vec<Address>, Error> {
+        extract_address_hashes(&self.script, &self.witness)
+    }
 }
 
 /// Bitcoin transaction output
@@ -624,6 +631,33 @@ impl sp_std::fmt::LowerHex for H256Le {
     }
 }
 
+/// Serialized as big endian hex, the order used by block explorers and bitcoind
+#[cfg(feature = "std")]
+impl serde::Serialize for H256Le {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut slice = [0u8; 2 + 2 * 32];
+        impl_serde::serialize::serialize_raw(&mut slice, &self.to_bytes_be(), serializer)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'de> serde::Deserialize<'de> for H256Le {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let mut bytes = [0u8; 32];
+        impl_serde::serialize::deserialize_check_len(
+            deserializer,
+            impl_serde::serialize::ExpectedLen::Exact(&mut bytes),
+        )?;
+        Ok(H256Le::from_bytes_be(&bytes))
+    }
+}
+
 impl PartialEq<H256Le> for H256 {
     fn eq(&self, other: &H256Le) -> bool {
         let bytes_le = H256Le::from_bytes_be(self.as_bytes());
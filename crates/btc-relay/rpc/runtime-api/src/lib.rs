@@ -0,0 +1,49 @@
+//! Runtime API definition for the BTC-Relay Module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use frame_support::dispatch::{DispatchError, DispatchResult};
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+    pub trait BtcRelayApi<H256Le, H256, RichBlockHeader, BlockNumber, DeploymentState> where
+        H256Le: Codec,
+        H256: Codec,
+        RichBlockHeader: Codec,
+        BlockNumber: Codec,
+        DeploymentState: Codec,
+    {
+        /// Get the hash of the best block in the main chain
+        fn get_best_block() -> H256Le;
+
+        /// Get the height of the best block in the main chain
+        fn get_best_block_height() -> u32;
+
+        /// Get a stored block header by its hash
+        fn get_block_header(block_hash: H256Le) -> Result<RichBlockHeader, DispatchError>;
+
+        /// Get a main chain block header by its height
+        fn get_block_header_at_height(block_height: u32) -> Result<RichBlockHeader, DispatchError>;
+
+        /// Get the tip hash and height of every fork
+        fn get_fork_tips() -> Vec<(H256Le, u32)>;
+
+        /// Get the number of Bitcoin confirmations required for stable transactions
+        fn get_bitcoin_confirmations() -> u32;
+
+        /// Get the number of Parachain confirmations required for stable transactions
+        fn get_parachain_confirmations() -> BlockNumber;
+
+        /// Check that a transaction is included in the main chain, without submitting an extrinsic
+        fn verify_transaction_inclusion(
+            tx_id: H256Le, raw_merkle_proof: Vec<u8>, confirmations: Option<u32>
+        ) -> DispatchResult;
+
+        /// Get the id of the request which was executed with the given transaction output, if any
+        fn get_consumed_output(tx_id: H256Le, vout: u32) -> Option<H256>;
+
+        /// Get the state of a soft fork deployment signalled with version bits
+        fn get_deployment_state(deployment_id: u32) -> Result<DeploymentState, DispatchError>;
+    }
+}
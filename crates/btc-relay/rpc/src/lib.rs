@@ -0,0 +1,222 @@
+//! RPC interface for the BTC-Relay Module.
+
+pub use self::gen_client::Client as BtcRelayClient;
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result as JsonRpcResult};
+use jsonrpc_derive::rpc;
+pub use module_btc_relay_rpc_runtime_api::BtcRelayApi as BtcRelayRuntimeApi;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT, DispatchError};
+use std::sync::Arc;
+
+#[rpc]
+pub trait BtcRelayApi<BlockHash, H256Le, H256, RichBlockHeader, BlockNumber, DeploymentState> {
+    #[rpc(name = "btcRelay_getBestBlock")]
+    fn get_best_block(&self, at: Option<BlockHash>) -> JsonRpcResult<H256Le>;
+
+    #[rpc(name = "btcRelay_getBestBlockHeight")]
+    fn get_best_block_height(&self, at: Option<BlockHash>) -> JsonRpcResult<u32>;
+
+    #[rpc(name = "btcRelay_getBlockHeader")]
+    fn get_block_header(&self, block_hash: H256Le, at: Option<BlockHash>) -> JsonRpcResult<RichBlockHeader>;
+
+    #[rpc(name = "btcRelay_getBlockHeaderAtHeight")]
+    fn get_block_header_at_height(&self, block_height: u32, at: Option<BlockHash>) -> JsonRpcResult<RichBlockHeader>;
+
+    #[rpc(name = "btcRelay_getForkTips")]
+    fn get_fork_tips(&self, at: Option<BlockHash>) -> JsonRpcResult<Vec<(H256Le, u32)>>;
+
+    #[rpc(name = "btcRelay_getBitcoinConfirmations")]
+    fn get_bitcoin_confirmations(&self, at: Option<BlockHash>) -> JsonRpcResult<u32>;
+
+    #[rpc(name = "btcRelay_getParachainConfirmations")]
+    fn get_parachain_confirmations(&self, at: Option<BlockHash>) -> JsonRpcResult<BlockNumber>;
+
+    #[rpc(name = "btcRelay_verifyTransactionInclusion")]
+    fn verify_transaction_inclusion(
+        &self,
+        tx_id: H256Le,
+        raw_merkle_proof: Vec<u8>,
+        confirmations: Option<u32>,
+        at: Option<BlockHash>,
+    ) -> JsonRpcResult<()>;
+
+    #[rpc(name = "btcRelay_getConsumedOutput")]
+    fn get_consumed_output(&self, tx_id: H256Le, vout: u32, at: Option<BlockHash>) -> JsonRpcResult<Option<H256>>;
+
+    #[rpc(name = "btcRelay_getDeploymentState")]
+    fn get_deployment_state(&self, deployment_id: u32, at: Option<BlockHash>) -> JsonRpcResult<DeploymentState>;
+}
+
+/// A struct that implements the [`BtcRelayApi`].
+pub struct BtcRelay<C, B> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> BtcRelay<C, B> {
+    /// Create new `BtcRelay` with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        BtcRelay {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+pub enum Error {
+    RuntimeError,
+}
+
+impl From<Error> for i64 {
+    fn from(e: Error) -> i64 {
+        match e {
+            Error::RuntimeError => 1,
+        }
+    }
+}
+
+fn runtime_error<E: std::fmt::Debug>(e: E, msg: &str) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(Error::RuntimeError.into()),
+        message: msg.into(),
+        data: Some(format!("{:?}", e).into()),
+    }
+}
+
+fn handle_response<T, E: std::fmt::Debug>(result: Result<T, E>, msg: &str) -> JsonRpcResult<T> {
+    result.map_err(|e| runtime_error(e, msg))
+}
+
+fn handle_dispatch_response<T, E: std::fmt::Debug>(
+    result: Result<Result<T, DispatchError>, E>,
+    msg: &str,
+) -> JsonRpcResult<T> {
+    handle_response(result, msg)?.map_err(|e| runtime_error(e, msg))
+}
+
+impl<C, Block, H256Le, H256, RichBlockHeader, BlockNumber, DeploymentState>
+    BtcRelayApi<<Block as BlockT>::Hash, H256Le, H256, RichBlockHeader, BlockNumber, DeploymentState>
+    for BtcRelay<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    C::Api: BtcRelayRuntimeApi<Block, H256Le, H256, RichBlockHeader, BlockNumber, DeploymentState>,
+    H256Le: Codec,
+    H256: Codec,
+    RichBlockHeader: Codec,
+    BlockNumber: Codec,
+    DeploymentState: Codec,
+{
+    fn get_best_block(&self, at: Option<<Block as BlockT>::Hash>) -> JsonRpcResult<H256Le> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        handle_response(api.get_best_block(&at), "Unable to get best block.")
+    }
+
+    fn get_best_block_height(&self, at: Option<<Block as BlockT>::Hash>) -> JsonRpcResult<u32> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        handle_response(api.get_best_block_height(&at), "Unable to get best block height.")
+    }
+
+    fn get_block_header(
+        &self,
+        block_hash: H256Le,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> JsonRpcResult<RichBlockHeader> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        handle_dispatch_response(api.get_block_header(&at, block_hash), "Unable to get block header.")
+    }
+
+    fn get_block_header_at_height(
+        &self,
+        block_height: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> JsonRpcResult<RichBlockHeader> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        handle_dispatch_response(
+            api.get_block_header_at_height(&at, block_height),
+            "Unable to get block header.",
+        )
+    }
+
+    fn get_fork_tips(&self, at: Option<<Block as BlockT>::Hash>) -> JsonRpcResult<Vec<(H256Le, u32)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        handle_response(api.get_fork_tips(&at), "Unable to get fork tips.")
+    }
+
+    fn get_bitcoin_confirmations(&self, at: Option<<Block as BlockT>::Hash>) -> JsonRpcResult<u32> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        handle_response(
+            api.get_bitcoin_confirmations(&at),
+            "Unable to get bitcoin confirmations.",
+        )
+    }
+
+    fn get_parachain_confirmations(&self, at: Option<<Block as BlockT>::Hash>) -> JsonRpcResult<BlockNumber> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        handle_response(
+            api.get_parachain_confirmations(&at),
+            "Unable to get parachain confirmations.",
+        )
+    }
+
+    fn verify_transaction_inclusion(
+        &self,
+        tx_id: H256Le,
+        raw_merkle_proof: Vec<u8>,
+        confirmations: Option<u32>,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> JsonRpcResult<()> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        handle_dispatch_response(
+            api.verify_transaction_inclusion(&at, tx_id, raw_merkle_proof, confirmations),
+            "Transaction inclusion could not be verified.",
+        )
+    }
+
+    fn get_consumed_output(
+        &self,
+        tx_id: H256Le,
+        vout: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> JsonRpcResult<Option<H256>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        handle_response(
+            api.get_consumed_output(&at, tx_id, vout),
+            "Unable to get consumed output.",
+        )
+    }
+
+    fn get_deployment_state(
+        &self,
+        deployment_id: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> JsonRpcResult<DeploymentState> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        handle_dispatch_response(
+            api.get_deployment_state(&at, deployment_id),
+            "Unable to get deployment state.",
+        )
+    }
+}
@@ -22,7 +22,8 @@ fn mine_genesis<T: Config>(account_id: T::AccountId, address: &BtcAddress, heigh
         .unwrap();
 
     let block_header = RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap();
-    BtcRelay::<T>::initialize(account_id, block_header, height).unwrap();
+    BtcRelay::<T>::insert_checkpoint(height, block.header.hash().unwrap()).unwrap();
+    BtcRelay::<T>::initialize(account_id, vec![block_header], height).unwrap();
 
     block
 }
@@ -72,6 +73,20 @@ fn mine_block_with_one_tx<T: Config>(
     (block, transaction)
 }
 
+fn mine_block_with_reward(prev: &Block, address: &BtcAddress, reward: i64) -> Block {
+    BlockBuilder::new()
+        .with_previous_hash(prev.header.hash().unwrap())
+        .with_version(2)
+        .with_coinbase(address, reward, 3)
+        .with_timestamp(1588813835)
+        .mine(U256::from(2).pow(254.into()))
+        .unwrap()
+}
+
+fn raw_header(block: &Block) -> RawBlockHeader {
+    RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap()
+}
+
 benchmarks! {
     verify_and_validate_transaction {
         let origin: T::AccountId = account("Origin", 0, 0);
@@ -127,6 +142,31 @@ benchmarks! {
 
     }: _(RawOrigin::Signed(origin), raw_tx, value.into(), address, Some(H256::from_slice(&op_return)))
 
+    store_fork_block_header {
+        let origin: T::AccountId = account("Origin", 0, 0);
+        let address = BtcAddress::P2PKH(H160::from([0; 20]));
+
+        let genesis = mine_genesis::<T>(origin.clone(), &address, 0);
+        let main = mine_block_with_reward(&genesis, &address, 50);
+        BtcRelay::<T>::_store_block_header(&origin, raw_header(&main)).unwrap();
+
+        MaxForks::<T>::put(0);
+        StaleForkDepth::<T>::put(0);
+
+        // the fork index makes storing a fork independent of the number of live forks
+        for i in 0..10 {
+            let fork = mine_block_with_reward(&genesis, &address, 100 + i as i64);
+            BtcRelay::<T>::_store_block_header(&origin, raw_header(&fork)).unwrap();
+        }
+
+        let new_fork = mine_block_with_reward(&genesis, &address, 99);
+    }: {
+        BtcRelay::<T>::store_block_header(&origin, raw_header(&new_fork))?
+    }
+    verify {
+        assert_eq!(ForkCount::<T>::get(), 11);
+    }
+
 }
 
 #[cfg(test)]
@@ -141,6 +181,7 @@ mod tests {
             assert_ok!(test_benchmark_verify_and_validate_transaction::<Test>());
             assert_ok!(test_benchmark_verify_transaction_inclusion::<Test>());
             assert_ok!(test_benchmark_validate_transaction::<Test>());
+            assert_ok!(test_benchmark_store_fork_block_header::<Test>());
         });
     }
 }
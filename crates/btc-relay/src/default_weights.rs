@@ -37,6 +37,7 @@ pub trait WeightInfo {
     fn verify_and_validate_transaction() -> Weight;
     fn verify_transaction_inclusion() -> Weight;
     fn validate_transaction() -> Weight;
+    fn store_fork_block_header() -> Weight;
 }
 
 // For backwards compatibility and tests
@@ -50,4 +51,10 @@ impl WeightInfo for () {
     fn validate_transaction() -> Weight {
         15_739_000_u64.saturating_add(RocksDbWeight::get().reads(1_u64))
     }
+    // ESTIMATE: not measured by the benchmark CLI, regenerate this file to replace it
+    fn store_fork_block_header() -> Weight {
+        86_214_000_u64
+            .saturating_add(RocksDbWeight::get().reads(13_u64))
+            .saturating_add(RocksDbWeight::get().writes(9_u64))
+    }
 }
@@ -3,12 +3,10 @@ use mocktopus::macros::mockable;
 
 #[cfg_attr(test, mockable)]
 pub(crate) mod security {
-    #[cfg(test)]
-    use security::types::ErrorCode;
-    #[cfg(test)]
-    use security::types::StatusCode;
+    use security::types::{ErrorCode, StatusChangeOrigin, StatusCode};
 
     use frame_support::dispatch::DispatchError;
+    use sp_std::vec::Vec;
     type UnitResult = Result<(), DispatchError>;
 
     #[cfg(test)]
@@ -42,6 +40,14 @@ pub(crate) mod security {
         <security::Pallet<T>>::insert_error(error)
     }
 
+    pub fn set_status_with_reason<T: security::Config>(status: StatusCode, reason: Vec<u8>) {
+        <security::Pallet<T>>::set_status_with_reason(status, StatusChangeOrigin::Runtime, reason)
+    }
+
+    pub fn insert_error_with_reason<T: security::Config>(error: ErrorCode, reason: Vec<u8>) {
+        <security::Pallet<T>>::insert_error_with_reason(error, StatusChangeOrigin::Runtime, reason)
+    }
+
     pub fn active_block_number<T: security::Config>() -> T::BlockNumber {
         <security::Pallet<T>>::active_block_number()
     }
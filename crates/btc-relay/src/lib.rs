@@ -29,7 +29,11 @@ use mocktopus::macros::mockable;
 
 use frame_support::{
     dispatch::{DispatchError, DispatchResult},
-    ensure, runtime_print, transactional,
+    ensure, runtime_print,
+    storage::migration::remove_storage_prefix,
+    traits::{Get, PalletInfoAccess},
+    transactional,
+    weights::Weight,
 };
 use frame_system::{ensure_root, ensure_signed};
 use sp_core::{H256, U256};
@@ -38,13 +42,14 @@ use sp_std::{collections::btree_set::BTreeSet, prelude::*};
 // Crates
 pub use bitcoin::{self, Address as BtcAddress, PublicKey as BtcPublicKey};
 use bitcoin::{
-    merkle::{MerkleProof, ProofResult},
+    merkle::{MerkleProof, MerkleTree, ProofResult},
     parser::{parse_block_header, parse_transaction},
     types::{BlockChain, BlockHeader, H256Le, RawBlockHeader, Transaction, TransactionOutput},
     Error as BitcoinError,
 };
-use security::types::ErrorCode;
-pub use types::RichBlockHeader;
+use codec::Encode;
+use security::types::{ErrorCode, OnErrorRecovery, StatusCode};
+pub use types::{DeploymentState, OnStaleBlockHeader, RichBlockHeader, Version, VersionBitsDeployment};
 
 pub use pallet::*;
 
@@ -67,10 +72,26 @@ pub mod pallet {
 
         /// Weight information for the extrinsics in this module.
         type WeightInfo: WeightInfo;
+
+        /// Callbacks of other pallets, run for each block header removed with a stale fork.
+        type OnStaleBlock: OnStaleBlockHeader<Self::AccountId>;
     }
 
     #[pallet::hooks]
-    impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {}
+    impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
+        fn on_initialize(_n: T::BlockNumber) -> Weight {
+            Self::prune_block_headers().saturating_add(Self::remove_stale_forks())
+        }
+
+        fn on_runtime_upgrade() -> Weight {
+            if Self::storage_version() != Version::V0 {
+                return T::DbWeight::get().reads(1);
+            }
+            let weight = Self::migrate_parameters().saturating_add(Self::migrate_fork_index());
+            StorageVersion::<T>::put(Version::V1);
+            weight.saturating_add(T::DbWeight::get().reads_writes(1, 1))
+        }
+    }
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
@@ -218,6 +239,172 @@ pub mod pallet {
             Self::clear_block_error(block_hash, error)?;
             Ok(().into())
         }
+
+        /// Approve a trusted checkpoint which the main chain must contain. Checkpoints at the
+        /// start of a difficulty adjustment period may be used to initialize the relay.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the dispatch origin of this call (must be _Root_)
+        /// * `block_height` - the height of the bitcoin block
+        /// * `block_hash` - the hash of the bitcoin block
+        ///
+        /// # Weight: `O(1)`
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn set_checkpoint(
+            origin: OriginFor<T>,
+            block_height: u32,
+            block_hash: H256Le,
+        ) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            Self::insert_checkpoint(block_height, block_hash)?;
+            Ok(().into())
+        }
+
+        /// Remove a trusted checkpoint.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the dispatch origin of this call (must be _Root_)
+        /// * `block_height` - the height of the checkpoint
+        ///
+        /// # Weight: `O(1)`
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn remove_checkpoint(origin: OriginFor<T>, block_height: u32) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            ensure!(Checkpoints::<T>::contains_key(block_height), Error::<T>::NoCheckpoint);
+            Checkpoints::<T>::remove(block_height);
+            Self::deposit_event(<Event<T>>::RemoveCheckpoint(block_height));
+            Ok(().into())
+        }
+
+        /// Lift the freeze of the heights affected by a deep reorg, after the reorg has been
+        /// investigated. Recovers the parachain if the main chain has no flagged blocks.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the dispatch origin of this call (must be _Root_)
+        ///
+        /// # Weight: `O(1)`
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn unfreeze_heights(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            let (start_height, end_height) = FrozenHeights::<T>::take().ok_or(Error::<T>::NotFrozen)?;
+
+            let main_chain = Self::get_block_chain_from_id(MAIN_CHAIN_ID)?;
+            if !main_chain.is_invalid() && !main_chain.is_no_data() {
+                Self::recover_if_needed()?;
+            }
+
+            Self::deposit_event(<Event<T>>::UnfreezeHeights(start_height, end_height));
+            Ok(().into())
+        }
+
+        /// Set the number of main chain blocks below the best block for which headers are retained.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the dispatch origin of this call (must be _Root_)
+        /// * `pruning_window` - the number of retained blocks, zero disables pruning
+        ///
+        /// # Weight: `O(1)`
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn set_pruning_window(origin: OriginFor<T>, pruning_window: u32) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            PruningWindow::<T>::put(pruning_window);
+            Ok(().into())
+        }
+
+        /// Set the maximum number of live forks.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the dispatch origin of this call (must be _Root_)
+        /// * `max_forks` - the maximum number of live forks, zero disables the limit
+        ///
+        /// # Weight: `O(1)`
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn set_max_forks(origin: OriginFor<T>, max_forks: u32) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            MaxForks::<T>::put(max_forks);
+            Ok(().into())
+        }
+
+        /// Set the number of blocks a fork tip may fall behind the best block before the fork is removed.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the dispatch origin of this call (must be _Root_)
+        /// * `stale_fork_depth` - the depth at which forks are removed, zero disables removal
+        ///
+        /// # Weight: `O(1)`
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn set_stale_fork_depth(origin: OriginFor<T>, stale_fork_depth: u32) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            StaleForkDepth::<T>::put(stale_fork_depth);
+            Ok(().into())
+        }
+
+        /// Set the maximum number of main chain blocks a reorg may orphan before the relay is frozen.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the dispatch origin of this call (must be _Root_)
+        /// * `max_reorg_depth` - the maximum reorg depth, zero disables the check
+        ///
+        /// # Weight: `O(1)`
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn set_max_reorg_depth(origin: OriginFor<T>, max_reorg_depth: u32) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            MaxReorgDepth::<T>::put(max_reorg_depth);
+            Ok(().into())
+        }
+
+        /// Configure a soft fork deployment whose version bits signalling is tracked.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the dispatch origin of this call (must be _Root_)
+        /// * `deployment_id` - the id of the deployment
+        /// * `deployment` - the version bit, start time, timeout and threshold of the deployment
+        ///
+        /// # Weight: `O(1)`
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn set_deployment(
+            origin: OriginFor<T>,
+            deployment_id: u32,
+            deployment: VersionBitsDeployment,
+        ) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            Self::insert_deployment(deployment_id, deployment)?;
+            Ok(().into())
+        }
+
+        /// Remove a soft fork deployment.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the dispatch origin of this call (must be _Root_)
+        /// * `deployment_id` - the id of the deployment
+        ///
+        /// # Weight: `O(1)`
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn remove_deployment(origin: OriginFor<T>, deployment_id: u32) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            ensure!(Deployments::<T>::contains_key(deployment_id), Error::<T>::NoDeployment);
+            Deployments::<T>::remove(deployment_id);
+            Self::deposit_event(<Event<T>>::RemoveDeployment(deployment_id));
+            Ok(().into())
+        }
     }
 
     #[pallet::event]
@@ -234,10 +421,26 @@ pub mod pallet {
         ChainReorg(H256Le, u32, u32),
         /// main chain height, fork height, fork id
         ForkAheadOfMainChain(u32, u32, u32),
+        /// fork id, fork height
+        RemoveStaleFork(u32, u32),
         /// block_hash, chain_id, error
         FlagBlockError(H256Le, u32, ErrorCode),
         /// block_hash, chain_id, error
         ClearBlockError(H256Le, u32, ErrorCode),
+        /// block_height, block_hash
+        SetCheckpoint(u32, H256Le),
+        /// block_height
+        RemoveCheckpoint(u32),
+        /// start_height, end_height, reorg_depth
+        FreezeHeights(u32, u32, u32),
+        /// start_height, end_height
+        UnfreezeHeights(u32, u32),
+        /// ids of the executed requests with proofs from blocks orphaned by a reorg
+        OrphanedRequests(Vec<H256>),
+        /// deployment_id
+        SetDeployment(u32),
+        /// deployment_id
+        RemoveDeployment(u32),
     }
 
     #[pallet::error]
@@ -338,6 +541,34 @@ pub mod pallet {
         ArithmeticUnderflow,
         /// Relayer is not registered
         RelayerNotAuthorized,
+        /// Block header has been pruned from the relay
+        BlockPruned,
+        /// Maximum number of live forks reached
+        TooManyForks,
+        /// Fork is too far behind the main chain
+        StaleFork,
+        /// No trusted checkpoint at this height
+        NoCheckpoint,
+        /// Block header does not match the trusted checkpoint at this height
+        CheckpointMismatch,
+        /// Initial block header is not at the start of a difficulty adjustment period
+        NotEpochStart,
+        /// Number of initial block headers is zero or exceeds the limit
+        InvalidInitialHeaders,
+        /// Batch of payments is empty, exceeds the limit or contains duplicate request ids
+        InvalidBatch,
+        /// Block is at a height which was frozen after a deep reorg
+        FrozenHeight,
+        /// No heights are frozen
+        NotFrozen,
+        /// Transaction output was already used to execute a request
+        OutputAlreadyConsumed,
+        /// No soft fork deployment with this id
+        NoDeployment,
+        /// Version bit, timeout or threshold of the deployment is invalid
+        InvalidDeployment,
+        /// Median time past before a difficulty adjustment period is unknown
+        NoPeriodMedianTime,
     }
 
     /// Store Bitcoin block headers
@@ -345,11 +576,14 @@ pub mod pallet {
     pub(super) type BlockHeaders<T: Config> =
         StorageMap<_, Blake2_128Concat, H256Le, RichBlockHeader<T::AccountId, T::BlockNumber>, ValueQuery>;
 
-    /// Priority queue of BlockChain elements, ordered by the maximum height (descending).
-    /// The first index into this mapping (0) is considered to be the longest chain. The value
-    /// of the entry is the index into `ChainsIndex` to retrieve the `BlockChain`.
+    /// Index of the live forks by their maximum height, mapping (max_height, chain_id) to unit.
+    /// The main chain is not part of this index.
+    #[pallet::storage]
+    pub(super) type ForkTips<T: Config> = StorageDoubleMap<_, Blake2_128Concat, u32, Blake2_128Concat, u32, ()>;
+
+    /// Number of live forks
     #[pallet::storage]
-    pub(super) type Chains<T: Config> = StorageMap<_, Blake2_128Concat, u32, u32>;
+    pub(super) type ForkCount<T: Config> = StorageValue<_, u32, ValueQuery>;
 
     /// Auxiliary mapping of chains ids to `BlockChain` entries. The first index into this
     /// mapping (0) is considered to be the Bitcoin main chain.
@@ -402,6 +636,123 @@ pub mod pallet {
     #[pallet::getter(fn disable_op_return_check)]
     pub(super) type DisableOpReturnCheck<T: Config> = StorageValue<_, bool, ValueQuery>;
 
+    /// Number of main chain blocks below the best block for which headers are retained.
+    /// Older headers (except retarget anchors) are pruned. Zero disables pruning.
+    #[pallet::storage]
+    #[pallet::getter(fn pruning_window)]
+    pub(super) type PruningWindow<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Lowest main chain height that has not yet been considered for pruning
+    #[pallet::storage]
+    pub(super) type NextPruneHeight<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Heights of recently pruned main chain block headers by hash, so that proofs against
+    /// them can be told apart from proofs against unknown blocks. Entries are forgotten
+    /// `PruningWindow` pruning steps after they were added, which bounds the map.
+    #[pallet::storage]
+    pub(super) type PrunedBlockHeights<T: Config> = StorageMap<_, Blake2_128Concat, H256Le, u32>;
+
+    /// Hash of the main chain block header pruned at each pruning step (height), used to
+    /// forget the entries of `PrunedBlockHeights`
+    #[pallet::storage]
+    pub(super) type PrunedBlockHashes<T: Config> = StorageMap<_, Twox64Concat, u32, H256Le>;
+
+    /// Maximum number of live forks. Zero disables the limit.
+    #[pallet::storage]
+    #[pallet::getter(fn max_forks)]
+    pub(super) type MaxForks<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Number of blocks a fork tip may fall behind the best block before the fork
+    /// is removed. Zero disables the removal of stale forks.
+    #[pallet::storage]
+    #[pallet::getter(fn stale_fork_depth)]
+    pub(super) type StaleForkDepth<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Lowest fork tip height that has not yet been checked for stale forks
+    #[pallet::storage]
+    pub(super) type NextStaleForkHeight<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Trusted block hashes by height which the main chain must contain
+    #[pallet::storage]
+    #[pallet::getter(fn checkpoint)]
+    pub(super) type Checkpoints<T: Config> = StorageMap<_, Blake2_128Concat, u32, H256Le>;
+
+    /// Maximum number of main chain blocks a reorg may orphan before the relay is frozen.
+    /// Zero disables the check.
+    #[pallet::storage]
+    #[pallet::getter(fn max_reorg_depth)]
+    pub(super) type MaxReorgDepth<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Range of heights (start, end) affected by a deep reorg, against which no transactions
+    /// can be verified until root lifts the freeze
+    #[pallet::storage]
+    #[pallet::getter(fn frozen_heights)]
+    pub(super) type FrozenHeights<T: Config> = StorageValue<_, (u32, u32)>;
+
+    /// Ids of the requests which were executed with a transaction inclusion proof from a block
+    #[pallet::storage]
+    pub(super) type ExecutedRequests<T: Config> = StorageMap<_, Blake2_128Concat, H256Le, Vec<H256>, ValueQuery>;
+
+    /// Id of the request which was executed with a transaction output (txid, vout), so that
+    /// the output cannot execute any other request
+    #[pallet::storage]
+    #[pallet::getter(fn consumed_output)]
+    pub(super) type ConsumedOutputs<T: Config> = StorageMap<_, Blake2_128Concat, (H256Le, u32), H256>;
+
+    /// Id of the transaction which settled a request, removed only when a reorg orphans the
+    /// block from which the inclusion proof was submitted. It is kept when that block is pruned,
+    /// so that a second payment for the request can still be reported as a double payment, and
+    /// grows with the number of executed requests like the requests themselves
+    #[pallet::storage]
+    #[pallet::getter(fn settling_transaction)]
+    pub(super) type SettlingTransactions<T: Config> = StorageMap<_, Blake2_128Concat, H256, H256Le>;
+
+    /// Ids of the requests paid by a batched transaction, in the order of the payment outputs,
+    /// and the pruning step at which the record expires, by the `OP_RETURN` payload which
+    /// commits to them
+    #[pallet::storage]
+    pub(super) type BatchPayments<T: Config> = StorageMap<_, Blake2_128Concat, H256, (Vec<H256>, u32)>;
+
+    /// Batched payments by the pruning step at which they expire, used to bound `BatchPayments`
+    #[pallet::storage]
+    pub(super) type ExpiringBatchPayments<T: Config> =
+        StorageDoubleMap<_, Twox64Concat, u32, Blake2_128Concat, H256, ()>;
+
+    /// Transaction outputs consumed with an inclusion proof from a block, removed when the block is pruned
+    #[pallet::storage]
+    pub(super) type BlockConsumedOutputs<T: Config> =
+        StorageMap<_, Blake2_128Concat, H256Le, Vec<(H256Le, u32)>, ValueQuery>;
+
+    /// Soft fork deployments whose version bits signalling is tracked
+    #[pallet::storage]
+    #[pallet::getter(fn deployment)]
+    pub(super) type Deployments<T: Config> = StorageMap<_, Blake2_128Concat, u32, VersionBitsDeployment>;
+
+    /// Number of main chain blocks signalling each version bit, per difficulty adjustment period
+    #[pallet::storage]
+    pub(super) type VersionBitsCounts<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, u32, Blake2_128Concat, u8, u32, ValueQuery>;
+
+    /// Median time past of the last main chain block before each difficulty adjustment period
+    #[pallet::storage]
+    pub(super) type PeriodMedianTimes<T: Config> = StorageMap<_, Blake2_128Concat, u32, u32>;
+
+    /// Oldest difficulty adjustment period whose version bits signalling is still tracked. The
+    /// signalling of older periods is pruned with their block headers.
+    #[pallet::storage]
+    #[pallet::getter(fn version_bits_start_period)]
+    pub(super) type VersionBitsStartPeriod<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    #[pallet::type_value]
+    pub(super) fn DefaultForStorageVersion() -> Version {
+        Version::V0
+    }
+
+    /// Build storage at V1 (requires default 0).
+    #[pallet::storage]
+    #[pallet::getter(fn storage_version)]
+    pub(super) type StorageVersion<T: Config> = StorageValue<_, Version, ValueQuery, DefaultForStorageVersion>;
+
     #[pallet::genesis_config]
     pub struct GenesisConfig<T: Config> {
         /// Global security parameter k for stable Bitcoin transactions
@@ -414,6 +765,18 @@ pub mod pallet {
         pub disable_inclusion_check: bool,
         /// Whether the module should perform OP_RETURN checks.
         pub disable_op_return_check: bool,
+        /// Number of main chain blocks to retain headers for, zero disables pruning.
+        pub pruning_window: u32,
+        /// Maximum number of live forks, zero disables the limit.
+        pub max_forks: u32,
+        /// Depth behind the best block at which forks are removed, zero disables removal.
+        pub stale_fork_depth: u32,
+        /// Trusted (height, hash) pairs, epoch start checkpoints may anchor the relay.
+        pub checkpoints: Vec<(u32, H256Le)>,
+        /// Number of orphaned blocks above which a reorg freezes the relay, zero disables the check.
+        pub max_reorg_depth: u32,
+        /// Soft fork deployments whose version bits signalling is tracked, by id.
+        pub deployments: Vec<(u32, VersionBitsDeployment)>,
     }
 
     #[cfg(feature = "std")]
@@ -425,6 +788,12 @@ pub mod pallet {
                 disable_difficulty_check: Default::default(),
                 disable_inclusion_check: Default::default(),
                 disable_op_return_check: Default::default(),
+                pruning_window: Default::default(),
+                max_forks: Default::default(),
+                stale_fork_depth: Default::default(),
+                checkpoints: Default::default(),
+                max_reorg_depth: Default::default(),
+                deployments: Default::default(),
             }
         }
     }
@@ -437,6 +806,18 @@ pub mod pallet {
             DisableDifficultyCheck::<T>::put(self.disable_difficulty_check);
             DisableInclusionCheck::<T>::put(self.disable_inclusion_check);
             DisableOpReturnCheck::<T>::put(self.disable_op_return_check);
+            PruningWindow::<T>::put(self.pruning_window);
+            ForkCount::<T>::put(0);
+            MaxForks::<T>::put(self.max_forks);
+            StaleForkDepth::<T>::put(self.stale_fork_depth);
+            for (block_height, block_hash) in self.checkpoints.iter() {
+                Checkpoints::<T>::insert(block_height, block_hash);
+            }
+            MaxReorgDepth::<T>::put(self.max_reorg_depth);
+            for (deployment_id, deployment) in self.deployments.iter() {
+                Deployments::<T>::insert(deployment_id, deployment);
+            }
+            StorageVersion::<T>::put(Version::V1);
         }
     }
 }
@@ -464,6 +845,42 @@ pub const ACCEPTED_MIN_TRANSACTION_OUTPUTS_WITH_OP_RETURN: u32 = 2;
 // Accepted maximum number of transaction outputs for validation
 pub const ACCEPTED_MAX_TRANSACTION_OUTPUTS: u32 = 32;
 
+/// Pruning window set on chains which predate pruning
+pub const DEFAULT_PRUNING_WINDOW: u32 = 4032;
+
+/// Maximum number of live forks set on chains which predate the limit
+pub const DEFAULT_MAX_FORKS: u32 = 32;
+
+/// Stale fork depth set on chains which predate the removal of stale forks
+pub const DEFAULT_STALE_FORK_DEPTH: u32 = 144;
+
+/// Maximum reorg depth set on chains which predate freezing the relay on deep reorgs
+pub const DEFAULT_MAX_REORG_DEPTH: u32 = 6;
+
+/// Maximum number of main chain blocks pruned in a single parachain block
+pub const MAX_PRUNED_BLOCKS_PER_BLOCK: u32 = 10;
+
+/// Maximum number of fork tip heights checked and forks removed in a single parachain block
+pub const MAX_STALE_FORK_CHECKS_PER_BLOCK: u32 = 10;
+
+/// Maximum number of block headers, including the checkpoint, accepted on initialization
+pub const MAX_INITIAL_BLOCK_HEADERS: u32 = 144;
+
+/// Maximum number of requests paid by a single batched transaction
+pub const MAX_BATCH_PAYMENTS: u32 = 16;
+
+/// Mask of the top bits of a block version which signals with version bits
+pub const VERSION_BITS_TOP_MASK: u32 = 0xe000_0000;
+
+/// Top bits of a block version which signals with version bits
+pub const VERSION_BITS_TOP_BITS: u32 = 0x2000_0000;
+
+/// Number of version bits available for signalling
+pub const VERSION_BITS_NUM_BITS: u8 = 29;
+
+/// Number of blocks whose median timestamp is the median time past, see BIP113
+pub const MEDIAN_TIME_SPAN: u32 = 11;
+
 /// Unrounded Maximum Target
 /// 0x00000000FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF
 pub const UNROUNDED_MAX_TARGET: U256 = U256([
@@ -508,14 +925,42 @@ fn maybe_get_payment_value(output: &TransactionOutput, recipient_btc_address: &B
 
 #[cfg_attr(test, mockable)]
 impl<T: Config> Pallet<T> {
-    pub fn initialize(relayer: T::AccountId, raw_block_header: RawBlockHeader, block_height: u32) -> DispatchResult {
+    /// Initializes the relay from a trusted checkpoint followed by a run of block headers.
+    ///
+    /// # Arguments
+    ///
+    /// * `relayer` - the account submitting the block headers
+    /// * `raw_block_headers` - the checkpoint block header followed by its descendants
+    /// * `block_height` - the height of the checkpoint block header
+    #[transactional]
+    pub fn initialize(
+        relayer: T::AccountId,
+        raw_block_headers: Vec<RawBlockHeader>,
+        block_height: u32,
+    ) -> DispatchResult {
         // Check if BTC-Relay was already initialized
         ensure!(!Self::best_block_exists(), Error::<T>::AlreadyInitialized);
 
+        ensure!(
+            !raw_block_headers.is_empty() && raw_block_headers.len() as u32 <= MAX_INITIAL_BLOCK_HEADERS,
+            Error::<T>::InvalidInitialHeaders
+        );
+        let raw_block_header = raw_block_headers[0];
+
         // Parse the block header bytes to extract the required info
         let basic_block_header = parse_block_header(&raw_block_header).map_err(Error::<T>::from)?;
         let block_header_hash = raw_block_header.hash();
 
+        // the relay may only be anchored at a root approved checkpoint, retargeting
+        // requires the first block of the difficulty adjustment period even if the
+        // difficulty check is disabled
+        ensure!(
+            block_height % DIFFICULTY_ADJUSTMENT_INTERVAL == 0,
+            Error::<T>::NotEpochStart
+        );
+        let checkpoint = Self::checkpoint(block_height).ok_or(Error::<T>::NoCheckpoint)?;
+        ensure!(checkpoint == block_header_hash, Error::<T>::CheckpointMismatch);
+
         // register the current height to track stable parachain confirmations
         let para_height = ext::security::active_block_number::<T>();
 
@@ -537,16 +982,25 @@ impl<T: Config> Pallet<T> {
         // Store a pointer to BlockChain in ChainsIndex
         Self::set_block_chain_from_id(MAIN_CHAIN_ID, &blockchain);
 
-        // Store the reference to the new BlockChain in Chains
-        Self::set_chain_from_position_and_id(0, MAIN_CHAIN_ID);
-
         // Set BestBlock and BestBlockHeight to the submitted block
         Self::set_best_block(block_header_hash);
         Self::set_best_block_height(block_height);
+        Self::add_version_bits(block_height, &basic_block_header);
         StartBlockHeight::<T>::set(block_height);
+        NextPruneHeight::<T>::set(block_height);
+        NextStaleForkHeight::<T>::set(block_height);
 
         // Emit a Initialized Event
-        Self::deposit_event(<Event<T>>::Initialized(block_height, block_header_hash, relayer));
+        Self::deposit_event(<Event<T>>::Initialized(
+            block_height,
+            block_header_hash,
+            relayer.clone(),
+        ));
+
+        // Store the remaining block headers on top of the checkpoint
+        for raw_block_header in raw_block_headers.into_iter().skip(1) {
+            Self::_store_block_header(&relayer, raw_block_header)?;
+        }
 
         Ok(())
     }
@@ -595,7 +1049,12 @@ impl<T: Config> Pallet<T> {
         let is_fork = prev_blockchain.max_height != prev_block_height;
         runtime_print!("Fork detected: {:?}", is_fork);
 
+        // no chain may contain a block conflicting with a checkpoint
+        Self::ensure_checkpoint_matches(current_block_height, block_header_hash)?;
+
         let blockchain = if is_fork {
+            // forks must not exceed the limit and must not be stale already
+            Self::ensure_fork_allowed(current_block_height)?;
             // create new blockchain element
             Self::create_blockchain(current_block_height, block_header_hash)
         } else {
@@ -624,8 +1083,8 @@ impl<T: Config> Pallet<T> {
             // create a new chain
             // Store a pointer to BlockChain in ChainsIndex
             Self::set_block_chain_from_id(blockchain.chain_id, &blockchain);
-            // Store the reference to the blockchain in Chains
-            Self::insert_sorted(&blockchain)?;
+            // Store the reference to the blockchain in ForkTips
+            Self::insert_fork_tip(&blockchain);
         } else {
             // extended the chain
             // Update the pointer to BlockChain in ChainsIndex
@@ -636,7 +1095,8 @@ impl<T: Config> Pallet<T> {
 
             if blockchain.chain_id == MAIN_CHAIN_ID {
                 Self::set_best_block(block_header_hash);
-                Self::set_best_block_height(current_block_height)
+                Self::set_best_block_height(current_block_height);
+                Self::add_version_bits(current_block_height, &basic_block_header);
             }
         };
 
@@ -685,6 +1145,23 @@ impl<T: Config> Pallet<T> {
         )
     }
 
+    /// Verifies the inclusion of a transaction which pays several requests at once, and
+    /// validates it against the given (recipient, minimum amount, request id) triples.
+    /// Returns the first input address and the value paid for each request.
+    pub fn _verify_and_validate_batch_transaction(
+        raw_merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+        payments: Vec<(BtcAddress, i64, H256)>,
+        confirmations: Option<u32>,
+    ) -> Result<(BtcAddress, Vec<i64>), DispatchError> {
+        let transaction = Self::parse_transaction(&raw_tx)?;
+
+        // Verify that the transaction is indeed included in the main chain
+        Self::_verify_transaction_inclusion(transaction.tx_id(), raw_merkle_proof, confirmations)?;
+
+        Self::_validate_batch_transaction(transaction, payments)
+    }
+
     pub fn _verify_transaction_inclusion(
         tx_id: H256Le,
         raw_merkle_proof: Vec<u8>,
@@ -699,8 +1176,12 @@ impl<T: Config> Pallet<T> {
 
         let merkle_proof = Self::parse_merkle_proof(&raw_merkle_proof)?;
 
-        let rich_header =
-            Self::get_block_header_from_hash(merkle_proof.block_header.hash().map_err(Error::<T>::from)?)?;
+        let block_hash = merkle_proof.block_header.hash().map_err(Error::<T>::from)?;
+        let rich_header = match Self::get_block_header_from_hash(block_hash) {
+            Ok(rich_header) => rich_header,
+            Err(_) if Self::is_block_pruned(block_hash) => return Err(Error::<T>::BlockPruned.into()),
+            Err(err) => return Err(err),
+        };
 
         ensure!(rich_header.chain_ref == MAIN_CHAIN_ID, Error::<T>::InvalidChainID);
 
@@ -861,6 +1342,63 @@ impl<T: Config> Pallet<T> {
         }
     }
 
+    /// Computes the `OP_RETURN` payload of a batched payment: the root of a Bitcoin merkle
+    /// tree with the request ids as leaves, in the order of the payment outputs. The payload
+    /// for a single request is the request id itself, as in the non-batched format.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_ids` - ids of the paid requests, must not be empty
+    pub fn compute_batch_op_return(request_ids: &[H256]) -> Result<H256, DispatchError> {
+        ensure!(!request_ids.is_empty(), Error::<T>::InvalidBatch);
+        let leaves = request_ids
+            .iter()
+            .map(|request_id| H256Le::from_bytes_le(request_id.as_bytes()))
+            .collect::<Vec<_>>();
+        let count = leaves.len() as u32;
+        let root =
+            MerkleTree::compute_root(0, MerkleTree::compute_height(count), count, &leaves).map_err(Error::<T>::from)?;
+        Ok(H256::from_slice(&root.to_bytes_le()))
+    }
+
+    /// Records the requests paid by a batched transaction under its `OP_RETURN` payload, so that
+    /// the payment can be told apart from a theft. Returns the payload. Payments for a single
+    /// request reference the request id directly and are not recorded.
+    ///
+    /// If pruning is enabled, the record expires when the main chain block `PruningWindow`
+    /// heights above the current best block is pruned, after which no transaction mined up
+    /// to that height can be proven anymore. A declared batch must thus be paid within
+    /// `PruningWindow` blocks. Registering the batch again renews the record.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_ids` - ids of the paid requests, in the order of the payment outputs
+    pub fn register_batch_payment(request_ids: Vec<H256>) -> Result<H256, DispatchError> {
+        ensure!(request_ids.len() as u32 <= MAX_BATCH_PAYMENTS, Error::<T>::InvalidBatch);
+        let unique_request_ids = request_ids.iter().collect::<BTreeSet<_>>();
+        ensure!(unique_request_ids.len() == request_ids.len(), Error::<T>::InvalidBatch);
+
+        let op_return = Self::compute_batch_op_return(&request_ids)?;
+        if request_ids.len() > 1 {
+            let window = Self::pruning_window();
+            let expiry = Self::get_best_block_height().saturating_add(window);
+            if window > 0 {
+                ExpiringBatchPayments::<T>::insert(expiry, op_return, ());
+            }
+            BatchPayments::<T>::insert(op_return, (request_ids, expiry));
+        }
+        Ok(op_return)
+    }
+
+    /// Returns the ids of the requests paid by a batched transaction, if it was registered.
+    ///
+    /// # Arguments
+    ///
+    /// * `op_return` - `OP_RETURN` payload of the batched transaction
+    pub fn batch_payment(op_return: &H256) -> Option<Vec<H256>> {
+        BatchPayments::<T>::get(op_return).map(|(request_ids, _)| request_ids)
+    }
+
     pub fn is_op_return_disabled() -> bool {
         Self::disable_op_return_check()
     }
@@ -873,12 +1411,7 @@ impl<T: Config> Pallet<T> {
         minimum_btc: Option<i64>,
         op_return_id: Option<Vec<u8>>,
     ) -> Result<(BtcAddress, i64), DispatchError> {
-        let input_address = transaction
-            .inputs
-            .get(0)
-            .ok_or(Error::<T>::MalformedTransaction)?
-            .extract_address()
-            .map_err(|_| Error::<T>::MalformedTransaction)?;
+        let input_address = Self::extract_input_address(&transaction)?;
 
         let extr_payment_value = if Self::is_op_return_disabled() {
             Self::extract_payment_value(transaction, recipient_btc_address)?
@@ -905,6 +1438,66 @@ impl<T: Config> Pallet<T> {
         Ok((input_address, extr_payment_value))
     }
 
+    /// Checks if a transaction validly pays a batch of requests. The payment for the i-th
+    /// request is the i-th output with an address, any further such outputs (e.g. change)
+    /// are ignored. Unless OP_RETURN checks are disabled, the transaction must contain exactly
+    /// one OP_RETURN output whose payload is given by `compute_batch_op_return`.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - Bitcoin transaction
+    /// * `payments` - (recipient, minimum amount, request id) of each paid request
+    fn _validate_batch_transaction(
+        transaction: Transaction,
+        payments: Vec<(BtcAddress, i64, H256)>,
+    ) -> Result<(BtcAddress, Vec<i64>), DispatchError> {
+        ensure!(
+            !payments.is_empty() && payments.len() as u32 <= MAX_BATCH_PAYMENTS,
+            Error::<T>::InvalidBatch
+        );
+        let request_ids = payments
+            .iter()
+            .map(|(_, _, request_id)| *request_id)
+            .collect::<Vec<_>>();
+        let unique_request_ids = request_ids.iter().collect::<BTreeSet<_>>();
+        ensure!(unique_request_ids.len() == request_ids.len(), Error::<T>::InvalidBatch);
+
+        let input_address = Self::extract_input_address(&transaction)?;
+        let (outputs, op_returns) = Self::extract_outputs(transaction)?;
+
+        if !Self::is_op_return_disabled() {
+            let op_return = Self::compute_batch_op_return(&request_ids)?;
+            match op_returns.as_slice() {
+                [(_, data)] => ensure!(data.as_slice() == op_return.as_bytes(), Error::<T>::InvalidOpReturn),
+                _ => return Err(Error::<T>::InvalidOpReturn.into()),
+            }
+        }
+
+        ensure!(outputs.len() >= payments.len(), Error::<T>::InvalidPayment);
+        let values = payments
+            .iter()
+            .zip(outputs.iter())
+            .map(|((recipient, minimum, _), (value, address))| {
+                ensure!(address == recipient, Error::<T>::InvalidPayment);
+                ensure!(value >= minimum, Error::<T>::InsufficientValue);
+                Ok(*value)
+            })
+            .collect::<Result<Vec<_>, DispatchError>>()?;
+
+        Ok((input_address, values))
+    }
+
+    /// Extracts the address of the first input, which can be used as the
+    /// destination address for a potential refund
+    fn extract_input_address(transaction: &Transaction) -> Result<BtcAddress, DispatchError> {
+        Ok(transaction
+            .inputs
+            .get(0)
+            .ok_or(Error::<T>::MalformedTransaction)?
+            .extract_address()
+            .map_err(|_| Error::<T>::MalformedTransaction)?)
+    }
+
     pub fn is_fully_initialized() -> Result<bool, DispatchError> {
         if !StartBlockHeight::<T>::exists() {
             return Ok(false);
@@ -921,19 +1514,9 @@ impl<T: Config> Pallet<T> {
     // START: Storage getter functions
     // ********************************
 
-    /// Get chain id from position (sorted by max block height)
-    fn get_chain_id_from_position(position: u32) -> Result<u32, DispatchError> {
-        Chains::<T>::get(position).ok_or(Error::<T>::InvalidChainID.into())
-    }
-
-    /// Get the position of the fork in Chains
-    fn get_chain_position_from_chain_id(chain_id: u32) -> Result<u32, DispatchError> {
-        for (k, v) in Chains::<T>::iter() {
-            if v == chain_id {
-                return Ok(k);
-            }
-        }
-        Err(Error::<T>::ForkIdNotFound.into())
+    /// Check if any live fork has its tip at the given height
+    fn fork_tip_exists(block_height: u32) -> bool {
+        ForkTips::<T>::iter_prefix(block_height).next().is_some()
     }
 
     /// Get a blockchain from the id
@@ -975,7 +1558,7 @@ impl<T: Config> Pallet<T> {
     }
 
     /// Get a block header from its hash
-    fn get_block_header_from_hash(
+    pub fn get_block_header_from_hash(
         block_hash: H256Le,
     ) -> Result<RichBlockHeader<T::AccountId, T::BlockNumber>, DispatchError> {
         if BlockHeaders::<T>::contains_key(block_hash) {
@@ -998,38 +1581,36 @@ impl<T: Config> Pallet<T> {
         Self::get_block_header_from_hash(block_hash)
     }
 
-    /// Storage setter functions
-    /// Set a new chain with position and id
-    fn set_chain_from_position_and_id(position: u32, id: u32) {
-        Chains::<T>::insert(position, id);
+    /// Get a main chain block header from its height
+    pub fn get_main_chain_block_header(
+        block_height: u32,
+    ) -> Result<RichBlockHeader<T::AccountId, T::BlockNumber>, DispatchError> {
+        let main_chain = Self::get_block_chain_from_id(MAIN_CHAIN_ID)?;
+        Self::get_block_header_from_height(&main_chain, block_height)
     }
 
-    /// Swap chain elements
-    fn swap_chain(pos_1: u32, pos_2: u32) {
-        // swaps the values of two keys
-        Chains::<T>::swap(pos_1, pos_2)
+    /// Get the tip hash and height of every fork
+    pub fn get_fork_tips() -> Vec<(H256Le, u32)> {
+        ForkTips::<T>::iter()
+            .filter_map(|(block_height, chain_id, _)| {
+                Self::get_block_hash(chain_id, block_height)
+                    .ok()
+                    .map(|block_hash| (block_hash, block_height))
+            })
+            .collect()
     }
 
-    /// Remove a chain id from chains
-    fn remove_blockchain_from_chain(position: u32) -> Result<(), DispatchError> {
-        // swap the element with the last element in the mapping
-        // collect the unsorted chains iterable as a vector and sort it by index
-        let mut chains = Chains::<T>::iter().collect::<Vec<(u32, u32)>>();
-        chains.sort_by_key(|k| k.0);
+    /// Storage setter functions
+    /// Insert a new fork into the ForkTips index
+    fn insert_fork_tip(fork: &BlockChain) {
+        ForkTips::<T>::insert(fork.max_height, fork.chain_id, ());
+        ForkCount::<T>::mutate(|count| *count = count.saturating_add(1));
+    }
 
-        // get the last position as stored in the list
-        let last_pos = match chains.len() {
-            0 => return Err(Error::<T>::ForkIdNotFound.into()),
-            // chains stores (position, index)
-            n => chains[n - 1].0,
-        };
-        Self::swap_chain(position, last_pos);
-        // don't remove main chain id
-        if last_pos > 0 {
-            // remove the old head (now the value at the initial position)
-            Chains::<T>::remove(last_pos);
-        }
-        Ok(())
+    /// Remove a fork from the ForkTips index
+    fn remove_fork_tip(fork: &BlockChain) {
+        ForkTips::<T>::remove(fork.max_height, fork.chain_id);
+        ForkCount::<T>::mutate(|count| *count = count.saturating_sub(1));
     }
 
     /// Set a new blockchain in ChainsIndex
@@ -1136,6 +1717,13 @@ impl<T: Config> Pallet<T> {
         }
         Self::insert_block_hash(blockchain.chain_id, block_height, *block_hash);
 
+        if blockchain.chain_id != MAIN_CHAIN_ID {
+            // move the fork to its new tip height
+            ensure!(block_height >= NextStaleForkHeight::<T>::get(), Error::<T>::StaleFork);
+            ForkTips::<T>::remove(blockchain.max_height, blockchain.chain_id);
+            ForkTips::<T>::insert(block_height, blockchain.chain_id, ());
+        }
+
         blockchain.max_height = block_height;
         Self::set_block_chain_from_id(blockchain.chain_id, &blockchain);
 
@@ -1326,9 +1914,6 @@ impl<T: Config> Pallet<T> {
         // get the best block hash
         let best_block = Self::get_block_hash(fork.chain_id, fork.max_height)?;
 
-        // get the position of the fork in Chains
-        let position: u32 = Self::get_chain_position_from_chain_id(fork.chain_id)?;
-
         // Update the stored main chain
         Self::set_block_chain_from_id(MAIN_CHAIN_ID, &main_chain);
 
@@ -1338,17 +1923,18 @@ impl<T: Config> Pallet<T> {
 
         // remove the fork from storage
         Self::remove_blockchain_from_chain_index(fork.chain_id);
-        Self::remove_blockchain_from_chain(position)?;
+        Self::remove_fork_tip(fork);
 
         // store the forked main chain
         Self::set_block_chain_from_id(forked_main_chain.chain_id, &forked_main_chain);
 
-        // insert the reference to the forked main chain in Chains
-        Self::insert_sorted(&forked_main_chain)?;
+        // insert the reference to the forked main chain in ForkTips
+        Self::insert_fork_tip(&forked_main_chain);
 
         // update all the forked block headers
         for height in fork.start_height..=forked_main_chain.max_height {
             let block_hash = Self::get_block_hash(main_chain.chain_id, height)?;
+            Self::remove_version_bits(height, &Self::get_block_header_from_hash(block_hash)?.block_header);
             Self::insert_block_hash(forked_main_chain.chain_id, height, block_hash);
             Self::mutate_block_header_from_chain_id(&block_hash, forked_main_chain.chain_id);
             Self::remove_block_hash(MAIN_CHAIN_ID, height);
@@ -1357,145 +1943,464 @@ impl<T: Config> Pallet<T> {
         // update all new main chain block headers
         for height in fork.start_height..=fork.max_height {
             let block = Self::get_block_hash(fork.chain_id, height)?;
+            // checkpoints may have been approved after the fork was stored
+            Self::ensure_checkpoint_matches(height, block)?;
+            Self::add_version_bits(height, &Self::get_block_header_from_hash(block)?.block_header);
             Self::mutate_block_header_from_chain_id(&block, MAIN_CHAIN_ID);
             Self::insert_block_hash(MAIN_CHAIN_ID, height, block);
         }
         ChainsHashes::<T>::remove_prefix(fork.chain_id);
         if !fork.is_invalid() && !fork.is_no_data() {
             Self::recover_if_needed()?
+        } else {
+            // blocks flagged while they were on the fork now affect the main chain
+            Self::raise_block_errors(fork, best_block);
         }
 
         Ok(())
     }
 
-    /// Checks if a newly inserted fork results in an update to the sorted
-    /// Chains mapping. This happens when the max height of the fork is greater
-    /// than the max height of the previous element in the Chains mapping.
+    /// Raises the errors of the flagged blocks of a chain which became the main chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `chain` - the chain with flagged blocks
+    /// * `best_block` - the new best block, recorded as the reason
+    fn raise_block_errors(chain: &BlockChain, best_block: H256Le) {
+        let reason = best_block.encode();
+        if chain.is_no_data() {
+            ext::security::insert_error_with_reason::<T>(ErrorCode::NoDataBTCRelay, reason.clone());
+        }
+        if chain.is_invalid() {
+            ext::security::insert_error_with_reason::<T>(ErrorCode::InvalidBTCRelay, reason.clone());
+        }
+        ext::security::set_status_with_reason::<T>(StatusCode::Error, reason);
+    }
+
+    /// Checks if an extended fork overtakes the main chain. This happens when
+    /// the max height of the fork is more than `StableBitcoinConfirmations`
+    /// ahead of the max height of the main chain. The cost of this check does
+    /// not depend on the number of live forks.
     ///
     /// # Arguments
     ///
     /// * `fork` - the blockchain element that may cause a reorg
     fn check_and_do_reorg(fork: &BlockChain) -> Result<(), DispatchError> {
-        // Check if the ordering needs updating
         // if the fork is the main chain, we don't need to update the ordering
         if fork.chain_id == MAIN_CHAIN_ID {
             return Ok(());
         }
 
-        // TODO: remove, fix for rm head_index
-        if Chains::<T>::get(0).is_none() {
-            Chains::<T>::insert(0, 0);
+        let main_height = Self::get_block_chain_from_id(MAIN_CHAIN_ID)?.max_height;
+        if fork.max_height <= main_height {
+            return Ok(());
         }
 
-        // get the position of the fork in Chains
-        let fork_position: u32 = Self::get_chain_position_from_chain_id(fork.chain_id)?;
-        // check if the previous element in Chains has a lower block_height
-        let mut current_position = fork_position;
-        let mut current_height = fork.max_height;
+        // if the current height is more than the STABLE_TRANSACTION_CONFIRMATIONS
+        // ahead we are swapping the main chain
+        if main_height + Self::get_stable_transaction_confirmations() < fork.max_height {
+            // requests executed with proofs from the blocks which are about to be orphaned
+            let orphaned_requests: Vec<H256> = (fork.start_height..=main_height)
+                .filter_map(|height| Self::get_block_hash(MAIN_CHAIN_ID, height).ok())
+                .flat_map(|block_hash| ExecutedRequests::<T>::get(block_hash))
+                .collect();
 
-        // swap elements as long as previous block height is smaller
-        while current_position > 0 {
-            // get the previous position
-            let prev_position = current_position - 1;
-            // get the blockchain id
-            let prev_blockchain_id = if let Ok(chain_id) = Self::get_chain_id_from_position(prev_position) {
-                chain_id
-            } else {
-                // swap chain positions if previous doesn't exist and retry
-                Self::swap_chain(prev_position, current_position);
-                continue;
-            };
-
-            // get the previous blockchain height
-            let prev_height = Self::get_block_chain_from_id(prev_blockchain_id)?.max_height;
-            // swap elements if block height is greater
-            if prev_height < current_height {
-                // Check if swap occurs on the main chain element
-                if prev_blockchain_id == MAIN_CHAIN_ID {
-                    // if the previous position is the top element
-                    // and the current height is more than the
-                    // STABLE_TRANSACTION_CONFIRMATIONS ahead
-                    // we are swapping the main chain
-                    if prev_height + Self::get_stable_transaction_confirmations() < current_height {
-                        Self::swap_main_blockchain(&fork)?;
-
-                        // announce the new main chain
-                        let new_chain_tip = BestBlock::<T>::get();
-                        let block_height = BestBlockHeight::<T>::get();
-                        let fork_depth = fork.max_height - fork.start_height;
-                        Self::deposit_event(<Event<T>>::ChainReorg(new_chain_tip, block_height, fork_depth));
-                    } else {
-                        Self::deposit_event(<Event<T>>::ForkAheadOfMainChain(
-                            prev_height,     // main chain height
-                            fork.max_height, // fork height
-                            fork.chain_id,   // fork id
-                        ));
-                    }
-                    // successful reorg
-                    break;
+            Self::swap_main_blockchain(&fork)?;
+
+            // the orphaned transactions no longer settle their requests
+            for request_id in &orphaned_requests {
+                SettlingTransactions::<T>::remove(request_id);
+            }
+
+            // announce the new main chain
+            let new_chain_tip = BestBlock::<T>::get();
+            let block_height = BestBlockHeight::<T>::get();
+            let fork_depth = fork.max_height - fork.start_height;
+            Self::deposit_event(<Event<T>>::ChainReorg(new_chain_tip, block_height, fork_depth));
+
+            let reorg_depth = main_height + 1 - fork.start_height;
+            let max_reorg_depth = Self::max_reorg_depth();
+            if max_reorg_depth > 0 && reorg_depth > max_reorg_depth {
+                Self::freeze_heights(fork.start_height, fork.max_height, reorg_depth);
+            }
+
+            if !orphaned_requests.is_empty() {
+                Self::deposit_event(<Event<T>>::OrphanedRequests(orphaned_requests));
+            }
+        } else {
+            Self::deposit_event(<Event<T>>::ForkAheadOfMainChain(
+                main_height,     // main chain height
+                fork.max_height, // fork height
+                fork.chain_id,   // fork id
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Freezes the heights affected by a reorg which orphaned more than `MaxReorgDepth`
+    /// blocks and flags the relay as invalid, until root lifts the freeze.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_height` - the lowest height on which the main chain changed
+    /// * `end_height` - the height of the new best block
+    /// * `reorg_depth` - the number of orphaned main chain blocks
+    fn freeze_heights(start_height: u32, end_height: u32, reorg_depth: u32) {
+        let (start_height, end_height) = match Self::frozen_heights() {
+            Some((frozen_start, frozen_end)) => (start_height.min(frozen_start), end_height.max(frozen_end)),
+            None => (start_height, end_height),
+        };
+        FrozenHeights::<T>::put((start_height, end_height));
+
+        // the reason records the frozen heights and the depth of the reorg which froze them
+        let reason = (start_height, end_height, reorg_depth).encode();
+        ext::security::insert_error_with_reason::<T>(ErrorCode::InvalidBTCRelay, reason.clone());
+        ext::security::set_status_with_reason::<T>(StatusCode::Error, reason);
+
+        Self::deposit_event(<Event<T>>::FreezeHeights(start_height, end_height, reorg_depth));
+    }
+
+    /// Marks the outputs of a verified transaction which pay the given requests as consumed,
+    /// so that the same payment cannot execute any other request, and records the requests
+    /// as executed in the block which includes the transaction and as settled by the transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw_merkle_proof` - the verified inclusion proof of the transaction
+    /// * `raw_tx` - the verified raw transaction
+    /// * `payments` - (recipient, request id) of each paid request, in the order of the payment outputs
+    pub fn consume_payment_outputs(
+        raw_merkle_proof: &[u8],
+        raw_tx: &[u8],
+        payments: Vec<(BtcAddress, H256)>,
+    ) -> DispatchResult {
+        let transaction = Self::parse_transaction(raw_tx)?;
+        let tx_id = transaction.tx_id();
+        let block_hash = Self::parse_merkle_proof(raw_merkle_proof)?
+            .block_header
+            .hash()
+            .map_err(Error::<T>::from)?;
+
+        // the payment of each request is the next output to its recipient
+        let mut outputs = transaction.outputs.iter().enumerate();
+        for (recipient, request_id) in payments {
+            let vout = outputs
+                .find(|(_, output)| matches!(output.extract_address(), Ok(address) if address == recipient))
+                .map(|(vout, _)| vout as u32)
+                .ok_or(Error::<T>::InvalidPayment)?;
+
+            let output = (tx_id, vout);
+            ensure!(
+                !ConsumedOutputs::<T>::contains_key(output),
+                Error::<T>::OutputAlreadyConsumed
+            );
+            ConsumedOutputs::<T>::insert(output, request_id);
+            SettlingTransactions::<T>::insert(request_id, tx_id);
+            BlockConsumedOutputs::<T>::append(block_hash, output);
+            ExecutedRequests::<T>::append(block_hash, request_id);
+        }
+        Ok(())
+    }
+
+    /// Configures a soft fork deployment whose version bits signalling is tracked.
+    ///
+    /// # Arguments
+    ///
+    /// * `deployment_id` - the id of the deployment
+    /// * `deployment` - the version bit, start time, timeout and threshold of the deployment
+    pub fn insert_deployment(deployment_id: u32, deployment: VersionBitsDeployment) -> DispatchResult {
+        ensure!(
+            deployment.bit < VERSION_BITS_NUM_BITS
+                && deployment.start_time < deployment.timeout
+                && deployment.threshold > 0
+                && deployment.threshold <= DIFFICULTY_ADJUSTMENT_INTERVAL,
+            Error::<T>::InvalidDeployment
+        );
+        Deployments::<T>::insert(deployment_id, deployment);
+        Self::deposit_event(<Event<T>>::SetDeployment(deployment_id));
+        Ok(())
+    }
+
+    /// Computes the BIP9 state of a deployment in the difficulty adjustment period of the
+    /// best block. The state changes at period boundaries, based on the median time past of
+    /// the last block before the new period and the signalling in the previous period.
+    ///
+    /// # Arguments
+    ///
+    /// * `deployment_id` - the id of the deployment
+    pub fn get_deployment_state(deployment_id: u32) -> Result<DeploymentState, DispatchError> {
+        let deployment = Self::deployment(deployment_id).ok_or(Error::<T>::NoDeployment)?;
+        let best_period = Self::get_best_block_height() / DIFFICULTY_ADJUSTMENT_INTERVAL;
+
+        let mut state = deployment.initial_state;
+        for period in Self::first_version_bits_period() + 1..=best_period {
+            state = Self::next_deployment_state(&deployment, state, period)?;
+        }
+        Ok(state)
+    }
+
+    /// Returns the oldest difficulty adjustment period whose signalling is tracked, in which
+    /// deployments are in their `initial_state`.
+    fn first_version_bits_period() -> u32 {
+        Self::version_bits_start_period().max(StartBlockHeight::<T>::get() / DIFFICULTY_ADJUSTMENT_INTERVAL)
+    }
+
+    /// Computes the BIP9 state of a deployment in a difficulty adjustment period, given its
+    /// state in the previous period. The timeout takes precedence over the signalling.
+    ///
+    /// # Arguments
+    ///
+    /// * `deployment` - the deployment
+    /// * `state` - the state of the deployment in the previous period
+    /// * `period` - the difficulty adjustment period
+    fn next_deployment_state(
+        deployment: &VersionBitsDeployment,
+        state: DeploymentState,
+        period: u32,
+    ) -> Result<DeploymentState, DispatchError> {
+        let median_time = || PeriodMedianTimes::<T>::get(period).ok_or(Error::<T>::NoPeriodMedianTime);
+        Ok(match state {
+            DeploymentState::Defined => {
+                let median_time = median_time()?;
+                if median_time >= deployment.timeout {
+                    DeploymentState::Failed
+                } else if median_time >= deployment.start_time {
+                    DeploymentState::Started
                 } else {
-                    // else, simply swap the chain_id ordering in Chains
-                    Self::swap_chain(prev_position, current_position);
+                    DeploymentState::Defined
                 }
+            }
+            DeploymentState::Started => {
+                if median_time()? >= deployment.timeout {
+                    DeploymentState::Failed
+                } else if VersionBitsCounts::<T>::get(period - 1, deployment.bit) >= deployment.threshold {
+                    DeploymentState::LockedIn
+                } else {
+                    DeploymentState::Started
+                }
+            }
+            DeploymentState::LockedIn => DeploymentState::Active,
+            state => state,
+        })
+    }
 
-                // update the current chain to the previous one
-                current_position = prev_position;
-                current_height = prev_height;
-            } else {
-                break;
+    /// Returns the median timestamp of a main chain block and the blocks before it, see BIP113.
+    /// Only stored blocks are considered, so this is `None` if the block itself is not stored.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_height` - the height of the block
+    pub fn get_median_time_past(block_height: u32) -> Option<u32> {
+        if !Self::block_exists(MAIN_CHAIN_ID, block_height) {
+            return None;
+        }
+        let mut timestamps: Vec<u32> = (block_height.saturating_sub(MEDIAN_TIME_SPAN - 1)..=block_height)
+            .filter_map(|height| Self::get_block_hash(MAIN_CHAIN_ID, height).ok())
+            .filter_map(|block_hash| Self::get_block_header_from_hash(block_hash).ok())
+            .map(|block_header| block_header.block_header.timestamp)
+            .collect();
+        timestamps.sort_unstable();
+        Some(timestamps[timestamps.len() / 2])
+    }
+
+    /// Advances all deployments to a difficulty adjustment period and forgets the signalling
+    /// before it, once the block headers before the period are pruned. Returns the number of
+    /// advanced deployments.
+    ///
+    /// # Arguments
+    ///
+    /// * `period` - the difficulty adjustment period, which must follow the oldest tracked one
+    fn prune_version_bits(period: u32) -> u32 {
+        if period != Self::first_version_bits_period() + 1 {
+            return 0;
+        }
+        let mut deployments = Vec::new();
+        for (deployment_id, mut deployment) in Deployments::<T>::iter() {
+            match Self::next_deployment_state(&deployment, deployment.initial_state, period) {
+                Ok(state) => {
+                    deployment.initial_state = state;
+                    deployments.push((deployment_id, deployment));
+                }
+                // keep the signalling, so that computing the state keeps failing loudly
+                Err(_) => return 0,
             }
         }
+        let advanced = deployments.len() as u32;
+        for (deployment_id, deployment) in deployments {
+            Deployments::<T>::insert(deployment_id, deployment);
+        }
 
+        VersionBitsCounts::<T>::remove_prefix(period - 1);
+        PeriodMedianTimes::<T>::remove(period);
+        VersionBitsStartPeriod::<T>::put(period);
+        advanced
+    }
+
+    /// Counts the version bits signalled by a block which was added to the main chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_height` - the height of the block
+    /// * `block_header` - the header of the block
+    fn add_version_bits(block_height: u32, block_header: &BlockHeader) {
+        let period = block_height / DIFFICULTY_ADJUSTMENT_INTERVAL;
+        if block_height % DIFFICULTY_ADJUSTMENT_INTERVAL == 0 {
+            if let Some(median_time) = block_height.checked_sub(1).and_then(Self::get_median_time_past) {
+                PeriodMedianTimes::<T>::insert(period, median_time);
+            }
+        }
+        for bit in Self::signalled_version_bits(block_header) {
+            VersionBitsCounts::<T>::mutate(period, bit, |count| *count = count.saturating_add(1));
+        }
+    }
+
+    /// Rolls back the version bits counted for a block which was removed from the main chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_height` - the height of the block
+    /// * `block_header` - the header of the block
+    fn remove_version_bits(block_height: u32, block_header: &BlockHeader) {
+        let period = block_height / DIFFICULTY_ADJUSTMENT_INTERVAL;
+        if block_height % DIFFICULTY_ADJUSTMENT_INTERVAL == 0 {
+            PeriodMedianTimes::<T>::remove(period);
+        }
+        for bit in Self::signalled_version_bits(block_header) {
+            VersionBitsCounts::<T>::mutate(period, bit, |count| *count = count.saturating_sub(1));
+        }
+    }
+
+    /// Returns the version bits set in a block version which uses the version bits scheme.
+    fn signalled_version_bits(block_header: &BlockHeader) -> Vec<u8> {
+        let version = block_header.version as u32;
+        if version & VERSION_BITS_TOP_MASK != VERSION_BITS_TOP_BITS {
+            return Vec::new();
+        }
+        (0..VERSION_BITS_NUM_BITS)
+            .filter(|bit| version & (1 << bit) != 0)
+            .collect()
+    }
+
+    /// Approves a trusted checkpoint, which must not conflict with the main chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_height` - the height of the bitcoin block
+    /// * `block_hash` - the hash of the bitcoin block
+    pub fn insert_checkpoint(block_height: u32, block_hash: H256Le) -> DispatchResult {
+        if let Ok(main_chain_hash) = Self::get_block_hash(MAIN_CHAIN_ID, block_height) {
+            ensure!(main_chain_hash == block_hash, Error::<T>::CheckpointMismatch);
+        }
+        Checkpoints::<T>::insert(block_height, block_hash);
+        Self::deposit_event(<Event<T>>::SetCheckpoint(block_height, block_hash));
         Ok(())
     }
 
-    /// Insert a new fork into the Chains mapping sorted by its max height
+    /// Checks that a block at `block_height` matches the checkpoint at this height, if any.
+    fn ensure_checkpoint_matches(block_height: u32, block_hash: H256Le) -> Result<(), DispatchError> {
+        match Self::checkpoint(block_height) {
+            Some(checkpoint) => {
+                ensure!(checkpoint == block_hash, Error::<T>::CheckpointMismatch);
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Checks that a new fork with its tip at `block_height` may be created.
     ///
     /// # Arguments
     ///
-    /// * `blockchain` - new blockchain element
-    fn insert_sorted(blockchain: &BlockChain) -> Result<(), DispatchError> {
-        // get a sorted vector over the Chains elements
-        // NOTE: LinkedStorageMap iterators are not sorted over the keys
-        let mut chains = Chains::<T>::iter().collect::<Vec<(u32, u32)>>();
-        chains.sort_by_key(|k| k.0);
-
-        let max_chain_element = chains.len() as u32;
-        // define the position of the new blockchain
-        // by default, we insert it as the last element
-        let mut position_blockchain = max_chain_element;
-
-        // Starting from the second highest element, find where to insert the new fork
-        // the previous element's block height should be higher or equal
-        // the next element's block height should be lower or equal
-        // NOTE: we never want to insert a new main chain through this function
-        for (curr_position, curr_chain_id) in chains.iter().skip(1) {
-            // get the height of the current chain_id
-            let curr_height = Self::get_block_chain_from_id(*curr_chain_id)?.max_height;
-
-            // if the height of the current blockchain is lower than
-            // the new blockchain, it should be inserted at that position
-            if curr_height <= blockchain.max_height {
-                position_blockchain = *curr_position;
-                break;
-            };
+    /// * `block_height` - height of the first block of the new fork
+    fn ensure_fork_allowed(block_height: u32) -> Result<(), DispatchError> {
+        let max_forks = Self::max_forks();
+        ensure!(
+            max_forks == 0 || ForkCount::<T>::get() < max_forks,
+            Error::<T>::TooManyForks
+        );
+        ensure!(block_height >= NextStaleForkHeight::<T>::get(), Error::<T>::StaleFork);
+        Ok(())
+    }
+
+    /// Removes forks whose tip is at least `StaleForkDepth` blocks behind the best
+    /// block, together with their block headers. At most `MAX_STALE_FORK_CHECKS_PER_BLOCK`
+    /// tip heights are checked and forks removed per call.
+    ///
+    /// Returns the consumed weight.
+    fn remove_stale_forks() -> Weight {
+        let db_weight = T::DbWeight::get();
+        let depth = Self::stale_fork_depth();
+        if depth == 0 {
+            return db_weight.reads(1);
         }
 
-        // insert the new fork into the chains element
-        Self::set_chain_from_position_and_id(max_chain_element, blockchain.chain_id);
-        // starting from the last element swap the positions until
-        // the new blockchain is at the position_blockchain
-        for curr_position in (position_blockchain + 1..max_chain_element + 1).rev() {
-            // stop when the blockchain element is at it's
-            // designated position
-            if curr_position < position_blockchain {
+        let best_height = Self::get_best_block_height();
+        let mut height = NextStaleForkHeight::<T>::get().max(StartBlockHeight::<T>::get());
+        let mut checks = 0;
+        let mut weight = db_weight.reads_writes(4, 1);
+
+        while height.saturating_add(depth) <= best_height && checks < MAX_STALE_FORK_CHECKS_PER_BLOCK {
+            let chain_ids = ForkTips::<T>::iter_prefix(height)
+                .map(|(chain_id, _)| chain_id)
+                .take((MAX_STALE_FORK_CHECKS_PER_BLOCK - checks) as usize)
+                .collect::<Vec<u32>>();
+            weight = weight.saturating_add(db_weight.reads(1));
+
+            for chain_id in chain_ids {
+                weight = weight.saturating_add(Self::remove_stale_fork(chain_id));
+                checks += 1;
+            }
+
+            if Self::fork_tip_exists(height) {
+                // continue with the remaining forks at this height in the next block
                 break;
-            };
-            let prev_position = curr_position - 1;
-            // swap the current element with the previous one
-            Self::swap_chain(curr_position, prev_position);
+            }
+            height += 1;
+            checks += 1;
         }
-        Ok(())
+
+        NextStaleForkHeight::<T>::put(height);
+
+        weight
+    }
+
+    /// Removes a stale fork and all of its block headers, notifying `OnStaleBlock` of the
+    /// relayer which submitted each header.
+    ///
+    /// # Arguments
+    ///
+    /// * `chain_id` - the id of the fork to remove
+    ///
+    /// Returns the consumed weight.
+    fn remove_stale_fork(chain_id: u32) -> Weight {
+        let db_weight = T::DbWeight::get();
+        let fork = match Self::get_block_chain_from_id(chain_id) {
+            Ok(fork) => fork,
+            Err(_) => return db_weight.reads(1),
+        };
+
+        let mut hook_weight: Weight = 0;
+        for height in fork.start_height..=fork.max_height {
+            if let Ok(block_hash) = Self::get_block_hash(chain_id, height) {
+                let block_header = BlockHeaders::<T>::take(block_hash);
+                hook_weight =
+                    hook_weight.saturating_add(T::OnStaleBlock::on_stale_block_header(&block_header.account_id));
+                // consumed outputs are kept, the transactions may be included in the main chain again
+                ExecutedRequests::<T>::remove(block_hash);
+                BlockConsumedOutputs::<T>::remove(block_hash);
+            }
+        }
+        ChainsHashes::<T>::remove_prefix(chain_id);
+        Self::remove_blockchain_from_chain_index(chain_id);
+        Self::remove_fork_tip(&fork);
+
+        Self::deposit_event(<Event<T>>::RemoveStaleFork(chain_id, fork.max_height));
+
+        let blocks = (fork.max_height - fork.start_height + 1) as Weight;
+        db_weight
+            .reads_writes(2 + 2 * blocks, 4 + 4 * blocks)
+            .saturating_add(hook_weight)
     }
 
     /// Flag an error in a block header. This function is called by the
@@ -1629,6 +2534,14 @@ impl<T: Config> Pallet<T> {
         let main_chain = Self::get_block_chain_from_id(MAIN_CHAIN_ID)?;
         ensure!(!main_chain.is_invalid(), Error::<T>::Invalid);
 
+        // Ensure the block is not affected by a deep reorg
+        if let Some((start_height, end_height)) = Self::frozen_heights() {
+            ensure!(
+                block_height < start_height || block_height > end_height,
+                Error::<T>::FrozenHeight
+            );
+        }
+
         // Check if a NO_DATA block exists at a lower height than block_height
         if main_chain.is_no_data() {
             match main_chain.no_data.iter().next_back() {
@@ -1640,27 +2553,155 @@ impl<T: Config> Pallet<T> {
     }
 
     fn ensure_no_ongoing_fork(best_block_height: u32) -> Result<(), DispatchError> {
-        // check if there is a next best fork
-        match Self::get_chain_id_from_position(1) {
-            // if yes, check that the main chain is at least Self::confirmations() ahead
-            Ok(id) => {
-                let next_best_fork_height = Self::get_block_chain_from_id(id)?.max_height;
-
-                runtime_print!("Best block height: {}", best_block_height);
-                runtime_print!("Next best fork height: {}", next_best_fork_height);
-                // fail if there is an ongoing fork
-                ensure!(
-                    best_block_height >= next_best_fork_height + Self::get_stable_transaction_confirmations(),
-                    Error::<T>::OngoingFork
-                );
-            }
-            // else, do nothing if there is no fork
-            Err(_) => {}
+        // the main chain must be at least Self::confirmations() ahead of every fork,
+        // and forks can be at most Self::confirmations() ahead of the main chain
+        // (otherwise they would have become the main chain)
+        let confirmations = Self::get_stable_transaction_confirmations();
+        let lowest_conflicting_height = best_block_height.saturating_add(1).saturating_sub(confirmations);
+        let highest_conflicting_height = best_block_height.saturating_add(confirmations);
+
+        for height in lowest_conflicting_height..=highest_conflicting_height {
+            // fail if there is an ongoing fork
+            ensure!(!Self::fork_tip_exists(height), Error::<T>::OngoingFork);
         }
         Ok(())
     }
 
+    /// Prunes main chain block headers (and their hash indices) which are more than
+    /// `PruningWindow` blocks below the best block. At most `MAX_PRUNED_BLOCKS_PER_BLOCK`
+    /// heights are processed per call. Headers at difficulty adjustment boundaries are
+    /// kept until the next boundary is pruned, since they are required to compute the
+    /// target of the next retarget. The records of pruned headers are forgotten another
+    /// `PruningWindow` heights later, after which proofs against them fail as unknown.
+    /// Batched payments expiring at a processed height are removed as well, and the version
+    /// bits signalling before a pruned retarget anchor is folded into the deployments.
+    ///
+    /// Returns the consumed weight.
+    fn prune_block_headers() -> Weight {
+        let db_weight = T::DbWeight::get();
+        let window = Self::pruning_window();
+        if window == 0 {
+            return db_weight.reads(1);
+        }
+
+        let cutoff = Self::get_best_block_height().saturating_sub(window);
+        // chains initialized before pruning was introduced have no prune height yet
+        let mut height = NextPruneHeight::<T>::get().max(StartBlockHeight::<T>::get());
+        let mut pruned = 0;
+        let mut expired = 0;
+        let mut deployments = 0;
+
+        while height < cutoff && pruned < MAX_PRUNED_BLOCKS_PER_BLOCK {
+            if height % DIFFICULTY_ADJUSTMENT_INTERVAL == 0 {
+                // keep this retarget anchor, the previous one is no longer needed
+                if let Some(prev_anchor) = height.checked_sub(DIFFICULTY_ADJUSTMENT_INTERVAL) {
+                    Self::prune_main_chain_block(prev_anchor, height);
+                }
+                deployments += Self::prune_version_bits(height / DIFFICULTY_ADJUSTMENT_INTERVAL);
+            } else {
+                Self::prune_main_chain_block(height, height);
+            }
+            if let Some(forgotten_height) = height.checked_sub(window) {
+                if let Some(block_hash) = PrunedBlockHashes::<T>::take(forgotten_height) {
+                    PrunedBlockHeights::<T>::remove(block_hash);
+                }
+            }
+            for (op_return, ()) in ExpiringBatchPayments::<T>::drain_prefix(height) {
+                // renewed records expire later
+                if BatchPayments::<T>::get(op_return).map(|(_, expiry)| expiry) == Some(height) {
+                    BatchPayments::<T>::remove(op_return);
+                }
+                expired += 1;
+            }
+            height += 1;
+            pruned += 1;
+        }
+
+        NextPruneHeight::<T>::put(height);
+
+        db_weight.reads_writes(
+            4 + 5 * pruned as Weight + 2 * expired as Weight + 2 * deployments as Weight,
+            1 + 8 * pruned as Weight + 2 * expired as Weight + deployments as Weight,
+        )
+    }
+
+    /// Removes the main chain block header at the given height, if it is stored.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_height` - height of the main chain block to remove
+    /// * `prune_height` - pruning step at which the block is removed
+    fn prune_main_chain_block(block_height: u32, prune_height: u32) {
+        if !Self::block_exists(MAIN_CHAIN_ID, block_height) {
+            return;
+        }
+        let block_hash = ChainsHashes::<T>::take(MAIN_CHAIN_ID, block_height);
+        BlockHeaders::<T>::remove(block_hash);
+        PrunedBlockHeights::<T>::insert(block_hash, block_height);
+        PrunedBlockHashes::<T>::insert(prune_height, block_hash);
+        Self::remove_block_payments(block_hash);
+    }
+
+    /// Removes the requests executed with an inclusion proof from a block, together with the
+    /// outputs which settled them. Returns the number of removed outputs. The settling
+    /// transactions are kept, since the block can no longer be orphaned.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_hash` - hash of the removed block
+    fn remove_block_payments(block_hash: H256Le) -> u32 {
+        ExecutedRequests::<T>::remove(block_hash);
+        let outputs = BlockConsumedOutputs::<T>::take(block_hash);
+        for output in &outputs {
+            ConsumedOutputs::<T>::remove(output);
+        }
+        outputs.len() as u32
+    }
+
+    /// Checks if an unknown block header belongs to the recently pruned part of the main chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `block_hash` - the hash of the header which is not stored in the relay
+    fn is_block_pruned(block_hash: H256Le) -> bool {
+        PrunedBlockHeights::<T>::contains_key(block_hash)
+    }
+
+    /// Initializes the parameters which were added after genesis on chains which predate them.
+    ///
+    /// Returns the consumed weight.
+    fn migrate_parameters() -> Weight {
+        PruningWindow::<T>::put(DEFAULT_PRUNING_WINDOW);
+        MaxForks::<T>::put(DEFAULT_MAX_FORKS);
+        StaleForkDepth::<T>::put(DEFAULT_STALE_FORK_DEPTH);
+        MaxReorgDepth::<T>::put(DEFAULT_MAX_REORG_DEPTH);
+        T::DbWeight::get().writes(4)
+    }
+
+    /// Indexes the forks of chains which predate `ForkTips` by their tip height, and removes
+    /// the sorted `Chains` map which the index replaces.
+    ///
+    /// Returns the consumed weight.
+    fn migrate_fork_index() -> Weight {
+        let db_weight = T::DbWeight::get();
+        let mut forks = 0;
+        for (chain_id, blockchain) in ChainsIndex::<T>::iter() {
+            if chain_id != MAIN_CHAIN_ID {
+                ForkTips::<T>::insert(blockchain.max_height, chain_id, ());
+                forks += 1;
+            }
+        }
+        ForkCount::<T>::put(forks);
+        remove_storage_prefix(<Self as PalletInfoAccess>::name().as_bytes(), b"Chains", &[]);
+
+        db_weight.reads_writes(1 + forks as Weight, 2 + 2 * forks as Weight)
+    }
+
     fn recover_if_needed() -> Result<(), DispatchError> {
+        if FrozenHeights::<T>::exists() {
+            // a deep reorg can only be cleared by root
+            return Ok(());
+        }
         if ext::security::is_parachain_error_invalid_btcrelay::<T>()
             || ext::security::is_parachain_error_no_data_btcrelay::<T>()
         {
@@ -1672,6 +2713,36 @@ impl<T: Config> Pallet<T> {
     }
 }
 
+impl<T: Config> OnErrorRecovery for Pallet<T> {
+    /// Clears the flags of a removed `NoDataBTCRelay` or `InvalidBTCRelay` error from the main
+    /// chain, so that transactions can be verified again once governance removes the error.
+    fn on_error_recovery(error_code: &ErrorCode) {
+        let mut main_chain = match Self::get_block_chain_from_id(MAIN_CHAIN_ID) {
+            Ok(main_chain) => main_chain,
+            Err(_) => return,
+        };
+        let heights = match error_code {
+            ErrorCode::NoDataBTCRelay => sp_std::mem::take(&mut main_chain.no_data),
+            ErrorCode::InvalidBTCRelay => sp_std::mem::take(&mut main_chain.invalid),
+            _ => return,
+        };
+        if heights.is_empty() {
+            return;
+        }
+        Self::mutate_block_chain_from_id(MAIN_CHAIN_ID, main_chain);
+
+        for block_height in heights {
+            if let Ok(block_hash) = Self::get_block_hash(MAIN_CHAIN_ID, block_height) {
+                Self::deposit_event(<Event<T>>::ClearBlockError(
+                    block_hash,
+                    MAIN_CHAIN_ID,
+                    error_code.clone(),
+                ));
+            }
+        }
+    }
+}
+
 impl<T: Config> From<BitcoinError> for Error<T> {
     fn from(err: BitcoinError) -> Self {
         match err {
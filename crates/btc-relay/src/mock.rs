@@ -1,12 +1,13 @@
 use crate as btc_relay;
-use crate::{Config, Error};
-use frame_support::{parameter_types, traits::GenesisBuild};
+use crate::{Config, Error, OnStaleBlockHeader};
+use frame_support::{parameter_types, traits::GenesisBuild, weights::Weight};
 use mocktopus::mocking::clear_mocks;
 use sp_core::H256;
 use sp_runtime::{
     testing::Header,
     traits::{BlakeTwo256, IdentityLookup},
 };
+use std::cell::RefCell;
 
 pub const BITCOIN_CONFIRMATIONS: u32 = 6;
 pub const PARACHAIN_CONFIRMATIONS: u64 = 20;
@@ -74,6 +75,24 @@ parameter_types! {
 impl Config for Test {
     type Event = TestEvent;
     type WeightInfo = ();
+    type OnStaleBlock = MockStaleBlock;
+}
+
+/// Weight returned by the stale block callback.
+pub const STALE_BLOCK_WEIGHT: Weight = 1_000;
+
+thread_local! {
+    pub static STALE_BLOCK_RELAYERS: RefCell<Vec<AccountId>> = RefCell::new(Vec::new());
+}
+
+/// Records the relayers passed to the stale block callback.
+pub struct MockStaleBlock;
+
+impl OnStaleBlockHeader<AccountId> for MockStaleBlock {
+    fn on_stale_block_header(relayer: &AccountId) -> Weight {
+        STALE_BLOCK_RELAYERS.with(|relayers| relayers.borrow_mut().push(*relayer));
+        STALE_BLOCK_WEIGHT
+    }
 }
 
 parameter_types! {
@@ -89,6 +108,8 @@ impl pallet_timestamp::Config for Test {
 
 impl security::Config for Test {
     type Event = TestEvent;
+    type OnRecovery = BTCRelay;
+    type PausableCalls = ();
 }
 
 pub type TestEvent = Event;
@@ -107,6 +128,12 @@ impl ExtBuilder {
             disable_difficulty_check: false,
             disable_inclusion_check: false,
             disable_op_return_check: false,
+            pruning_window: 0,
+            max_forks: 0,
+            stale_fork_depth: 0,
+            checkpoints: vec![],
+            max_reorg_depth: 0,
+            deployments: vec![],
         }
         .assimilate_storage(&mut storage)
         .unwrap();
@@ -13,23 +13,20 @@ use sp_std::{collections::btree_set::BTreeSet, convert::TryInto, str::FromStr};
 
 /// # Getters and setters
 ///
-/// get_chain_position_from_chain_id
-/// set_chain_from_position_and_id
+/// insert_fork_tip
+/// fork_tip_exists
 #[test]
-fn get_chain_position_from_chain_id_succeeds() {
+fn insert_fork_tip_succeeds() {
     run_test(|| {
-        // position and id of chains
-        let mut chains_pos_id: Vec<(u32, u32)> = Vec::new();
-        chains_pos_id.append(&mut vec![(0, 0), (1, 1), (2, 3), (3, 6)]);
+        // chain ids and heights of forks
+        let forks = vec![(1, 10), (3, 12), (6, 10)];
 
-        for (pos, id) in chains_pos_id.iter() {
-            // insert chain
-            BTCRelay::set_chain_from_position_and_id(*pos, *id);
-            // check that chain is in right position
-            let curr_pos = BTCRelay::get_chain_position_from_chain_id(*id).unwrap();
-
-            assert_eq!(curr_pos, *pos);
+        for (id, height) in forks.iter() {
+            BTCRelay::insert_fork_tip(&get_empty_block_chain_from_chain_id_and_height(*id, 5, *height));
+            assert!(BTCRelay::fork_tip_exists(*height));
         }
+        assert!(!BTCRelay::fork_tip_exists(11));
+        assert_eq!(crate::ForkCount::<Test>::get(), 3);
     })
 }
 
@@ -102,12 +99,13 @@ fn test_get_block_chain_from_id_empty_chain_fails() {
 #[test]
 fn initialize_once_succeeds() {
     run_test(|| {
-        let block_height: u32 = 1;
+        let block_height: u32 = 0;
         let block_header = RawBlockHeader::from_hex(sample_block_header_hex()).unwrap();
         let block_header_hash = block_header.hash();
         BTCRelay::best_block_exists.mock_safe(|| MockResult::Return(false));
 
-        assert_ok!(BTCRelay::initialize(3, block_header, block_height));
+        assert_ok!(BTCRelay::insert_checkpoint(block_height, block_header_hash));
+        assert_ok!(BTCRelay::initialize(3, vec![block_header], block_height));
 
         let init_event = TestEvent::btc_relay(Event::Initialized(block_height, block_header_hash, 3));
         assert!(System::events().iter().any(|a| a.event == init_event));
@@ -123,12 +121,115 @@ fn initialize_best_block_already_set_fails() {
         BTCRelay::best_block_exists.mock_safe(|| MockResult::Return(true));
 
         assert_err!(
-            BTCRelay::initialize(3, raw_block_header, block_height),
+            BTCRelay::initialize(3, vec![raw_block_header], block_height),
             TestError::AlreadyInitialized
         );
     })
 }
 
+#[test]
+fn initialize_with_block_headers_succeeds() {
+    run_test(|| {
+        let blocks = generate_block_chain(5);
+        let raw_block_headers = blocks.iter().map(get_raw_header).collect::<Vec<_>>();
+
+        assert_ok!(BTCRelay::insert_checkpoint(0, blocks[0].header.hash().unwrap()));
+        assert_ok!(BTCRelay::initialize(3, raw_block_headers, 0));
+
+        assert_eq!(BTCRelay::get_best_block_height(), 4);
+        assert_eq!(BTCRelay::get_best_block(), blocks[4].header.hash().unwrap());
+    })
+}
+
+#[test]
+fn initialize_without_checkpoint_fails() {
+    run_test(|| {
+        let blocks = generate_block_chain(1);
+
+        assert_err!(
+            BTCRelay::initialize(3, vec![get_raw_header(&blocks[0])], 0),
+            TestError::NoCheckpoint
+        );
+    })
+}
+
+#[test]
+fn initialize_checkpoint_mismatch_fails() {
+    run_test(|| {
+        let blocks = generate_block_chain(2);
+
+        assert_ok!(BTCRelay::insert_checkpoint(0, blocks[1].header.hash().unwrap()));
+        assert_err!(
+            BTCRelay::initialize(3, vec![get_raw_header(&blocks[0])], 0),
+            TestError::CheckpointMismatch
+        );
+    })
+}
+
+#[test]
+fn initialize_not_epoch_start_fails() {
+    run_test(|| {
+        let blocks = generate_block_chain(1);
+
+        assert_ok!(BTCRelay::insert_checkpoint(1, blocks[0].header.hash().unwrap()));
+        assert_err!(
+            BTCRelay::initialize(3, vec![get_raw_header(&blocks[0])], 1),
+            TestError::NotEpochStart
+        );
+    })
+}
+
+#[test]
+fn initialize_not_epoch_start_without_difficulty_check_fails() {
+    run_test(|| {
+        crate::DisableDifficultyCheck::<Test>::put(true);
+        let blocks = generate_block_chain(1);
+
+        assert_ok!(BTCRelay::insert_checkpoint(1, blocks[0].header.hash().unwrap()));
+        assert_err!(
+            BTCRelay::initialize(3, vec![get_raw_header(&blocks[0])], 1),
+            TestError::NotEpochStart
+        );
+    })
+}
+
+#[test]
+fn initialize_without_block_headers_fails() {
+    run_test(|| {
+        assert_err!(BTCRelay::initialize(3, vec![], 0), TestError::InvalidInitialHeaders);
+    })
+}
+
+#[test]
+fn store_block_header_checkpoint_mismatch_fails() {
+    run_test(|| {
+        let blocks = generate_block_chain(3);
+
+        assert_ok!(BTCRelay::insert_checkpoint(0, blocks[0].header.hash().unwrap()));
+        assert_ok!(BTCRelay::insert_checkpoint(2, H256Le::from_bytes_le(&[1; 32])));
+        assert_ok!(BTCRelay::initialize(3, vec![get_raw_header(&blocks[0])], 0));
+        assert_ok!(BTCRelay::store_block_header(&3, get_raw_header(&blocks[1])));
+        assert_err!(
+            BTCRelay::store_block_header(&3, get_raw_header(&blocks[2])),
+            TestError::CheckpointMismatch
+        );
+    })
+}
+
+#[test]
+fn insert_checkpoint_conflicting_main_chain_fails() {
+    run_test(|| {
+        let blocks = store_generated_block_chain(3);
+
+        assert_err!(
+            BTCRelay::insert_checkpoint(1, blocks[2].header.hash().unwrap()),
+            TestError::CheckpointMismatch
+        );
+        assert_ok!(BTCRelay::insert_checkpoint(1, blocks[1].header.hash().unwrap()));
+        assert_eq!(BTCRelay::checkpoint(1), Some(blocks[1].header.hash().unwrap()));
+    })
+}
+
 /// store_block_header function
 #[test]
 fn store_block_header_on_mainchain_succeeds() {
@@ -231,7 +332,7 @@ fn check_and_do_reorg_is_main_chain_succeeds() {
 }
 
 #[test]
-fn check_and_do_reorg_fork_id_not_found() {
+fn check_and_do_reorg_main_chain_not_found() {
     run_test(|| {
         let chain_ref: u32 = 99;
         let start_height: u32 = 3;
@@ -239,74 +340,54 @@ fn check_and_do_reorg_fork_id_not_found() {
 
         let blockchain = get_empty_block_chain_from_chain_id_and_height(chain_ref, start_height, block_height);
 
-        assert_err!(BTCRelay::check_and_do_reorg(&blockchain), TestError::ForkIdNotFound);
+        assert_err!(BTCRelay::check_and_do_reorg(&blockchain), TestError::InvalidChainID);
     })
 }
 
 #[test]
-fn check_and_do_reorg_swap_fork_position() {
+fn check_and_do_reorg_fork_behind_main_chain() {
     run_test(|| {
-        // insert the main chain in Chains and ChainsIndex
+        // insert the main chain in ChainsIndex
         let main_chain_ref: u32 = 0;
         let main_start_height: u32 = 3;
         let main_block_height: u32 = 110;
-        let main_position: u32 = 0;
         let main = get_empty_block_chain_from_chain_id_and_height(main_chain_ref, main_start_height, main_block_height);
-        BTCRelay::set_chain_from_position_and_id(main_position, main_chain_ref);
         BTCRelay::set_block_chain_from_id(main_chain_ref, &main);
 
-        // insert the fork chain in Chains and ChainsIndex
+        // insert the fork chain in ForkTips and ChainsIndex
         let fork_chain_ref: u32 = 4;
         let fork_start_height: u32 = 20;
         let fork_block_height: u32 = 100;
-        let fork_position: u32 = 2;
         let fork = get_empty_block_chain_from_chain_id_and_height(fork_chain_ref, fork_start_height, fork_block_height);
-        BTCRelay::set_chain_from_position_and_id(fork_position, fork_chain_ref);
+        BTCRelay::insert_fork_tip(&fork);
         BTCRelay::set_block_chain_from_id(fork_chain_ref, &fork);
 
-        // insert the swap chain in Chains and ChainsIndex
-        let swap_chain_ref: u32 = 3;
-        let swap_start_height: u32 = 43;
-        let swap_block_height: u32 = 99;
-        let swap_position: u32 = 1;
-        let swap = get_empty_block_chain_from_chain_id_and_height(swap_chain_ref, swap_start_height, swap_block_height);
-        BTCRelay::set_chain_from_position_and_id(swap_position, swap_chain_ref);
-        BTCRelay::set_block_chain_from_id(swap_chain_ref, &swap);
-
-        // check that fork is at its initial position
-        let current_position = BTCRelay::get_chain_position_from_chain_id(fork_chain_ref).unwrap();
-
-        assert_eq!(current_position, fork_position);
+        BTCRelay::swap_main_blockchain.mock_safe(|_| panic!("Should not be called"));
 
         assert_ok!(BTCRelay::check_and_do_reorg(&fork));
-        // assert that positions have been swapped
-        let new_position = BTCRelay::get_chain_position_from_chain_id(fork_chain_ref).unwrap();
-        assert_eq!(new_position, swap_position);
 
         // assert the main chain has not changed
         let curr_main_chain = BTCRelay::get_block_chain_from_id(main_chain_ref);
         assert_eq!(curr_main_chain, Ok(main));
+        assert!(BTCRelay::fork_tip_exists(fork_block_height));
     })
 }
 
 #[test]
 fn check_and_do_reorg_new_fork_is_main_chain() {
     run_test(|| {
-        // insert the main chain in Chains and ChainsIndex
+        // insert the main chain in ChainsIndex
         let main_chain_ref: u32 = 0;
         let main_start_height: u32 = 4;
         let main_block_height: u32 = 110;
-        let main_position: u32 = 0;
         let main = get_empty_block_chain_from_chain_id_and_height(main_chain_ref, main_start_height, main_block_height);
-        BTCRelay::set_chain_from_position_and_id(main_position, main_chain_ref);
         BTCRelay::set_block_chain_from_id(main_chain_ref, &main);
 
-        // insert the fork chain in Chains and ChainsIndex
+        // insert the fork chain in ForkTips and ChainsIndex
         let fork_chain_ref: u32 = 4;
         let fork_block_height: u32 = 117;
-        let fork_position: u32 = 1;
         let fork = get_empty_block_chain_from_chain_id_and_height(fork_chain_ref, main_start_height, fork_block_height);
-        BTCRelay::set_chain_from_position_and_id(fork_position, fork_chain_ref);
+        BTCRelay::insert_fork_tip(&fork);
         BTCRelay::set_block_chain_from_id(fork_chain_ref, &fork);
 
         // set the best block
@@ -314,11 +395,6 @@ fn check_and_do_reorg_new_fork_is_main_chain() {
         BTCRelay::set_best_block(best_block_hash);
         BTCRelay::set_best_block_height(fork_block_height);
 
-        // check that fork is at its initial position
-        let current_position = BTCRelay::get_chain_position_from_chain_id(fork_chain_ref).unwrap();
-
-        assert_eq!(current_position, fork_position);
-
         BTCRelay::swap_main_blockchain.mock_safe(|_| MockResult::Return(Ok(())));
 
         assert_ok!(BTCRelay::check_and_do_reorg(&fork));
@@ -331,24 +407,322 @@ fn check_and_do_reorg_new_fork_is_main_chain() {
         assert!(System::events().iter().any(|a| a.event == reorg_event));
     })
 }
+#[test]
+fn check_and_do_reorg_deep_reorg_freezes_heights() {
+    run_test(|| {
+        crate::MaxReorgDepth::<Test>::put(6);
+
+        // insert the main chain in ChainsIndex
+        let main_chain_ref: u32 = 0;
+        let main_block_height: u32 = 110;
+        let main = get_empty_block_chain_from_chain_id_and_height(main_chain_ref, 4, main_block_height);
+        BTCRelay::set_block_chain_from_id(main_chain_ref, &main);
+
+        // insert a fork which orphans the last 11 main chain blocks
+        let fork_chain_ref: u32 = 4;
+        let fork_start_height: u32 = 100;
+        let fork_block_height: u32 = 117;
+        let fork = get_empty_block_chain_from_chain_id_and_height(fork_chain_ref, fork_start_height, fork_block_height);
+        BTCRelay::insert_fork_tip(&fork);
+        BTCRelay::set_block_chain_from_id(fork_chain_ref, &fork);
+
+        BTCRelay::swap_main_blockchain.mock_safe(|_| MockResult::Return(Ok(())));
+
+        assert_ok!(BTCRelay::check_and_do_reorg(&fork));
+        assert_eq!(BTCRelay::frozen_heights(), Some((fork_start_height, fork_block_height)));
+        assert!(Security::is_parachain_error_invalid_btcrelay());
+        let event = TestEvent::btc_relay(Event::FreezeHeights(fork_start_height, fork_block_height, 11));
+        assert!(System::events().iter().any(|a| a.event == event));
+        // the change is recorded with the frozen heights as reason
+        let reason = codec::Encode::encode(&(fork_start_height, fork_block_height, 11u32));
+        let history = Security::get_status_history();
+        assert_eq!(history.len(), 2);
+        assert!(history.iter().all(|change| change.reason == reason));
+
+        // no transactions can be verified against the affected heights
+        assert_err!(
+            BTCRelay::transaction_verification_allowed(fork_start_height + 1),
+            TestError::FrozenHeight
+        );
+        assert_ok!(BTCRelay::transaction_verification_allowed(fork_start_height - 1));
+
+        // the relay is not recovered automatically while frozen
+        assert_ok!(BTCRelay::recover_if_needed());
+        assert!(Security::is_parachain_error_invalid_btcrelay());
+    })
+}
+
+#[test]
+fn check_and_do_reorg_shallow_reorg_does_not_freeze_heights() {
+    run_test(|| {
+        crate::MaxReorgDepth::<Test>::put(6);
+
+        let main = get_empty_block_chain_from_chain_id_and_height(0, 4, 110);
+        BTCRelay::set_block_chain_from_id(0, &main);
+
+        // the fork orphans exactly MaxReorgDepth blocks
+        let fork = get_empty_block_chain_from_chain_id_and_height(4, 105, 117);
+        BTCRelay::insert_fork_tip(&fork);
+        BTCRelay::set_block_chain_from_id(4, &fork);
+
+        BTCRelay::swap_main_blockchain.mock_safe(|_| MockResult::Return(Ok(())));
+
+        assert_ok!(BTCRelay::check_and_do_reorg(&fork));
+        assert_eq!(BTCRelay::frozen_heights(), None);
+        assert!(!Security::is_parachain_error_invalid_btcrelay());
+    })
+}
+
+#[test]
+fn check_and_do_reorg_reports_orphaned_requests() {
+    run_test(|| {
+        let main = get_empty_block_chain_from_chain_id_and_height(0, 4, 110);
+        BTCRelay::set_block_chain_from_id(0, &main);
+
+        // a request was executed with a proof from the main chain block at height 105
+        let proof = sample_merkle_proof();
+        let block_hash = proof.block_header.hash().unwrap();
+        BTCRelay::insert_block_hash(0, 105, block_hash);
+        BTCRelay::parse_merkle_proof.mock_safe(move |_| MockResult::Return(Ok(proof.clone())));
+        let raw_tx = hex::decode(sample_example_real_rawtx()).unwrap();
+        let recipient_btc_address =
+            BtcAddress::P2SH(H160::from_str(&"4a1154d50b03292b3024370901711946cb7cccc3").unwrap());
+        let request_id = H256::from_low_u64_be(1);
+        assert_ok!(BTCRelay::consume_payment_outputs(
+            &[0u8; 100],
+            &raw_tx,
+            vec![(recipient_btc_address, request_id)]
+        ));
+
+        let fork = get_empty_block_chain_from_chain_id_and_height(4, 100, 117);
+        BTCRelay::insert_fork_tip(&fork);
+        BTCRelay::set_block_chain_from_id(4, &fork);
+
+        BTCRelay::swap_main_blockchain.mock_safe(|_| MockResult::Return(Ok(())));
+
+        assert_ok!(BTCRelay::check_and_do_reorg(&fork));
+        let event = TestEvent::btc_relay(Event::OrphanedRequests(vec![request_id]));
+        assert!(System::events().iter().any(|a| a.event == event));
+    })
+}
+
+#[test]
+fn unfreeze_heights_succeeds() {
+    run_test(|| {
+        let main = get_empty_block_chain_from_chain_id_and_height(0, 4, 110);
+        BTCRelay::set_block_chain_from_id(0, &main);
+        crate::FrozenHeights::<Test>::put((100, 117));
+        Security::insert_error(ErrorCode::InvalidBTCRelay);
+        Security::set_status(StatusCode::Error);
+
+        assert_err!(
+            BTCRelay::unfreeze_heights(Origin::signed(3)),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(BTCRelay::unfreeze_heights(Origin::root()));
+
+        assert_eq!(BTCRelay::frozen_heights(), None);
+        assert!(!Security::is_parachain_error_invalid_btcrelay());
+        let event = TestEvent::btc_relay(Event::UnfreezeHeights(100, 117));
+        assert!(System::events().iter().any(|a| a.event == event));
+        assert_ok!(BTCRelay::transaction_verification_allowed(105));
+
+        assert_err!(BTCRelay::unfreeze_heights(Origin::root()), TestError::NotFrozen);
+    })
+}
+
+#[test]
+fn set_deployment_succeeds() {
+    run_test(|| {
+        let deployment = sample_deployment();
+        assert_err!(
+            BTCRelay::set_deployment(Origin::signed(3), 1, deployment),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(BTCRelay::set_deployment(Origin::root(), 1, deployment));
+        assert_eq!(BTCRelay::deployment(1), Some(deployment));
+
+        assert_ok!(BTCRelay::remove_deployment(Origin::root(), 1));
+        assert_eq!(BTCRelay::deployment(1), None);
+        assert_err!(BTCRelay::remove_deployment(Origin::root(), 1), TestError::NoDeployment);
+    })
+}
+
+#[test]
+fn set_deployment_fails_with_invalid_deployment() {
+    run_test(|| {
+        let invalid_deployments = vec![
+            VersionBitsDeployment {
+                bit: 29,
+                ..sample_deployment()
+            },
+            VersionBitsDeployment {
+                timeout: 100,
+                ..sample_deployment()
+            },
+            VersionBitsDeployment {
+                threshold: 2017,
+                ..sample_deployment()
+            },
+        ];
+        for deployment in invalid_deployments {
+            assert_err!(
+                BTCRelay::set_deployment(Origin::root(), 1, deployment),
+                TestError::InvalidDeployment
+            );
+        }
+    })
+}
+
+#[test]
+fn add_version_bits_counts_signalling_blocks() {
+    run_test(|| {
+        let mut block_header = sample_block_header();
+
+        BTCRelay::get_median_time_past.mock_safe(|height| MockResult::Return(Some(height * 600)));
+
+        // bits 0 and 2 are signalled
+        block_header.version = 0x2000_0005;
+        BTCRelay::add_version_bits(2016, &block_header);
+        BTCRelay::add_version_bits(2017, &block_header);
+        assert_eq!(crate::VersionBitsCounts::<Test>::get(1, 0), 2);
+        assert_eq!(crate::VersionBitsCounts::<Test>::get(1, 1), 0);
+        assert_eq!(crate::VersionBitsCounts::<Test>::get(1, 2), 2);
+        // the period starts at the median time past of the block before it
+        assert_eq!(crate::PeriodMedianTimes::<Test>::get(1), Some(2015 * 600));
+
+        // versions without the version bits top bits do not signal
+        block_header.version = 0x4000_0005;
+        BTCRelay::add_version_bits(2018, &block_header);
+        assert_eq!(crate::VersionBitsCounts::<Test>::get(1, 0), 2);
+
+        block_header.version = 0x2000_0005;
+        BTCRelay::remove_version_bits(2016, &block_header);
+        assert_eq!(crate::VersionBitsCounts::<Test>::get(1, 0), 1);
+        assert_eq!(crate::PeriodMedianTimes::<Test>::get(1), None);
+    })
+}
+
+#[test]
+fn get_median_time_past_succeeds() {
+    run_test(|| {
+        let blocks = store_generated_block_chain(20);
+
+        // the timestamps increase, so the median is the timestamp of the sixth last block
+        assert_eq!(BTCRelay::get_median_time_past(15), Some(blocks[10].header.timestamp));
+        // fewer blocks are stored after the start of the relay
+        assert_eq!(BTCRelay::get_median_time_past(3), Some(blocks[2].header.timestamp));
+        assert_eq!(BTCRelay::get_median_time_past(20), None);
+    })
+}
+
+#[test]
+fn get_deployment_state_follows_signalling() {
+    run_test(|| {
+        let deployment = sample_deployment();
+        assert_ok!(BTCRelay::insert_deployment(1, deployment));
+        crate::StartBlockHeight::<Test>::put(0);
+
+        let period_start_times = vec![0, 50, 150, 250, 350, 450];
+        for (period, period_start_time) in period_start_times.into_iter().enumerate() {
+            crate::PeriodMedianTimes::<Test>::insert(period as u32, period_start_time);
+        }
+        // too few signalling blocks in the first started period
+        crate::VersionBitsCounts::<Test>::insert(2, deployment.bit, deployment.threshold - 1);
+        crate::VersionBitsCounts::<Test>::insert(3, deployment.bit, deployment.threshold);
+
+        let expected_states = vec![
+            DeploymentState::Defined,
+            DeploymentState::Defined,
+            DeploymentState::Started,
+            DeploymentState::Started,
+            DeploymentState::LockedIn,
+            DeploymentState::Active,
+        ];
+        for (period, expected_state) in expected_states.into_iter().enumerate() {
+            BTCRelay::set_best_block_height(period as u32 * 2016 + 1);
+            assert_eq!(BTCRelay::get_deployment_state(1), Ok(expected_state));
+        }
+        assert_err!(BTCRelay::get_deployment_state(2), TestError::NoDeployment);
+    })
+}
+
+#[test]
+fn get_deployment_state_fails_without_period_median_time() {
+    run_test(|| {
+        assert_ok!(BTCRelay::insert_deployment(1, sample_deployment()));
+        crate::StartBlockHeight::<Test>::put(0);
+        crate::PeriodMedianTimes::<Test>::insert(2, 150);
+
+        BTCRelay::set_best_block_height(2 * 2016);
+        assert_err!(BTCRelay::get_deployment_state(1), TestError::NoPeriodMedianTime);
+    })
+}
+
+#[test]
+fn prune_version_bits_advances_deployments() {
+    run_test(|| {
+        let deployment = sample_deployment();
+        assert_ok!(BTCRelay::insert_deployment(1, deployment));
+        crate::StartBlockHeight::<Test>::put(0);
+        crate::PeriodMedianTimes::<Test>::insert(1, 50);
+        crate::PeriodMedianTimes::<Test>::insert(2, 150);
+        crate::PeriodMedianTimes::<Test>::insert(3, 250);
+        crate::VersionBitsCounts::<Test>::insert(2, deployment.bit, deployment.threshold);
+        BTCRelay::set_best_block_height(3 * 2016);
+
+        // only the period following the oldest tracked one can be pruned
+        assert_eq!(BTCRelay::prune_version_bits(2), 0);
+        assert_eq!(BTCRelay::prune_version_bits(1), 1);
+        assert_eq!(BTCRelay::prune_version_bits(2), 1);
+
+        assert_eq!(BTCRelay::version_bits_start_period(), 2);
+        assert_eq!(
+            BTCRelay::deployment(1).map(|deployment| deployment.initial_state),
+            Some(DeploymentState::Started)
+        );
+        assert_eq!(crate::PeriodMedianTimes::<Test>::get(2), None);
+        assert_eq!(BTCRelay::get_deployment_state(1), Ok(DeploymentState::LockedIn));
+    })
+}
+
+#[test]
+fn get_deployment_state_fails_after_timeout() {
+    run_test(|| {
+        let deployment = sample_deployment();
+        assert_ok!(BTCRelay::insert_deployment(1, deployment));
+        crate::StartBlockHeight::<Test>::put(0);
+
+        crate::PeriodMedianTimes::<Test>::insert(1, 150);
+        crate::PeriodMedianTimes::<Test>::insert(2, deployment.timeout);
+        crate::PeriodMedianTimes::<Test>::insert(3, deployment.timeout + 100);
+        // the timeout takes precedence over enough signalling in the last period
+        crate::VersionBitsCounts::<Test>::insert(1, deployment.bit, deployment.threshold);
+        crate::VersionBitsCounts::<Test>::insert(2, deployment.bit, deployment.threshold);
+
+        BTCRelay::set_best_block_height(2 * 2016);
+        assert_eq!(BTCRelay::get_deployment_state(1), Ok(DeploymentState::Failed));
+        // signalling after the timeout has no effect
+        BTCRelay::set_best_block_height(3 * 2016);
+        assert_eq!(BTCRelay::get_deployment_state(1), Ok(DeploymentState::Failed));
+    })
+}
+
 #[test]
 fn check_and_do_reorg_new_fork_below_stable_transaction_confirmations() {
     run_test(|| {
-        // insert the main chain in Chains and ChainsIndex
+        // insert the main chain in ChainsIndex
         let main_chain_ref: u32 = 0;
         let main_start_height: u32 = 4;
         let main_block_height: u32 = 110;
-        let main_position: u32 = 0;
         let main = get_empty_block_chain_from_chain_id_and_height(main_chain_ref, main_start_height, main_block_height);
-        BTCRelay::set_chain_from_position_and_id(main_position, main_chain_ref);
         BTCRelay::set_block_chain_from_id(main_chain_ref, &main);
 
-        // insert the fork chain in Chains and ChainsIndex
+        // insert the fork chain in ForkTips and ChainsIndex
         let fork_chain_ref: u32 = 4;
         let fork_block_height: u32 = 113;
-        let fork_position: u32 = 1;
         let fork = get_empty_block_chain_from_chain_id_and_height(fork_chain_ref, main_start_height, fork_block_height);
-        BTCRelay::set_chain_from_position_and_id(fork_position, fork_chain_ref);
+        BTCRelay::insert_fork_tip(&fork);
         BTCRelay::set_block_chain_from_id(fork_chain_ref, &fork);
 
         // set the best block
@@ -356,11 +730,6 @@ fn check_and_do_reorg_new_fork_below_stable_transaction_confirmations() {
         BTCRelay::set_best_block(best_block_hash);
         BTCRelay::set_best_block_height(fork_block_height);
 
-        // check that fork is at its initial position
-        let current_position = BTCRelay::get_chain_position_from_chain_id(fork_chain_ref).unwrap();
-
-        assert_eq!(current_position, fork_position);
-
         BTCRelay::swap_main_blockchain.mock_safe(|_| MockResult::Return(Ok(())));
 
         assert_ok!(BTCRelay::check_and_do_reorg(&fork));
@@ -374,47 +743,76 @@ fn check_and_do_reorg_new_fork_below_stable_transaction_confirmations() {
     })
 }
 
-/// insert_sorted
+/// remove_fork_tip
 #[test]
-fn insert_sorted_succeeds() {
+fn remove_fork_tip_succeeds() {
     run_test(|| {
-        // insert the main chain in Chains and ChainsIndex
-        let main_chain_ref: u32 = 0;
-        let main_start_height: u32 = 60;
-        let main_block_height: u32 = 110;
-        let main_position: u32 = 0;
-        let main = get_empty_block_chain_from_chain_id_and_height(main_chain_ref, main_start_height, main_block_height);
-        BTCRelay::set_block_chain_from_id(main_chain_ref, &main);
-        assert_eq!(Ok(()), BTCRelay::insert_sorted(&main));
-
-        let curr_main_pos = BTCRelay::get_chain_position_from_chain_id(main_chain_ref).unwrap();
-        assert_eq!(curr_main_pos, main_position);
-        // insert the swap chain in Chains and ChainsIndex
-        let swap_chain_ref: u32 = 3;
-        let swap_start_height: u32 = 70;
-        let swap_block_height: u32 = 99;
-        let swap_position: u32 = 1;
-        let swap = get_empty_block_chain_from_chain_id_and_height(swap_chain_ref, swap_start_height, swap_block_height);
-        BTCRelay::set_block_chain_from_id(swap_chain_ref, &swap);
-        assert_eq!(Ok(()), BTCRelay::insert_sorted(&swap));
-
-        let curr_swap_pos = BTCRelay::get_chain_position_from_chain_id(swap_chain_ref).unwrap();
-        assert_eq!(curr_swap_pos, swap_position);
-
-        // insert the fork chain in Chains and ChainsIndex
-        let fork_chain_ref: u32 = 4;
-        let fork_start_height: u32 = 77;
-        let fork_block_height: u32 = 100;
-        let fork_position: u32 = 1;
-        let new_swap_pos: u32 = 2;
-        let fork = get_empty_block_chain_from_chain_id_and_height(fork_chain_ref, fork_start_height, fork_block_height);
-        BTCRelay::set_block_chain_from_id(fork_chain_ref, &fork);
-        assert_eq!(Ok(()), BTCRelay::insert_sorted(&fork));
+        let fork_1 = get_empty_block_chain_from_chain_id_and_height(1, 70, 99);
+        let fork_2 = get_empty_block_chain_from_chain_id_and_height(2, 77, 99);
+        BTCRelay::insert_fork_tip(&fork_1);
+        BTCRelay::insert_fork_tip(&fork_2);
 
-        let curr_fork_pos = BTCRelay::get_chain_position_from_chain_id(fork_chain_ref).unwrap();
-        assert_eq!(curr_fork_pos, fork_position);
-        let curr_swap_pos = BTCRelay::get_chain_position_from_chain_id(swap_chain_ref).unwrap();
-        assert_eq!(curr_swap_pos, new_swap_pos);
+        BTCRelay::remove_fork_tip(&fork_1);
+        assert!(BTCRelay::fork_tip_exists(99));
+        assert_eq!(crate::ForkCount::<Test>::get(), 1);
+
+        BTCRelay::remove_fork_tip(&fork_2);
+        assert!(!BTCRelay::fork_tip_exists(99));
+        assert_eq!(crate::ForkCount::<Test>::get(), 0);
+    })
+}
+
+#[test]
+fn swap_main_blockchain_rolls_back_version_bits() {
+    run_test(|| {
+        let main = store_blockchain_and_random_headers(0, 0, 10);
+        let fork = store_blockchain_and_random_headers(4, 5, 17);
+        BTCRelay::increment_chain_counter.mock_safe(|| MockResult::Return(5));
+
+        // main chain blocks signal bit 1, fork blocks signal bit 2
+        for (chain, version) in vec![(&main, 0x2000_0002), (&fork, 0x2000_0004)] {
+            for height in chain.start_height..=chain.max_height {
+                let block_hash = BTCRelay::get_block_hash(chain.chain_id, height).unwrap();
+                crate::BlockHeaders::<Test>::mutate(block_hash, |header| header.block_header.version = version);
+            }
+        }
+        for height in main.start_height..=main.max_height {
+            let block_header = BTCRelay::get_block_header_from_height(&main, height).unwrap();
+            BTCRelay::add_version_bits(height, &block_header.block_header);
+        }
+
+        assert_ok!(BTCRelay::swap_main_blockchain(&fork));
+
+        // only the signalling of the new main chain is counted
+        assert_eq!(crate::VersionBitsCounts::<Test>::get(0, 1), fork.start_height);
+        assert_eq!(
+            crate::VersionBitsCounts::<Test>::get(0, 2),
+            fork.max_height - fork.start_height + 1
+        );
+    })
+}
+
+#[test]
+fn swap_main_blockchain_raises_errors_of_flagged_fork() {
+    run_test(|| {
+        store_blockchain_and_random_headers(0, 0, 10);
+        let fork = store_blockchain_and_random_headers(4, 5, 17);
+        BTCRelay::increment_chain_counter.mock_safe(|| MockResult::Return(5));
+
+        // a block error was accepted while the block was on a fork
+        let header = BTCRelay::get_block_header_from_height(&fork, 7).unwrap();
+        assert_ok!(BTCRelay::flag_block_error(
+            header.block_hash,
+            ErrorCode::InvalidBTCRelay
+        ));
+        assert_ok!(ext::security::ensure_parachain_status_running::<Test>());
+
+        let fork = BTCRelay::get_block_chain_from_id(4).unwrap();
+        assert_ok!(BTCRelay::swap_main_blockchain(&fork));
+
+        assert!(ext::security::is_parachain_error_invalid_btcrelay::<Test>());
+        assert!(!ext::security::is_parachain_error_no_data_btcrelay::<Test>());
+        assert_err!(BTCRelay::transaction_verification_allowed(1), TestError::Invalid);
     })
 }
 
@@ -426,8 +824,7 @@ fn swap_main_blockchain_succeeds() {
         let main_chain_ref: u32 = 0;
         let main_start: u32 = 0;
         let main_height: u32 = 10;
-        let main_position: u32 = 0;
-        let main = store_blockchain_and_random_headers(main_chain_ref, main_start, main_height, main_position);
+        let main = store_blockchain_and_random_headers(main_chain_ref, main_start, main_height);
 
         // simulate error
         let header = BTCRelay::get_block_header_from_height(&main, 2).unwrap();
@@ -438,9 +835,8 @@ fn swap_main_blockchain_succeeds() {
         let fork_chain_ref: u32 = 4;
         let fork_start: u32 = 5;
         let fork_height: u32 = 17;
-        let fork_position: u32 = 1;
 
-        let fork = store_blockchain_and_random_headers(fork_chain_ref, fork_start, fork_height, fork_position);
+        let fork = store_blockchain_and_random_headers(fork_chain_ref, fork_start, fork_height);
 
         let old_main_ref = fork_chain_ref + 1;
         // mock the chain counter
@@ -464,6 +860,7 @@ fn swap_main_blockchain_succeeds() {
             BTCRelay::get_block_chain_from_id(fork_chain_ref),
             TestError::InvalidChainID,
         );
+        assert!(!BTCRelay::fork_tip_exists(fork_height));
 
         // check that the parachain has recovered
         assert_ok!(ext::security::ensure_parachain_status_running::<Test>());
@@ -479,6 +876,8 @@ fn swap_main_blockchain_succeeds() {
 
         assert_eq!(main.no_data, old_main.no_data);
         assert_eq!(main.invalid, old_main.invalid);
+        assert!(BTCRelay::fork_tip_exists(main_height));
+        assert_eq!(crate::ForkCount::<Test>::get(), 1);
 
         // check that the best block is set
         assert_eq!(
@@ -967,6 +1366,83 @@ fn test_verify_and_validate_transaction_succeeds() {
     });
 }
 
+#[test]
+fn test_consume_payment_outputs_rejects_reuse() {
+    run_test(|| {
+        let raw_tx = hex::decode(sample_example_real_rawtx()).unwrap();
+        let tx_id = parse_transaction(&raw_tx).unwrap().tx_id();
+        let recipient_btc_address =
+            BtcAddress::P2SH(H160::from_str(&"4a1154d50b03292b3024370901711946cb7cccc3").unwrap());
+        BTCRelay::parse_merkle_proof.mock_safe(|_| MockResult::Return(Ok(sample_merkle_proof())));
+
+        let request_id = H256::from_low_u64_be(1);
+        assert_ok!(BTCRelay::consume_payment_outputs(
+            &[0u8; 100],
+            &raw_tx,
+            vec![(recipient_btc_address, request_id)]
+        ));
+        assert_eq!(BTCRelay::consumed_output((tx_id, 0)), Some(request_id));
+        assert_eq!(BTCRelay::settling_transaction(request_id), Some(tx_id));
+
+        // the same output cannot execute another request
+        assert_err!(
+            BTCRelay::consume_payment_outputs(
+                &[0u8; 100],
+                &raw_tx,
+                vec![(recipient_btc_address, H256::from_low_u64_be(2))]
+            ),
+            TestError::OutputAlreadyConsumed
+        );
+    });
+}
+
+#[test]
+fn test_consume_payment_outputs_requires_output_per_payment() {
+    run_test(|| {
+        let raw_tx = hex::decode(sample_example_real_rawtx()).unwrap();
+        let recipient_btc_address =
+            BtcAddress::P2SH(H160::from_str(&"4a1154d50b03292b3024370901711946cb7cccc3").unwrap());
+        BTCRelay::parse_merkle_proof.mock_safe(|_| MockResult::Return(Ok(sample_merkle_proof())));
+
+        // the transaction only has a single output to the recipient
+        assert_err!(
+            BTCRelay::consume_payment_outputs(
+                &[0u8; 100],
+                &raw_tx,
+                vec![
+                    (recipient_btc_address, H256::from_low_u64_be(1)),
+                    (recipient_btc_address, H256::from_low_u64_be(2))
+                ]
+            ),
+            TestError::InvalidPayment
+        );
+    });
+}
+
+#[test]
+fn test_prune_main_chain_block_removes_consumed_outputs() {
+    run_test(|| {
+        let raw_tx = hex::decode(sample_example_real_rawtx()).unwrap();
+        let tx_id = parse_transaction(&raw_tx).unwrap().tx_id();
+        let recipient_btc_address =
+            BtcAddress::P2SH(H160::from_str(&"4a1154d50b03292b3024370901711946cb7cccc3").unwrap());
+        let proof = sample_merkle_proof();
+        BTCRelay::insert_block_hash(0, 10, proof.block_header.hash().unwrap());
+        BTCRelay::parse_merkle_proof.mock_safe(move |_| MockResult::Return(Ok(proof.clone())));
+
+        assert_ok!(BTCRelay::consume_payment_outputs(
+            &[0u8; 100],
+            &raw_tx,
+            vec![(recipient_btc_address, H256::from_low_u64_be(1))]
+        ));
+
+        BTCRelay::prune_main_chain_block(10, 10);
+        assert_eq!(BTCRelay::consumed_output((tx_id, 0)), None);
+        // a second payment for the request can still be reported
+        assert_eq!(BTCRelay::settling_transaction(H256::from_low_u64_be(1)), Some(tx_id));
+    });
+}
+
 /// flag_block_error
 #[test]
 fn test_flag_block_error_succeeds() {
@@ -1096,6 +1572,37 @@ fn test_clear_block_error_succeeds() {
     })
 }
 
+#[test]
+fn test_removing_error_clears_main_chain_flags() {
+    run_test(|| {
+        let block_hash = H256Le::from_bytes_le(&[1; 32]);
+        let mut main_chain = get_empty_block_chain_from_chain_id_and_height(crate::MAIN_CHAIN_ID, 0, 10);
+        main_chain.invalid.insert(5);
+        BTCRelay::set_block_chain_from_id(crate::MAIN_CHAIN_ID, &main_chain);
+        BTCRelay::insert_block_hash(crate::MAIN_CHAIN_ID, 5, block_hash);
+        ext::security::insert_error::<Test>(ErrorCode::InvalidBTCRelay);
+        assert_err!(BTCRelay::transaction_verification_allowed(1), TestError::Invalid);
+
+        assert_ok!(Security::remove_parachain_error(
+            Origin::root(),
+            ErrorCode::InvalidBTCRelay,
+            vec![]
+        ));
+
+        assert!(BTCRelay::get_block_chain_from_id(crate::MAIN_CHAIN_ID)
+            .unwrap()
+            .invalid
+            .is_empty());
+        assert_ok!(BTCRelay::transaction_verification_allowed(1));
+        let clear_event = TestEvent::btc_relay(Event::ClearBlockError(
+            block_hash,
+            crate::MAIN_CHAIN_ID,
+            ErrorCode::InvalidBTCRelay,
+        ));
+        assert!(System::events().iter().any(|a| a.event == clear_event));
+    })
+}
+
 #[test]
 fn test_clear_block_error_fails() {
     run_test(|| {
@@ -1220,7 +1727,7 @@ fn test_verify_transaction_inclusion_succeeds() {
 
         let fork = get_empty_block_chain_from_chain_id_and_height(fork_ref, start, fork_chain_height);
 
-        BTCRelay::get_chain_id_from_position.mock_safe(move |_| MockResult::Return(Ok(fork_ref)));
+        BTCRelay::insert_fork_tip(&fork);
         BTCRelay::get_block_chain_from_id.mock_safe(move |id| {
             if id == chain_ref {
                 MockResult::Return(Ok(main.clone()))
@@ -1318,7 +1825,7 @@ fn test_verify_transaction_inclusion_invalid_tx_id_fails() {
 
         let fork = get_empty_block_chain_from_chain_id_and_height(fork_ref, start, fork_chain_height);
 
-        BTCRelay::get_chain_id_from_position.mock_safe(move |_| MockResult::Return(Ok(fork_ref)));
+        BTCRelay::insert_fork_tip(&fork);
         BTCRelay::get_block_chain_from_id.mock_safe(move |id| {
             if id == chain_ref {
                 MockResult::Return(Ok(main.clone()))
@@ -1371,7 +1878,7 @@ fn test_verify_transaction_inclusion_invalid_merkle_root_fails() {
 
         let fork = get_empty_block_chain_from_chain_id_and_height(fork_ref, start, fork_chain_height);
 
-        BTCRelay::get_chain_id_from_position.mock_safe(move |_| MockResult::Return(Ok(fork_ref)));
+        BTCRelay::insert_fork_tip(&fork);
         BTCRelay::get_block_chain_from_id.mock_safe(move |id| {
             if id == chain_ref {
                 MockResult::Return(Ok(main.clone()))
@@ -1405,8 +1912,7 @@ fn test_verify_transaction_inclusion_invalid_merkle_root_fails() {
 #[test]
 fn test_verify_transaction_inclusion_fails_with_ongoing_fork() {
     run_test(|| {
-        BTCRelay::get_chain_id_from_position.mock_safe(|_| MockResult::Return(Ok(1)));
-        BTCRelay::get_block_chain_from_id.mock_safe(|_| MockResult::Return(Ok(BlockChain::default())));
+        BTCRelay::insert_fork_tip(&get_empty_block_chain_from_chain_id_and_height(1, 0, 0));
 
         let tx_id = sample_valid_proof_result().transaction_hash;
         let raw_merkle_proof = vec![0u8; 100];
@@ -1534,13 +2040,11 @@ fn get_chain_from_id_err() {
 #[test]
 fn get_chain_from_id_ok() {
     run_test(|| {
-        // insert the main chain in Chains and ChainsIndex
+        // insert the main chain in ChainsIndex
         let main_chain_ref: u32 = 0;
         let main_start_height: u32 = 3;
         let main_block_height: u32 = 110;
-        let main_position: u32 = 0;
         let main = get_empty_block_chain_from_chain_id_and_height(main_chain_ref, main_start_height, main_block_height);
-        BTCRelay::set_chain_from_position_and_id(main_position, main_chain_ref);
         BTCRelay::set_block_chain_from_id(main_chain_ref, &main);
 
         assert_eq!(Ok(main), BTCRelay::get_block_chain_from_id(main_chain_ref));
@@ -1555,7 +2059,8 @@ fn store_generated_block_headers() {
 
     run_test(|| {
         let mut last_block = BlockBuilder::new().with_coinbase(&miner, 50, 0).mine(target).unwrap();
-        assert_ok!(BTCRelay::initialize(3, get_header(&last_block), 0));
+        assert_ok!(BTCRelay::insert_checkpoint(0, last_block.header.hash().unwrap()));
+        assert_ok!(BTCRelay::initialize(3, vec![get_header(&last_block)], 0));
         for i in 1..20 {
             last_block = BlockBuilder::new()
                 .with_coinbase(&miner, 50, i)
@@ -1570,6 +2075,195 @@ fn store_generated_block_headers() {
     })
 }
 
+fn generate_block_chain(length: u32) -> Vec<Block> {
+    let target = U256::from(2).pow(254.into());
+    let miner = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+
+    let mut blocks = vec![BlockBuilder::new()
+        .with_coinbase(&miner, 50, 0)
+        .with_timestamp(1588813835)
+        .mine(target)
+        .unwrap()];
+    for i in 1..length {
+        let block = BlockBuilder::new()
+            .with_coinbase(&miner, 50, i)
+            .with_timestamp(1588813835 + i * 600)
+            .with_previous_hash(blocks[i as usize - 1].header.hash().unwrap())
+            .mine(target)
+            .unwrap();
+        blocks.push(block);
+    }
+    blocks
+}
+
+fn get_raw_header(block: &Block) -> RawBlockHeader {
+    RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap()
+}
+
+fn store_generated_block_chain(length: u32) -> Vec<Block> {
+    let blocks = generate_block_chain(length);
+    assert_ok!(BTCRelay::insert_checkpoint(0, blocks[0].header.hash().unwrap()));
+    assert_ok!(BTCRelay::initialize(3, vec![get_raw_header(&blocks[0])], 0));
+    for block in blocks.iter().skip(1) {
+        assert_ok!(BTCRelay::store_block_header(&3, get_raw_header(block)));
+    }
+    blocks
+}
+
+#[test]
+fn test_prune_block_headers_succeeds() {
+    use crate::PruningWindow;
+    use frame_support::traits::OnInitialize;
+
+    run_test(|| {
+        PruningWindow::<Test>::put(5);
+        let blocks = store_generated_block_chain(20);
+
+        // work per block is bounded
+        BTCRelay::on_initialize(2);
+        assert!(!BTCRelay::block_exists(crate::MAIN_CHAIN_ID, 9));
+        assert!(BTCRelay::block_exists(crate::MAIN_CHAIN_ID, 10));
+
+        BTCRelay::on_initialize(3);
+        for height in 1..14 {
+            assert!(!BTCRelay::block_exists(crate::MAIN_CHAIN_ID, height));
+            assert!(!BTCRelay::block_header_exists(
+                blocks[height as usize].header.hash().unwrap()
+            ));
+        }
+        for height in 14..20 {
+            assert!(BTCRelay::block_exists(crate::MAIN_CHAIN_ID, height));
+            assert!(BTCRelay::block_header_exists(
+                blocks[height as usize].header.hash().unwrap()
+            ));
+        }
+
+        // the retarget anchor is retained
+        assert!(BTCRelay::block_exists(crate::MAIN_CHAIN_ID, 0));
+        assert!(BTCRelay::block_header_exists(blocks[0].header.hash().unwrap()));
+
+        // only the records of the last pruning window are kept
+        assert_eq!(crate::PrunedBlockHeights::<Test>::iter().count(), 5);
+        assert_eq!(crate::PrunedBlockHashes::<Test>::iter().count(), 5);
+    })
+}
+
+#[test]
+fn test_prune_block_headers_removes_expired_batch_payments() {
+    use crate::{ExpiringBatchPayments, PruningWindow};
+    use frame_support::traits::OnInitialize;
+
+    run_test(|| {
+        PruningWindow::<Test>::put(5);
+        let renewed_ids = vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)];
+        let expired_ids = vec![H256::from_low_u64_be(3), H256::from_low_u64_be(4)];
+        let renewed = BTCRelay::register_batch_payment(renewed_ids.clone()).unwrap();
+        let expired = BTCRelay::register_batch_payment(expired_ids).unwrap();
+
+        store_generated_block_chain(20);
+        assert_ok!(BTCRelay::register_batch_payment(renewed_ids.clone()));
+
+        BTCRelay::on_initialize(2);
+        assert_eq!(BTCRelay::batch_payment(&renewed), Some(renewed_ids));
+        assert_eq!(BTCRelay::batch_payment(&expired), None);
+        assert_eq!(ExpiringBatchPayments::<Test>::iter().count(), 1);
+    })
+}
+
+#[test]
+fn test_prune_block_headers_disabled() {
+    use frame_support::traits::OnInitialize;
+
+    run_test(|| {
+        store_generated_block_chain(20);
+        BTCRelay::on_initialize(2);
+        assert_eq!(BTCRelay::_blocks_count(crate::MAIN_CHAIN_ID), 20);
+    })
+}
+
+#[test]
+fn test_verify_transaction_inclusion_pruned_block_fails() {
+    use crate::PruningWindow;
+    use frame_support::traits::OnInitialize;
+
+    run_test(|| {
+        PruningWindow::<Test>::put(5);
+        let blocks = store_generated_block_chain(20);
+        BTCRelay::on_initialize(2);
+
+        let tx_id = blocks[7].transactions[0].tx_id();
+        let raw_merkle_proof = blocks[7].merkle_proof(&[tx_id]).unwrap().try_format().unwrap();
+
+        assert_err!(
+            BTCRelay::_verify_transaction_inclusion(tx_id, raw_merkle_proof, Some(0)),
+            TestError::BlockPruned
+        );
+
+        // blocks pruned a pruning window ago are forgotten
+        let tx_id = blocks[3].transactions[0].tx_id();
+        let raw_merkle_proof = blocks[3].merkle_proof(&[tx_id]).unwrap().try_format().unwrap();
+
+        assert_err!(
+            BTCRelay::_verify_transaction_inclusion(tx_id, raw_merkle_proof, Some(0)),
+            TestError::BlockNotFound
+        );
+
+        // unknown blocks that are newer than the pruned range are not reported as pruned
+        let tx_id = blocks[15].transactions[0].tx_id();
+        let mut merkle_proof = blocks[15].merkle_proof(&[tx_id]).unwrap();
+        merkle_proof.block_header.nonce += 1;
+
+        assert_err!(
+            BTCRelay::_verify_transaction_inclusion(tx_id, merkle_proof.try_format().unwrap(), Some(0)),
+            TestError::BlockNotFound
+        );
+
+        // neither are unknown blocks with a timestamp older than the pruned range
+        merkle_proof.block_header.timestamp = 0;
+        assert_err!(
+            BTCRelay::_verify_transaction_inclusion(tx_id, merkle_proof.try_format().unwrap(), Some(0)),
+            TestError::BlockNotFound
+        );
+    })
+}
+
+#[test]
+fn test_set_pruning_window_succeeds() {
+    run_test(|| {
+        assert_err!(
+            BTCRelay::set_pruning_window(Origin::signed(3), 10),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(BTCRelay::set_pruning_window(Origin::root(), 10));
+        assert_eq!(BTCRelay::pruning_window(), 10);
+    })
+}
+
+#[test]
+fn test_migrate_parameters_sets_defaults() {
+    use crate::PruningWindow;
+    use frame_support::traits::OnRuntimeUpgrade;
+
+    run_test(|| {
+        crate::StorageVersion::<Test>::put(Version::V0);
+        PruningWindow::<Test>::kill();
+        crate::MaxForks::<Test>::kill();
+        crate::StaleForkDepth::<Test>::kill();
+        crate::MaxReorgDepth::<Test>::kill();
+        BTCRelay::on_runtime_upgrade();
+        assert_eq!(BTCRelay::pruning_window(), crate::DEFAULT_PRUNING_WINDOW);
+        assert_eq!(BTCRelay::max_forks(), crate::DEFAULT_MAX_FORKS);
+        assert_eq!(BTCRelay::stale_fork_depth(), crate::DEFAULT_STALE_FORK_DEPTH);
+        assert_eq!(BTCRelay::max_reorg_depth(), crate::DEFAULT_MAX_REORG_DEPTH);
+        assert!(BTCRelay::storage_version() == Version::V1);
+
+        // migrated chains are not migrated again
+        assert_ok!(BTCRelay::set_pruning_window(Origin::root(), 0));
+        BTCRelay::on_runtime_upgrade();
+        assert_eq!(BTCRelay::pruning_window(), 0);
+    })
+}
+
 #[test]
 fn test_extract_value_fails_with_invalid_payment() {
     run_test(|| {
@@ -1683,6 +2377,135 @@ fn test_extract_value_and_op_return_succeeds() {
     })
 }
 
+fn sample_batch_payments() -> Vec<(BtcAddress, i64, H256)> {
+    vec![
+        (
+            BtcAddress::P2SH(H160::from_low_u64_be(1)),
+            1000,
+            H256::from_low_u64_be(1),
+        ),
+        (
+            BtcAddress::P2PKH(H160::from_low_u64_be(2)),
+            2000,
+            H256::from_low_u64_be(2),
+        ),
+        (
+            BtcAddress::P2SH(H160::from_low_u64_be(1)),
+            3000,
+            H256::from_low_u64_be(3),
+        ),
+    ]
+}
+
+fn sample_batch_outputs(payments: &[(BtcAddress, i64, H256)]) -> Vec<TransactionOutput> {
+    let request_ids = payments.iter().map(|(_, _, id)| *id).collect::<Vec<_>>();
+    let op_return = BTCRelay::compute_batch_op_return(&request_ids).unwrap();
+    let mut outputs = payments
+        .iter()
+        .map(|(recipient, value, _)| TransactionOutput::payment(*value, recipient))
+        .collect::<Vec<_>>();
+    outputs.push(TransactionOutput::op_return(0, op_return.as_bytes()));
+    outputs
+}
+
+#[test]
+fn test_compute_batch_op_return_of_single_request_is_request_id() {
+    run_test(|| {
+        let request_id = H256::from_low_u64_be(42);
+        assert_eq!(BTCRelay::compute_batch_op_return(&[request_id]), Ok(request_id));
+        assert_err!(BTCRelay::compute_batch_op_return(&[]), TestError::InvalidBatch);
+    })
+}
+
+#[test]
+fn test_register_batch_payment_succeeds() {
+    run_test(|| {
+        let request_ids = vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)];
+        let op_return = BTCRelay::register_batch_payment(request_ids.clone()).unwrap();
+        assert_eq!(op_return, BTCRelay::compute_batch_op_return(&request_ids).unwrap());
+        assert_eq!(BTCRelay::batch_payment(&op_return), Some(request_ids));
+
+        // payments for a single request reference the request directly
+        let request_id = H256::from_low_u64_be(3);
+        assert_eq!(BTCRelay::register_batch_payment(vec![request_id]), Ok(request_id));
+        assert_eq!(BTCRelay::batch_payment(&request_id), None);
+
+        assert_err!(
+            BTCRelay::register_batch_payment(vec![request_id, request_id]),
+            TestError::InvalidBatch
+        );
+    })
+}
+
+#[test]
+fn test_validate_batch_transaction_succeeds() {
+    run_test(|| {
+        let payments = sample_batch_payments();
+        let mut outputs = sample_batch_outputs(&payments);
+        // change output
+        outputs.push(TransactionOutput::payment(5000, &BtcAddress::P2WPKHv0(H160::zero())));
+
+        let (_, values) = BTCRelay::_validate_batch_transaction(sample_transaction_parsed(&outputs), payments).unwrap();
+        assert_eq!(values, vec![1000, 2000, 3000]);
+    })
+}
+
+#[test]
+fn test_validate_batch_transaction_wrong_order_fails() {
+    run_test(|| {
+        let payments = sample_batch_payments();
+        let outputs = sample_batch_outputs(&payments);
+        let reordered = vec![payments[1], payments[0], payments[2]];
+
+        assert_err!(
+            BTCRelay::_validate_batch_transaction(sample_transaction_parsed(&outputs), reordered),
+            TestError::InvalidOpReturn
+        );
+    })
+}
+
+#[test]
+fn test_validate_batch_transaction_wrong_recipient_fails() {
+    run_test(|| {
+        let payments = sample_batch_payments();
+        let mut outputs = sample_batch_outputs(&payments);
+        outputs[1] = TransactionOutput::payment(2000, &BtcAddress::P2PKH(H160::zero()));
+
+        assert_err!(
+            BTCRelay::_validate_batch_transaction(sample_transaction_parsed(&outputs), payments),
+            TestError::InvalidPayment
+        );
+    })
+}
+
+#[test]
+fn test_validate_batch_transaction_insufficient_value_fails() {
+    run_test(|| {
+        let payments = sample_batch_payments();
+        let mut outputs = sample_batch_outputs(&payments);
+        outputs[2] = TransactionOutput::payment(2999, &payments[2].0);
+
+        assert_err!(
+            BTCRelay::_validate_batch_transaction(sample_transaction_parsed(&outputs), payments),
+            TestError::InsufficientValue
+        );
+    })
+}
+
+#[test]
+fn test_validate_batch_transaction_duplicate_request_fails() {
+    run_test(|| {
+        let mut payments = sample_batch_payments();
+        payments[2].2 = payments[0].2;
+        let outputs = sample_batch_outputs(&payments);
+
+        assert_err!(
+            BTCRelay::_validate_batch_transaction(sample_transaction_parsed(&outputs), payments),
+            TestError::InvalidBatch
+        );
+    })
+}
+
 #[test]
 fn test_extract_value_and_op_return_fails_with_return_to_self() {
     run_test(|| {
@@ -1710,15 +2533,12 @@ fn test_extract_value_and_op_return_fails_with_return_to_self() {
 
 #[test]
 fn test_check_and_do_reorg() {
-    use crate::{Chains, ChainsIndex};
+    use crate::ChainsIndex;
     use bitcoin::types::BlockChain;
     use sp_std::collections::btree_set::BTreeSet;
 
     // data taken from testnet fork
     run_test(|| {
-        Chains::<Test>::insert(0, 0);
-        Chains::<Test>::insert(2, 7);
-
         ChainsIndex::<Test>::insert(
             0,
             BlockChain {
@@ -1776,8 +2596,6 @@ fn test_check_and_do_reorg() {
 
         BTCRelay::swap_main_blockchain.mock_safe(|_| MockResult::Return(Ok(())));
 
-        // we should skip empty `Chains`, this can occur if the
-        // previous index is accidentally deleted
         assert_ok!(BTCRelay::check_and_do_reorg(&BlockChain {
             chain_id: 7,
             start_height: 1_897_317,
@@ -1789,19 +2607,171 @@ fn test_check_and_do_reorg() {
 }
 
 #[test]
-fn test_remove_blockchain_from_chain() {
-    use crate::Chains;
+fn test_store_fork_exceeding_max_forks_fails() {
+    use crate::MaxForks;
+
+    let target = U256::from(2).pow(254.into());
+    let miner = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+    let get_header = |block: &Block| RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap();
 
     run_test(|| {
-        Chains::<Test>::insert(0, 0);
-        Chains::<Test>::insert(8, 5);
-        Chains::<Test>::insert(2, 7);
+        MaxForks::<Test>::put(2);
+        let blocks = store_generated_block_chain(3);
 
-        assert_ok!(BTCRelay::remove_blockchain_from_chain(2));
+        // forks branching off the first block
+        for i in 0..3 {
+            let fork_block = BlockBuilder::new()
+                .with_coinbase(&miner, 100 + i, 1)
+                .with_previous_hash(blocks[0].header.hash().unwrap())
+                .mine(target)
+                .unwrap();
+            let result = BTCRelay::store_block_header(&3, get_header(&fork_block));
+            if i < 2 {
+                assert_ok!(result);
+            } else {
+                assert_err!(result, TestError::TooManyForks);
+            }
+        }
+        assert_eq!(crate::ForkCount::<Test>::get(), 2);
+    })
+}
+
+#[test]
+fn test_migrate_fork_index_succeeds() {
+    use crate::{ForkCount, ForkTips};
+    use codec::Encode;
+    use frame_support::{
+        storage::migration::{get_storage_value, put_storage_value},
+        traits::OnRuntimeUpgrade,
+        Blake2_128Concat, StorageHasher,
+    };
+
+    let target = U256::from(2).pow(254.into());
+    let miner = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+    let get_header = |block: &Block| RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap();
+
+    run_test(|| {
+        let blocks = store_generated_block_chain(3);
+        let fork_block = BlockBuilder::new()
+            .with_coinbase(&miner, 100, 1)
+            .with_previous_hash(blocks[0].header.hash().unwrap())
+            .mine(target)
+            .unwrap();
+        assert_ok!(BTCRelay::store_block_header(&3, get_header(&fork_block)));
+        let fork_ref = BTCRelay::get_block_header_from_hash(fork_block.header.hash().unwrap())
+            .unwrap()
+            .chain_ref;
+
+        // storage of a chain which predates the fork index
+        crate::StorageVersion::<Test>::put(Version::V0);
+        ForkTips::<Test>::remove(1, fork_ref);
+        ForkCount::<Test>::kill();
+        let key = 0u32.using_encoded(Blake2_128Concat::hash);
+        put_storage_value(b"BTCRelay", b"Chains", &key, fork_ref);
+
+        BTCRelay::on_runtime_upgrade();
+        assert!(ForkTips::<Test>::contains_key(1, fork_ref));
+        assert_eq!(ForkCount::<Test>::get(), 1);
+        assert_eq!(get_storage_value::<u32>(b"BTCRelay", b"Chains", &key), None);
+    })
+}
+
+#[test]
+fn test_set_fork_parameters_succeeds() {
+    run_test(|| {
+        assert_err!(
+            BTCRelay::set_max_forks(Origin::signed(3), 10),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(BTCRelay::set_max_forks(Origin::root(), 10));
+        assert_eq!(BTCRelay::max_forks(), 10);
+
+        assert_err!(
+            BTCRelay::set_stale_fork_depth(Origin::signed(3), 10),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(BTCRelay::set_stale_fork_depth(Origin::root(), 10));
+        assert_eq!(BTCRelay::stale_fork_depth(), 10);
+
+        assert_err!(
+            BTCRelay::set_max_reorg_depth(Origin::signed(3), 10),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(BTCRelay::set_max_reorg_depth(Origin::root(), 10));
+        assert_eq!(BTCRelay::max_reorg_depth(), 10);
+    })
+}
+
+#[test]
+fn test_remove_stale_forks_succeeds() {
+    use crate::{BlockConsumedOutputs, ConsumedOutputs, ExecutedRequests, SettlingTransactions, StaleForkDepth};
+    use frame_support::traits::OnInitialize;
+
+    let target = U256::from(2).pow(254.into());
+    let miner = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+    let get_header = |block: &Block| RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap();
 
-        let mut chains = Chains::<Test>::iter().collect::<Vec<(u32, u32)>>();
-        chains.sort_by_key(|k| k.0);
-        assert_eq!(chains, vec![(0, 0), (2, 5)]);
+    run_test(|| {
+        StaleForkDepth::<Test>::put(5);
+        let blocks = store_generated_block_chain(3);
+
+        let fork_block = BlockBuilder::new()
+            .with_coinbase(&miner, 100, 1)
+            .with_previous_hash(blocks[0].header.hash().unwrap())
+            .mine(target)
+            .unwrap();
+        assert_ok!(BTCRelay::store_block_header(&3, get_header(&fork_block)));
+        let fork_hash = fork_block.header.hash().unwrap();
+        let fork_ref = BTCRelay::get_block_header_from_hash(fork_hash).unwrap().chain_ref;
+
+        // a request executed with a proof against the fork
+        let output = (H256Le::from_bytes_le(&[1; 32]), 0);
+        ConsumedOutputs::<Test>::insert(output, H256::from_low_u64_be(1));
+        BlockConsumedOutputs::<Test>::insert(fork_hash, vec![output]);
+        ExecutedRequests::<Test>::insert(fork_hash, vec![H256::from_low_u64_be(1)]);
+        SettlingTransactions::<Test>::insert(H256::from_low_u64_be(1), output.0);
+
+        // the fork is not yet stale
+        BTCRelay::on_initialize(2);
+        assert!(BTCRelay::block_header_exists(fork_hash));
+
+        // extend the main chain until the fork is 5 blocks behind
+        let mut last_block = blocks[2].clone();
+        for i in 3..7 {
+            last_block = BlockBuilder::new()
+                .with_coinbase(&miner, 50, i)
+                .with_previous_hash(last_block.header.hash().unwrap())
+                .mine(target)
+                .unwrap();
+            assert_ok!(BTCRelay::store_block_header(&3, get_header(&last_block)));
+        }
+
+        // the mock has no database weight, only the stale block callback is weighed
+        assert_eq!(BTCRelay::on_initialize(3), STALE_BLOCK_WEIGHT);
+        assert!(!BTCRelay::block_header_exists(fork_hash));
+        assert!(!BTCRelay::fork_tip_exists(1));
+        assert_err!(BTCRelay::get_block_chain_from_id(fork_ref), TestError::InvalidChainID);
+        assert_eq!(crate::ForkCount::<Test>::get(), 0);
+        // the transaction may be included in the main chain again
+        assert_eq!(BTCRelay::consumed_output(output), Some(H256::from_low_u64_be(1)));
+        assert!(!BlockConsumedOutputs::<Test>::contains_key(fork_hash));
+        assert!(!ExecutedRequests::<Test>::contains_key(fork_hash));
+        assert_eq!(BTCRelay::settling_transaction(H256::from_low_u64_be(1)), Some(output.0));
+
+        let remove_event = TestEvent::btc_relay(Event::RemoveStaleFork(fork_ref, 1));
+        assert!(System::events().iter().any(|a| a.event == remove_event));
+        STALE_BLOCK_RELAYERS.with(|relayers| assert_eq!(*relayers.borrow(), vec![3]));
+
+        // new forks at or below the removed height are rejected
+        let stale_block = BlockBuilder::new()
+            .with_coinbase(&miner, 200, 1)
+            .with_previous_hash(blocks[0].header.hash().unwrap())
+            .mine(target)
+            .unwrap();
+        assert_err!(
+            BTCRelay::store_block_header(&3, get_header(&stale_block)),
+            TestError::StaleFork
+        );
     })
 }
 
@@ -1820,6 +2790,16 @@ fn sample_merkle_proof() -> MerkleProof {
     }
 }
 
+fn sample_deployment() -> VersionBitsDeployment {
+    VersionBitsDeployment {
+        bit: 2,
+        start_time: 100,
+        timeout: 1000,
+        threshold: 1916,
+        initial_state: DeploymentState::Defined,
+    }
+}
+
 fn sample_block_header() -> BlockHeader {
     BlockHeader {
         merkle_root: H256Le::from_hex_le(SAMPLE_MERKLE_ROOT),
@@ -1876,7 +2856,7 @@ fn get_nodata_empty_block_chain_from_chain_id_and_height(
     blockchain
 }
 
-fn store_blockchain_and_random_headers(id: u32, start_height: u32, max_height: u32, position: u32) -> BlockChain {
+fn store_blockchain_and_random_headers(id: u32, start_height: u32, max_height: u32) -> BlockChain {
     let mut chain = get_empty_block_chain_from_chain_id_and_height(id, start_height, max_height);
 
     // create and insert main chain headers
@@ -1898,8 +2878,10 @@ fn store_blockchain_and_random_headers(id: u32, start_height: u32, max_height: u
         BTCRelay::set_block_header_from_hash(block_hash, &rich_header);
         chain = BTCRelay::extend_blockchain(height, &block_hash, chain).unwrap();
     }
-    // insert the main chain in Chains and ChainsIndex
-    BTCRelay::set_chain_from_position_and_id(position, id);
+    // insert the chain in ForkTips and ChainsIndex
+    if id != crate::MAIN_CHAIN_ID {
+        BTCRelay::insert_fork_tip(&chain);
+    }
     BTCRelay::set_block_chain_from_id(id, &chain);
 
     chain
@@ -2078,3 +3060,28 @@ fn set_parachain_nodata_error() {
     ext::security::set_status::<Test>(StatusCode::Error);
     assert!(ext::security::is_parachain_error_no_data_btcrelay::<Test>());
 }
+
+#[test]
+fn test_get_fork_tips_and_main_chain_block_header_succeeds() {
+    let target = U256::from(2).pow(254.into());
+    let miner = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+
+    run_test(|| {
+        let blocks = store_generated_block_chain(3);
+        assert_eq!(BTCRelay::get_fork_tips(), vec![]);
+
+        let fork_block = BlockBuilder::new()
+            .with_coinbase(&miner, 100, 1)
+            .with_previous_hash(blocks[0].header.hash().unwrap())
+            .mine(target)
+            .unwrap();
+        assert_ok!(BTCRelay::store_block_header(&3, get_raw_header(&fork_block)));
+
+        assert_eq!(BTCRelay::get_fork_tips(), vec![(fork_block.header.hash().unwrap(), 1)]);
+        assert_eq!(
+            BTCRelay::get_main_chain_block_header(1).unwrap().block_hash,
+            blocks[1].header.hash().unwrap()
+        );
+        assert_err!(BTCRelay::get_main_chain_block_header(3), TestError::MissingBlockHeight);
+    })
+}
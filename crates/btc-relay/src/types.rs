@@ -4,9 +4,36 @@ use bitcoin::{
     Error,
 };
 use codec::{Decode, Encode};
+use frame_support::weights::Weight;
+
+/// Hook for pallets which need to act when a block header is removed with a stale fork.
+pub trait OnStaleBlockHeader<AccountId> {
+    /// Called with the account which submitted the removed block header, returns the consumed
+    /// weight.
+    fn on_stale_block_header(relayer: &AccountId) -> Weight;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl<AccountId> OnStaleBlockHeader<AccountId> for Tuple {
+    fn on_stale_block_header(relayer: &AccountId) -> Weight {
+        let mut weight: Weight = 0;
+        for_tuples!( #( weight = weight.saturating_add(Tuple::on_stale_block_header(relayer)); )* );
+        weight
+    }
+}
+
+/// Storage version.
+#[derive(Encode, Decode, Eq, PartialEq)]
+pub enum Version {
+    /// Initial version.
+    V0,
+    /// pruning, fork index and reorg depth parameters
+    V1,
+}
 
 /// Bitcoin Enriched Block Headers
 #[derive(Encode, Decode, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
 pub struct RichBlockHeader<AccountId, BlockNumber> {
     pub block_hash: H256Le,
     pub block_header: BlockHeader,
@@ -45,3 +72,36 @@ impl<AccountId, BlockNumber> RichBlockHeader<AccountId, BlockNumber> {
         })
     }
 }
+
+/// State of a soft fork deployment signalled with version bits, see BIP9
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeploymentState {
+    /// The deployment has not started yet
+    Defined,
+    /// Blocks signal readiness for the deployment
+    Started,
+    /// Enough blocks signalled readiness, the deployment activates in the next period
+    LockedIn,
+    /// The deployment is active
+    Active,
+    /// The deployment timed out without locking in
+    Failed,
+}
+
+/// Soft fork deployment signalled with version bits, see BIP9
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct VersionBitsDeployment {
+    /// Version bit which signals readiness for the deployment
+    pub bit: u8,
+    /// Timestamp from which blocks may signal readiness
+    pub start_time: u32,
+    /// Timestamp at which the deployment fails unless it is locked in
+    pub timeout: u32,
+    /// Number of signalling blocks in a difficulty adjustment period required to lock in
+    pub threshold: u32,
+    /// State of the deployment in the oldest difficulty adjustment period whose signalling is
+    /// tracked, which advances as the signalling of older periods is pruned
+    pub initial_state: DeploymentState,
+}
@@ -5,6 +5,7 @@ use mocktopus::macros::mockable;
 pub(crate) mod security {
     use frame_support::dispatch::DispatchResult;
     use security::{ErrorCode, StatusCode};
+    use sp_std::collections::btree_set::BTreeSet;
 
     pub fn ensure_parachain_status_not_shutdown<T: security::Config>() -> DispatchResult {
         <security::Pallet<T>>::ensure_parachain_status_not_shutdown()
@@ -14,6 +15,14 @@ pub(crate) mod security {
         <security::Pallet<T>>::recover_from_oracle_offline()
     }
 
+    pub fn recover_from_stale_btc_tx_fees<T: security::Config>() {
+        <security::Pallet<T>>::recover_from_stale_btc_tx_fees()
+    }
+
+    pub fn get_errors<T: security::Config>() -> BTreeSet<ErrorCode> {
+        <security::Pallet<T>>::get_errors()
+    }
+
     pub(crate) fn set_status<T: security::Config>(status_code: StatusCode) {
         <security::Pallet<T>>::set_status(status_code)
     }
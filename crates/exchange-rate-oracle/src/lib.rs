@@ -101,8 +101,20 @@ pub mod pallet {
     impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
         fn on_initialize(n: T::BlockNumber) -> Weight {
             Self::begin_block(n);
-            // TODO: calculate weight
-            0
+            // the update times and delays, the timestamp and the errors are read, the errors
+            // inserted with their start and the status history written
+            T::DbWeight::get().reads_writes(6, 4)
+        }
+
+        fn on_runtime_upgrade() -> Weight {
+            if Self::storage_version() == Version::V2 {
+                return T::DbWeight::get().reads(1);
+            }
+            // the fees used to expire with the exchange rate, and were last set before the upgrade
+            MaxBtcTxFeesDelay::<T>::put(MaxDelay::<T>::get());
+            LastBtcTxFeesTime::<T>::put(Self::get_current_time());
+            StorageVersion::<T>::put(Version::V2);
+            T::DbWeight::get().reads_writes(3, 3)
         }
     }
 
@@ -119,11 +131,20 @@ pub mod pallet {
     #[pallet::getter(fn satoshi_per_bytes)]
     pub type SatoshiPerBytes<T: Config> = StorageValue<_, BtcTxFeesPerByte, ValueQuery>;
 
+    /// Last btc tx fees time
+    #[pallet::storage]
+    pub type LastBtcTxFeesTime<T: Config> = StorageValue<_, T::Moment, ValueQuery>;
+
     /// Maximum delay (milliseconds) for the exchange rate to be used
     #[pallet::storage]
     #[pallet::getter(fn max_delay)]
     pub type MaxDelay<T: Config> = StorageValue<_, T::Moment, ValueQuery>;
 
+    /// Maximum delay (milliseconds) for the estimated Bitcoin transaction fees to be used
+    #[pallet::storage]
+    #[pallet::getter(fn max_btc_tx_fees_delay)]
+    pub type MaxBtcTxFeesDelay<T: Config> = StorageValue<_, T::Moment, ValueQuery>;
+
     // Oracles allowed to set the exchange rate, maps to the name
     #[pallet::storage]
     #[pallet::getter(fn authorized_oracles)]
@@ -134,7 +155,7 @@ pub mod pallet {
         Version::V0
     }
 
-    /// Build storage at V1 (requires default 0).
+    /// Build storage at V2 (requires default 0).
     #[pallet::storage]
     #[pallet::getter(fn storage_version)]
     pub(super) type StorageVersion<T: Config> = StorageValue<_, Version, ValueQuery, DefaultForStorageVersion>;
@@ -142,6 +163,7 @@ pub mod pallet {
     #[pallet::genesis_config]
     pub struct GenesisConfig<T: Config> {
         pub max_delay: u32,
+        pub max_btc_tx_fees_delay: u32,
         pub authorized_oracles: Vec<(T::AccountId, Vec<u8>)>,
     }
 
@@ -150,6 +172,7 @@ pub mod pallet {
         fn default() -> Self {
             Self {
                 max_delay: Default::default(),
+                max_btc_tx_fees_delay: Default::default(),
                 authorized_oracles: Default::default(),
             }
         }
@@ -161,6 +184,8 @@ pub mod pallet {
             // T::Moment doesn't implement serialize so we use
             // From<u32> as bound by AtLeast32Bit
             MaxDelay::<T>::put(T::Moment::from(self.max_delay));
+            MaxBtcTxFeesDelay::<T>::put(T::Moment::from(self.max_btc_tx_fees_delay));
+            StorageVersion::<T>::put(Version::V2);
 
             for (ref who, name) in self.authorized_oracles.iter() {
                 AuthorizedOracles::<T>::insert(who, name);
@@ -224,7 +249,7 @@ pub mod pallet {
 
             // write the new values to storage
             let fees = BtcTxFeesPerByte { fast, half, hour };
-            <SatoshiPerBytes<T>>::put(fees);
+            Self::_set_btc_tx_fees_per_byte(fees);
 
             Self::deposit_event(Event::<T>::SetBtcTxFeesPerByte(signer, fast, half, hour));
 
@@ -268,6 +293,9 @@ impl<T: Config> Pallet<T> {
         if Self::is_max_delay_passed() {
             Self::report_oracle_offline();
         }
+        if Self::is_btc_tx_fees_delay_passed() {
+            Self::report_stale_btc_tx_fees();
+        }
     }
 
     /// Public getters
@@ -336,6 +364,10 @@ impl<T: Config> Pallet<T> {
         <MaxDelay<T>>::get()
     }
 
+    fn get_max_btc_tx_fees_delay() -> T::Moment {
+        <MaxBtcTxFeesDelay<T>>::get()
+    }
+
     /// Set the current exchange rate
     ///
     /// # Arguments
@@ -352,6 +384,20 @@ impl<T: Config> Pallet<T> {
         Ok(())
     }
 
+    /// Set the estimated Bitcoin transaction fees
+    ///
+    /// # Arguments
+    ///
+    /// * `fees` - estimated satoshis per byte
+    pub fn _set_btc_tx_fees_per_byte(fees: BtcTxFeesPerByte) {
+        <SatoshiPerBytes<T>>::put(fees);
+        // recover if the fees were reported as stale
+        if ext::security::get_errors::<T>().contains(&ErrorCode::StaleBtcTxFees) {
+            ext::security::recover_from_stale_btc_tx_fees::<T>();
+        }
+        <LastBtcTxFeesTime<T>>::put(Self::get_current_time());
+    }
+
     fn set_last_exchange_rate_time(time: T::Moment) {
         <LastExchangeRateTime<T>>::put(time);
     }
@@ -365,6 +411,11 @@ impl<T: Config> Pallet<T> {
         ext::security::recover_from_oracle_offline::<T>()
     }
 
+    fn report_stale_btc_tx_fees() {
+        // only the operations which pay Bitcoin transaction fees are restricted
+        ext::security::insert_error::<T>(ErrorCode::StaleBtcTxFees);
+    }
+
     /// Returns true if the last update to the exchange rate
     /// was before the maximum allowed delay
     pub fn is_max_delay_passed() -> bool {
@@ -374,6 +425,15 @@ impl<T: Config> Pallet<T> {
         last_update + max_delay < timestamp
     }
 
+    /// Returns true if the last update to the estimated Bitcoin
+    /// transaction fees was before the maximum allowed delay
+    pub fn is_btc_tx_fees_delay_passed() -> bool {
+        let timestamp = Self::get_current_time();
+        let last_update = <LastBtcTxFeesTime<T>>::get();
+        let max_delay = Self::get_max_btc_tx_fees_delay();
+        last_update + max_delay < timestamp
+    }
+
     /// Returns the current timestamp
     fn get_current_time() -> T::Moment {
         <pallet_timestamp::Pallet<T>>::get()
@@ -157,6 +157,8 @@ impl currency::Config<currency::Wrapped> for Test {
 
 impl security::Config for Test {
     type Event = TestEvent;
+    type OnRecovery = ();
+    type PausableCalls = ();
 }
 
 pub type TestEvent = Event;
@@ -171,6 +173,7 @@ impl ExtBuilder {
         exchange_rate_oracle::GenesisConfig::<Test> {
             authorized_oracles: vec![(0, "test".as_bytes().to_vec())],
             max_delay: 0,
+            max_btc_tx_fees_delay: 0,
         }
         .assimilate_storage(&mut storage)
         .unwrap();
@@ -1,9 +1,10 @@
 use crate::{
-    mock::{run_test, ExchangeRateOracle, Origin, System, Test, TestError, TestEvent},
+    mock::{run_test, ExchangeRateOracle, Origin, Security, System, Test, TestError, TestEvent},
     BtcTxFeesPerByte,
 };
 use frame_support::{assert_err, assert_ok, dispatch::DispatchError};
 use mocktopus::mocking::*;
+use security::{ErrorCode, StatusCode};
 use sp_arithmetic::FixedU128;
 use sp_runtime::FixedPointNumber;
 
@@ -149,6 +150,44 @@ fn is_max_delay_passed() {
     });
 }
 
+#[test]
+fn begin_block_reports_stale_btc_tx_fees() {
+    run_test(|| {
+        ExchangeRateOracle::is_max_delay_passed.mock_safe(|| MockResult::Return(false));
+        ExchangeRateOracle::is_btc_tx_fees_delay_passed.mock_safe(|| MockResult::Return(true));
+        ExchangeRateOracle::begin_block(0);
+        assert_eq!(Security::get_parachain_status(), StatusCode::Running);
+        assert!(Security::get_errors().contains(&ErrorCode::StaleBtcTxFees));
+
+        // updating the fees recovers
+        ExchangeRateOracle::is_authorized.mock_safe(|_| MockResult::Return(true));
+        assert_ok!(ExchangeRateOracle::set_btc_tx_fees_per_byte(Origin::signed(3), 1, 1, 1));
+        assert_eq!(Security::get_parachain_status(), StatusCode::Running);
+        assert!(Security::get_errors().is_empty());
+    });
+}
+
+#[test]
+fn is_btc_tx_fees_delay_passed() {
+    run_test(|| {
+        let now = 1585776145;
+
+        ExchangeRateOracle::get_current_time.mock_safe(move || MockResult::Return(now));
+        crate::LastBtcTxFeesTime::<Test>::put(now - 3600);
+
+        // the exchange rate delay does not apply to the fees
+        ExchangeRateOracle::get_max_delay.mock_safe(|| MockResult::Return(7200));
+
+        // max delay is 30 minutes but 1 hour passed
+        crate::MaxBtcTxFeesDelay::<Test>::put(1800);
+        assert!(ExchangeRateOracle::is_btc_tx_fees_delay_passed());
+
+        // max delay is 2 hours and 1 hour passed
+        crate::MaxBtcTxFeesDelay::<Test>::put(7200);
+        assert!(!ExchangeRateOracle::is_btc_tx_fees_delay_passed());
+    });
+}
+
 #[test]
 fn oracle_names_have_genesis_info() {
     run_test(|| {
@@ -266,3 +305,29 @@ fn begin_block_set_oracle_offline_succeeds() {
         }
     });
 }
+
+#[test]
+fn on_runtime_upgrade_sets_btc_tx_fees_delay_and_time() {
+    use crate::types::Version;
+    use frame_support::traits::OnRuntimeUpgrade;
+
+    run_test(|| {
+        let now = 1585776145;
+        ExchangeRateOracle::get_current_time.mock_safe(move || MockResult::Return(now));
+
+        crate::StorageVersion::<Test>::put(Version::V1);
+        crate::MaxDelay::<Test>::put(3600);
+        crate::MaxBtcTxFeesDelay::<Test>::kill();
+        crate::LastBtcTxFeesTime::<Test>::kill();
+        ExchangeRateOracle::on_runtime_upgrade();
+        assert_eq!(ExchangeRateOracle::max_btc_tx_fees_delay(), 3600);
+        assert_eq!(crate::LastBtcTxFeesTime::<Test>::get(), now);
+        assert!(ExchangeRateOracle::storage_version() == Version::V2);
+        assert!(!ExchangeRateOracle::is_btc_tx_fees_delay_passed());
+
+        // the delay is not overwritten
+        crate::MaxBtcTxFeesDelay::<Test>::put(600);
+        ExchangeRateOracle::on_runtime_upgrade();
+        assert_eq!(ExchangeRateOracle::max_btc_tx_fees_delay(), 600);
+    });
+}
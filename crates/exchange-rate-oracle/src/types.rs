@@ -21,6 +21,8 @@ pub enum Version {
     V0,
     /// BtcAddress type with script format.
     V1,
+    /// Estimated Bitcoin transaction fees expire separately from the exchange rate.
+    V2,
 }
 
 #[derive(Encode, Decode, Default, Eq, PartialEq, Debug)]
@@ -332,6 +332,16 @@ impl<T: Config> Module<T> {
         Ok(())
     }
 
+    /// Distribute collateral rewards to staked relayers only.
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - amount of the collateral currency
+    pub fn distribute_relayer_collateral_rewards(amount: Collateral<T>) -> Result<(), DispatchError> {
+        Self::distribute::<_, _, T::SignedFixedPoint, T::CollateralRelayerRewards>(amount)?;
+        Ok(())
+    }
+
     /// Calculate the required issue fee in tokens.
     ///
     /// # Arguments
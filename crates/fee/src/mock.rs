@@ -173,6 +173,8 @@ impl pallet_timestamp::Config for Test {
 
 impl security::Config for Test {
     type Event = TestEvent;
+    type OnRecovery = ();
+    type PausableCalls = ();
 }
 
 parameter_types! {
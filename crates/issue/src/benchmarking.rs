@@ -55,7 +55,8 @@ benchmarks! {
         let block_hash = block.header.hash().unwrap();
         let block_header = RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap();
         Security::<T>::set_active_block_number(1u32.into());
-        BtcRelay::<T>::initialize(relayer_id.clone(), block_header, height).unwrap();
+        BtcRelay::<T>::insert_checkpoint(height, block_hash).unwrap();
+        BtcRelay::<T>::initialize(relayer_id.clone(), vec![block_header], height).unwrap();
 
         let vault_btc_address = BtcAddress::P2SH(H160::zero());
 
@@ -125,7 +126,8 @@ benchmarks! {
         let block_hash = block.header.hash().unwrap();
         let block_header = RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap();
         Security::<T>::set_active_block_number(1u32.into());
-        BtcRelay::<T>::initialize(relayer_id.clone(), block_header, height).unwrap();
+        BtcRelay::<T>::insert_checkpoint(height, block_hash).unwrap();
+        BtcRelay::<T>::initialize(relayer_id.clone(), vec![block_header], height).unwrap();
 
         let transaction = TransactionBuilder::new()
             .with_version(2)
@@ -4,7 +4,7 @@ use mocktopus::macros::mockable;
 #[cfg_attr(test, mockable)]
 pub(crate) mod btc_relay {
     use btc_relay::BtcAddress;
-    use frame_support::dispatch::DispatchError;
+    use frame_support::dispatch::{DispatchError, DispatchResult};
     use sp_core::H256;
     use sp_std::vec::Vec;
 
@@ -30,6 +30,14 @@ pub(crate) mod btc_relay {
         <btc_relay::Pallet<T>>::get_best_block_height()
     }
 
+    pub fn consume_payment_outputs<T: btc_relay::Config>(
+        raw_merkle_proof: &[u8],
+        raw_tx: &[u8],
+        payments: Vec<(BtcAddress, H256)>,
+    ) -> DispatchResult {
+        <btc_relay::Pallet<T>>::consume_payment_outputs(raw_merkle_proof, raw_tx, payments)
+    }
+
     pub fn is_fully_initialized<T: btc_relay::Config>() -> Result<bool, DispatchError> {
         <btc_relay::Pallet<T>>::is_fully_initialized()
     }
@@ -138,6 +146,7 @@ pub(crate) mod treasury {
 #[cfg_attr(test, mockable)]
 pub(crate) mod security {
     use frame_support::dispatch::{DispatchError, DispatchResult};
+    use security::{ActiveClock, ErrorCode};
     use sp_core::H256;
 
     pub fn get_secure_id<T: security::Config>(id: &T::AccountId) -> H256 {
@@ -148,15 +157,23 @@ pub(crate) mod security {
         <security::Pallet<T>>::ensure_parachain_status_not_shutdown()
     }
 
+    pub fn ensure_call_enabled<T: security::Config>(module: &[u8], call: &[u8]) -> DispatchResult {
+        <security::Pallet<T>>::ensure_call_enabled(module, call)
+    }
+
+    pub fn ensure_parachain_does_not_have_errors<T: security::Config>(error_codes: &[ErrorCode]) -> DispatchResult {
+        <security::Pallet<T>>::ensure_parachain_does_not_have_errors(error_codes)
+    }
+
     pub fn active_block_number<T: security::Config>() -> T::BlockNumber {
-        <security::Pallet<T>>::active_block_number()
+        <security::Pallet<T>>::active_clock_number(ActiveClock::Issue)
     }
 
     pub fn has_expired<T: security::Config>(
         opentime: T::BlockNumber,
         period: T::BlockNumber,
     ) -> Result<bool, DispatchError> {
-        <security::Pallet<T>>::has_expired(opentime, period)
+        <security::Pallet<T>>::has_expired(ActiveClock::Issue, opentime, period)
     }
 }
 
@@ -37,11 +37,16 @@ use frame_support::{
     ensure, transactional,
 };
 use frame_system::{ensure_root, ensure_signed};
+use security::ErrorCode;
 use sp_core::H256;
 use sp_runtime::traits::*;
 use sp_std::{convert::TryInto, vec::Vec};
 use vault_registry::{CurrencySource, VaultStatus};
 
+/// Calls which root may pause. `execute_issue` is not pausable, since the requester may
+/// already have paid while the request expires.
+pub const PAUSABLE_CALLS: &[&[u8]] = &[b"request_issue"];
+
 /// The pallet's configuration trait.
 pub trait Config:
     frame_system::Config
@@ -133,6 +138,7 @@ decl_module! {
             #[compact] griefing_collateral: Collateral<T>
         ) -> DispatchResult {
             let requester = ensure_signed(origin)?;
+            ext::security::ensure_call_enabled::<T>(b"Issue", b"request_issue")?;
             Self::_request_issue(requester, amount, vault_id, griefing_collateral)?;
             Ok(())
         }
@@ -201,6 +207,8 @@ impl<T: Config> Module<T> {
     ) -> Result<H256, DispatchError> {
         // Check that Parachain is RUNNING
         ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+        // no new tokens may be backed by an undercollateralized system
+        ext::security::ensure_parachain_does_not_have_errors::<T>(&[ErrorCode::SystemUndercollateralized])?;
 
         ensure!(
             ext::btc_relay::is_fully_initialized::<T>()?,
@@ -289,13 +297,14 @@ impl<T: Config> Module<T> {
         );
 
         let (refund_address, amount_transferred) = ext::btc_relay::verify_and_validate_transaction::<T>(
-            merkle_proof,
-            raw_tx,
+            merkle_proof.clone(),
+            raw_tx.clone(),
             issue.btc_address,
             None,
             None,
             None,
         )?;
+        ext::btc_relay::consume_payment_outputs::<T>(&merkle_proof, &raw_tx, vec![(issue.btc_address, issue_id)])?;
 
         let expected_total_amount = issue
             .amount
@@ -531,6 +540,16 @@ impl<T: Config> Module<T> {
     }
 }
 
+impl<T: Config> security::PausableCalls for Module<T> {
+    fn pausable_calls(module: &[u8]) -> Option<&'static [&'static [u8]]> {
+        if module == b"Issue" {
+            Some(PAUSABLE_CALLS)
+        } else {
+            None
+        }
+    }
+}
+
 decl_error! {
     pub enum Error for Module<T: Config> {
         InsufficientCollateral,
@@ -14,6 +14,10 @@ pub(crate) mod security {
         <security::Pallet<T>>::ensure_parachain_status_running()
     }
 
+    pub fn ensure_call_enabled<T: security::Config>(module: &[u8], call: &[u8]) -> DispatchResult {
+        <security::Pallet<T>>::ensure_call_enabled(module, call)
+    }
+
     pub fn active_block_number<T: security::Config>() -> T::BlockNumber {
         <security::Pallet<T>>::active_block_number()
     }
@@ -42,6 +42,9 @@ pub trait WeightInfo {
 }
 
 /// ## Configuration and Constants
+/// Calls which root may pause
+pub const PAUSABLE_CALLS: &[&[u8]] = &[b"opt_in_to_nomination", b"deposit_nominated_collateral"];
+
 /// The pallet's configuration trait.
 pub trait Config:
     frame_system::Config
@@ -134,6 +137,7 @@ decl_module! {
         #[transactional]
         fn opt_in_to_nomination(origin) -> DispatchResult {
             ext::security::ensure_parachain_status_running::<T>()?;
+            ext::security::ensure_call_enabled::<T>(b"Nomination", b"opt_in_to_nomination")?;
             Self::_opt_in_to_nomination(&ensure_signed(origin)?)
         }
 
@@ -149,6 +153,7 @@ decl_module! {
         fn deposit_nominated_collateral(origin, operator_id: T::AccountId, amount: Collateral<T>) -> DispatchResult {
             let sender = ensure_signed(origin)?;
             ext::security::ensure_parachain_status_running::<T>()?;
+            ext::security::ensure_call_enabled::<T>(b"Nomination", b"deposit_nominated_collateral")?;
             Self::_deposit_nominated_collateral(&sender, &operator_id, amount)
         }
 
@@ -372,6 +377,16 @@ impl<T: Config> Module<T> {
     }
 }
 
+impl<T: Config> security::PausableCalls for Module<T> {
+    fn pausable_calls(module: &[u8]) -> Option<&'static [&'static [u8]]> {
+        if module == b"Nomination" {
+            Some(PAUSABLE_CALLS)
+        } else {
+            None
+        }
+    }
+}
+
 impl<T: Config> From<SlashingError> for Error<T> {
     fn from(err: SlashingError) -> Self {
         match err {
@@ -193,6 +193,8 @@ impl vault_registry::Config for Test {
 
 impl security::Config for Test {
     type Event = TestEvent;
+    type OnRecovery = ();
+    type PausableCalls = ();
 }
 
 parameter_types! {
@@ -305,6 +307,7 @@ impl ExtBuilder {
             secure_collateral_threshold: FixedU128::checked_from_rational(200, 100).unwrap(),
             premium_redeem_threshold: FixedU128::checked_from_rational(120, 100).unwrap(),
             liquidation_collateral_threshold: FixedU128::checked_from_rational(110, 100).unwrap(),
+            max_wallet_migration_period: 100,
         }
         .assimilate_storage(&mut storage)
         .unwrap();
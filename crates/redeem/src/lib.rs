@@ -35,8 +35,10 @@ use frame_support::{
     decl_error, decl_event, decl_module, decl_storage,
     dispatch::{DispatchError, DispatchResult},
     ensure, transactional,
+    weights::Weight,
 };
 use frame_system::{ensure_root, ensure_signed};
+use security::ErrorCode;
 use sp_core::H256;
 use sp_runtime::traits::*;
 use sp_std::{convert::TryInto, vec::Vec};
@@ -179,6 +181,25 @@ decl_module! {
             Ok(())
         }
 
+        /// Completes several redeem requests with a single Bitcoin transaction. The transaction
+        /// pays the requests in the given order and commits to their ids in one OP_RETURN output.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - anyone executing these redeem requests
+        /// * `redeem_ids` - identifiers of the redeem requests, in the order of the payment outputs
+        /// * `merkle_proof` - raw bytes
+        /// * `raw_tx` - raw bytes
+        #[weight = <T as Config>::WeightInfo::execute_redeem().saturating_mul(redeem_ids.len() as Weight)]
+        #[transactional]
+        fn execute_redeems(origin, redeem_ids: Vec<H256>, merkle_proof: Vec<u8>, raw_tx: Vec<u8>)
+            -> DispatchResult
+        {
+            let _ = ensure_signed(origin)?;
+            Self::_execute_redeems(redeem_ids, merkle_proof, raw_tx)?;
+            Ok(())
+        }
+
         /// If a redeem request is not completed on time, the redeem request can be cancelled.
         /// The user that initially requested the redeem process calls this function to obtain
         /// the Vault’s collateral as compensation for not refunding the BTC back to their address.
@@ -248,6 +269,8 @@ impl<T: Config> Module<T> {
         vault_id: T::AccountId,
     ) -> Result<H256, DispatchError> {
         ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+        // the bitcoin transfer fee is paid by the redeemer, so it must be up to date
+        ext::security::ensure_parachain_does_not_have_errors::<T>(&[ErrorCode::StaleBtcTxFees])?;
 
         let redeemer_balance = ext::treasury::get_balance::<T>(redeemer.clone());
         ensure!(amount_wrapped <= redeemer_balance, Error::<T>::AmountExceedsUserBalance);
@@ -351,26 +374,86 @@ impl<T: Config> Module<T> {
     fn _execute_redeem(redeem_id: H256, merkle_proof: Vec<u8>, raw_tx: Vec<u8>) -> Result<(), DispatchError> {
         ext::security::ensure_parachain_status_not_shutdown::<T>()?;
 
-        let redeem = Self::get_open_redeem_request_from_id(&redeem_id)?;
-
-        // only executable before the request has expired
-        ensure!(
-            !ext::security::has_expired::<T>(redeem.opentime, Self::redeem_period().max(redeem.period))?,
-            Error::<T>::CommitPeriodExpired
-        );
+        let redeem = Self::get_executable_redeem_request(&redeem_id)?;
 
         let amount: usize = redeem.amount_btc.try_into().map_err(|_e| Error::<T>::TryIntoIntError)?;
 
         // check the transaction inclusion and validity
         ext::btc_relay::verify_and_validate_transaction::<T>(
-            merkle_proof,
-            raw_tx,
+            merkle_proof.clone(),
+            raw_tx.clone(),
             redeem.btc_address,
             Some(amount as i64),
             Some(redeem_id),
             None,
         )?;
+        ext::btc_relay::consume_payment_outputs::<T>(&merkle_proof, &raw_tx, vec![(redeem.btc_address, redeem_id)])?;
+
+        Self::complete_redeem(redeem_id, redeem)
+    }
+
+    fn _execute_redeems(redeem_ids: Vec<H256>, merkle_proof: Vec<u8>, raw_tx: Vec<u8>) -> Result<(), DispatchError> {
+        ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+
+        let redeems = redeem_ids
+            .into_iter()
+            .map(|redeem_id| Ok((redeem_id, Self::get_executable_redeem_request(&redeem_id)?)))
+            .collect::<Result<Vec<_>, DispatchError>>()?;
+        // a batch is paid from the wallet of a single vault
+        ensure!(
+            redeems.iter().all(|(_, redeem)| redeem.vault == redeems[0].1.vault),
+            Error::<T>::BatchOfDifferentVaults
+        );
+
+        let payments = redeems
+            .iter()
+            .map(|(redeem_id, redeem)| {
+                let amount: usize = redeem.amount_btc.try_into().map_err(|_e| Error::<T>::TryIntoIntError)?;
+                Ok((redeem.btc_address, amount as i64, *redeem_id))
+            })
+            .collect::<Result<Vec<_>, DispatchError>>()?;
+
+        let outputs = payments
+            .iter()
+            .map(|(btc_address, _, request_id)| (*btc_address, *request_id))
+            .collect();
+
+        // check the transaction inclusion and validity for all requests at once
+        ext::btc_relay::verify_and_validate_batch_transaction::<T>(
+            merkle_proof.clone(),
+            raw_tx.clone(),
+            payments,
+            None,
+        )?;
+        ext::btc_relay::consume_payment_outputs::<T>(&merkle_proof, &raw_tx, outputs)?;
+        ext::btc_relay::register_batch_payment::<T>(redeems.iter().map(|(redeem_id, _)| *redeem_id).collect())?;
+
+        for (redeem_id, redeem) in redeems {
+            Self::complete_redeem(redeem_id, redeem)?;
+        }
+        Ok(())
+    }
+
+    /// Fetches an open redeem request which has not yet expired.
+    fn get_executable_redeem_request(
+        redeem_id: &H256,
+    ) -> Result<RedeemRequest<T::AccountId, T::BlockNumber, Wrapped<T>, Collateral<T>>, DispatchError> {
+        let redeem = Self::get_open_redeem_request_from_id(redeem_id)?;
+
+        // only executable before the request has expired
+        ensure!(
+            !ext::security::has_expired::<T>(redeem.opentime, Self::redeem_period().max(redeem.period))?,
+            Error::<T>::CommitPeriodExpired
+        );
+
+        Ok(redeem)
+    }
 
+    /// Burns the redeemed tokens and releases the fees once the BTC payment was verified.
+    fn complete_redeem(
+        redeem_id: H256,
+        redeem: RedeemRequest<T::AccountId, T::BlockNumber, Wrapped<T>, Collateral<T>>,
+    ) -> Result<(), DispatchError> {
         // burn amount (without parachain fee, but including transfer fee)
         let burn_amount = redeem
             .amount_btc
@@ -582,7 +665,10 @@ impl<T: Config> Module<T> {
     ///
     /// * `key` - 256-bit identifier of the redeem request
     /// * `value` - the redeem request
-    fn insert_redeem_request(key: H256, value: RedeemRequest<T::AccountId, T::BlockNumber, Wrapped<T>, Collateral<T>>) {
+    pub fn insert_redeem_request(
+        key: H256,
+        value: RedeemRequest<T::AccountId, T::BlockNumber, Wrapped<T>, Collateral<T>>,
+    ) {
         <RedeemRequests<T>>::insert(key, value)
     }
 
@@ -690,6 +776,8 @@ decl_error! {
         RedeemIdNotFound,
         /// Unable to convert value
         TryIntoIntError,
+        /// The requests of a batch belong to different vaults
+        BatchOfDifferentVaults,
         ArithmeticOverflow,
         ArithmeticUnderflow,
         AmountBelowDustAmount,
@@ -194,10 +194,13 @@ impl reward::Config<reward::WrappedRelayer> for Test {
 impl btc_relay::Config for Test {
     type Event = TestEvent;
     type WeightInfo = ();
+    type OnStaleBlock = ();
 }
 
 impl security::Config for Test {
     type Event = TestEvent;
+    type OnRecovery = ();
+    type PausableCalls = ();
 }
 
 parameter_types! {
@@ -298,6 +301,7 @@ impl ExtBuilder {
             secure_collateral_threshold: FixedU128::checked_from_rational(200, 100).unwrap(),
             premium_redeem_threshold: FixedU128::checked_from_rational(120, 100).unwrap(),
             liquidation_collateral_threshold: FixedU128::checked_from_rational(110, 100).unwrap(),
+            max_wallet_migration_period: 100,
         }
         .assimilate_storage(&mut storage)
         .unwrap();
@@ -313,6 +317,10 @@ impl ExtBuilder {
             relayer_target_sla: FixedI128::from(100),
             relayer_store_block: FixedI128::from(1),
             relayer_theft_report: FixedI128::from(1),
+            relayer_correct_block_error_proposal: FixedI128::from(1),
+            relayer_stale_block: FixedI128::from(-1),
+            relayer_outdated_block: FixedI128::from(-1),
+            relayer_failed_theft_report: FixedI128::from(-1),
         }
         .assimilate_storage(&mut storage)
         .unwrap();
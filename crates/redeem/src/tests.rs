@@ -57,6 +57,18 @@ fn test_request_redeem_fails_with_amount_exceeds_user_balance() {
     })
 }
 
+#[test]
+fn test_request_redeem_fails_with_stale_btc_tx_fees() {
+    run_test(|| {
+        <currency::Pallet<Test, currency::Wrapped>>::mint(ALICE, 2);
+        Security::<Test>::insert_error(security::ErrorCode::StaleBtcTxFees);
+        assert_noop!(
+            Redeem::request_redeem(Origin::signed(ALICE), 1, BtcAddress::default(), BOB),
+            security::Error::<Test>::ParachainStaleBtcTxFeesError
+        );
+    })
+}
+
 #[test]
 fn test_request_redeem_fails_with_amount_below_minimum() {
     run_test(|| {
@@ -234,6 +246,30 @@ fn test_liquidation_redeem_succeeds() {
     })
 }
 
+#[test]
+fn test_redeem_module_cannot_be_paused() {
+    run_test(|| {
+        assert_noop!(
+            Security::<Test>::set_module_paused(Origin::root(), b"Redeem".to_vec(), true),
+            security::Error::<Test>::UnknownModule
+        );
+
+        // execute stays available while cancel_redeem is open
+        ext::oracle::wrapped_to_collateral::<Test>.mock_safe(|x| MockResult::Return(btcdot_parity(x)));
+        assert_err!(
+            Redeem::execute_redeem(Origin::signed(BOB), H256([0u8; 32]), Vec::default(), Vec::default()),
+            TestError::RedeemIdNotFound
+        );
+
+        // users can still leave the system
+        <currency::Pallet<Test, currency::Wrapped>>::mint(ALICE, 2);
+        assert_err!(
+            Redeem::request_redeem(Origin::signed(ALICE), 10_000_000, BtcAddress::default(), BOB),
+            TestError::AmountExceedsUserBalance
+        );
+    })
+}
+
 #[test]
 fn test_execute_redeem_fails_with_redeem_id_not_found() {
     run_test(|| {
@@ -268,6 +304,7 @@ fn test_execute_redeem_succeeds_with_another_account() {
         );
         ext::btc_relay::verify_and_validate_transaction::<Test>
             .mock_safe(|_, _, _, _, _, _| MockResult::Return(Ok((BtcAddress::P2SH(H160::zero()), 0))));
+        ext::btc_relay::consume_payment_outputs::<Test>.mock_safe(|_, _, _| MockResult::Return(Ok(())));
 
         inject_redeem_request(
             H256([0u8; 32]),
@@ -366,6 +403,7 @@ fn test_execute_redeem_succeeds() {
         );
         ext::btc_relay::verify_and_validate_transaction::<Test>
             .mock_safe(|_, _, _, _, _, _| MockResult::Return(Ok((BtcAddress::P2SH(H160::zero()), 0))));
+        ext::btc_relay::consume_payment_outputs::<Test>.mock_safe(|_, _, _| MockResult::Return(Ok(())));
 
         inject_redeem_request(
             H256([0u8; 32]),
@@ -413,6 +451,147 @@ fn test_execute_redeem_succeeds() {
     })
 }
 
+#[test]
+fn test_execute_redeems_succeeds() {
+    run_test(|| {
+        ext::oracle::wrapped_to_collateral::<Test>.mock_safe(|x| MockResult::Return(btcdot_parity(x)));
+        Security::<Test>::set_active_block_number(40);
+        <vault_registry::Pallet<Test>>::insert_vault(
+            &BOB,
+            vault_registry::Vault {
+                id: BOB,
+                to_be_replaced_tokens: 0,
+                to_be_issued_tokens: 0,
+                issued_tokens: 200,
+                to_be_redeemed_tokens: 200,
+                backing_collateral: 0,
+                replace_collateral: 0,
+                wallet: Wallet::new(dummy_public_key()),
+                banned_until: None,
+                status: VaultStatus::Active(true),
+                ..Default::default()
+            },
+        );
+
+        let redeem_ids = vec![H256([1u8; 32]), H256([2u8; 32])];
+        let btc_addresses = vec![BtcAddress::random(), BtcAddress::random()];
+        for (redeem_id, btc_address) in redeem_ids.iter().zip(btc_addresses.iter()) {
+            inject_redeem_request(
+                *redeem_id,
+                RedeemRequest {
+                    period: 0,
+                    vault: BOB,
+                    opentime: 40,
+                    fee: 0,
+                    amount_btc: 100,
+                    premium: 0,
+                    redeemer: ALICE,
+                    btc_address: *btc_address,
+                    btc_height: 0,
+                    status: RedeemRequestStatus::Pending,
+                    transfer_fee_btc: Redeem::get_current_inclusion_fee().unwrap(),
+                },
+            );
+        }
+
+        let expected_payments = vec![
+            (btc_addresses[0], 100, redeem_ids[0]),
+            (btc_addresses[1], 100, redeem_ids[1]),
+        ];
+        ext::btc_relay::verify_and_validate_batch_transaction::<Test>.mock_safe(move |_, _, payments, _| {
+            assert_eq!(payments, expected_payments);
+            MockResult::Return(Ok((BtcAddress::P2SH(H160::zero()), vec![100, 100])))
+        });
+        ext::btc_relay::consume_payment_outputs::<Test>.mock_safe(|_, _, _| MockResult::Return(Ok(())));
+        ext::treasury::burn::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::vault_registry::redeem_tokens::<Test>.mock_safe(|_, _, _, _| MockResult::Return(Ok(())));
+
+        assert_ok!(Redeem::execute_redeems(
+            Origin::signed(BOB),
+            redeem_ids.clone(),
+            Vec::default(),
+            Vec::default()
+        ));
+        for redeem_id in redeem_ids {
+            assert_emitted!(Event::ExecuteRedeem(redeem_id, ALICE, 100, 0, BOB,));
+            assert_err!(
+                Redeem::get_open_redeem_request_from_id(&redeem_id),
+                TestError::RedeemCompleted,
+            );
+        }
+    })
+}
+
+#[test]
+fn test_execute_redeems_fails_with_different_vaults() {
+    run_test(|| {
+        Security::<Test>::set_active_block_number(40);
+
+        for (redeem_id, vault) in &[(H256([1u8; 32]), BOB), (H256([2u8; 32]), CAROL)] {
+            inject_redeem_request(
+                *redeem_id,
+                RedeemRequest {
+                    period: 0,
+                    vault: *vault,
+                    opentime: 40,
+                    fee: 0,
+                    amount_btc: 100,
+                    premium: 0,
+                    redeemer: ALICE,
+                    btc_address: BtcAddress::random(),
+                    btc_height: 0,
+                    status: RedeemRequestStatus::Pending,
+                    transfer_fee_btc: Redeem::get_current_inclusion_fee().unwrap(),
+                },
+            );
+        }
+
+        assert_err!(
+            Redeem::execute_redeems(
+                Origin::signed(BOB),
+                vec![H256([1u8; 32]), H256([2u8; 32])],
+                Vec::default(),
+                Vec::default()
+            ),
+            TestError::BatchOfDifferentVaults
+        );
+    })
+}
+
+#[test]
+fn test_execute_redeems_fails_with_commit_period_expired() {
+    run_test(|| {
+        Security::<Test>::set_active_block_number(40);
+
+        inject_redeem_request(
+            H256([1u8; 32]),
+            RedeemRequest {
+                period: 0,
+                vault: BOB,
+                opentime: 20,
+                fee: 0,
+                amount_btc: 0,
+                premium: 0,
+                redeemer: ALICE,
+                btc_address: BtcAddress::random(),
+                btc_height: 0,
+                status: RedeemRequestStatus::Pending,
+                transfer_fee_btc: Redeem::get_current_inclusion_fee().unwrap(),
+            },
+        );
+
+        assert_err!(
+            Redeem::execute_redeems(
+                Origin::signed(BOB),
+                vec![H256([1u8; 32])],
+                Vec::default(),
+                Vec::default()
+            ),
+            TestError::CommitPeriodExpired
+        );
+    })
+}
+
 #[test]
 fn test_cancel_redeem_fails_with_redeem_id_not_found() {
     run_test(|| {
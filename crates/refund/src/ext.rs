@@ -28,7 +28,7 @@ pub(crate) mod sla {
 #[cfg_attr(test, mockable)]
 pub(crate) mod btc_relay {
     use btc_relay::BtcAddress;
-    use frame_support::dispatch::DispatchError;
+    use frame_support::dispatch::{DispatchError, DispatchResult};
     use sp_core::H256;
     use sp_std::vec::Vec;
 
@@ -49,6 +49,32 @@ pub(crate) mod btc_relay {
             confirmations,
         )
     }
+
+    pub fn consume_payment_outputs<T: btc_relay::Config>(
+        raw_merkle_proof: &[u8],
+        raw_tx: &[u8],
+        payments: Vec<(BtcAddress, H256)>,
+    ) -> DispatchResult {
+        <btc_relay::Pallet<T>>::consume_payment_outputs(raw_merkle_proof, raw_tx, payments)
+    }
+
+    pub fn register_batch_payment<T: btc_relay::Config>(request_ids: Vec<H256>) -> Result<H256, DispatchError> {
+        <btc_relay::Pallet<T>>::register_batch_payment(request_ids)
+    }
+
+    pub fn verify_and_validate_batch_transaction<T: btc_relay::Config>(
+        raw_merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+        payments: Vec<(BtcAddress, i64, H256)>,
+        confirmations: Option<u32>,
+    ) -> Result<(BtcAddress, Vec<i64>), DispatchError> {
+        <btc_relay::Pallet<T>>::_verify_and_validate_batch_transaction(
+            raw_merkle_proof,
+            raw_tx,
+            payments,
+            confirmations,
+        )
+    }
 }
 
 #[cfg_attr(test, mockable)]
@@ -24,7 +24,7 @@ pub mod types;
 
 use btc_relay::BtcAddress;
 use frame_support::{
-    decl_error, decl_event, decl_module, decl_storage, dispatch::DispatchError, ensure, transactional,
+    decl_error, decl_event, decl_module, decl_storage, dispatch::DispatchError, ensure, transactional, weights::Weight,
 };
 use frame_system::ensure_signed;
 use sp_core::H256;
@@ -97,6 +97,20 @@ decl_module! {
             ensure_signed(origin)?;
             Self::_execute_refund(refund_id, merkle_proof, raw_tx)
         }
+
+        /// Executes several refund requests which were paid with a single Bitcoin transaction,
+        /// in the order of the payment outputs.
+        #[weight = <T as Config>::WeightInfo::execute_refund().saturating_mul(refund_ids.len() as Weight)]
+        #[transactional]
+        fn execute_refunds(
+            origin,
+            refund_ids: Vec<H256>,
+            merkle_proof: Vec<u8>,
+            raw_tx: Vec<u8>,
+        ) -> Result<(), DispatchError> {
+            ensure_signed(origin)?;
+            Self::_execute_refunds(refund_ids, merkle_proof, raw_tx)
+        }
     }
 }
 
@@ -182,13 +196,69 @@ impl<T: Config> Module<T> {
 
         // check the transaction inclusion and validity
         ext::btc_relay::verify_and_validate_transaction::<T>(
-            merkle_proof,
-            raw_tx,
+            merkle_proof.clone(),
+            raw_tx.clone(),
             request.btc_address,
             Some(amount as i64),
             Some(refund_id),
             None,
         )?;
+        ext::btc_relay::consume_payment_outputs::<T>(&merkle_proof, &raw_tx, vec![(request.btc_address, refund_id)])?;
+
+        Self::complete_refund(refund_id, request)
+    }
+
+    fn _execute_refunds(refund_ids: Vec<H256>, merkle_proof: Vec<u8>, raw_tx: Vec<u8>) -> Result<(), DispatchError> {
+        ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+
+        let requests = refund_ids
+            .into_iter()
+            .map(|refund_id| Ok((refund_id, Self::get_open_refund_request_from_id(&refund_id)?)))
+            .collect::<Result<Vec<_>, DispatchError>>()?;
+        // a batch is paid from the wallet of a single vault
+        ensure!(
+            requests.iter().all(|(_, request)| request.vault == requests[0].1.vault),
+            Error::<T>::BatchOfDifferentVaults
+        );
+
+        let payments = requests
+            .iter()
+            .map(|(refund_id, request)| {
+                let amount: usize = request
+                    .amount_wrapped
+                    .try_into()
+                    .map_err(|_e| Error::<T>::TryIntoIntError)?;
+                Ok((request.btc_address, amount as i64, *refund_id))
+            })
+            .collect::<Result<Vec<_>, DispatchError>>()?;
+
+        let outputs = payments
+            .iter()
+            .map(|(btc_address, _, request_id)| (*btc_address, *request_id))
+            .collect();
+
+        // check the transaction inclusion and validity for all requests at once
+        ext::btc_relay::verify_and_validate_batch_transaction::<T>(
+            merkle_proof.clone(),
+            raw_tx.clone(),
+            payments,
+            None,
+        )?;
+        ext::btc_relay::consume_payment_outputs::<T>(&merkle_proof, &raw_tx, outputs)?;
+        ext::btc_relay::register_batch_payment::<T>(requests.iter().map(|(refund_id, _)| *refund_id).collect())?;
+
+        for (refund_id, request) in requests {
+            Self::complete_refund(refund_id, request)?;
+        }
+        Ok(())
+    }
+
+    /// Rewards the vault for a refund once the BTC payment was verified.
+    fn complete_refund(refund_id: H256, request: RefundRequest<T::AccountId, Wrapped<T>>) -> Result<(), DispatchError> {
+        let amount: usize = request
+            .amount_wrapped
+            .try_into()
+            .map_err(|_e| Error::<T>::TryIntoIntError)?;
 
         // mint issued tokens corresponding to the fee. Note that this can fail
         ext::vault_registry::try_increase_to_be_issued_tokens::<T>(&request.vault, request.fee)?;
@@ -297,6 +367,8 @@ decl_error! {
         RefundIdNotFound,
         RefundCompleted,
         TryIntoIntError,
-        UnauthorizedVault
+        UnauthorizedVault,
+        /// The requests of a batch belong to different vaults
+        BatchOfDifferentVaults
     }
 }
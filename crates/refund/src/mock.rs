@@ -206,10 +206,13 @@ impl sla::Config for Test {
 impl btc_relay::Config for Test {
     type Event = TestEvent;
     type WeightInfo = ();
+    type OnStaleBlock = ();
 }
 
 impl security::Config for Test {
     type Event = TestEvent;
+    type OnRecovery = ();
+    type PausableCalls = ();
 }
 
 parameter_types! {
@@ -251,6 +254,7 @@ impl pallet_timestamp::Config for Test {
 }
 
 pub type TestEvent = Event;
+pub type TestError = crate::Error<Test>;
 
 pub struct ExtBuilder;
 
@@ -1,6 +1,6 @@
-use crate::{ext, mock::*, RawEvent};
+use crate::{ext, mock::*, RawEvent, RefundRequest};
 use btc_relay::BtcAddress;
-use frame_support::assert_ok;
+use frame_support::{assert_err, assert_ok};
 use mocktopus::mocking::*;
 use sp_core::{H160, H256};
 
@@ -12,6 +12,7 @@ fn test_refund_succeeds() {
         ext::vault_registry::issue_tokens::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
         ext::btc_relay::verify_and_validate_transaction::<Test>
             .mock_safe(|_, _, _, _, _, _| MockResult::Return(Ok((BtcAddress::P2SH(H160::zero()), 995))));
+        ext::btc_relay::consume_payment_outputs::<Test>.mock_safe(|_, _, _| MockResult::Return(Ok(())));
 
         let issue_id = H256::zero();
         assert_ok!(Refund::request_refund(
@@ -43,3 +44,68 @@ fn test_refund_succeeds() {
         assert_ok!(Refund::_execute_refund(refund_id, vec![0u8; 100], vec![0u8; 100],));
     })
 }
+
+fn test_request(vault: AccountId, btc_address: BtcAddress) -> RefundRequest<AccountId, Balance> {
+    RefundRequest {
+        vault,
+        amount_wrapped: 995,
+        fee: 5,
+        amount_btc: 1000,
+        issuer: USER,
+        btc_address,
+        issue_id: H256::zero(),
+        completed: false,
+    }
+}
+
+#[test]
+fn test_execute_refunds_succeeds() {
+    run_test(|| {
+        ext::vault_registry::try_increase_to_be_issued_tokens::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::vault_registry::issue_tokens::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::btc_relay::consume_payment_outputs::<Test>.mock_safe(|_, _, _| MockResult::Return(Ok(())));
+        Refund::get_open_refund_request_from_id.mock_safe(|refund_id| {
+            let btc_address = BtcAddress::P2SH(H160::from_low_u64_be(refund_id.to_low_u64_be()));
+            MockResult::Return(Ok(test_request(VAULT, btc_address)))
+        });
+
+        let refund_ids = vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)];
+        let expected_payments = vec![
+            (BtcAddress::P2SH(H160::from_low_u64_be(1)), 995, refund_ids[0]),
+            (BtcAddress::P2SH(H160::from_low_u64_be(2)), 995, refund_ids[1]),
+        ];
+        ext::btc_relay::verify_and_validate_batch_transaction::<Test>.mock_safe(move |_, _, payments, _| {
+            assert_eq!(payments, expected_payments);
+            MockResult::Return(Ok((BtcAddress::P2SH(H160::zero()), vec![995, 995])))
+        });
+
+        assert_ok!(Refund::_execute_refunds(refund_ids.clone(), Vec::new(), Vec::new()));
+        for refund_id in refund_ids.clone() {
+            assert!(System::events().iter().any(|x| matches!(
+                x.event,
+                TestEvent::refund(RawEvent::ExecuteRefund(id, USER, VAULT, 995)) if id == refund_id
+            )));
+        }
+        let op_return = BTCRelay::compute_batch_op_return(&refund_ids).unwrap();
+        assert_eq!(BTCRelay::batch_payment(&op_return), Some(refund_ids));
+    })
+}
+
+#[test]
+fn test_execute_refunds_of_different_vaults_fails() {
+    run_test(|| {
+        Refund::get_open_refund_request_from_id.mock_safe(|refund_id| {
+            let vault = if refund_id.to_low_u64_be() == 2 { USER } else { VAULT };
+            MockResult::Return(Ok(test_request(vault, BtcAddress::P2SH(H160::zero()))))
+        });
+
+        assert_err!(
+            Refund::_execute_refunds(
+                vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)],
+                Vec::new(),
+                Vec::new()
+            ),
+            TestError::BatchOfDifferentVaults
+        );
+    })
+}
@@ -137,7 +137,8 @@ benchmarks! {
         let block_hash = block.header.hash().unwrap();
         let block_header = RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap();
         Security::<T>::set_active_block_number(1u32.into());
-        BtcRelay::<T>::initialize(relayer_id.clone(), block_header, height).unwrap();
+        BtcRelay::<T>::insert_checkpoint(height, block_hash).unwrap();
+        BtcRelay::<T>::initialize(relayer_id.clone(), vec![block_header], height).unwrap();
 
         let value = 0;
         let transaction = TransactionBuilder::new()
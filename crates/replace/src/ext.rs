@@ -4,7 +4,7 @@ use mocktopus::macros::mockable;
 #[cfg_attr(test, mockable)]
 pub(crate) mod btc_relay {
     use btc_relay::BtcAddress;
-    use frame_support::dispatch::DispatchError;
+    use frame_support::dispatch::{DispatchError, DispatchResult};
     use sp_core::H256;
     use sp_std::vec::Vec;
 
@@ -26,9 +26,35 @@ pub(crate) mod btc_relay {
         )
     }
 
+    pub fn verify_and_validate_batch_transaction<T: btc_relay::Config>(
+        raw_merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+        payments: Vec<(BtcAddress, i64, H256)>,
+        confirmations: Option<u32>,
+    ) -> Result<(BtcAddress, Vec<i64>), DispatchError> {
+        <btc_relay::Pallet<T>>::_verify_and_validate_batch_transaction(
+            raw_merkle_proof,
+            raw_tx,
+            payments,
+            confirmations,
+        )
+    }
+
     pub fn get_best_block_height<T: btc_relay::Config>() -> u32 {
         <btc_relay::Pallet<T>>::get_best_block_height()
     }
+
+    pub fn consume_payment_outputs<T: btc_relay::Config>(
+        raw_merkle_proof: &[u8],
+        raw_tx: &[u8],
+        payments: Vec<(BtcAddress, H256)>,
+    ) -> DispatchResult {
+        <btc_relay::Pallet<T>>::consume_payment_outputs(raw_merkle_proof, raw_tx, payments)
+    }
+
+    pub fn register_batch_payment<T: btc_relay::Config>(request_ids: Vec<H256>) -> Result<H256, DispatchError> {
+        <btc_relay::Pallet<T>>::register_batch_payment(request_ids)
+    }
 }
 
 #[cfg_attr(test, mockable)]
@@ -167,6 +193,7 @@ pub(crate) mod collateral {
 #[cfg_attr(test, mockable)]
 pub(crate) mod security {
     use frame_support::dispatch::{DispatchError, DispatchResult};
+    use security::ActiveClock;
     use sp_core::H256;
 
     pub fn get_secure_id<T: security::Config>(id: &T::AccountId) -> H256 {
@@ -177,15 +204,19 @@ pub(crate) mod security {
         <security::Pallet<T>>::ensure_parachain_status_not_shutdown()
     }
 
+    pub fn ensure_call_enabled<T: security::Config>(module: &[u8], call: &[u8]) -> DispatchResult {
+        <security::Pallet<T>>::ensure_call_enabled(module, call)
+    }
+
     pub fn active_block_number<T: security::Config>() -> T::BlockNumber {
-        <security::Pallet<T>>::active_block_number()
+        <security::Pallet<T>>::active_clock_number(ActiveClock::Replace)
     }
 
     pub fn has_expired<T: security::Config>(
         opentime: T::BlockNumber,
         period: T::BlockNumber,
     ) -> Result<bool, DispatchError> {
-        <security::Pallet<T>>::has_expired(opentime, period)
+        <security::Pallet<T>>::has_expired(ActiveClock::Replace, opentime, period)
     }
 }
 
@@ -18,6 +18,7 @@ use frame_support::{
     decl_error, decl_event, decl_module, decl_storage,
     dispatch::{DispatchError, DispatchResult},
     ensure, transactional,
+    weights::Weight,
 };
 use frame_system::{ensure_root, ensure_signed};
 #[cfg(test)]
@@ -34,6 +35,10 @@ pub use crate::types::{ReplaceRequest, ReplaceRequestStatus};
 use crate::types::{Collateral, Version, Wrapped};
 use vault_registry::CurrencySource;
 
+/// Calls which root may pause. The execute calls are not pausable, since the new vault may
+/// already have been paid while the request expires.
+pub const PAUSABLE_CALLS: &[&[u8]] = &[b"request_replace", b"accept_replace"];
+
 mod ext;
 pub mod types;
 
@@ -128,6 +133,7 @@ decl_module! {
             -> DispatchResult
         {
             ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+            ext::security::ensure_call_enabled::<T>(b"Replace", b"request_replace")?;
             let old_vault = ensure_signed(origin)?;
             Self::_request_replace(old_vault, amount, griefing_collateral)?;
             Ok(())
@@ -163,6 +169,7 @@ decl_module! {
             -> DispatchResult
         {
             ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+            ext::security::ensure_call_enabled::<T>(b"Replace", b"accept_replace")?;
             let new_vault = ensure_signed(origin)?;
             Self::_accept_replace(old_vault, new_vault, amount_btc, collateral, btc_address)?;
             Ok(())
@@ -185,6 +192,24 @@ decl_module! {
             Ok(())
         }
 
+        /// Execute several vault replacements with a single Bitcoin transaction. The transaction
+        /// pays the requests in the given order and commits to their ids in one OP_RETURN output.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - sender of the transaction
+        /// * `replace_ids` - the IDs of the replacement requests, in the order of the payment outputs
+        /// * 'merkle_proof' - the merkle root of the block
+        /// * `raw_tx` - the transaction id in bytes
+        #[weight = <T as Config>::WeightInfo::execute_replace().saturating_mul(replace_ids.len() as Weight)]
+        #[transactional]
+        fn execute_replaces(origin, replace_ids: Vec<H256>, merkle_proof: Vec<u8>, raw_tx: Vec<u8>) -> DispatchResult {
+            ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+            let _ = ensure_signed(origin)?;
+            Self::_execute_replaces(replace_ids, merkle_proof, raw_tx)?;
+            Ok(())
+        }
+
         /// Cancel vault replacement
         ///
         /// # Arguments
@@ -370,29 +395,89 @@ impl<T: Config> Module<T> {
 
     fn _execute_replace(replace_id: H256, merkle_proof: Vec<u8>, raw_tx: Vec<u8>) -> Result<(), DispatchError> {
         // Retrieve the ReplaceRequest as per the replaceId parameter from Vaults in the VaultRegistry
-        let replace = Self::get_open_replace_request(&replace_id)?;
-
-        // NOTE: anyone can call this method provided the proof is correct
-        let new_vault_id = replace.new_vault;
-        let old_vault_id = replace.old_vault;
-
-        // only executable before the request has expired
-        ensure!(
-            !ext::security::has_expired::<T>(replace.accept_time, Self::replace_period().max(replace.period))?,
-            Error::<T>::ReplacePeriodExpired
-        );
+        let replace = Self::get_executable_replace_request(&replace_id)?;
 
         let amount = TryInto::<u64>::try_into(replace.amount).map_err(|_e| Error::<T>::TryIntoIntError)? as i64;
 
         // check the transaction inclusion and validity
         ext::btc_relay::verify_and_validate_transaction::<T>(
-            merkle_proof,
-            raw_tx,
+            merkle_proof.clone(),
+            raw_tx.clone(),
             replace.btc_address,
             Some(amount),
             Some(replace_id),
             None,
         )?;
+        ext::btc_relay::consume_payment_outputs::<T>(&merkle_proof, &raw_tx, vec![(replace.btc_address, replace_id)])?;
+
+        Self::complete_replace(replace_id, replace)
+    }
+
+    fn _execute_replaces(replace_ids: Vec<H256>, merkle_proof: Vec<u8>, raw_tx: Vec<u8>) -> Result<(), DispatchError> {
+        let replaces = replace_ids
+            .into_iter()
+            .map(|replace_id| Ok((replace_id, Self::get_executable_replace_request(&replace_id)?)))
+            .collect::<Result<Vec<_>, DispatchError>>()?;
+        // a batch is paid from the wallet of a single vault
+        ensure!(
+            replaces
+                .iter()
+                .all(|(_, replace)| replace.old_vault == replaces[0].1.old_vault),
+            Error::<T>::BatchOfDifferentVaults
+        );
+
+        let payments = replaces
+            .iter()
+            .map(|(replace_id, replace)| {
+                let amount = TryInto::<u64>::try_into(replace.amount).map_err(|_e| Error::<T>::TryIntoIntError)? as i64;
+                Ok((replace.btc_address, amount, *replace_id))
+            })
+            .collect::<Result<Vec<_>, DispatchError>>()?;
+
+        let outputs = payments
+            .iter()
+            .map(|(btc_address, _, request_id)| (*btc_address, *request_id))
+            .collect();
+
+        // check the transaction inclusion and validity for all requests at once
+        ext::btc_relay::verify_and_validate_batch_transaction::<T>(
+            merkle_proof.clone(),
+            raw_tx.clone(),
+            payments,
+            None,
+        )?;
+        ext::btc_relay::consume_payment_outputs::<T>(&merkle_proof, &raw_tx, outputs)?;
+        ext::btc_relay::register_batch_payment::<T>(replaces.iter().map(|(replace_id, _)| *replace_id).collect())?;
+
+        for (replace_id, replace) in replaces {
+            Self::complete_replace(replace_id, replace)?;
+        }
+        Ok(())
+    }
+
+    /// Fetches an accepted replace request which has not yet expired.
+    fn get_executable_replace_request(
+        replace_id: &H256,
+    ) -> Result<ReplaceRequest<T::AccountId, T::BlockNumber, Wrapped<T>, Collateral<T>>, DispatchError> {
+        let replace = Self::get_open_replace_request(replace_id)?;
+
+        // only executable before the request has expired
+        ensure!(
+            !ext::security::has_expired::<T>(replace.accept_time, Self::replace_period().max(replace.period))?,
+            Error::<T>::ReplacePeriodExpired
+        );
+
+        Ok(replace)
+    }
+
+    /// Moves the replaced tokens to the new vault once the BTC payment was verified.
+    fn complete_replace(
+        replace_id: H256,
+        replace: ReplaceRequest<T::AccountId, T::BlockNumber, Wrapped<T>, Collateral<T>>,
+    ) -> Result<(), DispatchError> {
+        // NOTE: anyone can call this method provided the proof is correct
+        let new_vault_id = replace.new_vault;
+        let old_vault_id = replace.old_vault;
 
         // decrease old-vault's issued & to-be-redeemed tokens, and
         // change new-vault's to-be-issued tokens to issued tokens
@@ -548,6 +633,16 @@ impl<T: Config> Module<T> {
     }
 }
 
+impl<T: Config> security::PausableCalls for Module<T> {
+    fn pausable_calls(module: &[u8]) -> Option<&'static [&'static [u8]]> {
+        if module == b"Replace" {
+            Some(PAUSABLE_CALLS)
+        } else {
+            None
+        }
+    }
+}
+
 decl_error! {
     pub enum Error for Module<T: Config> {
         AmountBelowDustAmount,
@@ -566,6 +661,8 @@ decl_error! {
         ReplaceIdNotFound,
         /// Unable to convert value
         TryIntoIntError,
+        /// The requests of a batch belong to different vaults
+        BatchOfDifferentVaults,
         ArithmeticUnderflow,
         ArithmeticOverflow,
     }
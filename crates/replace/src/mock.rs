@@ -191,10 +191,13 @@ impl vault_registry::Config for Test {
 impl btc_relay::Config for Test {
     type Event = TestEvent;
     type WeightInfo = ();
+    type OnStaleBlock = ();
 }
 
 impl security::Config for Test {
     type Event = TestEvent;
+    type OnRecovery = ();
+    type PausableCalls = ();
 }
 
 impl sla::Config for Test {
@@ -181,6 +181,7 @@ mod execute_replace_test {
         ext::security::has_expired::<Test>.mock_safe(|_, _| MockResult::Return(Ok(false)));
         ext::btc_relay::verify_and_validate_transaction::<Test>
             .mock_safe(|_, _, _, _, _, _| MockResult::Return(Ok((BtcAddress::P2SH(H160::zero()), 0))));
+        ext::btc_relay::consume_payment_outputs::<Test>.mock_safe(|_, _, _| MockResult::Return(Ok(())));
         ext::vault_registry::replace_tokens::<Test>.mock_safe(|_, _, _, _| MockResult::Return(Ok(())));
         ext::collateral::release_collateral::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
     }
@@ -206,6 +207,58 @@ mod execute_replace_test {
             );
         })
     }
+
+    #[test]
+    fn test_execute_replaces_succeeds() {
+        run_test(|| {
+            setup_mocks();
+            Replace::get_open_replace_request.mock_safe(|replace_id| {
+                let mut replace = test_request();
+                replace.btc_address = BtcAddress::P2SH(H160::from_low_u64_be(replace_id.to_low_u64_be()));
+                MockResult::Return(Ok(replace))
+            });
+
+            let replace_ids = vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)];
+            let expected_payments = vec![
+                (BtcAddress::P2SH(H160::from_low_u64_be(1)), 10, replace_ids[0]),
+                (BtcAddress::P2SH(H160::from_low_u64_be(2)), 10, replace_ids[1]),
+            ];
+            ext::btc_relay::verify_and_validate_batch_transaction::<Test>.mock_safe(move |_, _, payments, _| {
+                assert_eq!(payments, expected_payments);
+                MockResult::Return(Ok((BtcAddress::P2SH(H160::zero()), vec![10, 10])))
+            });
+
+            assert_ok!(Replace::_execute_replaces(replace_ids.clone(), Vec::new(), Vec::new()));
+            for replace_id in replace_ids.clone() {
+                assert_event_matches!(Event::ExecuteReplace(id, OLD_VAULT, NEW_VAULT) if id == replace_id);
+            }
+            let op_return = BTCRelay::compute_batch_op_return(&replace_ids).unwrap();
+            assert_eq!(BTCRelay::batch_payment(&op_return), Some(replace_ids));
+        })
+    }
+
+    #[test]
+    fn test_execute_replaces_of_different_vaults_fails() {
+        run_test(|| {
+            setup_mocks();
+            Replace::get_open_replace_request.mock_safe(|replace_id| {
+                let mut replace = test_request();
+                if replace_id.to_low_u64_be() == 2 {
+                    replace.old_vault = CAROL;
+                }
+                MockResult::Return(Ok(replace))
+            });
+
+            assert_err!(
+                Replace::_execute_replaces(
+                    vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)],
+                    Vec::new(),
+                    Vec::new()
+                ),
+                TestError::BatchOfDifferentVaults
+            );
+        })
+    }
 }
 
 mod cancel_replace_tests {
@@ -0,0 +1,15 @@
+//! Runtime API definition for the Security Module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+    pub trait SecurityApi<StatusChange> where
+        StatusChange: Codec,
+    {
+        /// Get the most recent changes to the parachain status and errors, oldest first
+        fn get_status_history() -> Vec<StatusChange>;
+    }
+}
@@ -0,0 +1,65 @@
+//! RPC interface for the Security Module.
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result as JsonRpcResult};
+use jsonrpc_derive::rpc;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use std::sync::Arc;
+
+pub use self::gen_client::Client as SecurityClient;
+pub use module_security_rpc_runtime_api::SecurityApi as SecurityRuntimeApi;
+
+#[rpc]
+pub trait SecurityApi<BlockHash, StatusChange> {
+    #[rpc(name = "security_getStatusHistory")]
+    fn get_status_history(&self, at: Option<BlockHash>) -> JsonRpcResult<Vec<StatusChange>>;
+}
+
+/// A struct that implements the [`SecurityApi`].
+pub struct Security<C, B> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Security<C, B> {
+    /// Create new `Security` with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        Security {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+pub enum Error {
+    RuntimeError,
+}
+
+impl From<Error> for i64 {
+    fn from(e: Error) -> i64 {
+        match e {
+            Error::RuntimeError => 1,
+        }
+    }
+}
+
+impl<C, Block, StatusChange> SecurityApi<<Block as BlockT>::Hash, StatusChange> for Security<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    C::Api: SecurityRuntimeApi<Block, StatusChange>,
+    StatusChange: Codec,
+{
+    fn get_status_history(&self, at: Option<<Block as BlockT>::Hash>) -> JsonRpcResult<Vec<StatusChange>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.get_status_history(&at).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(Error::RuntimeError.into()),
+            message: "Unable to fetch status history.".into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
+}
@@ -22,12 +22,15 @@ extern crate mocktopus;
 use mocktopus::macros::mockable;
 
 #[doc(inline)]
-pub use crate::types::{ErrorCode, StatusCode};
+pub use crate::types::{
+    ActiveClock, ErrorCode, OnErrorRecovery, PausableCalls, StatusChange, StatusChangeOrigin, StatusCode,
+    StatusTransition,
+};
 
 use codec::Encode;
 use frame_support::{
     dispatch::{DispatchError, DispatchResult},
-    transactional,
+    ensure, transactional,
     weights::Weight,
 };
 use frame_system::ensure_root;
@@ -37,6 +40,9 @@ use sp_std::{collections::btree_set::BTreeSet, prelude::*, vec};
 
 pub use pallet::*;
 
+/// Maximum number of status changes kept in the history.
+pub const MAX_STATUS_HISTORY_LENGTH: usize = 100;
+
 #[frame_support::pallet]
 pub mod pallet {
     use super::*;
@@ -49,6 +55,12 @@ pub mod pallet {
     pub trait Config: frame_system::Config {
         /// The overarching event type.
         type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+        /// Recovery callbacks of other pallets, run when an `ErrorCode` is removed.
+        type OnRecovery: OnErrorRecovery;
+
+        /// Calls of other pallets which may be paused, by module.
+        type PausableCalls: PausableCalls;
     }
 
     #[pallet::event]
@@ -57,6 +69,14 @@ pub mod pallet {
     pub enum Event<T: Config> {
         RecoverFromErrors(StatusCode, Vec<ErrorCode>),
         UpdateActiveBlock(T::BlockNumber),
+        /// module
+        PauseModule(Vec<u8>),
+        /// module
+        UnpauseModule(Vec<u8>),
+        /// module, call
+        PauseCall(Vec<u8>, Vec<u8>),
+        /// module, call
+        UnpauseCall(Vec<u8>, Vec<u8>),
     }
 
     #[pallet::error]
@@ -68,8 +88,18 @@ pub mod pallet {
         ParachainNotRunningOrLiquidation,
         ParachainOracleOfflineError,
         ParachainLiquidationError,
+        ParachainUndercollateralizedError,
+        ParachainStaleBtcTxFeesError,
         InvalidErrorCode,
         ArithmeticOverflow,
+        /// All calls of this module are paused
+        ModulePaused,
+        /// This call is paused
+        CallPaused,
+        /// The module has no pausable calls
+        UnknownModule,
+        /// The call is not pausable
+        UnknownCall,
     }
 
     impl<T: Config> From<ErrorCode> for Error<T> {
@@ -78,6 +108,8 @@ pub mod pallet {
                 ErrorCode::NoDataBTCRelay => Error::NoDataBTCRelay,
                 ErrorCode::InvalidBTCRelay => Error::InvalidBTCRelay,
                 ErrorCode::OracleOffline => Error::ParachainOracleOfflineError,
+                ErrorCode::SystemUndercollateralized => Error::ParachainUndercollateralizedError,
+                ErrorCode::StaleBtcTxFees => Error::ParachainStaleBtcTxFeesError,
                 _ => Error::InvalidErrorCode,
             }
         }
@@ -87,6 +119,19 @@ pub mod pallet {
     impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
         fn on_initialize(_chain_height: T::BlockNumber) -> Weight {
             Self::increment_active_block();
+            Self::increment_active_clocks();
+            0
+        }
+
+        fn on_runtime_upgrade() -> Weight {
+            // requests opened before the clocks existed were measured against the active
+            // block count, so the clocks start from there
+            let active_block_number = Self::active_block_number();
+            for clock in ActiveClock::ALL.iter() {
+                if !ActiveClockCount::<T>::contains_key(clock) {
+                    ActiveClockCount::<T>::insert(clock, active_block_number);
+                }
+            }
             0
         }
     }
@@ -115,6 +160,30 @@ pub mod pallet {
     #[pallet::getter(fn active_block_number)]
     pub type ActiveBlockCount<T: Config> = StorageValue<_, T::BlockNumber, ValueQuery>;
 
+    /// Like `ActiveBlockCount`, but per module: only increments while there is no error which
+    /// prevents the requests of the module from being executed.
+    #[pallet::storage]
+    #[pallet::getter(fn active_clock_number)]
+    pub type ActiveClockCount<T: Config> = StorageMap<_, Blake2_128Concat, ActiveClock, T::BlockNumber, ValueQuery>;
+
+    /// Parachain block at which each current `ErrorCode` was inserted.
+    #[pallet::storage]
+    pub type ErrorsSince<T: Config> = StorageMap<_, Blake2_128Concat, ErrorCode, T::BlockNumber, OptionQuery>;
+
+    /// Most recent changes to the status and errors, oldest first.
+    #[pallet::storage]
+    #[pallet::getter(fn status_history)]
+    pub type StatusHistory<T: Config> = StorageValue<_, Vec<StatusChange<T::AccountId, T::BlockNumber>>, ValueQuery>;
+
+    /// Modules whose calls are paused, except for those which let users exit.
+    #[pallet::storage]
+    pub type PausedModules<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, (), OptionQuery>;
+
+    /// Paused calls, by module and call name.
+    #[pallet::storage]
+    pub type PausedCalls<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, Vec<u8>, Blake2_128Concat, Vec<u8>, (), OptionQuery>;
+
     #[pallet::pallet]
     pub struct Pallet<T>(_);
 
@@ -127,13 +196,18 @@ pub mod pallet {
         ///
         /// * `origin` - the dispatch origin of this call (must be _Root_)
         /// * `status_code` - the status code to set
+        /// * `reason` - encoded reason, recorded in the status history
         ///
         /// # Weight: `O(1)`
         #[pallet::weight(0)]
         #[transactional]
-        pub fn set_parachain_status(origin: OriginFor<T>, status_code: StatusCode) -> DispatchResultWithPostInfo {
+        pub fn set_parachain_status(
+            origin: OriginFor<T>,
+            status_code: StatusCode,
+            reason: Vec<u8>,
+        ) -> DispatchResultWithPostInfo {
             ensure_root(origin)?;
-            Self::set_status(status_code);
+            Self::set_status_with_reason(status_code, StatusChangeOrigin::Root, reason);
             Ok(().into())
         }
 
@@ -143,13 +217,18 @@ pub mod pallet {
         ///
         /// * `origin` - the dispatch origin of this call (must be _Root_)
         /// * `error_code` - the error code to insert
+        /// * `reason` - encoded reason, recorded in the status history
         ///
         /// # Weight: `O(1)`
         #[pallet::weight(0)]
         #[transactional]
-        pub fn insert_parachain_error(origin: OriginFor<T>, error_code: ErrorCode) -> DispatchResultWithPostInfo {
+        pub fn insert_parachain_error(
+            origin: OriginFor<T>,
+            error_code: ErrorCode,
+            reason: Vec<u8>,
+        ) -> DispatchResultWithPostInfo {
             ensure_root(origin)?;
-            Self::insert_error(error_code);
+            Self::insert_error_with_reason(error_code, StatusChangeOrigin::Root, reason);
             Ok(().into())
         }
 
@@ -159,13 +238,83 @@ pub mod pallet {
         ///
         /// * `origin` - the dispatch origin of this call (must be _Root_)
         /// * `error_code` - the error code to remove
+        /// * `reason` - encoded reason, recorded in the status history
         ///
         /// # Weight: `O(1)`
         #[pallet::weight(0)]
         #[transactional]
-        pub fn remove_parachain_error(origin: OriginFor<T>, error_code: ErrorCode) -> DispatchResultWithPostInfo {
+        pub fn remove_parachain_error(
+            origin: OriginFor<T>,
+            error_code: ErrorCode,
+            reason: Vec<u8>,
+        ) -> DispatchResultWithPostInfo {
             ensure_root(origin)?;
-            Self::remove_error(error_code);
+            Self::remove_error_with_reason(error_code, StatusChangeOrigin::Root, reason);
+            Ok(().into())
+        }
+
+        /// Pause or unpause all calls of a module which are guarded by `ensure_call_enabled`.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the dispatch origin of this call (must be _Root_)
+        /// * `module` - the name of the module, e.g. `Issue`
+        /// * `paused` - whether the module is paused
+        ///
+        /// # Weight: `O(1)`
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn set_module_paused(origin: OriginFor<T>, module: Vec<u8>, paused: bool) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            Self::pausable_calls(&module)?;
+            if paused == PausedModules::<T>::contains_key(&module) {
+                return Ok(().into());
+            }
+
+            if paused {
+                PausedModules::<T>::insert(&module, ());
+                Self::deposit_event(Event::PauseModule(module));
+            } else {
+                PausedModules::<T>::remove(&module);
+                Self::deposit_event(Event::UnpauseModule(module));
+            }
+            Ok(().into())
+        }
+
+        /// Pause or unpause a single call which is guarded by `ensure_call_enabled`.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the dispatch origin of this call (must be _Root_)
+        /// * `module` - the name of the module, e.g. `Issue`
+        /// * `call` - the name of the call, e.g. `request_issue`
+        /// * `paused` - whether the call is paused
+        ///
+        /// # Weight: `O(1)`
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn set_call_paused(
+            origin: OriginFor<T>,
+            module: Vec<u8>,
+            call: Vec<u8>,
+            paused: bool,
+        ) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            ensure!(
+                Self::pausable_calls(&module)?.contains(&call.as_slice()),
+                Error::<T>::UnknownCall
+            );
+            if paused == PausedCalls::<T>::contains_key(&module, &call) {
+                return Ok(().into());
+            }
+
+            if paused {
+                PausedCalls::<T>::insert(&module, &call, ());
+                Self::deposit_event(Event::PauseCall(module, call));
+            } else {
+                PausedCalls::<T>::remove(&module, &call);
+                Self::deposit_event(Event::UnpauseCall(module, call));
+            }
             Ok(().into())
         }
     }
@@ -192,6 +341,39 @@ impl<T: Config> Pallet<T> {
         }
     }
 
+    /// Ensures that neither the call nor its module is paused. Calls which let users exit,
+    /// such as cancelling requests or withdrawing collateral, are never guarded by this
+    /// check, so they remain available while other calls are paused.
+    ///
+    /// # Arguments
+    ///
+    /// * `module` - the name of the module, e.g. `Issue`
+    /// * `call` - the name of the call, e.g. `request_issue`
+    pub fn ensure_call_enabled(module: &[u8], call: &[u8]) -> DispatchResult {
+        ensure!(!PausedModules::<T>::contains_key(module), Error::<T>::ModulePaused);
+        ensure!(!PausedCalls::<T>::contains_key(module, call), Error::<T>::CallPaused);
+        Ok(())
+    }
+
+    /// Returns the pausable calls of a module.
+    fn pausable_calls(module: &[u8]) -> Result<&'static [&'static [u8]], DispatchError> {
+        T::PausableCalls::pausable_calls(module).ok_or_else(|| Error::<T>::UnknownModule.into())
+    }
+
+    /// Ensures that none of the given `ErrorCode`s is set, e.g. because the operation depends on
+    /// the data or invariant that the error reports as broken.
+    ///
+    /// # Arguments
+    ///
+    /// * `error_codes` - the errors which restrict the operation.
+    pub fn ensure_parachain_does_not_have_errors(error_codes: &[ErrorCode]) -> DispatchResult {
+        let errors = <Errors<T>>::get();
+        match error_codes.iter().find(|error_code| errors.contains(error_code)) {
+            Some(error_code) => Err(Error::<T>::from(error_code.clone()).into()),
+            None => Ok(()),
+        }
+    }
+
     /// Checks if the Parachain has a NoDataBTCRelay Error state
     pub fn is_parachain_error_no_data_btcrelay() -> bool {
         <ParachainStatus<T>>::get() == StatusCode::Error && <Errors<T>>::get().contains(&ErrorCode::NoDataBTCRelay)
@@ -218,7 +400,22 @@ impl<T: Config> Pallet<T> {
     ///
     /// * `status_code` - to set in storage.
     pub fn set_status(status_code: StatusCode) {
-        <ParachainStatus<T>>::set(status_code);
+        Self::set_status_with_reason(status_code, StatusChangeOrigin::Runtime, Vec::new())
+    }
+
+    /// Sets the given `StatusCode` and records the change in the status history.
+    ///
+    /// # Arguments
+    ///
+    /// * `status_code` - to set in storage.
+    /// * `origin` - who set the status.
+    /// * `reason` - encoded reason for the change.
+    pub fn set_status_with_reason(status_code: StatusCode, origin: StatusChangeOrigin<T::AccountId>, reason: Vec<u8>) {
+        if <ParachainStatus<T>>::get() == status_code {
+            return;
+        }
+        <ParachainStatus<T>>::set(status_code.clone());
+        Self::record_status_change(origin, StatusTransition::SetStatus(status_code), reason);
     }
 
     /// Get the current set of `ErrorCode`.
@@ -232,9 +429,23 @@ impl<T: Config> Pallet<T> {
     ///
     /// * `error_code` - the error to insert.
     pub fn insert_error(error_code: ErrorCode) {
-        <Errors<T>>::mutate(|errors| {
-            errors.insert(error_code);
-        })
+        Self::insert_error_with_reason(error_code, StatusChangeOrigin::Runtime, Vec::new())
+    }
+
+    /// Inserts the given `ErrorCode` and records the change in the status history.
+    ///
+    /// # Arguments
+    ///
+    /// * `error_code` - the error to insert.
+    /// * `origin` - who inserted the error.
+    /// * `reason` - encoded reason for the change.
+    pub fn insert_error_with_reason(error_code: ErrorCode, origin: StatusChangeOrigin<T::AccountId>, reason: Vec<u8>) {
+        let inserted = <Errors<T>>::mutate(|errors| errors.insert(error_code.clone()));
+        if !inserted {
+            return;
+        }
+        <ErrorsSince<T>>::insert(&error_code, frame_system::Pallet::<T>::block_number());
+        Self::record_status_change(origin, StatusTransition::InsertError(error_code), reason);
     }
 
     /// Removes the given `ErrorCode`.
@@ -243,14 +454,71 @@ impl<T: Config> Pallet<T> {
     ///
     /// * `error_code` - the error to remove.
     pub fn remove_error(error_code: ErrorCode) {
-        <Errors<T>>::mutate(|errors| {
-            errors.remove(&error_code);
+        Self::remove_error_with_reason(error_code, StatusChangeOrigin::Runtime, Vec::new())
+    }
+
+    /// Removes the given `ErrorCode`, records the change in the status history and runs the
+    /// recovery callbacks.
+    ///
+    /// # Arguments
+    ///
+    /// * `error_code` - the error to remove.
+    /// * `origin` - who removed the error.
+    /// * `reason` - encoded reason for the change.
+    pub fn remove_error_with_reason(error_code: ErrorCode, origin: StatusChangeOrigin<T::AccountId>, reason: Vec<u8>) {
+        let removed = <Errors<T>>::mutate(|errors| errors.remove(&error_code));
+        if !removed {
+            return;
+        }
+        let since = <ErrorsSince<T>>::take(&error_code).unwrap_or_default();
+        let duration = frame_system::Pallet::<T>::block_number().saturating_sub(since);
+        Self::record_status_change(
+            origin,
+            StatusTransition::RemoveError(error_code.clone(), duration),
+            reason,
+        );
+        T::OnRecovery::on_error_recovery(&error_code);
+    }
+
+    /// Appends a change to the status history, dropping the oldest entry if it is full.
+    fn record_status_change(
+        origin: StatusChangeOrigin<T::AccountId>,
+        transition: StatusTransition<T::BlockNumber>,
+        reason: Vec<u8>,
+    ) {
+        let change = StatusChange {
+            block_number: frame_system::Pallet::<T>::block_number(),
+            origin,
+            transition,
+            reason,
+        };
+        <StatusHistory<T>>::mutate(|history| {
+            if history.len() >= MAX_STATUS_HISTORY_LENGTH {
+                history.remove(0);
+            }
+            history.push(change);
         })
     }
 
-    pub fn has_expired(opentime: T::BlockNumber, period: T::BlockNumber) -> Result<bool, DispatchError> {
+    /// Get the most recent changes to the status and errors, oldest first.
+    pub fn get_status_history() -> Vec<StatusChange<T::AccountId, T::BlockNumber>> {
+        <StatusHistory<T>>::get()
+    }
+
+    /// Checks if a request opened at `opentime` on the given clock has exceeded its `period`.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - the clock of the module of the request.
+    /// * `opentime` - the value of the clock when the request was opened.
+    /// * `period` - the number of active blocks during which the request can be executed.
+    pub fn has_expired(
+        clock: ActiveClock,
+        opentime: T::BlockNumber,
+        period: T::BlockNumber,
+    ) -> Result<bool, DispatchError> {
         let expiration_block = opentime.checked_add(&period).ok_or(Error::<T>::ArithmeticOverflow)?;
-        Ok(Self::active_block_number() > expiration_block)
+        Ok(Self::active_clock_number(clock) > expiration_block)
     }
 
     fn recover_from_(error_codes: Vec<ErrorCode>) {
@@ -258,7 +526,7 @@ impl<T: Config> Pallet<T> {
             Self::remove_error(error_code);
         }
 
-        if Self::get_errors().is_empty() {
+        if Self::get_errors().iter().all(ErrorCode::is_operation_restriction) {
             Self::set_status(StatusCode::Running);
         }
 
@@ -271,6 +539,18 @@ impl<T: Config> Pallet<T> {
         Self::recover_from_(vec![ErrorCode::OracleOffline])
     }
 
+    /// Recovers the BTC Parachain state from a `SYSTEM_UNDERCOLLATERALIZED` error
+    /// and sets ParachainStatus to `RUNNING` if there are no other errors.
+    pub fn recover_from_system_undercollateralized() {
+        Self::recover_from_(vec![ErrorCode::SystemUndercollateralized])
+    }
+
+    /// Recovers the BTC Parachain state from a `STALE_BTC_TX_FEES` error
+    /// and sets ParachainStatus to `RUNNING` if there are no other errors.
+    pub fn recover_from_stale_btc_tx_fees() {
+        Self::recover_from_(vec![ErrorCode::StaleBtcTxFees])
+    }
+
     /// Recovers the BTC Parachain state from a `NO_DATA_BTC_RELAY` or `INVALID_BTC_RELAY` error
     /// (when a chain reorganization occurs and the new main chain has no errors)
     /// and sets ParachainStatus to `RUNNING` if there are no other errors.
@@ -297,6 +577,26 @@ impl<T: Config> Pallet<T> {
         }
     }
 
+    fn increment_active_clocks() {
+        let status = Self::status();
+        let errors = Self::get_errors();
+        for clock in ActiveClock::ALL.iter() {
+            let paused = match status {
+                StatusCode::Running => false,
+                StatusCode::Shutdown => true,
+                // without error codes which halt the parachain there is no way to tell which
+                // modules are affected
+                StatusCode::Error => {
+                    errors.iter().all(ErrorCode::is_operation_restriction)
+                        || clock.paused_by().iter().any(|error| errors.contains(error))
+                }
+            };
+            if !paused {
+                ActiveClockCount::<T>::mutate(clock, |n| *n = n.saturating_add(1u32.into()));
+            }
+        }
+    }
+
     /// Generates a 256-bit unique hash from an `AccountId` and the
     /// internal (auto-incrementing) `Nonce` to prevent replay attacks.
     ///
@@ -315,8 +615,11 @@ impl<T: Config> Pallet<T> {
         H256(result)
     }
 
-    /// for testing purposes only!
+    /// for testing purposes only! Also sets the clocks of all modules.
     pub fn set_active_block_number(n: T::BlockNumber) {
         ActiveBlockCount::<T>::set(n);
+        for clock in ActiveClock::ALL.iter() {
+            ActiveClockCount::<T>::insert(clock, n);
+        }
     }
 }
@@ -1,8 +1,9 @@
 use crate as security;
-use crate::{Config, Error};
+use crate::{Config, Error, ErrorCode, OnErrorRecovery, PausableCalls};
 use frame_support::parameter_types;
 use mocktopus::mocking::clear_mocks;
 use sp_core::H256;
+use std::cell::RefCell;
 
 use sp_runtime::{
     testing::Header,
@@ -60,6 +61,34 @@ impl frame_system::Config for Test {
 
 impl Config for Test {
     type Event = TestEvent;
+    type OnRecovery = MockRecovery;
+    type PausableCalls = MockPausableCalls;
+}
+
+thread_local! {
+    pub static RECOVERED_ERRORS: RefCell<Vec<ErrorCode>> = RefCell::new(Vec::new());
+}
+
+/// Records the errors passed to the recovery callback.
+pub struct MockRecovery;
+
+impl OnErrorRecovery for MockRecovery {
+    fn on_error_recovery(error_code: &ErrorCode) {
+        RECOVERED_ERRORS.with(|errors| errors.borrow_mut().push(error_code.clone()));
+    }
+}
+
+/// Pausable calls of a mock `Issue` module.
+pub struct MockPausableCalls;
+
+impl PausableCalls for MockPausableCalls {
+    fn pausable_calls(module: &[u8]) -> Option<&'static [&'static [u8]]> {
+        if module == b"Issue" {
+            Some(&[b"request_issue"])
+        } else {
+            None
+        }
+    }
 }
 
 pub type TestEvent = Event;
@@ -80,6 +109,7 @@ where
     T: FnOnce(),
 {
     clear_mocks();
+    RECOVERED_ERRORS.with(|errors| errors.borrow_mut().clear());
     ExtBuilder::build().execute_with(|| {
         System::set_block_number(1);
         Security::set_active_block_number(1);
@@ -1,4 +1,4 @@
-use crate::{mock::*, ErrorCode, StatusCode};
+use crate::{mock::*, ActiveClock, ErrorCode, StatusChange, StatusChangeOrigin, StatusCode, StatusTransition};
 use frame_support::{assert_noop, assert_ok};
 use sp_core::H256;
 
@@ -193,3 +193,248 @@ fn testget_active_block_not_incremented_if_not_running() {
         assert_eq!(Security::active_block_number(), initial_active_block);
     })
 }
+
+#[test]
+fn test_active_clocks_only_paused_by_relevant_errors() {
+    run_test(|| {
+        Security::set_status(StatusCode::Error);
+        Security::insert_error(ErrorCode::OracleOffline);
+        Security::increment_active_clocks();
+        assert_eq!(Security::active_clock_number(ActiveClock::Issue), 1);
+        assert_eq!(Security::active_clock_number(ActiveClock::Redeem), 2);
+        assert_eq!(Security::active_clock_number(ActiveClock::Replace), 2);
+
+        Security::insert_error(ErrorCode::InvalidBTCRelay);
+        Security::increment_active_clocks();
+        assert_eq!(Security::active_clock_number(ActiveClock::Issue), 1);
+        assert_eq!(Security::active_clock_number(ActiveClock::Redeem), 2);
+
+        Security::recover_from_btc_relay_failure();
+        Security::recover_from_oracle_offline();
+        Security::increment_active_clocks();
+        assert_eq!(Security::active_clock_number(ActiveClock::Issue), 2);
+        assert_eq!(Security::active_clock_number(ActiveClock::Redeem), 3);
+    })
+}
+
+#[test]
+fn test_active_clocks_paused_without_error_codes_or_on_shutdown() {
+    run_test(|| {
+        Security::set_status(StatusCode::Error);
+        Security::increment_active_clocks();
+        Security::set_status(StatusCode::Shutdown);
+        Security::increment_active_clocks();
+        for clock in ActiveClock::ALL.iter() {
+            assert_eq!(Security::active_clock_number(clock), 1);
+        }
+    })
+}
+
+#[test]
+fn test_has_expired_uses_active_clock() {
+    run_test(|| {
+        Security::set_status(StatusCode::Error);
+        Security::insert_error(ErrorCode::OracleOffline);
+        for _ in 0..5 {
+            Security::increment_active_clocks();
+        }
+        assert_eq!(Security::has_expired(ActiveClock::Issue, 1, 4), Ok(false));
+        assert_eq!(Security::has_expired(ActiveClock::Redeem, 1, 4), Ok(true));
+    })
+}
+
+#[test]
+fn test_recover_with_remaining_operation_restrictions() {
+    run_test(|| {
+        Security::set_status(StatusCode::Error);
+        Security::insert_error(ErrorCode::OracleOffline);
+        Security::insert_error(ErrorCode::StaleBtcTxFees);
+
+        Security::recover_from_oracle_offline();
+        assert_eq!(Security::get_parachain_status(), StatusCode::Running);
+        assert_eq!(
+            Security::get_errors().into_iter().collect::<Vec<_>>(),
+            vec![ErrorCode::StaleBtcTxFees]
+        );
+    })
+}
+
+#[test]
+fn test_ensure_parachain_does_not_have_errors() {
+    run_test(|| {
+        Security::insert_error(ErrorCode::StaleBtcTxFees);
+        assert_ok!(Security::ensure_parachain_does_not_have_errors(&[
+            ErrorCode::SystemUndercollateralized
+        ]));
+        assert_noop!(
+            Security::ensure_parachain_does_not_have_errors(&[
+                ErrorCode::SystemUndercollateralized,
+                ErrorCode::StaleBtcTxFees
+            ]),
+            TestError::ParachainStaleBtcTxFeesError
+        );
+    })
+}
+
+#[test]
+fn test_ensure_call_enabled_fails_if_module_paused() {
+    run_test(|| {
+        assert_ok!(Security::ensure_call_enabled(b"Issue", b"request_issue"));
+        assert_ok!(Security::set_module_paused(Origin::root(), b"Issue".to_vec(), true));
+        assert_emitted!(Event::PauseModule(b"Issue".to_vec()));
+
+        assert_noop!(
+            Security::ensure_call_enabled(b"Issue", b"request_issue"),
+            TestError::ModulePaused
+        );
+        assert_ok!(Security::ensure_call_enabled(b"Redeem", b"execute_redeem"));
+
+        assert_ok!(Security::set_module_paused(Origin::root(), b"Issue".to_vec(), false));
+        assert_emitted!(Event::UnpauseModule(b"Issue".to_vec()));
+        assert_ok!(Security::ensure_call_enabled(b"Issue", b"request_issue"));
+    })
+}
+
+#[test]
+fn test_ensure_call_enabled_fails_if_call_paused() {
+    run_test(|| {
+        assert_ok!(Security::set_call_paused(
+            Origin::root(),
+            b"Issue".to_vec(),
+            b"request_issue".to_vec(),
+            true
+        ));
+        assert_emitted!(Event::PauseCall(b"Issue".to_vec(), b"request_issue".to_vec()));
+
+        assert_noop!(
+            Security::ensure_call_enabled(b"Issue", b"request_issue"),
+            TestError::CallPaused
+        );
+        assert_ok!(Security::ensure_call_enabled(b"Issue", b"execute_issue"));
+
+        assert_ok!(Security::set_call_paused(
+            Origin::root(),
+            b"Issue".to_vec(),
+            b"request_issue".to_vec(),
+            false
+        ));
+        assert_emitted!(Event::UnpauseCall(b"Issue".to_vec(), b"request_issue".to_vec()));
+        assert_ok!(Security::ensure_call_enabled(b"Issue", b"request_issue"));
+    })
+}
+
+#[test]
+fn test_set_paused_fails_with_unknown_names() {
+    run_test(|| {
+        assert_noop!(
+            Security::set_module_paused(Origin::root(), b"Isue".to_vec(), true),
+            TestError::UnknownModule
+        );
+        assert_noop!(
+            Security::set_call_paused(Origin::root(), b"Isue".to_vec(), b"request_issue".to_vec(), true),
+            TestError::UnknownModule
+        );
+        assert_noop!(
+            Security::set_call_paused(Origin::root(), b"Issue".to_vec(), b"cancel_issue".to_vec(), true),
+            TestError::UnknownCall
+        );
+        // exit paths are never paused
+        assert_noop!(
+            Security::set_call_paused(Origin::root(), b"Issue".to_vec(), b"execute_issue".to_vec(), true),
+            TestError::UnknownCall
+        );
+        assert_noop!(
+            Security::set_module_paused(Origin::root(), b"Redeem".to_vec(), true),
+            TestError::UnknownModule
+        );
+    })
+}
+
+#[test]
+fn test_set_module_paused_only_emits_on_transition() {
+    run_test(|| {
+        assert_ok!(Security::set_module_paused(Origin::root(), b"Issue".to_vec(), false));
+        assert_ok!(Security::set_module_paused(Origin::root(), b"Issue".to_vec(), true));
+        assert_ok!(Security::set_module_paused(Origin::root(), b"Issue".to_vec(), true));
+        assert_emitted!(Event::PauseModule(b"Issue".to_vec()), 1);
+        assert_emitted!(Event::UnpauseModule(b"Issue".to_vec()), 0);
+    })
+}
+
+#[test]
+fn test_status_changes_are_recorded() {
+    run_test(|| {
+        assert_ok!(Security::insert_parachain_error(
+            Origin::root(),
+            ErrorCode::OracleOffline,
+            b"oracle".to_vec()
+        ));
+        assert_ok!(Security::set_parachain_status(
+            Origin::root(),
+            StatusCode::Error,
+            b"oracle".to_vec()
+        ));
+        // unchanged status is not recorded
+        Security::set_status(StatusCode::Error);
+
+        System::set_block_number(5);
+        Security::recover_from_oracle_offline();
+
+        assert_eq!(
+            Security::get_status_history(),
+            vec![
+                StatusChange {
+                    block_number: 1,
+                    origin: StatusChangeOrigin::Root,
+                    transition: StatusTransition::InsertError(ErrorCode::OracleOffline),
+                    reason: b"oracle".to_vec(),
+                },
+                StatusChange {
+                    block_number: 1,
+                    origin: StatusChangeOrigin::Root,
+                    transition: StatusTransition::SetStatus(StatusCode::Error),
+                    reason: b"oracle".to_vec(),
+                },
+                StatusChange {
+                    block_number: 5,
+                    origin: StatusChangeOrigin::Runtime,
+                    transition: StatusTransition::RemoveError(ErrorCode::OracleOffline, 4),
+                    reason: vec![],
+                },
+                StatusChange {
+                    block_number: 5,
+                    origin: StatusChangeOrigin::Runtime,
+                    transition: StatusTransition::SetStatus(StatusCode::Running),
+                    reason: vec![],
+                },
+            ]
+        );
+    })
+}
+
+#[test]
+fn test_status_history_is_bounded() {
+    run_test(|| {
+        for _ in 0..crate::MAX_STATUS_HISTORY_LENGTH {
+            Security::insert_error(ErrorCode::OracleOffline);
+            Security::remove_error(ErrorCode::OracleOffline);
+        }
+        let history = Security::get_status_history();
+        assert_eq!(history.len(), crate::MAX_STATUS_HISTORY_LENGTH);
+        assert_eq!(
+            history.last().unwrap().transition,
+            StatusTransition::RemoveError(ErrorCode::OracleOffline, 0)
+        );
+    })
+}
+
+#[test]
+fn test_recovery_callbacks_run_when_error_clears() {
+    run_test(|| {
+        Security::insert_error(ErrorCode::InvalidBTCRelay);
+        // removing an error which is not set does not run the callbacks
+        Security::remove_error(ErrorCode::OracleOffline);
+        Security::recover_from_btc_relay_failure();
+        RECOVERED_ERRORS.with(|errors| assert_eq!(*errors.borrow(), vec![ErrorCode::InvalidBTCRelay]));
+    })
+}
@@ -1,8 +1,11 @@
 use codec::{Decode, Encode};
-use sp_std::{cmp::Ord, fmt::Debug};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_std::{cmp::Ord, fmt::Debug, prelude::*};
 
 /// Enum indicating the status of the BTC Parachain.
 #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub enum StatusCode {
     /// BTC Parachain is fully operational.
     Running = 0,
@@ -20,6 +23,7 @@ impl Default for StatusCode {
 
 /// Enum specifying errors which lead to the Error status, tacked in Errors
 #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, Ord, PartialOrd)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub enum ErrorCode {
     /// No error. Used as default value
     None = 0,
@@ -34,6 +38,20 @@ pub enum ErrorCode {
     InvalidBTCRelay = 2,
     /// The exchangeRateOracle experienced a liveness failure (no up-to-date exchange rate available)
     OracleOffline = 3,
+    /// The total collateralization of the system is below the liquidation threshold, so no new
+    /// tokens may be issued and vaults may not withdraw collateral until it recovers
+    SystemUndercollateralized = 4,
+    /// The estimated Bitcoin transaction fees have not been updated within the maximum delay, so
+    /// redeem requests cannot be priced
+    StaleBtcTxFees = 5,
+}
+
+impl ErrorCode {
+    /// Returns true if the error only restricts the operations which depend on it, rather
+    /// than setting the parachain status to `Error`.
+    pub fn is_operation_restriction(&self) -> bool {
+        matches!(self, ErrorCode::SystemUndercollateralized | ErrorCode::StaleBtcTxFees)
+    }
 }
 
 impl Default for ErrorCode {
@@ -42,6 +60,104 @@ impl Default for ErrorCode {
     }
 }
 
+/// Clock measuring the time during which the requests of a module can be executed. Request
+/// deadlines are measured against the clock of their module, so that they are only extended by
+/// outages which prevent the requests from being executed.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug, Ord, PartialOrd)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum ActiveClock {
+    Issue,
+    Redeem,
+    Replace,
+}
+
+impl ActiveClock {
+    pub const ALL: [ActiveClock; 3] = [ActiveClock::Issue, ActiveClock::Redeem, ActiveClock::Replace];
+
+    /// Returns the errors during which the clock is paused.
+    pub fn paused_by(&self) -> &'static [ErrorCode] {
+        match self {
+            // executing an issue request with an incorrect amount may need the exchange rate
+            // to adjust the collateral of the vault
+            ActiveClock::Issue => &[
+                ErrorCode::NoDataBTCRelay,
+                ErrorCode::InvalidBTCRelay,
+                ErrorCode::OracleOffline,
+            ],
+            ActiveClock::Redeem | ActiveClock::Replace => &[ErrorCode::NoDataBTCRelay, ErrorCode::InvalidBTCRelay],
+        }
+    }
+}
+
+/// Origin of a change to the parachain status or errors.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum StatusChangeOrigin<AccountId> {
+    /// Governance, through one of the root calls of this pallet.
+    Root,
+    /// A signed account, e.g. a staked relayer.
+    Signed(AccountId),
+    /// Another pallet, e.g. BTC-Relay detecting an invalid block.
+    Runtime,
+}
+
+/// Change to the parachain status or errors.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum StatusTransition<BlockNumber> {
+    /// The `StatusCode` was set.
+    SetStatus(StatusCode),
+    /// The `ErrorCode` was inserted.
+    InsertError(ErrorCode),
+    /// The `ErrorCode` was removed after being set for the given number of blocks.
+    RemoveError(ErrorCode, BlockNumber),
+}
+
+/// Entry of the status change history, used to review incidents.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct StatusChange<AccountId, BlockNumber> {
+    /// Parachain block in which the change occurred.
+    pub block_number: BlockNumber,
+    /// Who made the change.
+    pub origin: StatusChangeOrigin<AccountId>,
+    /// What was changed.
+    pub transition: StatusTransition<BlockNumber>,
+    /// Encoded reason given for the change, empty if none was given.
+    pub reason: Vec<u8>,
+}
+
+/// Hook for pallets which need to act when an `ErrorCode` is cleared.
+pub trait OnErrorRecovery {
+    /// Called after the given `ErrorCode` was removed.
+    fn on_error_recovery(error_code: &ErrorCode);
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl OnErrorRecovery for Tuple {
+    fn on_error_recovery(error_code: &ErrorCode) {
+        for_tuples!( #( Tuple::on_error_recovery(error_code); )* );
+    }
+}
+
+/// Calls of a module which root may pause with `set_module_paused` or `set_call_paused`.
+pub trait PausableCalls {
+    /// Returns the calls guarded by `ensure_call_enabled` if `module` is the name of this module.
+    fn pausable_calls(module: &[u8]) -> Option<&'static [&'static [u8]]>;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl PausableCalls for Tuple {
+    fn pausable_calls(module: &[u8]) -> Option<&'static [&'static [u8]]> {
+        for_tuples!( #(
+            if let Some(calls) = Tuple::pausable_calls(module) {
+                return Some(calls);
+            }
+        )* );
+        None
+    }
+}
+
 #[macro_export]
 macro_rules! error_set {
     () => { BTreeSet::<ErrorCode>::new() };
@@ -28,6 +28,7 @@ use sp_runtime::traits::MaybeSerializeDeserialize;
 use sp_std::{
     convert::{TryFrom, TryInto},
     fmt::Debug,
+    vec::Vec,
 };
 
 /// The pallet's configuration trait.
@@ -111,6 +112,10 @@ decl_storage! {
 
         RelayerStoreBlock get(fn relayer_store_block) config(): SignedFixedPoint<T>;
         RelayerTheftReport get(fn relayer_theft_report) config(): SignedFixedPoint<T>;
+        RelayerCorrectBlockErrorProposal get(fn relayer_correct_block_error_proposal) config(): SignedFixedPoint<T>;
+        RelayerStaleBlock get(fn relayer_stale_block) config(): SignedFixedPoint<T>;
+        RelayerOutdatedBlock get(fn relayer_outdated_block) config(): SignedFixedPoint<T>;
+        RelayerFailedTheftReport get(fn relayer_failed_theft_report) config(): SignedFixedPoint<T>;
     }
 }
 
@@ -226,6 +231,31 @@ impl<T: Config> Module<T> {
         Ok(())
     }
 
+    /// Resets the SLA score of the relayer, withdrawing its full stake from the relayer reward
+    /// pools. Rewards which were already distributed to the relayer remain withdrawable.
+    ///
+    /// # Arguments
+    ///
+    /// * `relayer_id` - account id of the relayer
+    pub fn liquidate_relayer_sla(relayer_id: &T::AccountId) -> Result<(), DispatchError> {
+        Self::liquidate_stake::<T::CollateralRelayerRewards>(relayer_id)?;
+        Self::liquidate_stake::<T::WrappedRelayerRewards>(relayer_id)?;
+
+        let delta_sla = <RelayerSla<T>>::get(relayer_id)
+            .checked_mul(&SignedFixedPoint::<T>::saturating_from_integer(-1))
+            .unwrap_or(Zero::zero());
+        let new_sla = SignedFixedPoint::<T>::zero();
+        <RelayerSla<T>>::insert(relayer_id, new_sla);
+        Self::deposit_event(<Event<T>>::UpdateRelayerSLA(relayer_id.clone(), new_sla, delta_sla));
+
+        Ok(())
+    }
+
+    /// Returns the relayers which have an SLA score.
+    pub fn get_relayers_with_sla() -> Vec<T::AccountId> {
+        <RelayerSla<T>>::iter_keys().collect()
+    }
+
     /// Calculate the amount that is slashed when the the vault fails to execute.
     /// We reduce the amount of slashed collateral based on a Vaults SLA. The minimum amount
     /// slashed is given by the LiquidationThreshold, the maximum amount slashed by the
@@ -483,6 +513,10 @@ impl<T: Config> Module<T> {
         match event {
             RelayerEvent::StoreBlock => <RelayerStoreBlock<T>>::get(),
             RelayerEvent::TheftReport => <RelayerTheftReport<T>>::get(),
+            RelayerEvent::CorrectBlockErrorProposal => <RelayerCorrectBlockErrorProposal<T>>::get(),
+            RelayerEvent::StaleBlock => <RelayerStaleBlock<T>>::get(),
+            RelayerEvent::OutdatedBlock => <RelayerOutdatedBlock<T>>::get(),
+            RelayerEvent::FailedTheftReport => <RelayerFailedTheftReport<T>>::get(),
         }
     }
 
@@ -491,6 +525,10 @@ impl<T: Config> Module<T> {
         match event {
             RelayerEvent::StoreBlock => <RelayerStoreBlock<T>>::set(value),
             RelayerEvent::TheftReport => <RelayerTheftReport<T>>::set(value),
+            RelayerEvent::CorrectBlockErrorProposal => <RelayerCorrectBlockErrorProposal<T>>::set(value),
+            RelayerEvent::StaleBlock => <RelayerStaleBlock<T>>::set(value),
+            RelayerEvent::OutdatedBlock => <RelayerOutdatedBlock<T>>::set(value),
+            RelayerEvent::FailedTheftReport => <RelayerFailedTheftReport<T>>::set(value),
         }
     }
 
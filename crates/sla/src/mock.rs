@@ -162,6 +162,8 @@ impl reward::Config<reward::WrappedRelayer> for Test {
 
 impl security::Config for Test {
     type Event = TestEvent;
+    type OnRecovery = ();
+    type PausableCalls = ();
 }
 
 parameter_types! {
@@ -208,6 +210,10 @@ impl ExtBuilder {
             relayer_target_sla: FixedI128::from(100),
             relayer_store_block: FixedI128::from(1),
             relayer_theft_report: FixedI128::from(1),
+            relayer_correct_block_error_proposal: FixedI128::from(1),
+            relayer_stale_block: FixedI128::from(-1),
+            relayer_outdated_block: FixedI128::from(-1),
+            relayer_failed_theft_report: FixedI128::from(-1),
         }
         .assimilate_storage(&mut storage)
         .unwrap();
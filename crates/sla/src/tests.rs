@@ -135,6 +135,55 @@ fn test_event_update_relayer_sla_limits() {
     })
 }
 
+#[test]
+fn test_event_update_relayer_sla_penalizes_stale_block() {
+    run_test(|| {
+        assert_ok!(Sla::set_relayer_sla(
+            Origin::root(),
+            RelayerEvent::StaleBlock,
+            FixedI128::from(-2)
+        ));
+        for _ in 0..3 {
+            Sla::event_update_relayer_sla(&ALICE, RelayerEvent::StoreBlock).unwrap();
+        }
+
+        Sla::event_update_relayer_sla(&ALICE, RelayerEvent::StaleBlock).unwrap();
+        assert_eq!(<RelayerSla<Test>>::get(ALICE), FixedI128::from(1));
+        assert_eq!(
+            <CollateralRelayerRewards as reward::Rewards<AccountId>>::get_stake(&ALICE),
+            FixedI128::from(1)
+        );
+
+        // neither the SLA nor the reward stake drop below zero
+        Sla::event_update_relayer_sla(&ALICE, RelayerEvent::StaleBlock).unwrap();
+        assert_eq!(<RelayerSla<Test>>::get(ALICE), FixedI128::zero());
+        assert_eq!(
+            <CollateralRelayerRewards as reward::Rewards<AccountId>>::get_stake(&ALICE),
+            FixedI128::zero()
+        );
+    })
+}
+
+#[test]
+fn test_liquidate_relayer_sla_resets_stake() {
+    run_test(|| {
+        Sla::event_update_relayer_sla(&ALICE, RelayerEvent::StoreBlock).unwrap();
+        Sla::event_update_relayer_sla(&ALICE, RelayerEvent::TheftReport).unwrap();
+        assert!(<CollateralRelayerRewards as reward::Rewards<AccountId>>::get_stake(&ALICE) > Zero::zero());
+
+        assert_ok!(Sla::liquidate_relayer_sla(&ALICE));
+        assert_eq!(<RelayerSla<Test>>::get(ALICE), FixedI128::zero());
+        assert_eq!(
+            <CollateralRelayerRewards as reward::Rewards<AccountId>>::get_stake(&ALICE),
+            FixedI128::zero()
+        );
+        assert_eq!(
+            <WrappedRelayerRewards as reward::Rewards<AccountId>>::get_stake(&ALICE),
+            FixedI128::zero()
+        );
+    })
+}
+
 #[test]
 fn test_deposit_sla_change() {
     run_test(|| {
@@ -17,6 +17,10 @@ pub enum VaultEvent<Wrapped, Collateral> {
 pub enum RelayerEvent {
     StoreBlock,
     TheftReport,
+    CorrectBlockErrorProposal,
+    StaleBlock,
+    OutdatedBlock,
+    FailedTheftReport,
 }
 
 pub(crate) type Collateral<T> = <<T as currency::Config<currency::Collateral>>::Currency as Currency<
@@ -7,11 +7,23 @@ use frame_support::dispatch::DispatchResult;
 use sp_std::prelude::Vec;
 
 sp_api::decl_runtime_apis! {
-    pub trait StakedRelayersApi<AccountId> where
+    pub trait StakedRelayersApi<AccountId, TheftReportVerdict> where
         AccountId: Codec,
+        TheftReportVerdict: Codec,
     {
+        /// Check if the transaction of the vault is invalid, without checking its inclusion
         fn is_transaction_invalid(
             vault_id: AccountId, raw_tx: Vec<u8>
         ) -> DispatchResult;
+
+        /// Dry-run a theft report, returns whether the report would succeed or why not
+        fn check_theft_report(
+            vault_id: AccountId, merkle_proof: Vec<u8>, raw_tx: Vec<u8>
+        ) -> TheftReportVerdict;
+
+        /// Dry-run a double payment report, returns whether the report would succeed or why not
+        fn check_double_payment_report(
+            vault_id: AccountId, merkle_proofs: (Vec<u8>, Vec<u8>), raw_txs: (Vec<u8>, Vec<u8>)
+        ) -> TheftReportVerdict;
     }
 }
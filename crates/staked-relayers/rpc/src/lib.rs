@@ -12,9 +12,27 @@ pub use self::gen_client::Client as StakedRelayersClient;
 pub use module_staked_relayers_rpc_runtime_api::StakedRelayersApi as StakedRelayersRuntimeApi;
 
 #[rpc]
-pub trait StakedRelayersApi<BlockHash, AccountId> {
+pub trait StakedRelayersApi<BlockHash, AccountId, TheftReportVerdict> {
     #[rpc(name = "stakedRelayers_isTransactionInvalid")]
     fn is_transaction_invalid(&self, vault_id: AccountId, raw_tx: Vec<u8>, at: Option<BlockHash>) -> Result<()>;
+
+    #[rpc(name = "stakedRelayers_checkTheftReport")]
+    fn check_theft_report(
+        &self,
+        vault_id: AccountId,
+        merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+        at: Option<BlockHash>,
+    ) -> Result<TheftReportVerdict>;
+
+    #[rpc(name = "stakedRelayers_checkDoublePaymentReport")]
+    fn check_double_payment_report(
+        &self,
+        vault_id: AccountId,
+        merkle_proofs: (Vec<u8>, Vec<u8>),
+        raw_txs: (Vec<u8>, Vec<u8>),
+        at: Option<BlockHash>,
+    ) -> Result<TheftReportVerdict>;
 }
 
 /// A struct that implements the [`StakedRelayersApi`].
@@ -45,12 +63,14 @@ impl From<Error> for i64 {
     }
 }
 
-impl<C, Block, AccountId> StakedRelayersApi<<Block as BlockT>::Hash, AccountId> for StakedRelayers<C, Block>
+impl<C, Block, AccountId, TheftReportVerdict> StakedRelayersApi<<Block as BlockT>::Hash, AccountId, TheftReportVerdict>
+    for StakedRelayers<C, Block>
 where
     Block: BlockT,
     C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
-    C::Api: StakedRelayersRuntimeApi<Block, AccountId>,
+    C::Api: StakedRelayersRuntimeApi<Block, AccountId, TheftReportVerdict>,
     AccountId: Codec,
+    TheftReportVerdict: Codec,
 {
     fn is_transaction_invalid(
         &self,
@@ -78,4 +98,40 @@ where
             },
         )
     }
+
+    fn check_theft_report(
+        &self,
+        vault_id: AccountId,
+        merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<TheftReportVerdict> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.check_theft_report(&at, vault_id, merkle_proof, raw_tx)
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(Error::RuntimeError.into()),
+                message: "Unable to check theft report.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
+
+    fn check_double_payment_report(
+        &self,
+        vault_id: AccountId,
+        merkle_proofs: (Vec<u8>, Vec<u8>),
+        raw_txs: (Vec<u8>, Vec<u8>),
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<TheftReportVerdict> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.check_double_payment_report(&at, vault_id, merkle_proofs, raw_txs)
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(Error::RuntimeError.into()),
+                message: "Unable to check double payment report.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
 }
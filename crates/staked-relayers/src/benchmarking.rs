@@ -2,13 +2,19 @@ use super::*;
 use crate::Pallet as StakedRelayers;
 use bitcoin::{
     formatter::{Formattable, TryFormattable},
-    types::{BlockBuilder, H256Le, RawBlockHeader, TransactionBuilder, TransactionInputBuilder, TransactionOutput},
+    types::{
+        BlockBuilder, H256Le, RawBlockHeader, TransactionBuilder, TransactionInput, TransactionInputBuilder,
+        TransactionOutput,
+    },
 };
-use btc_relay::{BtcAddress, BtcPublicKey, Pallet as BtcRelay};
+use btc_relay::{BtcAddress, BtcPublicKey, Pallet as BtcRelay, MAX_BATCH_PAYMENTS};
 use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite};
+use frame_support::{traits::Currency, StorageDoubleMap, StorageMap, StorageValue};
 use frame_system::RawOrigin;
+use redeem::{types::RedeemRequest, Pallet as Redeem};
 use security::Pallet as Security;
-use sp_core::{H160, U256};
+use sp_core::{H160, H256, U256};
+use sp_runtime::FixedPointNumber;
 use sp_std::prelude::*;
 use vault_registry::{
     types::{Vault, Wallet},
@@ -22,6 +28,46 @@ fn dummy_public_key() -> BtcPublicKey {
     ])
 }
 
+fn make_free_balance_be<T: currency::Config<currency::Collateral>>(account_id: &T::AccountId, amount: Collateral<T>) {
+    <<T as currency::Config<currency::Collateral>>::Currency>::make_free_balance_be(account_id, amount);
+}
+
+fn initialize_relay<T: crate::Config>(relayer: T::AccountId) -> H256Le {
+    let address = BtcAddress::P2PKH(H160::from([0; 20]));
+    let height = 0;
+    let block = BlockBuilder::new()
+        .with_version(2)
+        .with_coinbase(&address, 50, 3)
+        .with_timestamp(1588813835)
+        .mine(U256::from(2).pow(254.into()))
+        .unwrap();
+    let block_hash = block.header.hash().unwrap();
+    let raw_block_header = RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap();
+    BtcRelay::<T>::insert_checkpoint(height, block_hash).unwrap();
+    BtcRelay::<T>::initialize(relayer, vec![raw_block_header], height).unwrap();
+    block_hash
+}
+
+/// Input spending from the vault address used in the theft benchmarks.
+fn vault_input() -> TransactionInput {
+    TransactionInputBuilder::new()
+        .with_coinbase(false)
+        .with_sequence(4294967295)
+        .with_previous_index(1)
+        .with_previous_hash(H256Le::from_bytes_le(&[
+            193, 80, 65, 160, 109, 235, 107, 56, 24, 176, 34, 250, 197, 88, 218, 76, 226, 9, 127, 8, 96, 200, 246, 66,
+            16, 91, 186, 217, 210, 155, 224, 42,
+        ]))
+        .with_script(&[
+            73, 48, 70, 2, 33, 0, 207, 210, 162, 211, 50, 178, 154, 220, 225, 25, 197, 90, 159, 173, 211, 192, 115, 51,
+            32, 36, 183, 226, 114, 81, 62, 81, 98, 60, 161, 89, 147, 72, 2, 33, 0, 155, 72, 45, 127, 123, 77, 71, 154,
+            255, 98, 189, 205, 174, 165, 70, 103, 115, 125, 86, 248, 212, 214, 61, 208, 62, 195, 239, 101, 30, 217,
+            162, 84, 1, 33, 3, 37, 248, 176, 57, 161, 24, 97, 101, 156, 155, 240, 63, 67, 252, 78, 160, 85, 243, 167,
+            28, 214, 12, 123, 31, 212, 116, 171, 87, 143, 153, 119, 250,
+        ])
+        .build()
+}
+
 benchmarks! {
 
     initialize {
@@ -36,7 +82,8 @@ benchmarks! {
             .with_timestamp(1588813835)
             .mine(U256::from(2).pow(254.into())).unwrap();
         let block_header = RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap();
-    }: _(RawOrigin::Signed(origin), block_header, height)
+        BtcRelay::<T>::insert_checkpoint(height, block.header.hash().unwrap()).unwrap();
+    }: _(RawOrigin::Signed(origin), vec![block_header], height)
 
     store_block_header {
         let origin: T::AccountId = account("Origin", 0, 0);
@@ -55,7 +102,8 @@ benchmarks! {
         let raw_block_header = RawBlockHeader::from_bytes(&init_block.header.try_format().unwrap())
             .expect("could not serialize block header");
 
-        BtcRelay::<T>::initialize(origin.clone(), raw_block_header, height).unwrap();
+        BtcRelay::<T>::insert_checkpoint(height, init_block_hash).unwrap();
+        BtcRelay::<T>::initialize(origin.clone(), vec![raw_block_header], height).unwrap();
 
         let block = BlockBuilder::new()
             .with_previous_hash(init_block_hash)
@@ -100,30 +148,14 @@ benchmarks! {
         let block_hash = block.header.hash().unwrap();
         let block_header = RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap();
         Security::<T>::set_active_block_number(1u32.into());
-        BtcRelay::<T>::initialize(relayer_id.clone(), block_header, height).unwrap();
+        BtcRelay::<T>::insert_checkpoint(height, block_hash).unwrap();
+        BtcRelay::<T>::initialize(relayer_id.clone(), vec![block_header], height).unwrap();
 
         let value = 0;
         let transaction = TransactionBuilder::new()
             .with_version(2)
             .add_input(
-                TransactionInputBuilder::new()
-                    .with_coinbase(false)
-                    .with_sequence(4294967295)
-                    .with_previous_index(1)
-                    .with_previous_hash(H256Le::from_bytes_le(&[
-                        193, 80, 65, 160, 109, 235, 107, 56, 24, 176, 34, 250, 197, 88, 218, 76,
-                        226, 9, 127, 8, 96, 200, 246, 66, 16, 91, 186, 217, 210, 155, 224, 42,
-                    ]))
-                    .with_script(&[
-                        73, 48, 70, 2, 33, 0, 207, 210, 162, 211, 50, 178, 154, 220, 225, 25, 197,
-                        90, 159, 173, 211, 192, 115, 51, 32, 36, 183, 226, 114, 81, 62, 81, 98, 60,
-                        161, 89, 147, 72, 2, 33, 0, 155, 72, 45, 127, 123, 77, 71, 154, 255, 98,
-                        189, 205, 174, 165, 70, 103, 115, 125, 86, 248, 212, 214, 61, 208, 62, 195,
-                        239, 101, 30, 217, 162, 84, 1, 33, 3, 37, 248, 176, 57, 161, 24, 97, 101,
-                        156, 155, 240, 63, 67, 252, 78, 160, 85, 243, 167, 28, 214, 12, 123, 31,
-                        212, 116, 171, 87, 143, 153, 119, 250,
-                    ])
-                    .build(),
+                vault_input(),
             )
             .add_output(TransactionOutput::payment(value.into(), &address))
             .build();
@@ -145,6 +177,239 @@ benchmarks! {
         Security::<T>::set_active_block_number(Security::<T>::active_block_number() + BtcRelay::<T>::parachain_confirmations() + 1u32.into());
 
     }: _(RawOrigin::Signed(origin), vault_id, proof, raw_tx)
+
+    report_vault_thefts {
+        let n in 1 .. MAX_THEFT_REPORTS_PER_BATCH;
+
+        let origin: T::AccountId = account("Origin", 0, 0);
+        let relayer_id: T::AccountId = account("Relayer", 0, 0);
+
+        let vault_address = BtcAddress::P2PKH(H160::from_slice(&[
+            126, 125, 148, 208, 221, 194, 29, 131, 191, 188, 252, 119, 152, 228, 84, 126, 223, 8,
+            50, 170,
+        ]));
+
+        let address = BtcAddress::P2PKH(H160([0; 20]));
+
+        // all vaults share the address, so the same transaction is a theft by each of them
+        let vault_ids: Vec<T::AccountId> = (0..n).map(|i| account("Vault", i, 0)).collect();
+        for vault_id in vault_ids.iter() {
+            let mut vault = Vault::default();
+            vault.id = vault_id.clone();
+            vault.wallet = Wallet::new(dummy_public_key());
+            vault.wallet.add_btc_address(vault_address);
+            VaultRegistry::<T>::insert_vault(
+                vault_id,
+                vault
+            );
+        }
+
+        Security::<T>::set_active_block_number(1u32.into());
+        let block_hash = initialize_relay::<T>(relayer_id.clone());
+
+        let transaction = TransactionBuilder::new()
+            .with_version(2)
+            .add_input(vault_input())
+            .add_output(TransactionOutput::payment(0, &address))
+            .build();
+
+        let block = BlockBuilder::new()
+            .with_previous_hash(block_hash)
+            .with_version(2)
+            .with_coinbase(&address, 50, 3)
+            .with_timestamp(1588813835)
+            .add_transaction(transaction.clone())
+            .mine(U256::from(2).pow(254.into())).unwrap();
+
+        let proof = block.merkle_proof(&[transaction.tx_id()]).unwrap().try_format().unwrap();
+        let raw_tx = transaction.format_with(true);
+        let reports: Vec<_> = vault_ids
+            .into_iter()
+            .map(|vault_id| (vault_id, proof.clone(), raw_tx.clone()))
+            .collect();
+
+        let block_header = RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap();
+        BtcRelay::<T>::store_block_header(&relayer_id, block_header).unwrap();
+        Security::<T>::set_active_block_number(Security::<T>::active_block_number() + BtcRelay::<T>::parachain_confirmations() + 1u32.into());
+
+    }: _(RawOrigin::Signed(origin), reports)
+
+    dispute_theft_report {
+        let origin: T::AccountId = account("Origin", 0, 0);
+        let reporter: T::AccountId = account("Reporter", 0, 0);
+        let end = Security::<T>::active_block_number() + 1u32.into();
+        PendingTheftReports::<T>::insert(&origin, PendingTheftReport {
+            reporter,
+            tx_ids: vec![H256Le::zero()],
+            bond: 0u32.into(),
+            dispute_bond: 0u32.into(),
+            end,
+            disputed: false,
+        });
+        TheftChallengeEnds::<T>::insert(end, &origin, ());
+        let context = vec![0; MAX_DISPUTE_CONTEXT_LENGTH as usize];
+    }: _(RawOrigin::Signed(origin), context)
+
+    resolve_theft_report {
+        let reporter: T::AccountId = account("Reporter", 0, 0);
+        let vault_id: T::AccountId = account("Vault", 0, 0);
+        let mut vault = Vault::default();
+        vault.id = vault_id.clone();
+        vault.wallet = Wallet::new(dummy_public_key());
+        VaultRegistry::<T>::insert_vault(
+            &vault_id,
+            vault
+        );
+
+        let end = Security::<T>::active_block_number() + 1u32.into();
+        PendingTheftReports::<T>::insert(&vault_id, PendingTheftReport {
+            reporter,
+            tx_ids: vec![H256Le::zero()],
+            bond: 0u32.into(),
+            dispute_bond: 0u32.into(),
+            end,
+            disputed: true,
+        });
+        TheftChallengeEnds::<T>::insert(end, &vault_id, ());
+    }: _(RawOrigin::Root, vault_id, true)
+
+    report_vault_double_payment {
+        let origin: T::AccountId = account("Origin", 0, 0);
+        let relayer_id: T::AccountId = account("Relayer", 0, 0);
+
+        let vault_address = BtcAddress::P2PKH(H160::from_slice(&[
+            126, 125, 148, 208, 221, 194, 29, 131, 191, 188, 252, 119, 152, 228, 84, 126, 223, 8,
+            50, 170,
+        ]));
+
+        let address = BtcAddress::P2PKH(H160([0; 20]));
+
+        let vault_id: T::AccountId = account("Vault", 0, 0);
+        let mut vault = Vault::default();
+        vault.id = vault_id.clone();
+        vault.wallet = Wallet::new(dummy_public_key());
+        vault.wallet.add_btc_address(vault_address);
+        VaultRegistry::<T>::insert_vault(
+            &vault_id,
+            vault
+        );
+
+        let height = 0;
+        let block = BlockBuilder::new()
+            .with_version(2)
+            .with_coinbase(&address, 50, 3)
+            .with_timestamp(1588813835)
+            .mine(U256::from(2).pow(254.into())).unwrap();
+
+        let block_hash = block.header.hash().unwrap();
+        let block_header = RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap();
+        Security::<T>::set_active_block_number(1u32.into());
+        BtcRelay::<T>::insert_checkpoint(height, block_hash).unwrap();
+        BtcRelay::<T>::initialize(relayer_id.clone(), vec![block_header], height).unwrap();
+
+        let request_id = H256::zero();
+        let transactions: Vec<_> = (1..=2)
+            .map(|value| {
+                TransactionBuilder::new()
+                    .with_version(2)
+                    .add_input(vault_input())
+                    .add_output(TransactionOutput::payment(value, &address))
+                    .add_output(TransactionOutput::op_return(0, request_id.as_bytes()))
+                    .build()
+            })
+            .collect();
+
+        let block = BlockBuilder::new()
+            .with_previous_hash(block_hash)
+            .with_version(2)
+            .with_coinbase(&address, 50, 3)
+            .with_timestamp(1588813835)
+            .add_transaction(transactions[0].clone())
+            .add_transaction(transactions[1].clone())
+            .mine(U256::from(2).pow(254.into())).unwrap();
+
+        let proofs: Vec<_> = transactions
+            .iter()
+            .map(|transaction| block.merkle_proof(&[transaction.tx_id()]).unwrap().try_format().unwrap())
+            .collect();
+        let raw_txs: Vec<_> = transactions.iter().map(|transaction| transaction.format_with(true)).collect();
+
+        let block_header = RawBlockHeader::from_bytes(&block.header.try_format().unwrap()).unwrap();
+        BtcRelay::<T>::store_block_header(&relayer_id, block_header).unwrap();
+        Security::<T>::set_active_block_number(Security::<T>::active_block_number() + BtcRelay::<T>::parachain_confirmations() + 1u32.into());
+
+    }: _(
+        RawOrigin::Signed(origin),
+        vault_id,
+        (proofs[0].clone(), proofs[1].clone()),
+        (raw_txs[0].clone(), raw_txs[1].clone())
+    )
+
+    declare_batch_payment {
+        let n in 1 .. MAX_BATCH_PAYMENTS;
+
+        let vault_id: T::AccountId = account("Vault", 0, 0);
+        let request_ids: Vec<_> = (0..n).map(|i| H256::from_low_u64_be(i as u64)).collect();
+        for request_id in &request_ids {
+            let mut redeem_request = RedeemRequest::default();
+            redeem_request.vault = vault_id.clone();
+            Redeem::<T>::insert_redeem_request(*request_id, redeem_request);
+        }
+    }: _(RawOrigin::Signed(vault_id), request_ids)
+
+    register_staked_relayer {
+        let origin: T::AccountId = account("Origin", 0, 0);
+        make_free_balance_be::<T>(&origin, (1u32 << 31).into());
+        let stake = StakedRelayers::<T>::minimum_stake();
+    }: _(RawOrigin::Signed(origin), stake)
+
+    deregister_staked_relayer {
+        let origin: T::AccountId = account("Origin", 0, 0);
+        make_free_balance_be::<T>(&origin, (1u32 << 31).into());
+        StakedRelayers::<T>::_register_staked_relayer(origin.clone(), StakedRelayers::<T>::minimum_stake()).unwrap();
+    }: _(RawOrigin::Signed(origin))
+
+    withdraw_unbonded_stake {
+        let origin: T::AccountId = account("Origin", 0, 0);
+        make_free_balance_be::<T>(&origin, (1u32 << 31).into());
+        StakedRelayers::<T>::_register_staked_relayer(origin.clone(), StakedRelayers::<T>::minimum_stake()).unwrap();
+        StakedRelayers::<T>::_deregister_staked_relayer(origin.clone()).unwrap();
+        Security::<T>::set_active_block_number(Security::<T>::active_block_number() + StakedRelayers::<T>::unbonding_period());
+    }: _(RawOrigin::Signed(origin))
+
+    propose_block_error {
+        let origin: T::AccountId = account("Origin", 0, 0);
+        make_free_balance_be::<T>(&origin, (1u32 << 31).into());
+        StakedRelayers::<T>::_register_staked_relayer(origin.clone(), StakedRelayers::<T>::minimum_stake()).unwrap();
+        let block_hash = initialize_relay::<T>(origin.clone());
+    }: _(RawOrigin::Signed(origin), block_hash, ErrorCode::NoDataBTCRelay)
+
+    vote_on_block_error {
+        let origin: T::AccountId = account("Origin", 0, 0);
+        let proposer: T::AccountId = account("Proposer", 0, 0);
+        make_free_balance_be::<T>(&origin, (1u32 << 31).into());
+        make_free_balance_be::<T>(&proposer, (1u32 << 31).into());
+        StakedRelayers::<T>::_register_staked_relayer(origin.clone(), StakedRelayers::<T>::minimum_stake()).unwrap();
+        StakedRelayers::<T>::_register_staked_relayer(proposer.clone(), StakedRelayers::<T>::minimum_stake()).unwrap();
+        // the proposal stays open until both relayers approve
+        VoteThreshold::<T>::put(UnsignedFixedPoint::<T>::one());
+        let block_hash = initialize_relay::<T>(proposer.clone());
+        let proposal_id = StakedRelayers::<T>::_propose_block_error(proposer, block_hash, ErrorCode::NoDataBTCRelay).unwrap();
+    }: _(RawOrigin::Signed(origin), proposal_id, true)
+
+    close_block_error_proposal {
+        let origin: T::AccountId = account("Origin", 0, 0);
+        let proposer: T::AccountId = account("Proposer", 0, 0);
+        make_free_balance_be::<T>(&origin, (1u32 << 31).into());
+        make_free_balance_be::<T>(&proposer, (1u32 << 31).into());
+        StakedRelayers::<T>::_register_staked_relayer(origin.clone(), StakedRelayers::<T>::minimum_stake()).unwrap();
+        StakedRelayers::<T>::_register_staked_relayer(proposer.clone(), StakedRelayers::<T>::minimum_stake()).unwrap();
+        // the proposal stays open until both relayers approve
+        VoteThreshold::<T>::put(UnsignedFixedPoint::<T>::one());
+        let block_hash = initialize_relay::<T>(proposer.clone());
+        let proposal_id = StakedRelayers::<T>::_propose_block_error(proposer, block_hash, ErrorCode::NoDataBTCRelay).unwrap();
+        Security::<T>::set_active_block_number(Security::<T>::active_block_number() + StakedRelayers::<T>::voting_period());
+    }: _(RawOrigin::Signed(origin), proposal_id)
 }
 
 impl_benchmark_test_suite!(
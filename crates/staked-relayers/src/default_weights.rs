@@ -8,7 +8,18 @@ use frame_support::weights::{constants::RocksDbWeight as DbWeight, Weight};
 pub trait WeightInfo {
     fn initialize() -> Weight;
     fn report_vault_theft() -> Weight;
+    fn report_vault_thefts(n: u32) -> Weight;
+    fn dispute_theft_report() -> Weight;
+    fn resolve_theft_report() -> Weight;
+    fn report_vault_double_payment() -> Weight;
+    fn declare_batch_payment(n: u32) -> Weight;
     fn store_block_header() -> Weight;
+    fn register_staked_relayer() -> Weight;
+    fn deregister_staked_relayer() -> Weight;
+    fn withdraw_unbonded_stake() -> Weight;
+    fn propose_block_error() -> Weight;
+    fn vote_on_block_error() -> Weight;
+    fn close_block_error_proposal() -> Weight;
 }
 
 impl crate::WeightInfo for () {
@@ -23,9 +34,79 @@ impl crate::WeightInfo for () {
             .saturating_add(DbWeight::get().reads(16 as Weight))
             .saturating_add(DbWeight::get().writes(5 as Weight))
     }
+    // ESTIMATE: not measured by the benchmark CLI, regenerate this file to replace it
+    fn report_vault_thefts(n: u32) -> Weight {
+        (14_372_000 as Weight)
+            .saturating_add((253_914_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(DbWeight::get().reads(3 as Weight))
+            .saturating_add(DbWeight::get().reads((14 as Weight).saturating_mul(n as Weight)))
+            .saturating_add(DbWeight::get().writes((5 as Weight).saturating_mul(n as Weight)))
+    }
+    // ESTIMATE: not measured by the benchmark CLI, regenerate this file to replace it
+    fn dispute_theft_report() -> Weight {
+        (52_164_000 as Weight)
+            .saturating_add(DbWeight::get().reads(6 as Weight))
+            .saturating_add(DbWeight::get().writes(6 as Weight))
+    }
+    // ESTIMATE: not measured by the benchmark CLI, regenerate this file to replace it
+    fn resolve_theft_report() -> Weight {
+        (201_402_000 as Weight)
+            .saturating_add(DbWeight::get().reads(17 as Weight))
+            .saturating_add(DbWeight::get().writes(13 as Weight))
+    }
+    // ESTIMATE: not measured by the benchmark CLI, regenerate this file to replace it
+    fn report_vault_double_payment() -> Weight {
+        (398_512_000 as Weight)
+            .saturating_add(DbWeight::get().reads(19 as Weight))
+            .saturating_add(DbWeight::get().writes(6 as Weight))
+    }
+    // ESTIMATE: not measured by the benchmark CLI, regenerate this file to replace it
+    fn declare_batch_payment(n: u32) -> Weight {
+        (31_482_000 as Weight)
+            .saturating_add((9_317_000 as Weight).saturating_mul(n as Weight))
+            .saturating_add(DbWeight::get().reads(5 as Weight))
+            .saturating_add(DbWeight::get().reads((3 as Weight).saturating_mul(n as Weight)))
+            .saturating_add(DbWeight::get().writes(3 as Weight))
+    }
     fn store_block_header() -> Weight {
         (123_623_000 as Weight)
             .saturating_add(DbWeight::get().reads(13 as Weight))
             .saturating_add(DbWeight::get().writes(8 as Weight))
     }
+    // ESTIMATE: not measured by the benchmark CLI, regenerate this file to replace it
+    fn register_staked_relayer() -> Weight {
+        (58_214_000 as Weight)
+            .saturating_add(DbWeight::get().reads(6 as Weight))
+            .saturating_add(DbWeight::get().writes(3 as Weight))
+    }
+    // ESTIMATE: not measured by the benchmark CLI, regenerate this file to replace it
+    fn deregister_staked_relayer() -> Weight {
+        (71_932_000 as Weight)
+            .saturating_add(DbWeight::get().reads(8 as Weight))
+            .saturating_add(DbWeight::get().writes(7 as Weight))
+    }
+    // ESTIMATE: not measured by the benchmark CLI, regenerate this file to replace it
+    fn withdraw_unbonded_stake() -> Weight {
+        (49_381_000 as Weight)
+            .saturating_add(DbWeight::get().reads(3 as Weight))
+            .saturating_add(DbWeight::get().writes(3 as Weight))
+    }
+    // ESTIMATE: not measured by the benchmark CLI, regenerate this file to replace it
+    fn propose_block_error() -> Weight {
+        (104_857_000 as Weight)
+            .saturating_add(DbWeight::get().reads(14 as Weight))
+            .saturating_add(DbWeight::get().writes(9 as Weight))
+    }
+    // ESTIMATE: not measured by the benchmark CLI, regenerate this file to replace it
+    fn vote_on_block_error() -> Weight {
+        (112_306_000 as Weight)
+            .saturating_add(DbWeight::get().reads(15 as Weight))
+            .saturating_add(DbWeight::get().writes(9 as Weight))
+    }
+    // ESTIMATE: not measured by the benchmark CLI, regenerate this file to replace it
+    fn close_block_error_proposal() -> Weight {
+        (96_745_000 as Weight)
+            .saturating_add(DbWeight::get().reads(12 as Weight))
+            .saturating_add(DbWeight::get().writes(8 as Weight))
+    }
 }
@@ -5,8 +5,10 @@ use mocktopus::macros::mockable;
 pub(crate) mod vault_registry {
     use crate::{Collateral, Wrapped};
     use ::vault_registry::VaultStatus;
+    use btc_relay::BtcAddress;
     use frame_support::dispatch::{DispatchError, DispatchResult};
-    use vault_registry::types::Vault;
+    use sp_std::vec::Vec;
+    use vault_registry::types::{CurrencySource, Vault, WalletMigration};
 
     pub fn get_active_vault_from_id<T: vault_registry::Config>(
         vault_id: &T::AccountId,
@@ -17,17 +19,102 @@ pub(crate) mod vault_registry {
         <vault_registry::Pallet<T>>::get_active_vault_from_id(vault_id)
     }
 
-    pub fn liquidate_theft_vault<T: vault_registry::Config>(vault_id: &T::AccountId) -> DispatchResult {
-        let _ = <vault_registry::Pallet<T>>::liquidate_vault_with_status(vault_id, VaultStatus::CommittedTheft)?;
-        Ok(())
+    pub fn liquidate_theft_vault<T: vault_registry::Config>(
+        vault_id: &T::AccountId,
+    ) -> Result<Collateral<T>, DispatchError> {
+        <vault_registry::Pallet<T>>::liquidate_vault_with_status(vault_id, VaultStatus::CommittedTheft)
+    }
+
+    pub fn transfer_funds<T: vault_registry::Config>(
+        from: CurrencySource<T>,
+        to: CurrencySource<T>,
+        amount: Collateral<T>,
+    ) -> DispatchResult {
+        <vault_registry::Pallet<T>>::transfer_funds(from, to, amount)
+    }
+
+    pub fn freeze_vault<T: vault_registry::Config>(vault_id: &T::AccountId) -> DispatchResult {
+        <vault_registry::Pallet<T>>::freeze_vault(vault_id)
+    }
+
+    pub fn unfreeze_vault<T: vault_registry::Config>(vault_id: &T::AccountId) {
+        <vault_registry::Pallet<T>>::unfreeze_vault(vault_id)
+    }
+
+    pub fn get_pending_wallet_migrations<T: vault_registry::Config>(
+        vault_id: &T::AccountId,
+    ) -> Vec<WalletMigration<T::BlockNumber>> {
+        <vault_registry::Pallet<T>>::get_pending_wallet_migrations(vault_id)
+    }
+
+    pub fn is_reserved_by_vault<T: vault_registry::Config>(vault_id: &T::AccountId, btc_address: &BtcAddress) -> bool {
+        <vault_registry::Pallet<T>>::is_reserved_by_vault(vault_id, btc_address)
+    }
+}
+
+#[cfg_attr(test, mockable)]
+pub(crate) mod collateral {
+    use crate::types::Collateral;
+    use frame_support::dispatch::DispatchResult;
+
+    type CollateralPallet<T> = currency::Pallet<T, currency::Collateral>;
+
+    pub fn lock_collateral<T: currency::Config<currency::Collateral>>(
+        sender: &T::AccountId,
+        amount: Collateral<T>,
+    ) -> DispatchResult {
+        CollateralPallet::<T>::lock(sender, amount)
+    }
+
+    pub fn release_collateral<T: currency::Config<currency::Collateral>>(
+        sender: &T::AccountId,
+        amount: Collateral<T>,
+    ) -> DispatchResult {
+        CollateralPallet::<T>::release(sender, amount)
+    }
+
+    pub fn burn_collateral<T: currency::Config<currency::Collateral>>(
+        sender: &T::AccountId,
+        amount: Collateral<T>,
+    ) -> DispatchResult {
+        CollateralPallet::<T>::burn(sender, amount)
+    }
+
+    pub fn unlock_and_transfer<T: currency::Config<currency::Collateral>>(
+        source: &T::AccountId,
+        destination: &T::AccountId,
+        amount: Collateral<T>,
+    ) -> DispatchResult {
+        CollateralPallet::<T>::unlock_and_transfer(source, destination, amount)
+    }
+}
+
+#[cfg_attr(test, mockable)]
+pub(crate) mod fee {
+    use crate::types::{Collateral, UnsignedFixedPoint};
+    use frame_support::dispatch::{DispatchError, DispatchResult};
+
+    pub fn fee_pool_account_id<T: fee::Config>() -> T::AccountId {
+        <fee::Pallet<T>>::fee_pool_account_id()
+    }
+
+    pub fn distribute_relayer_collateral_rewards<T: fee::Config>(amount: Collateral<T>) -> DispatchResult {
+        <fee::Pallet<T>>::distribute_relayer_collateral_rewards(amount)
+    }
+
+    pub fn collateral_for<T: fee::Config>(
+        amount: Collateral<T>,
+        percentage: UnsignedFixedPoint<T>,
+    ) -> Result<Collateral<T>, DispatchError> {
+        <fee::Pallet<T>>::collateral_for(amount, percentage)
     }
 }
 
 #[cfg_attr(test, mockable)]
 pub(crate) mod security {
     use frame_support::dispatch::DispatchResult;
-    use security::types::ErrorCode;
-    use sp_std::collections::btree_set::BTreeSet;
+    use security::types::{ErrorCode, StatusChangeOrigin, StatusCode};
+    use sp_std::{collections::btree_set::BTreeSet, vec::Vec};
 
     #[allow(dead_code)]
     pub(crate) fn get_errors<T: security::Config>() -> BTreeSet<ErrorCode> {
@@ -37,21 +124,39 @@ pub(crate) mod security {
     pub fn ensure_parachain_status_not_shutdown<T: security::Config>() -> DispatchResult {
         <security::Pallet<T>>::ensure_parachain_status_not_shutdown()
     }
+
+    pub fn ensure_call_enabled<T: security::Config>(module: &[u8], call: &[u8]) -> DispatchResult {
+        <security::Pallet<T>>::ensure_call_enabled(module, call)
+    }
+
+    pub fn active_block_number<T: security::Config>() -> T::BlockNumber {
+        <security::Pallet<T>>::active_block_number()
+    }
+
+    pub fn set_status<T: security::Config>(status_code: StatusCode, relayer: T::AccountId, reason: Vec<u8>) {
+        <security::Pallet<T>>::set_status_with_reason(status_code, StatusChangeOrigin::Signed(relayer), reason)
+    }
+
+    pub fn insert_error<T: security::Config>(error_code: ErrorCode, relayer: T::AccountId, reason: Vec<u8>) {
+        <security::Pallet<T>>::insert_error_with_reason(error_code, StatusChangeOrigin::Signed(relayer), reason)
+    }
 }
 
 #[cfg_attr(test, mockable)]
 pub(crate) mod btc_relay {
     use bitcoin::types::{H256Le, RawBlockHeader, Transaction};
-    use btc_relay::BtcAddress;
-    use frame_support::dispatch::DispatchResult;
+    use btc_relay::{BtcAddress, RichBlockHeader};
+    use frame_support::dispatch::{DispatchError, DispatchResult};
+    use security::types::ErrorCode;
+    use sp_core::H256;
     use sp_std::prelude::*;
 
     pub fn initialize<T: btc_relay::Config>(
         relayer: T::AccountId,
-        raw_block_header: RawBlockHeader,
+        raw_block_headers: Vec<RawBlockHeader>,
         block_height: u32,
     ) -> DispatchResult {
-        <btc_relay::Pallet<T>>::initialize(relayer, raw_block_header, block_height)
+        <btc_relay::Pallet<T>>::initialize(relayer, raw_block_headers, block_height)
     }
 
     pub fn store_block_header<T: btc_relay::Config>(
@@ -61,6 +166,34 @@ pub(crate) mod btc_relay {
         <btc_relay::Pallet<T>>::store_block_header(relayer, raw_block_header)
     }
 
+    pub fn get_best_block_height<T: btc_relay::Config>() -> u32 {
+        <btc_relay::Pallet<T>>::get_best_block_height()
+    }
+
+    pub fn get_stable_transaction_confirmations<T: btc_relay::Config>() -> u32 {
+        <btc_relay::Pallet<T>>::bitcoin_confirmations()
+    }
+
+    pub fn get_block_header_from_hash<T: btc_relay::Config>(
+        block_hash: H256Le,
+    ) -> Result<RichBlockHeader<T::AccountId, T::BlockNumber>, DispatchError> {
+        <btc_relay::Pallet<T>>::get_block_header_from_hash(block_hash)
+    }
+
+    pub fn get_main_chain_block_header<T: btc_relay::Config>(
+        block_height: u32,
+    ) -> Result<RichBlockHeader<T::AccountId, T::BlockNumber>, DispatchError> {
+        <btc_relay::Pallet<T>>::get_main_chain_block_header(block_height)
+    }
+
+    pub fn block_header_exists<T: btc_relay::Config>(block_hash: H256Le) -> bool {
+        <btc_relay::Pallet<T>>::block_header_exists(block_hash)
+    }
+
+    pub fn flag_block_error<T: btc_relay::Config>(block_hash: H256Le, error: ErrorCode) -> DispatchResult {
+        <btc_relay::Pallet<T>>::flag_block_error(block_hash, error)
+    }
+
     pub(crate) fn verify_transaction_inclusion<T: btc_relay::Config>(
         tx_id: H256Le,
         raw_merkle_proof: Vec<u8>,
@@ -68,6 +201,18 @@ pub(crate) mod btc_relay {
         <btc_relay::Pallet<T>>::_verify_transaction_inclusion(tx_id, raw_merkle_proof, None)
     }
 
+    pub fn get_settling_transaction<T: btc_relay::Config>(request_id: &H256) -> Option<H256Le> {
+        <btc_relay::Pallet<T>>::settling_transaction(request_id)
+    }
+
+    pub fn get_batch_payment<T: btc_relay::Config>(op_return: &H256) -> Option<Vec<H256>> {
+        <btc_relay::Pallet<T>>::batch_payment(op_return)
+    }
+
+    pub fn register_batch_payment<T: btc_relay::Config>(request_ids: Vec<H256>) -> Result<H256, DispatchError> {
+        <btc_relay::Pallet<T>>::register_batch_payment(request_ids)
+    }
+
     pub(crate) fn extract_outputs<T: btc_relay::Config>(
         tx: Transaction,
     ) -> Result<(Vec<(i64, BtcAddress)>, Vec<(i64, Vec<u8>)>), btc_relay::Error<T>> {
@@ -121,6 +266,7 @@ pub(crate) mod refund {
 pub(crate) mod sla {
     use frame_support::dispatch::DispatchError;
     pub use sla::types::RelayerEvent;
+    use sp_std::vec::Vec;
 
     pub fn event_update_relayer_sla<T: sla::Config>(
         relayer_id: &T::AccountId,
@@ -128,4 +274,12 @@ pub(crate) mod sla {
     ) -> Result<(), DispatchError> {
         <sla::Pallet<T>>::event_update_relayer_sla(relayer_id, event)
     }
+
+    pub fn liquidate_relayer_sla<T: sla::Config>(relayer_id: &T::AccountId) -> Result<(), DispatchError> {
+        <sla::Pallet<T>>::liquidate_relayer_sla(relayer_id)
+    }
+
+    pub fn get_relayers_with_sla<T: sla::Config>() -> Vec<T::AccountId> {
+        <sla::Pallet<T>>::get_relayers_with_sla()
+    }
 }
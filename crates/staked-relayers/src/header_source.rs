@@ -0,0 +1,128 @@
+//! # Header Sources
+//! Sources of Bitcoin block headers which are polled by the off-chain worker to keep
+//! BTC-Relay in sync when no external relayer is submitting headers.
+
+use bitcoin::types::{H256Le, RawBlockHeader};
+use sp_core::offchain::{Duration, StorageKind};
+use sp_runtime::offchain::http;
+use sp_std::{str, vec::Vec};
+
+/// Key in the persistent off-chain storage which holds the base URL of the Esplora API,
+/// e.g. `https://blockstream.info/api`. Node operators set it with `offchain_localStorageSet`.
+pub const ESPLORA_URL_KEY: &[u8] = b"staked-relayers::esplora-url";
+
+/// Time after which a pending HTTP request to the header source is abandoned.
+const HTTP_TIMEOUT_MILLIS: u64 = 5_000;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum HeaderSourceError {
+    /// No header source is configured on this node
+    Disabled,
+    /// The request to the header source failed or timed out
+    RequestFailed,
+    /// The header source returned a malformed response
+    InvalidResponse,
+}
+
+/// A source of Bitcoin main chain block headers.
+pub trait HeaderSource {
+    /// Returns the height of the best block known to the source.
+    fn get_best_block_height() -> Result<u32, HeaderSourceError>;
+
+    /// Returns the hash of the main chain block at the given height.
+    fn get_block_hash(block_height: u32) -> Result<H256Le, HeaderSourceError>;
+
+    /// Returns the raw header of the block with the given hash.
+    fn get_raw_block_header(block_hash: H256Le) -> Result<RawBlockHeader, HeaderSourceError>;
+}
+
+/// Disables the off-chain header sync.
+impl HeaderSource for () {
+    fn get_best_block_height() -> Result<u32, HeaderSourceError> {
+        Err(HeaderSourceError::Disabled)
+    }
+
+    fn get_block_hash(_block_height: u32) -> Result<H256Le, HeaderSourceError> {
+        Err(HeaderSourceError::Disabled)
+    }
+
+    fn get_raw_block_header(_block_hash: H256Le) -> Result<RawBlockHeader, HeaderSourceError> {
+        Err(HeaderSourceError::Disabled)
+    }
+}
+
+/// Fetches block headers from an Esplora compatible HTTP API. The sync is disabled
+/// until a base URL has been set under [`ESPLORA_URL_KEY`].
+pub struct EsploraHeaderSource;
+
+impl EsploraHeaderSource {
+    fn get(path: &[u8]) -> Result<Vec<u8>, HeaderSourceError> {
+        let mut url = sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, ESPLORA_URL_KEY)
+            .ok_or(HeaderSourceError::Disabled)?;
+        url.extend_from_slice(path);
+        let url = str::from_utf8(&url).map_err(|_| HeaderSourceError::Disabled)?;
+
+        let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(HTTP_TIMEOUT_MILLIS));
+        let response = http::Request::get(url)
+            .deadline(deadline)
+            .send()
+            .map_err(|_| HeaderSourceError::RequestFailed)?
+            .try_wait(deadline)
+            .map_err(|_| HeaderSourceError::RequestFailed)?
+            .map_err(|_| HeaderSourceError::RequestFailed)?;
+        if response.code != 200 {
+            return Err(HeaderSourceError::RequestFailed);
+        }
+        Ok(response.body().collect())
+    }
+
+    fn get_str(path: &[u8]) -> Result<Vec<u8>, HeaderSourceError> {
+        let mut body = Self::get(path)?;
+        // responses are plain text, possibly terminated by a newline
+        while body.last().map_or(false, u8::is_ascii_whitespace) {
+            body.pop();
+        }
+        Ok(body)
+    }
+}
+
+impl HeaderSource for EsploraHeaderSource {
+    fn get_best_block_height() -> Result<u32, HeaderSourceError> {
+        let body = Self::get_str(b"/blocks/tip/height")?;
+        str::from_utf8(&body)
+            .ok()
+            .and_then(|height| height.parse().ok())
+            .ok_or(HeaderSourceError::InvalidResponse)
+    }
+
+    fn get_block_hash(block_height: u32) -> Result<H256Le, HeaderSourceError> {
+        let mut path = b"/block-height/".to_vec();
+        push_decimal(&mut path, block_height);
+
+        // block hashes are displayed in big endian
+        let mut bytes_be = [0u8; 32];
+        hex::decode_to_slice(Self::get_str(&path)?, &mut bytes_be).map_err(|_| HeaderSourceError::InvalidResponse)?;
+        Ok(H256Le::from_bytes_be(&bytes_be))
+    }
+
+    fn get_raw_block_header(block_hash: H256Le) -> Result<RawBlockHeader, HeaderSourceError> {
+        let mut hash_hex = [0u8; 64];
+        hex::encode_to_slice(block_hash.to_bytes_be(), &mut hash_hex)
+            .map_err(|_| HeaderSourceError::InvalidResponse)?;
+        let mut path = b"/block/".to_vec();
+        path.extend_from_slice(&hash_hex);
+        path.extend_from_slice(b"/header");
+
+        let mut bytes = [0u8; 80];
+        hex::decode_to_slice(Self::get_str(&path)?, &mut bytes).map_err(|_| HeaderSourceError::InvalidResponse)?;
+        RawBlockHeader::from_bytes(&bytes).map_err(|_| HeaderSourceError::InvalidResponse)
+    }
+}
+
+/// Appends the decimal representation of `value` to `buf`.
+fn push_decimal(buf: &mut Vec<u8>, value: u32) {
+    if value >= 10 {
+        push_decimal(buf, value / 10);
+    }
+    buf.push(b'0' + (value % 10) as u8);
+}
@@ -6,6 +6,7 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 mod ext;
+pub mod header_source;
 pub mod types;
 
 #[cfg(any(feature = "runtime-benchmarks", test))]
@@ -28,18 +29,94 @@ use mocktopus::macros::mockable;
 
 pub use security;
 
-use crate::types::{Collateral, Wrapped};
+use crate::{
+    header_source::{HeaderSource, HeaderSourceError},
+    types::{
+        BlockErrorProposal, Collateral, MaintenanceTransaction, PendingTheftReport, StakedRelayer, TheftReportVerdict,
+        UnsignedFixedPoint, Version, Wrapped,
+    },
+};
 use bitcoin::{parser::parse_transaction, types::*};
 
 use btc_relay::BtcAddress;
+use codec::{Decode, Encode};
 use frame_support::{
-    decl_error, decl_event, decl_module, decl_storage, dispatch::DispatchResult, ensure, transactional,
+    decl_error, decl_event, decl_module, decl_storage,
+    dispatch::{DispatchError, DispatchResult},
+    ensure,
+    traits::{Get, PalletInfo},
+    transactional,
+    weights::Weight,
+};
+use frame_system::{
+    ensure_root, ensure_signed,
+    offchain::{AppCrypto, CreateSignedTransaction, SendSignedTransaction, Signer},
 };
-use frame_system::ensure_signed;
-use sp_core::H256;
+use security::{ErrorCode, StatusCode};
+use sp_core::{crypto::KeyTypeId, offchain::StorageKind, H256};
+use sp_runtime::traits::{CheckedAdd, CheckedSub, Saturating, Zero};
 
 use sp_std::{collections::btree_set::BTreeSet, convert::TryInto, vec::Vec};
-use vault_registry::Wallet;
+use vault_registry::{types::CurrencySource, Wallet};
+
+/// Key type of the local key which the off-chain worker uses to sign block header submissions.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"rlay");
+
+/// Calls which root may pause. `dispute_theft_report` is not pausable, since the theft
+/// challenge period of the vault keeps running.
+pub const PAUSABLE_CALLS: &[&[u8]] = &[
+    b"initialize",
+    b"register_staked_relayer",
+    b"propose_block_error",
+    b"vote_on_block_error",
+    b"store_block_header",
+    b"report_vault_theft",
+    b"report_vault_thefts",
+    b"report_vault_double_payment",
+    b"declare_batch_payment",
+];
+
+/// Maximum number of block hashes the off-chain worker requests from the header source per block.
+pub const MAX_HEADER_SOURCE_LOOKUPS: u32 = 16;
+
+/// Key in the persistent off-chain storage which holds the hash of the block header last
+/// submitted by the off-chain worker and the parachain block in which it was submitted.
+pub const LAST_SUBMITTED_HEADER_KEY: &[u8] = b"staked-relayers::last-submitted-header";
+
+/// Number of parachain blocks after which the off-chain worker submits a block header again
+/// if it has not been stored yet, e.g. because the transaction was dropped.
+pub const HEADER_RESUBMIT_INTERVAL: u32 = 10;
+
+/// Maximum number of block heights checked and header deposits settled in a single parachain block.
+pub const MAX_SETTLED_HEADER_DEPOSITS_PER_BLOCK: u32 = 10;
+
+/// Maximum number of thefts reported in a single `report_vault_thefts` call.
+pub const MAX_THEFT_REPORTS_PER_BATCH: u32 = 32;
+
+/// Maximum number of challenge period ends checked and theft reports confirmed in a single parachain block.
+pub const MAX_CONFIRMED_THEFT_REPORTS_PER_BLOCK: u32 = 10;
+
+/// Maximum length of the context a vault provides when disputing a theft report.
+pub const MAX_DISPUTE_CONTEXT_LENGTH: u32 = 1024;
+
+pub mod crypto {
+    use super::KEY_TYPE;
+    use sp_runtime::{
+        app_crypto::{app_crypto, sr25519},
+        MultiSignature, MultiSigner,
+    };
+
+    app_crypto!(sr25519, KEY_TYPE);
+
+    /// The local relayer key used by the off-chain worker.
+    pub struct RelayerAuthId;
+
+    impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for RelayerAuthId {
+        type RuntimeAppPublic = Public;
+        type GenericSignature = sp_core::sr25519::Signature;
+        type GenericPublic = sp_core::sr25519::Public;
+    }
+}
 
 /// ## Configuration
 /// The pallet's configuration trait.
@@ -55,12 +132,19 @@ pub trait Config:
     + refund::Config
     + sla::Config
     + fee::Config
+    + CreateSignedTransaction<Call<Self>>
 {
     /// The overarching event type.
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
 
     /// Weight information for the extrinsics in this module.
     type WeightInfo: WeightInfo;
+
+    /// The local key used by the off-chain worker to sign block header submissions.
+    type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+
+    /// The source of block headers polled by the off-chain worker, `()` disables the sync.
+    type HeaderSource: HeaderSource;
 }
 
 // This pallet's storage items.
@@ -69,6 +153,85 @@ decl_storage! {
         /// Mapping of Bitcoin transaction identifiers (SHA256 hashes) to account
         /// identifiers of Vaults accused of theft.
         TheftReports get(fn theft_report): map hasher(blake2_128_concat) H256Le => BTreeSet<T::AccountId>;
+
+        /// Collateral locked by a relayer for every block header it submits, zero disables deposits.
+        HeaderDeposit get(fn header_deposit) config(): Collateral<T>;
+
+        /// Deposits of submitted block headers which are not settled yet, by block height and hash.
+        HeaderDeposits: double_map hasher(twox_64_concat) u32, hasher(blake2_128_concat) H256Le => Option<(T::AccountId, Collateral<T>)>;
+
+        /// Lowest block height which may have unsettled header deposits.
+        NextHeaderDepositHeight: Option<u32>;
+
+        /// Minimum collateral a relayer must bond to register as a staked relayer.
+        MinimumStake get(fn minimum_stake) config(): Collateral<T>;
+
+        /// Number of blocks the stake of a deregistered relayer stays locked, and can be slashed.
+        UnbondingPeriod get(fn unbonding_period) config(): T::BlockNumber;
+
+        /// Bonded relayers, only these earn relayer rewards.
+        StakedRelayers get(fn staked_relayer): map hasher(blake2_128_concat) T::AccountId => Option<StakedRelayer<Collateral<T>, T::BlockNumber>>;
+
+        /// Stake of deregistered relayers with the block at which it can be withdrawn.
+        UnbondingStake get(fn unbonding_stake): map hasher(blake2_128_concat) T::AccountId => Option<(T::BlockNumber, Collateral<T>)>;
+
+        /// Sum of the stake of all bonded relayers.
+        TotalStake get(fn total_stake): Collateral<T>;
+
+        /// Share of the total stake which must vote for a block error proposal to accept it.
+        VoteThreshold get(fn vote_threshold) config(): UnsignedFixedPoint<T>;
+
+        /// Number of blocks after which an undecided block error proposal can be closed.
+        VotingPeriod get(fn voting_period) config(): T::BlockNumber;
+
+        /// Minimum number of bonded relayers, including the proposer, which must approve a block
+        /// error proposal to accept it.
+        MinimumVoters get(fn minimum_voters) config(): u32 = 2;
+
+        /// Share of the collateral confiscated from a thief which is paid to the reporter.
+        TheftReportBounty get(fn theft_report_bounty) config(): UnsignedFixedPoint<T>;
+
+        /// Maximum collateral paid to the reporter of a single theft.
+        MaxTheftReportBounty get(fn max_theft_report_bounty) config(): Collateral<T>;
+
+        /// Number of blocks during which the vault can dispute a theft report before it is
+        /// liquidated, zero liquidates the vault as soon as the theft is reported.
+        TheftChallengePeriod get(fn theft_challenge_period) config(): T::BlockNumber;
+
+        /// Collateral locked by the reporter of a theft during the challenge period.
+        TheftReportBond get(fn theft_report_bond) config(): Collateral<T>;
+
+        /// Collateral locked by a vault which disputes a theft report until the report is decided.
+        DisputeBond get(fn dispute_bond) config(): Collateral<T>;
+
+        /// Number of blocks governance has to decide a disputed theft report before it is
+        /// confirmed.
+        DisputeResolutionPeriod get(fn dispute_resolution_period) config(): T::BlockNumber;
+
+        /// Theft reports in their challenge period or awaiting a decision by governance, by vault.
+        PendingTheftReports get(fn pending_theft_report): map hasher(blake2_128_concat) T::AccountId => Option<PendingTheftReport<T::AccountId, T::BlockNumber, Collateral<T>>>;
+
+        /// Vaults of pending theft reports by the end of their challenge period, or by the
+        /// deadline for governance to decide them once disputed.
+        TheftChallengeEnds: double_map hasher(twox_64_concat) T::BlockNumber, hasher(blake2_128_concat) T::AccountId => ();
+
+        /// Lowest challenge period end or dispute deadline which may have pending theft reports.
+        NextTheftChallengeEnd: Option<T::BlockNumber>;
+
+        /// Collateral slashed from the proposer of a rejected block error proposal.
+        RejectedProposalSlash get(fn rejected_proposal_slash) config(): Collateral<T>;
+
+        /// Number of block error proposals, used to generate proposal identifiers.
+        ProposalCount: u64;
+
+        /// Undecided block error proposals by identifier.
+        BlockErrorProposals get(fn block_error_proposal): map hasher(twox_64_concat) u64 => Option<BlockErrorProposal<T::AccountId, T::BlockNumber>>;
+
+        /// Identifiers of undecided block error proposals by block hash and error.
+        OpenBlockErrorProposals: double_map hasher(blake2_128_concat) H256Le, hasher(blake2_128_concat) ErrorCode => Option<u64>;
+
+        /// Build storage at V1 (requires default 0).
+        StorageVersion get(fn storage_version) build(|_| Version::V1): Version = Version::V0;
     }
 }
 
@@ -79,51 +242,187 @@ decl_module! {
 
         fn deposit_event() = default;
 
-        /// One time function to initialize the BTC-Relay with the first block
+        fn on_initialize(_n: T::BlockNumber) -> Weight {
+            Self::settle_header_deposits().saturating_add(Self::decide_expired_theft_reports())
+        }
+
+        fn on_runtime_upgrade() -> Weight {
+            if Self::storage_version() != Version::V0 {
+                return T::DbWeight::get().reads(1);
+            }
+
+            // relayers earned rewards before they had to bond, and none is bonded yet
+            let relayers = ext::sla::get_relayers_with_sla::<T>();
+            for relayer in relayers.iter().filter(|relayer| !<StakedRelayers<T>>::contains_key(relayer)) {
+                if let Err(err) = ext::sla::liquidate_relayer_sla::<T>(relayer) {
+                    log::error!("Failed to reset the SLA of relayer {:?}: {:?}", relayer, err);
+                }
+            }
+            StorageVersion::put(Version::V1);
+
+            // the SLA, the bond and the stake in both reward pools are read and written per relayer
+            let relayers = relayers.len() as Weight;
+            T::DbWeight::get().reads_writes(
+                relayers.saturating_mul(6).saturating_add(2),
+                relayers.saturating_mul(5).saturating_add(1),
+            )
+        }
+
+        fn offchain_worker(n: T::BlockNumber) {
+            log::debug!("Off-chain header sync started on block {:?}", n);
+            Self::_offchain_worker(n);
+        }
+
+        /// One time function to initialize the BTC-Relay from a trusted checkpoint
         ///
         /// # Arguments
         ///
-        /// * `block_header_bytes` - 80 byte raw Bitcoin block header.
-        /// * `block_height` - starting Bitcoin block height of the submitted block header.
+        /// * `raw_block_headers` - 80 byte raw Bitcoin block headers, starting with the checkpoint
+        ///   block header at the start of a difficulty adjustment period, followed by its descendants.
+        /// * `block_height` - starting Bitcoin block height of the checkpoint block header.
         ///
         /// # <weight>
         /// - Storage Reads:
         /// 	- One storage read to check that parachain is not shutdown. O(1)
         /// 	- One storage read to check if relayer authorization is disabled. O(1)
         /// 	- One storage read to check if relayer is authorized. O(1)
+        /// 	- One storage read to retrieve the checkpoint. O(1)
         /// - Storage Writes:
         ///     - One storage write to store block hash. O(1)
         ///     - One storage write to store block header. O(1)
         /// 	- One storage write to initialize main chain. O(1)
         ///     - One storage write to store best block hash. O(1)
         ///     - One storage write to store best block height. O(1)
+        /// - Storage Operations:
+        /// 	- One block header store per descendant block header. O(H)
         /// - Events:
         /// 	- One event for initialization.
+        /// 	- One event per descendant block header.
         ///
-        /// Total Complexity: O(1)
+        /// Total Complexity: O(H) where H is the number of block headers
         /// # </weight>
-        #[weight = <T as Config>::WeightInfo::initialize()]
+        #[weight = <T as Config>::WeightInfo::initialize().saturating_add(
+            <T as Config>::WeightInfo::store_block_header()
+                .saturating_mul(raw_block_headers.len().saturating_sub(1) as Weight)
+        )]
         #[transactional]
         fn initialize(
             origin,
-            raw_block_header: RawBlockHeader,
+            raw_block_headers: Vec<RawBlockHeader>,
             block_height: u32)
             -> DispatchResult
         {
             ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+            ext::security::ensure_call_enabled::<T>(b"StakedRelayers", b"initialize")?;
+            let relayer = ensure_signed(origin)?;
+            ext::btc_relay::initialize::<T>(relayer, raw_block_headers, block_height)
+        }
+
+
+        /// Registers the caller as a staked relayer, locking the given stake. Only staked
+        /// relayers earn relayer rewards.
+        ///
+        /// # Arguments
+        ///
+        /// * `stake` - collateral to bond, at least `MinimumStake`.
+        #[weight = <T as Config>::WeightInfo::register_staked_relayer()]
+        #[transactional]
+        fn register_staked_relayer(origin, stake: Collateral<T>) -> DispatchResult {
+            ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+            ext::security::ensure_call_enabled::<T>(b"StakedRelayers", b"register_staked_relayer")?;
             let relayer = ensure_signed(origin)?;
-            ext::btc_relay::initialize::<T>(relayer, raw_block_header, block_height)
+            Self::_register_staked_relayer(relayer, stake)
         }
 
+        /// Deregisters the caller as a staked relayer. Its stake stays locked for the
+        /// `UnbondingPeriod`, after which it can be withdrawn.
+        #[weight = <T as Config>::WeightInfo::deregister_staked_relayer()]
+        #[transactional]
+        fn deregister_staked_relayer(origin) -> DispatchResult {
+            let relayer = ensure_signed(origin)?;
+            Self::_deregister_staked_relayer(relayer)
+        }
+
+        /// Releases the stake of the caller once its unbonding period has passed.
+        #[weight = <T as Config>::WeightInfo::withdraw_unbonded_stake()]
+        #[transactional]
+        fn withdraw_unbonded_stake(origin) -> DispatchResult {
+            let relayer = ensure_signed(origin)?;
+            Self::_withdraw_unbonded_stake(relayer)
+        }
+
+        /// A staked relayer proposes to flag an error on a Bitcoin block, voting for it. Once
+        /// relayers holding `VoteThreshold` of the total stake approve, the block is flagged in
+        /// BTC-Relay and, if it is in the main chain, the error is raised in the security pallet.
+        /// The proposer is rewarded if the proposal is accepted and slashed if it is rejected.
+        ///
+        /// # Arguments
+        ///
+        /// * `block_hash` - hash of the flagged block
+        /// * `error` - either `NoDataBTCRelay` or `InvalidBTCRelay`
+        #[weight = <T as Config>::WeightInfo::propose_block_error()]
+        #[transactional]
+        fn propose_block_error(origin, block_hash: H256Le, error: ErrorCode) -> DispatchResult {
+            ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+            ext::security::ensure_call_enabled::<T>(b"StakedRelayers", b"propose_block_error")?;
+            let proposer = ensure_signed(origin)?;
+            Self::_propose_block_error(proposer, block_hash, error)?;
+            Ok(())
+        }
+
+        /// A staked relayer votes on an undecided block error proposal, weighted by its stake.
+        ///
+        /// # Arguments
+        ///
+        /// * `proposal_id` - identifier of the proposal
+        /// * `approve` - whether the relayer observed the error
+        #[weight = <T as Config>::WeightInfo::vote_on_block_error()]
+        #[transactional]
+        fn vote_on_block_error(origin, proposal_id: u64, approve: bool) -> DispatchResult {
+            ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+            ext::security::ensure_call_enabled::<T>(b"StakedRelayers", b"vote_on_block_error")?;
+            let voter = ensure_signed(origin)?;
+            Self::_vote_on_block_error(voter, proposal_id, approve)
+        }
+
+        /// Rejects a block error proposal which is still undecided after the `VotingPeriod`.
+        /// Callable by anyone.
+        ///
+        /// # Arguments
+        ///
+        /// * `proposal_id` - identifier of the proposal
+        #[weight = <T as Config>::WeightInfo::close_block_error_proposal()]
+        #[transactional]
+        fn close_block_error_proposal(origin, proposal_id: u64) -> DispatchResult {
+            ensure_signed(origin)?;
+            Self::_close_block_error_proposal(proposal_id)
+        }
+
+        /// Slashes the stake of a relayer for provably bad behaviour, distributing it to the
+        /// relayer reward pool. Both bonded and unbonding stake can be slashed. A relayer whose
+        /// remaining stake is below the `MinimumStake` is deregistered.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the dispatch origin of this call (must be _Root_)
+        /// * `relayer` - account of the relayer
+        /// * `amount` - maximum amount of collateral to slash
+        #[weight = 0]
+        #[transactional]
+        fn slash_staked_relayer(origin, relayer: T::AccountId, amount: Collateral<T>) -> DispatchResult {
+            ensure_root(origin)?;
+            Self::slash_stake(&relayer, amount)?;
+            Ok(())
+        }
 
-        /// Stores a single new block header
+        /// Stores a single new block header. Submitting a header which is already stored below
+        /// the best block succeeds without storing it, but decreases the relayer's SLA.
         ///
         /// # Arguments
         ///
         /// * `raw_block_header` - 80 byte raw Bitcoin block header.
         ///
         /// # <weight>
-        /// Key: C (len of chains), P (len of positions)
         /// - Storage Reads:
         /// 	- One storage read to check that parachain is not shutdown. O(1)
         /// 	- One storage read to check if relayer authorization is disabled. O(1)
@@ -132,28 +431,30 @@ decl_module! {
         /// 	- One storage read to retrieve parent block hash. O(1)
         /// 	- One storage read to check if difficulty check is disabled. O(1)
         /// 	- One storage read to retrieve last re-target. O(1)
-        /// 	- One storage read to retrieve all Chains. O(C)
+        /// 	- One storage read to retrieve the main chain. O(1)
         /// - Storage Writes:
         ///     - One storage write to store block hash. O(1)
         ///     - One storage write to store block header. O(1)
         /// 	- One storage mutate to extend main chain. O(1)
+        /// 	- One storage write to move the fork tip. O(1)
         ///     - One storage write to store best block hash. O(1)
         ///     - One storage write to store best block height. O(1)
-        /// - Notable Computation:
-        /// 	- O(P) sort to reorg chains.
         /// - External Module Operations:
         /// 	- Updates relayer sla score.
         /// - Events:
         /// 	- One event for block stored (fork or extension).
         ///
-        /// Total Complexity: O(C + P)
+        /// Total Complexity: O(1), excluding reorgs
         /// # </weight>
-        #[weight = <T as Config>::WeightInfo::store_block_header()]
+        #[weight = <T as Config>::WeightInfo::store_block_header().max(
+            <<T as btc_relay::Config>::WeightInfo as btc_relay::WeightInfo>::store_fork_block_header()
+        )]
         #[transactional]
         fn store_block_header(
             origin, raw_block_header: RawBlockHeader
         ) -> DispatchResult {
             ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+            ext::security::ensure_call_enabled::<T>(b"StakedRelayers", b"store_block_header")?;
             let relayer = ensure_signed(origin)?;
             Self::store_block_header_and_update_sla(&relayer, raw_block_header)
         }
@@ -172,183 +473,1463 @@ decl_module! {
         #[transactional]
         fn report_vault_theft(origin, vault_id: T::AccountId, merkle_proof: Vec<u8>, raw_tx: Vec<u8>) -> DispatchResult {
             ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+            ext::security::ensure_call_enabled::<T>(b"StakedRelayers", b"report_vault_theft")?;
+            let signer = ensure_signed(origin)?;
+            Self::report_vault_theft_and_update_sla(&signer, vault_id, merkle_proof, raw_tx)
+        }
+
+        /// A Staked Relayer reports the theft of multiple Vaults at once. Invalid reports are
+        /// skipped with a `SkipTheftReport` event, the `check_theft_report` runtime API tells
+        /// beforehand why a report would be skipped. False reports of bonded relayers are
+        /// penalised with a `FalseTheftReport` event instead.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin`: Any signed user.
+        /// * `reports`: The vault, proof of tx inclusion and raw Bitcoin transaction of each theft.
+        #[weight = <T as Config>::WeightInfo::report_vault_thefts(reports.len() as u32)]
+        #[transactional]
+        fn report_vault_thefts(origin, reports: Vec<(T::AccountId, Vec<u8>, Vec<u8>)>) -> DispatchResult {
+            ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+            ext::security::ensure_call_enabled::<T>(b"StakedRelayers", b"report_vault_thefts")?;
             let signer = ensure_signed(origin)?;
+            ensure!(
+                reports.len() <= MAX_THEFT_REPORTS_PER_BATCH as usize,
+                Error::<T>::TooManyTheftReports
+            );
 
-            let transaction = parse_transaction(raw_tx.as_slice()).map_err(|_| Error::<T>::InvalidTransaction)?;
-            let tx_id = transaction.tx_id();
+            for (vault_id, merkle_proof, raw_tx) in reports {
+                if let Err(error) = Self::report_vault_theft_and_update_sla(&signer, vault_id.clone(), merkle_proof, raw_tx) {
+                    Self::deposit_event(<Event<T>>::SkipTheftReport(vault_id, Self::theft_report_verdict(error)));
+                }
+            }
+            Ok(())
+        }
 
-            // liquidated vaults are removed, so no need for check here
+        /// The vault disputes a theft report during its challenge period, providing context on
+        /// why the reported transaction is valid, and locks the `DisputeBond`. A disputed report
+        /// is not confirmed when the challenge period ends, governance decides it with
+        /// `resolve_theft_report` instead, or it is confirmed once the `DisputeResolutionPeriod`
+        /// has passed.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin`: The reported vault.
+        /// * `context`: Explanation for governance, at most `MAX_DISPUTE_CONTEXT_LENGTH` bytes.
+        #[weight = <T as Config>::WeightInfo::dispute_theft_report()]
+        #[transactional]
+        fn dispute_theft_report(origin, context: Vec<u8>) -> DispatchResult {
+            ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+            let vault_id = ensure_signed(origin)?;
+            Self::_dispute_theft_report(vault_id, context)
+        }
 
-            // throw if already reported
-            if <TheftReports<T>>::contains_key(&tx_id) {
-                ensure!(
-                    !<TheftReports<T>>::get(&tx_id).contains(&vault_id),
-                    Error::<T>::VaultAlreadyReported,
-                );
+        /// Decides a pending theft report, disputed or not. Confirming the theft liquidates the
+        /// vault, rewards the reporter and transfers the vault's dispute bond to the reporter.
+        /// Rejecting it unfreezes the vault, the reporter's bond is transferred to the vault and
+        /// the transactions may be reported again. Disputed reports which are not decided within
+        /// the `DisputeResolutionPeriod` are confirmed.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin` - the dispatch origin of this call (must be _Root_)
+        /// * `vault_id` - account of the reported vault
+        /// * `is_theft` - whether the reported transaction is theft
+        #[weight = <T as Config>::WeightInfo::resolve_theft_report()]
+        #[transactional]
+        fn resolve_theft_report(origin, vault_id: T::AccountId, is_theft: bool) -> DispatchResult {
+            ensure_root(origin)?;
+            if is_theft {
+                Self::confirm_theft_report(&vault_id)
+            } else {
+                Self::reject_theft_report(&vault_id)
             }
+        }
 
-            ext::btc_relay::verify_transaction_inclusion::<T>(tx_id, merkle_proof)?;
-            Self::_is_parsed_transaction_invalid(&vault_id, transaction)?;
+        /// A Staked Relayer reports that a Vault paid the same request twice, providing the
+        /// inclusion proofs of two different transactions which reference the same request id
+        /// of the vault and both pay the recipient of the request.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin`: Any signed user.
+        /// * `vault_id`: The account of the vault to check.
+        /// * `merkle_proofs`: The proofs of inclusion of both transactions.
+        /// * `raw_txs`: The raw Bitcoin transactions.
+        #[weight = <T as Config>::WeightInfo::report_vault_double_payment()]
+        #[transactional]
+        fn report_vault_double_payment(
+            origin,
+            vault_id: T::AccountId,
+            merkle_proofs: (Vec<u8>, Vec<u8>),
+            raw_txs: (Vec<u8>, Vec<u8>),
+        ) -> DispatchResult {
+            ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+            ext::security::ensure_call_enabled::<T>(b"StakedRelayers", b"report_vault_double_payment")?;
+            let signer = ensure_signed(origin)?;
 
-            ext::vault_registry::liquidate_theft_vault::<T>(&vault_id)?;
+            let (request_id, left_tx_id, right_tx_id) =
+                Self::verify_double_payment_report(&vault_id, merkle_proofs, raw_txs)?;
 
-            <TheftReports<T>>::mutate(&tx_id, |reports| {
-                reports.insert(vault_id.clone());
-            });
+            for tx_id in &[left_tx_id, right_tx_id] {
+                <TheftReports<T>>::mutate(tx_id, |reports| {
+                    reports.insert(vault_id.clone());
+                });
+            }
 
-            // reward the participant by increasing their SLA
-            ext::sla::event_update_relayer_sla::<T>(&signer, ext::sla::RelayerEvent::TheftReport)?;
+            Self::process_theft_report(&signer, &vault_id, sp_std::vec![left_tx_id, right_tx_id])?;
 
-            Self::deposit_event(<Event<T>>::VaultTheft(
+            Self::deposit_event(<Event<T>>::VaultDoublePayment(
                 vault_id,
-                tx_id
+                request_id,
+                left_tx_id,
+                right_tx_id,
             ));
 
             Ok(())
         }
+
+        /// A Vault declares the requests it is about to pay with a single Bitcoin transaction,
+        /// so that the payment is recognised by theft detection before it is executed. The
+        /// transaction pays the requests in the given order and commits to their ids in one
+        /// OP_RETURN output.
+        ///
+        /// # Arguments
+        ///
+        /// * `origin`: The vault paying the requests.
+        /// * `request_ids`: The redeem, replace or refund requests of the vault, in the order of the payment outputs.
+        #[weight = <T as Config>::WeightInfo::declare_batch_payment(request_ids.len() as u32)]
+        #[transactional]
+        fn declare_batch_payment(origin, request_ids: Vec<H256>) -> DispatchResult {
+            ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+            ext::security::ensure_call_enabled::<T>(b"StakedRelayers", b"declare_batch_payment")?;
+            let vault_id = ensure_signed(origin)?;
+
+            ensure!(
+                request_ids.len() as u32 <= btc_relay::MAX_BATCH_PAYMENTS,
+                Error::<T>::InvalidBatchPayment
+            );
+            // a batch is paid from the wallet of a single vault
+            for request_id in &request_ids {
+                ensure!(
+                    Self::get_request_payment(&vault_id, request_id).is_some(),
+                    Error::<T>::InvalidBatchPayment
+                );
+            }
+            let op_return = ext::btc_relay::register_batch_payment::<T>(request_ids)?;
+
+            Self::deposit_event(<Event<T>>::DeclareBatchPayment(vault_id, op_return));
+            Ok(())
+        }
     }
 }
 
 // "Internal" functions, callable by code.
 #[cfg_attr(test, mockable)]
 impl<T: Config> Module<T> {
-    fn store_block_header_and_update_sla(relayer: &T::AccountId, raw_block_header: RawBlockHeader) -> DispatchResult {
-        ext::btc_relay::store_block_header::<T>(&relayer, raw_block_header)?;
-        // reward the participant by increasing their SLA
-        ext::sla::event_update_relayer_sla::<T>(&relayer, ext::sla::RelayerEvent::StoreBlock)?;
-        Ok(())
+    fn _offchain_worker(block_number: T::BlockNumber) {
+        let signer = Signer::<T, T::AuthorityId>::any_account();
+        if !signer.can_sign() {
+            // no local relayer key, nothing to sign with
+            return;
+        }
+
+        match Self::next_block_header() {
+            Ok(Some(raw_block_header)) => {
+                let block_hash = raw_block_header.hash();
+                // the last submission may still be pending in the transaction pool
+                if let Some((last_hash, submitted_at)) =
+                    sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, LAST_SUBMITTED_HEADER_KEY)
+                        .and_then(|value| <(H256Le, T::BlockNumber)>::decode(&mut &value[..]).ok())
+                {
+                    if last_hash == block_hash
+                        && block_number < submitted_at.saturating_add(HEADER_RESUBMIT_INTERVAL.into())
+                    {
+                        return;
+                    }
+                }
+
+                log::info!("Submitting block header {:?}", block_hash);
+                let result = signer.send_signed_transaction(|_| Call::store_block_header(raw_block_header));
+                match result {
+                    Some((_, Ok(()))) => sp_io::offchain::local_storage_set(
+                        StorageKind::PERSISTENT,
+                        LAST_SUBMITTED_HEADER_KEY,
+                        &(block_hash, block_number).encode(),
+                    ),
+                    Some((_, Err(()))) => log::warn!("Failed to submit block header {:?}", block_hash),
+                    None => {}
+                }
+            }
+            Ok(None) | Err(HeaderSourceError::Disabled) => {}
+            Err(err) => log::warn!("Failed to fetch block header: {:?}", err),
+        }
     }
 
-    /// Checks if the vault is doing a valid merge transaction to move funds between
-    /// addresses.
-    ///
-    /// # Arguments
-    ///
-    /// * `payments` - all payment outputs extracted from tx
-    /// * `op_returns` - all op_return outputs extracted from tx
-    /// * `wallet` - vault btc addresses
-    pub(crate) fn is_valid_merge_transaction(
-        payments: &[(i64, BtcAddress)],
-        op_returns: &[(i64, Vec<u8>)],
-        wallet: &Wallet,
-    ) -> bool {
-        if !op_returns.is_empty() {
-            // migration should only contain payments
-            return false;
+    /// Returns the first block header on the main chain of the header source which
+    /// BTC-Relay has not yet stored, if any. If the source has reorganized, this walks
+    /// back to the last block both agree on so the relay can follow the new chain.
+    pub(crate) fn next_block_header() -> Result<Option<RawBlockHeader>, HeaderSourceError> {
+        let source_height = T::HeaderSource::get_best_block_height()?;
+        let mut height = ext::btc_relay::get_best_block_height::<T>().min(source_height);
+        let mut lookups = 0;
+
+        // find the last block of the source which is known to the relay
+        loop {
+            if lookups >= MAX_HEADER_SOURCE_LOOKUPS {
+                return Ok(None);
+            }
+            lookups += 1;
+            if ext::btc_relay::block_header_exists::<T>(T::HeaderSource::get_block_hash(height)?) {
+                break;
+            }
+            match height.checked_sub(1) {
+                Some(prev_height) => height = prev_height,
+                None => return Ok(None),
+            }
         }
 
-        for (_value, address) in payments {
-            if !wallet.has_btc_address(&address) {
-                return false;
+        // skip blocks the relay already knows as part of a fork
+        while height < source_height && lookups < MAX_HEADER_SOURCE_LOOKUPS {
+            height += 1;
+            lookups += 1;
+            let block_hash = T::HeaderSource::get_block_hash(height)?;
+            if !ext::btc_relay::block_header_exists::<T>(block_hash) {
+                let raw_block_header = T::HeaderSource::get_raw_block_header(block_hash)?;
+                ensure!(
+                    raw_block_header.hash() == block_hash,
+                    HeaderSourceError::InvalidResponse
+                );
+                return Ok(Some(raw_block_header));
             }
         }
 
-        true
+        Ok(None)
     }
 
-    /// Checks if the vault is sending a valid request transaction.
-    ///
-    /// # Arguments
+    fn store_block_header_and_update_sla(relayer: &T::AccountId, raw_block_header: RawBlockHeader) -> DispatchResult {
+        if let Err(err) = ext::btc_relay::store_block_header::<T>(&relayer, raw_block_header) {
+            if err == DispatchError::from(btc_relay::Error::<T>::OutdatedBlock)
+                && <StakedRelayers<T>>::contains_key(relayer)
+            {
+                // the bonded relayer lags behind the best block, failing would revert the penalty
+                return ext::sla::event_update_relayer_sla::<T>(relayer, ext::sla::RelayerEvent::OutdatedBlock);
+            }
+            return Err(err);
+        }
+
+        let deposit = Self::header_deposit();
+        if deposit.is_zero() {
+            // reward the participant by increasing their SLA
+            Self::update_relayer_sla(&relayer, ext::sla::RelayerEvent::StoreBlock)?;
+            return Ok(());
+        }
+
+        // the SLA is only increased once the block header is stable in the main chain
+        let block_hash = raw_block_header.hash();
+        let block_height = ext::btc_relay::get_block_header_from_hash::<T>(block_hash)?.block_height;
+        ext::collateral::lock_collateral::<T>(relayer, deposit)?;
+        <HeaderDeposits<T>>::insert(block_height, block_hash, (relayer.clone(), deposit));
+        NextHeaderDepositHeight::mutate(|next| {
+            *next = Some(next.map_or(block_height, |next| next.min(block_height)));
+        });
+
+        Self::deposit_event(<Event<T>>::LockHeaderDeposit(relayer.clone(), block_hash, deposit));
+        Ok(())
+    }
+
+    /// Settles the deposits of block headers which are at least `StableBitcoinConfirmations`
+    /// blocks below the best block. At most `MAX_SETTLED_HEADER_DEPOSITS_PER_BLOCK` heights
+    /// are checked and deposits settled per call.
     ///
-    /// * `request_value` - amount of btc as specified in the request
-    /// * `request_address` - recipient btc address
-    /// * `payments` - all payment outputs extracted from tx
-    /// * `wallet` - vault btc addresses
-    pub(crate) fn is_valid_request_transaction(
-        request_value: Wrapped<T>,
-        request_address: BtcAddress,
-        payments: &[(i64, BtcAddress)],
-        wallet: &Wallet,
-    ) -> bool {
-        let request_value = match TryInto::<u64>::try_into(request_value).map_err(|_e| Error::<T>::TryIntoIntError) {
-            Ok(value) => value as i64,
-            Err(_) => return false,
+    /// Returns the consumed weight.
+    fn settle_header_deposits() -> Weight {
+        let db_weight = T::DbWeight::get();
+        let mut height = match NextHeaderDepositHeight::get() {
+            Some(height) => height,
+            None => return db_weight.reads(1),
         };
 
-        // check all outputs, vault cannot pay to unknown recipients
-        for (value, address) in payments {
-            if *address == request_address {
-                if *value < request_value {
-                    // insufficient payment to recipient
-                    return false;
+        let best_height = ext::btc_relay::get_best_block_height::<T>();
+        let confirmations = ext::btc_relay::get_stable_transaction_confirmations::<T>();
+        let mut checks = 0;
+        let mut weight = db_weight.reads_writes(3, 1);
+
+        while height.saturating_add(confirmations) <= best_height && checks < MAX_SETTLED_HEADER_DEPOSITS_PER_BLOCK {
+            let deposits = <HeaderDeposits<T>>::iter_prefix(height)
+                .take((MAX_SETTLED_HEADER_DEPOSITS_PER_BLOCK - checks) as usize)
+                .collect::<Vec<_>>();
+            weight = weight.saturating_add(db_weight.reads(1));
+
+            for (block_hash, (relayer, deposit)) in deposits {
+                <HeaderDeposits<T>>::remove(height, block_hash);
+                if let Err(err) = Self::settle_header_deposit(height, block_hash, &relayer, deposit) {
+                    log::warn!("Failed to settle header deposit of {:?}: {:?}", block_hash, err);
+                    // the deposit is no longer tracked, so it must not stay locked
+                    if let Err(err) = ext::collateral::release_collateral::<T>(&relayer, deposit) {
+                        log::error!("Failed to release header deposit of {:?}: {:?}", block_hash, err);
+                    }
                 }
-            } else if !wallet.has_btc_address(&address) {
-                // payment to unknown address
-                return false;
+                weight = weight.saturating_add(db_weight.reads_writes(8, 8));
+                checks += 1;
+            }
+
+            if <HeaderDeposits<T>>::iter_prefix(height).next().is_some() {
+                // continue with the remaining deposits at this height in the next block
+                break;
             }
+            height += 1;
+            checks += 1;
         }
 
-        // tx has sufficient payment to recipient and
-        // all refunds are to wallet addresses
-        true
+        NextHeaderDepositHeight::put(height);
+
+        weight
     }
 
-    /// Check if a vault transaction is invalid. Returns `Ok` if invalid or `Err` otherwise.
-    /// This method should be callable over RPC for a staked-relayer client to check validity.
+    /// Settles the deposit of a block header once it is stable. The deposit is returned if the
+    /// header is in the main chain, and the relayer is rewarded with an SLA increase, which raises
+    /// its share of the relayer reward pool. Otherwise the header is on a stale fork, so the
+    /// deposit is forfeited and burned. It is not paid into the relayer reward pool, which
+    /// would return a share of it to the relayer which forfeited it.
     ///
     /// # Arguments
     ///
-    /// `vault_id`: the vault.
-    /// `raw_tx`: the BTC transaction by the vault.
-    pub fn is_transaction_invalid(vault_id: &T::AccountId, raw_tx: Vec<u8>) -> DispatchResult {
-        let tx = parse_transaction(raw_tx.as_slice()).map_err(|_| Error::<T>::InvalidTransaction)?;
-        Self::_is_parsed_transaction_invalid(vault_id, tx)
-    }
-
-    /// Check if a vault transaction is invalid. Returns `Ok` if invalid or `Err` otherwise.
-    pub fn _is_parsed_transaction_invalid(vault_id: &T::AccountId, tx: Transaction) -> DispatchResult {
-        let vault = ext::vault_registry::get_active_vault_from_id::<T>(vault_id)?;
+    /// * `block_height` - height of the block header
+    /// * `block_hash` - hash of the block header
+    /// * `relayer` - account which submitted the block header
+    /// * `deposit` - collateral locked by the relayer
+    #[transactional]
+    fn settle_header_deposit(
+        block_height: u32,
+        block_hash: H256Le,
+        relayer: &T::AccountId,
+        deposit: Collateral<T>,
+    ) -> DispatchResult {
+        let is_main_chain = ext::btc_relay::get_main_chain_block_header::<T>(block_height)
+            .map(|block_header| block_header.block_hash == block_hash)
+            .unwrap_or(false);
 
-        // collect all addresses that feature in the inputs of the transaction
-        let input_addresses: Vec<Result<BtcAddress, _>> = tx
-            .clone()
-            .inputs
-            .into_iter()
-            .map(|input| input.extract_address())
-            .collect();
+        if is_main_chain {
+            ext::collateral::release_collateral::<T>(relayer, deposit)?;
+            Self::update_relayer_sla(relayer, ext::sla::RelayerEvent::StoreBlock)?;
+            Self::deposit_event(<Event<T>>::RefundHeaderDeposit(relayer.clone(), block_hash, deposit));
+        } else {
+            ext::collateral::burn_collateral::<T>(relayer, deposit)?;
+            Self::deposit_event(<Event<T>>::ForfeitHeaderDeposit(relayer.clone(), block_hash, deposit));
+        }
+        Ok(())
+    }
 
-        // check if vault's btc address features in an input of the transaction
+    fn _register_staked_relayer(relayer: T::AccountId, stake: Collateral<T>) -> DispatchResult {
         ensure!(
-            // TODO: can a vault steal funds if it registers a P2WPKH-P2SH since we
-            // would extract the `P2WPKHv0`?
-            input_addresses.into_iter().any(|address_result| {
-                match address_result {
-                    Ok(address) => vault.wallet.has_btc_address(&address),
-                    _ => false,
-                }
-            }),
-            // since the transaction does not have any inputs that correspond
-            // to any of the vault's registered BTC addresses, return Err
-            Error::<T>::VaultNoInputToTransaction
+            !<StakedRelayers<T>>::contains_key(&relayer),
+            Error::<T>::AlreadyRegistered
+        );
+        ensure!(
+            !<UnbondingStake<T>>::contains_key(&relayer),
+            Error::<T>::UnbondingStakeNotWithdrawn
         );
+        ensure!(stake >= Self::minimum_stake(), Error::<T>::InsufficientStake);
 
-        // Vaults are required to move funds for redeem and replace operations.
-        // Each transaction MUST feature at least two or three outputs as follows:
-        // * recipient: the recipient of the redeem / replace
-        // * op_return: the associated ID encoded in the OP_RETURN
-        // * vault: any "spare change" the vault is transferring
+        ext::collateral::lock_collateral::<T>(&relayer, stake)?;
+        let height = ext::security::active_block_number::<T>();
+        <StakedRelayers<T>>::insert(&relayer, StakedRelayer { stake, height });
+        <TotalStake<T>>::mutate(|total| *total = total.saturating_add(stake));
 
-        // should only err if there are too many outputs
-        if let Ok((payments, op_returns)) = ext::btc_relay::extract_outputs::<T>(tx) {
-            // check if the transaction is a "migration"
+        Self::deposit_event(<Event<T>>::RegisterStakedRelayer(relayer, stake));
+        Ok(())
+    }
+
+    fn _deregister_staked_relayer(relayer: T::AccountId) -> DispatchResult {
+        let staked_relayer = <StakedRelayers<T>>::take(&relayer).ok_or(Error::<T>::NotRegistered)?;
+        <TotalStake<T>>::mutate(|total| *total = total.saturating_sub(staked_relayer.stake));
+
+        // rewards which were already distributed can still be withdrawn from the fee pallet
+        ext::sla::liquidate_relayer_sla::<T>(&relayer)?;
+
+        let unlock_height = ext::security::active_block_number::<T>()
+            .checked_add(&Self::unbonding_period())
+            .ok_or(Error::<T>::ArithmeticOverflow)?;
+        <UnbondingStake<T>>::insert(&relayer, (unlock_height, staked_relayer.stake));
+
+        Self::deposit_event(<Event<T>>::DeregisterStakedRelayer(
+            relayer,
+            staked_relayer.stake,
+            unlock_height,
+        ));
+        Ok(())
+    }
+
+    fn _withdraw_unbonded_stake(relayer: T::AccountId) -> DispatchResult {
+        let (unlock_height, stake) = <UnbondingStake<T>>::get(&relayer).ok_or(Error::<T>::NoUnbondingStake)?;
+        ensure!(
+            ext::security::active_block_number::<T>() >= unlock_height,
+            Error::<T>::UnbondingPeriodNotExpired
+        );
+
+        ext::collateral::release_collateral::<T>(&relayer, stake)?;
+        <UnbondingStake<T>>::remove(&relayer);
+
+        Self::deposit_event(<Event<T>>::WithdrawUnbondedStake(relayer, stake));
+        Ok(())
+    }
+
+    /// Slashes up to `amount` of the bonded or unbonding stake of a relayer and distributes it
+    /// to the relayer reward pool. A bonded relayer whose remaining stake is below the
+    /// `MinimumStake` is deregistered.
+    ///
+    /// Returns the slashed amount.
+    ///
+    /// # Arguments
+    ///
+    /// * `relayer` - account of the relayer
+    /// * `amount` - maximum amount of collateral to slash
+    pub(crate) fn slash_stake(relayer: &T::AccountId, amount: Collateral<T>) -> Result<Collateral<T>, DispatchError> {
+        let slashed = if let Some(mut staked_relayer) = <StakedRelayers<T>>::get(relayer) {
+            let slashed = amount.min(staked_relayer.stake);
+            staked_relayer.stake = staked_relayer
+                .stake
+                .checked_sub(&slashed)
+                .ok_or(Error::<T>::ArithmeticUnderflow)?;
+            <StakedRelayers<T>>::insert(relayer, staked_relayer);
+            <TotalStake<T>>::mutate(|total| *total = total.saturating_sub(slashed));
+            slashed
+        } else if let Some((unlock_height, stake)) = <UnbondingStake<T>>::get(relayer) {
+            let slashed = amount.min(stake);
+            <UnbondingStake<T>>::insert(relayer, (unlock_height, stake.saturating_sub(slashed)));
+            slashed
+        } else {
+            return Err(Error::<T>::NotRegistered.into());
+        };
+
+        // the slashed relayer must not earn a share of its own stake
+        let below_minimum = <StakedRelayers<T>>::get(relayer)
+            .map(|staked_relayer| staked_relayer.stake < Self::minimum_stake())
+            .unwrap_or(false);
+        if below_minimum {
+            Self::_deregister_staked_relayer(relayer.clone())?;
+        }
+
+        if !slashed.is_zero() {
+            ext::collateral::unlock_and_transfer::<T>(relayer, &ext::fee::fee_pool_account_id::<T>(), slashed)?;
+            ext::fee::distribute_relayer_collateral_rewards::<T>(slashed)?;
+        }
+
+        Self::deposit_event(<Event<T>>::SlashStakedRelayer(relayer.clone(), slashed));
+        Ok(slashed)
+    }
+
+    fn _propose_block_error(
+        proposer: T::AccountId,
+        block_hash: H256Le,
+        error: ErrorCode,
+    ) -> Result<u64, DispatchError> {
+        ensure!(<StakedRelayers<T>>::contains_key(&proposer), Error::<T>::NotRegistered);
+        ensure!(
+            matches!(error, ErrorCode::NoDataBTCRelay | ErrorCode::InvalidBTCRelay),
+            Error::<T>::InvalidErrorCode
+        );
+        ensure!(
+            !<OpenBlockErrorProposals>::contains_key(&block_hash, &error),
+            Error::<T>::ProposalAlreadyOpen
+        );
+        // only blocks known to the relay can be flagged
+        ext::btc_relay::get_block_header_from_hash::<T>(block_hash)?;
+
+        let proposal_id = ProposalCount::mutate(|count| {
+            *count = count.wrapping_add(1);
+            *count
+        });
+        let mut ayes = BTreeSet::new();
+        ayes.insert(proposer.clone());
+        let proposal = BlockErrorProposal {
+            proposer: proposer.clone(),
+            block_hash,
+            error: error.clone(),
+            start: ext::security::active_block_number::<T>(),
+            ayes,
+            nays: BTreeSet::new(),
+        };
+        <OpenBlockErrorProposals>::insert(&block_hash, &error, proposal_id);
+        // the proposal is only tallied once other relayers voted on it
+        <BlockErrorProposals<T>>::insert(proposal_id, proposal);
+
+        Self::deposit_event(<Event<T>>::ProposeBlockError(proposal_id, proposer, block_hash, error));
+        Ok(proposal_id)
+    }
+
+    fn _vote_on_block_error(voter: T::AccountId, proposal_id: u64, approve: bool) -> DispatchResult {
+        ensure!(<StakedRelayers<T>>::contains_key(&voter), Error::<T>::NotRegistered);
+        let mut proposal = <BlockErrorProposals<T>>::get(proposal_id).ok_or(Error::<T>::ProposalNotFound)?;
+        ensure!(
+            !proposal.ayes.contains(&voter) && !proposal.nays.contains(&voter),
+            Error::<T>::AlreadyVoted
+        );
+
+        if approve {
+            proposal.ayes.insert(voter.clone());
+        } else {
+            proposal.nays.insert(voter.clone());
+        }
+
+        Self::deposit_event(<Event<T>>::VoteOnBlockError(proposal_id, voter, approve));
+        Self::tally_block_error_proposal(proposal_id, proposal)?;
+        Ok(())
+    }
+
+    fn _close_block_error_proposal(proposal_id: u64) -> DispatchResult {
+        let proposal = <BlockErrorProposals<T>>::get(proposal_id).ok_or(Error::<T>::ProposalNotFound)?;
+        let end = proposal
+            .start
+            .checked_add(&Self::voting_period())
+            .ok_or(Error::<T>::ArithmeticOverflow)?;
+        ensure!(
+            ext::security::active_block_number::<T>() >= end,
+            Error::<T>::VotingPeriodNotExpired
+        );
+
+        // relayers may have unbonded since the last vote, which changes the outcome
+        if !Self::tally_block_error_proposal(proposal_id, proposal.clone())? {
+            Self::reject_block_error_proposal(proposal_id, proposal)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the sum of the stake of the given relayers, relayers which are no longer bonded
+    /// have no weight.
+    fn voting_stake(voters: &BTreeSet<T::AccountId>) -> Collateral<T> {
+        voters
+            .iter()
+            .filter_map(<StakedRelayers<T>>::get)
+            .fold(Zero::zero(), |total: Collateral<T>, staked_relayer| {
+                total.saturating_add(staked_relayer.stake)
+            })
+    }
+
+    /// Tallies the stake-weighted votes on a block error proposal. The proposal is accepted once
+    /// the approving stake reaches the `VoteThreshold` of the total stake and at least
+    /// `MinimumVoters` bonded relayers approve it, and rejected once the stake can no longer
+    /// reach the threshold. Otherwise the updated proposal is stored.
+    ///
+    /// Returns true if the proposal was decided.
+    fn tally_block_error_proposal(
+        proposal_id: u64,
+        proposal: BlockErrorProposal<T::AccountId, T::BlockNumber>,
+    ) -> Result<bool, DispatchError> {
+        let total_stake = Self::total_stake();
+        let required_stake = ext::fee::collateral_for::<T>(total_stake, Self::vote_threshold())?;
+        let aye_stake = Self::voting_stake(&proposal.ayes);
+        let nay_stake = Self::voting_stake(&proposal.nays);
+        let aye_voters = proposal
+            .ayes
+            .iter()
+            .filter(|voter| <StakedRelayers<T>>::contains_key(voter))
+            .count();
+
+        if !aye_stake.is_zero() && aye_stake >= required_stake && aye_voters >= Self::minimum_voters() as usize {
+            Self::accept_block_error_proposal(proposal_id, proposal)?;
+            Ok(true)
+        } else if nay_stake > total_stake.saturating_sub(required_stake) {
+            Self::reject_block_error_proposal(proposal_id, proposal)?;
+            Ok(true)
+        } else {
+            <BlockErrorProposals<T>>::insert(proposal_id, proposal);
+            Ok(false)
+        }
+    }
+
+    fn accept_block_error_proposal(
+        proposal_id: u64,
+        proposal: BlockErrorProposal<T::AccountId, T::BlockNumber>,
+    ) -> DispatchResult {
+        Self::remove_block_error_proposal(proposal_id, &proposal);
+        ext::btc_relay::flag_block_error::<T>(proposal.block_hash, proposal.error.clone())?;
+
+        // errors on forks do not affect transactions verified against the main chain
+        let block_height = ext::btc_relay::get_block_header_from_hash::<T>(proposal.block_hash)?.block_height;
+        let is_main_chain = ext::btc_relay::get_main_chain_block_header::<T>(block_height)
+            .map(|block_header| block_header.block_hash == proposal.block_hash)
+            .unwrap_or(false);
+        if is_main_chain {
+            // the error is attributed to the relayer which proposed it
+            let reason = proposal_id.encode();
+            ext::security::insert_error::<T>(proposal.error.clone(), proposal.proposer.clone(), reason.clone());
+            ext::security::set_status::<T>(StatusCode::Error, proposal.proposer.clone(), reason);
+        }
+
+        Self::update_relayer_sla(&proposal.proposer, ext::sla::RelayerEvent::CorrectBlockErrorProposal)?;
+
+        Self::deposit_event(<Event<T>>::AcceptBlockErrorProposal(proposal_id));
+        Ok(())
+    }
+
+    fn reject_block_error_proposal(
+        proposal_id: u64,
+        proposal: BlockErrorProposal<T::AccountId, T::BlockNumber>,
+    ) -> DispatchResult {
+        Self::remove_block_error_proposal(proposal_id, &proposal);
+
+        // a proposer which already withdrew its stake cannot be slashed
+        if <StakedRelayers<T>>::contains_key(&proposal.proposer)
+            || <UnbondingStake<T>>::contains_key(&proposal.proposer)
+        {
+            Self::slash_stake(&proposal.proposer, Self::rejected_proposal_slash())?;
+        }
+
+        Self::deposit_event(<Event<T>>::RejectBlockErrorProposal(proposal_id));
+        Ok(())
+    }
+
+    fn remove_block_error_proposal(proposal_id: u64, proposal: &BlockErrorProposal<T::AccountId, T::BlockNumber>) {
+        <BlockErrorProposals<T>>::remove(proposal_id);
+        <OpenBlockErrorProposals>::remove(&proposal.block_hash, &proposal.error);
+    }
+
+    /// Updates the SLA of the relayer on the given event, which only bonded relayers earn.
+    fn update_relayer_sla(relayer: &T::AccountId, event: ext::sla::RelayerEvent) -> DispatchResult {
+        if <StakedRelayers<T>>::contains_key(relayer) {
+            ext::sla::event_update_relayer_sla::<T>(relayer, event)?;
+        }
+        Ok(())
+    }
+
+    /// Reports a theft, decreasing the SLA of a bonded reporter instead if the reported
+    /// transaction is a valid vault transaction. Such a report does not fail, which would
+    /// revert the penalty, as no challenge period may be left for governance to reject it.
+    ///
+    /// # Arguments
+    ///
+    /// * `reporter` - account which reported the theft
+    /// * `vault_id` - account of the reported vault
+    /// * `merkle_proof` - the proof of tx inclusion
+    /// * `raw_tx` - the raw Bitcoin transaction
+    fn report_vault_theft_and_update_sla(
+        reporter: &T::AccountId,
+        vault_id: T::AccountId,
+        merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+    ) -> DispatchResult {
+        let error = match Self::_report_vault_theft(reporter, vault_id.clone(), merkle_proof, raw_tx) {
+            Ok(()) => return Ok(()),
+            Err(error) => error,
+        };
+
+        let verdict = Self::theft_report_verdict(error);
+        if verdict.is_valid_transaction() && <StakedRelayers<T>>::contains_key(reporter) {
+            ext::sla::event_update_relayer_sla::<T>(reporter, ext::sla::RelayerEvent::FailedTheftReport)?;
+            Self::deposit_event(<Event<T>>::FalseTheftReport(vault_id, reporter.clone(), verdict));
+            return Ok(());
+        }
+        Err(error)
+    }
+
+    /// Reports a theft, rolling back its changes if it fails so that a batch of reports can
+    /// skip it.
+    #[transactional]
+    fn _report_vault_theft(
+        reporter: &T::AccountId,
+        vault_id: T::AccountId,
+        merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+    ) -> DispatchResult {
+        let tx_id = Self::verify_theft_report(&vault_id, merkle_proof, raw_tx)?;
+
+        <TheftReports<T>>::mutate(&tx_id, |reports| {
+            reports.insert(vault_id.clone());
+        });
+
+        Self::process_theft_report(reporter, &vault_id, sp_std::vec![tx_id])?;
+
+        Self::deposit_event(<Event<T>>::VaultTheft(vault_id, tx_id));
+        Ok(())
+    }
+
+    /// Punishes the vault of a verified theft report right away if there is no challenge
+    /// period. Otherwise the vault is frozen and the reporter locks the `TheftReportBond`
+    /// until the report is confirmed or rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `reporter` - account which reported the theft
+    /// * `vault_id` - account of the reported vault
+    /// * `tx_ids` - the reported transactions
+    fn process_theft_report(reporter: &T::AccountId, vault_id: &T::AccountId, tx_ids: Vec<H256Le>) -> DispatchResult {
+        let challenge_period = Self::theft_challenge_period();
+        if challenge_period.is_zero() {
+            return Self::punish_vault_theft(reporter, vault_id);
+        }
+
+        let bond = Self::theft_report_bond();
+        ext::collateral::lock_collateral::<T>(reporter, bond)?;
+        ext::vault_registry::freeze_vault::<T>(vault_id)?;
+
+        let end = ext::security::active_block_number::<T>()
+            .checked_add(&challenge_period)
+            .ok_or(Error::<T>::ArithmeticOverflow)?;
+        <PendingTheftReports<T>>::insert(
+            vault_id,
+            PendingTheftReport {
+                reporter: reporter.clone(),
+                tx_ids,
+                bond,
+                dispute_bond: Zero::zero(),
+                end,
+                disputed: false,
+            },
+        );
+        Self::schedule_theft_report_end(vault_id, end);
+
+        Self::deposit_event(<Event<T>>::OpenTheftChallenge(vault_id.clone(), reporter.clone(), end));
+        Ok(())
+    }
+
+    /// Schedules the pending theft report of the vault to be decided at the given height.
+    ///
+    /// # Arguments
+    ///
+    /// * `vault_id` - account of the reported vault
+    /// * `end` - height at which the report is confirmed
+    fn schedule_theft_report_end(vault_id: &T::AccountId, end: T::BlockNumber) {
+        <TheftChallengeEnds<T>>::insert(end, vault_id, ());
+        <NextTheftChallengeEnd<T>>::mutate(|next| {
+            *next = Some(next.map_or(end, |next| next.min(end)));
+        });
+    }
+
+    /// Liquidates the vault and rewards the reporter by increasing their SLA and paying the bounty.
+    ///
+    /// # Arguments
+    ///
+    /// * `reporter` - account which reported the theft
+    /// * `vault_id` - account of the reported vault
+    fn punish_vault_theft(reporter: &T::AccountId, vault_id: &T::AccountId) -> DispatchResult {
+        let confiscated_collateral = ext::vault_registry::liquidate_theft_vault::<T>(vault_id)?;
+        Self::update_relayer_sla(reporter, ext::sla::RelayerEvent::TheftReport)?;
+        Self::pay_theft_report_bounty(reporter, vault_id, confiscated_collateral)
+    }
+
+    fn _dispute_theft_report(vault_id: T::AccountId, context: Vec<u8>) -> DispatchResult {
+        ensure!(
+            context.len() <= MAX_DISPUTE_CONTEXT_LENGTH as usize,
+            Error::<T>::DisputeContextTooLong
+        );
+        let mut report = <PendingTheftReports<T>>::get(&vault_id).ok_or(Error::<T>::TheftReportNotFound)?;
+        ensure!(!report.disputed, Error::<T>::TheftReportAlreadyDisputed);
+        let height = ext::security::active_block_number::<T>();
+        ensure!(height < report.end, Error::<T>::ChallengePeriodExpired);
+
+        let dispute_bond = Self::dispute_bond();
+        ext::collateral::lock_collateral::<T>(&vault_id, dispute_bond)?;
+        report.dispute_bond = dispute_bond;
+
+        <TheftChallengeEnds<T>>::remove(report.end, &vault_id);
+        report.end = height
+            .checked_add(&Self::dispute_resolution_period())
+            .ok_or(Error::<T>::ArithmeticOverflow)?;
+        report.disputed = true;
+        Self::schedule_theft_report_end(&vault_id, report.end);
+        <PendingTheftReports<T>>::insert(&vault_id, report);
+
+        Self::deposit_event(<Event<T>>::DisputeTheftReport(vault_id, context));
+        Ok(())
+    }
+
+    /// Confirms a pending theft report, liquidating the vault, returning the reporter's bond and
+    /// transferring the vault's dispute bond to the reporter.
+    ///
+    /// # Arguments
+    ///
+    /// * `vault_id` - account of the reported vault
+    #[transactional]
+    fn confirm_theft_report(vault_id: &T::AccountId) -> DispatchResult {
+        let report = <PendingTheftReports<T>>::take(vault_id).ok_or(Error::<T>::TheftReportNotFound)?;
+        <TheftChallengeEnds<T>>::remove(report.end, vault_id);
+        ext::vault_registry::unfreeze_vault::<T>(vault_id);
+        ext::collateral::release_collateral::<T>(&report.reporter, report.bond)?;
+        ext::collateral::unlock_and_transfer::<T>(vault_id, &report.reporter, report.dispute_bond)?;
+
+        // the vault may have been liquidated for undercollateralization in the meantime
+        if ext::vault_registry::get_active_vault_from_id::<T>(vault_id).is_ok() {
+            Self::punish_vault_theft(&report.reporter, vault_id)?;
+        }
+
+        Self::deposit_event(<Event<T>>::ConfirmTheftReport(vault_id.clone(), report.reporter));
+        Ok(())
+    }
+
+    /// Rejects a pending theft report, unfreezing the vault, returning its dispute bond,
+    /// transferring the reporter's bond to the vault and decreasing the reporter's SLA. The
+    /// reported transactions may be reported again afterwards.
+    ///
+    /// # Arguments
+    ///
+    /// * `vault_id` - account of the reported vault
+    #[transactional]
+    fn reject_theft_report(vault_id: &T::AccountId) -> DispatchResult {
+        let report = <PendingTheftReports<T>>::take(vault_id).ok_or(Error::<T>::TheftReportNotFound)?;
+        <TheftChallengeEnds<T>>::remove(report.end, vault_id);
+        ext::vault_registry::unfreeze_vault::<T>(vault_id);
+        ext::collateral::unlock_and_transfer::<T>(&report.reporter, vault_id, report.bond)?;
+        ext::collateral::release_collateral::<T>(vault_id, report.dispute_bond)?;
+        for tx_id in &report.tx_ids {
+            <TheftReports<T>>::mutate_exists(tx_id, |reports| {
+                if let Some(vault_ids) = reports {
+                    vault_ids.remove(vault_id);
+                    if vault_ids.is_empty() {
+                        *reports = None;
+                    }
+                }
+            });
+        }
+        Self::update_relayer_sla(&report.reporter, ext::sla::RelayerEvent::FailedTheftReport)?;
+
+        Self::deposit_event(<Event<T>>::RejectTheftReport(
+            vault_id.clone(),
+            report.reporter,
+            report.bond,
+        ));
+        Ok(())
+    }
+
+    /// Confirms the undisputed theft reports whose challenge period has ended and the disputed
+    /// reports which governance did not decide in time. At most
+    /// `MAX_CONFIRMED_THEFT_REPORTS_PER_BLOCK` heights are checked and reports decided per call.
+    ///
+    /// Returns the consumed weight.
+    fn decide_expired_theft_reports() -> Weight {
+        let db_weight = T::DbWeight::get();
+        let mut end = match <NextTheftChallengeEnd<T>>::get() {
+            Some(end) => end,
+            None => return db_weight.reads(1),
+        };
+
+        if <PendingTheftReports<T>>::iter().next().is_none() {
+            // stop advancing the cursor until the next report is opened
+            <NextTheftChallengeEnd<T>>::kill();
+            return db_weight.reads_writes(2, 1);
+        }
+
+        let height = ext::security::active_block_number::<T>();
+        let mut checks = 0;
+        let mut weight = db_weight.reads_writes(3, 1);
+
+        while end <= height && checks < MAX_CONFIRMED_THEFT_REPORTS_PER_BLOCK {
+            let vault_ids = <TheftChallengeEnds<T>>::iter_prefix(end)
+                .map(|(vault_id, _)| vault_id)
+                .take((MAX_CONFIRMED_THEFT_REPORTS_PER_BLOCK - checks) as usize)
+                .collect::<Vec<_>>();
+            weight = weight.saturating_add(db_weight.reads(1));
+
+            for vault_id in vault_ids {
+                if let Err(err) = Self::confirm_theft_report(&vault_id) {
+                    log::warn!("Failed to decide theft report of {:?}: {:?}", vault_id, err);
+                    // leave the report to governance rather than retrying it every block
+                    <TheftChallengeEnds<T>>::remove(end, &vault_id);
+                }
+                weight = weight.saturating_add(db_weight.reads_writes(23, 18));
+                checks += 1;
+            }
+
+            if <TheftChallengeEnds<T>>::iter_prefix(end).next().is_some() {
+                // continue with the remaining reports of this height in the next block
+                break;
+            }
+            end = end.saturating_add(1u32.into());
+            checks += 1;
+        }
+
+        <NextTheftChallengeEnd<T>>::put(end);
+
+        weight
+    }
+
+    /// Checks that the transaction is not yet reported for the vault, is included in the
+    /// main chain and is not a valid vault transaction. Returns the id of the transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `vault_id` - the account of the vault to check
+    /// * `merkle_proof` - the proof of tx inclusion
+    /// * `raw_tx` - the raw Bitcoin transaction
+    fn verify_theft_report(
+        vault_id: &T::AccountId,
+        merkle_proof: Vec<u8>,
+        raw_tx: Vec<u8>,
+    ) -> Result<H256Le, DispatchError> {
+        let transaction = parse_transaction(raw_tx.as_slice()).map_err(|_| Error::<T>::InvalidTransaction)?;
+        let tx_id = transaction.tx_id();
+
+        // throw if already reported
+        ensure!(
+            !<TheftReports<T>>::get(&tx_id).contains(vault_id),
+            Error::<T>::VaultAlreadyReported,
+        );
+        ensure!(
+            !<PendingTheftReports<T>>::contains_key(vault_id),
+            Error::<T>::TheftReportPending
+        );
+
+        ext::btc_relay::verify_transaction_inclusion::<T>(tx_id, merkle_proof)?;
+        // also fails if the vault is liquidated
+        Self::_is_parsed_transaction_invalid(vault_id, transaction)?;
+
+        Ok(tx_id)
+    }
+
+    /// Checks that both transactions are included in the main chain, spend from the vault's
+    /// wallet and pay the recipient of the same request of the vault, and that neither is
+    /// reported yet. Returns the id of the request and of both transactions.
+    ///
+    /// # Arguments
+    ///
+    /// * `vault_id` - the account of the vault to check
+    /// * `merkle_proofs` - the proofs of inclusion of both transactions
+    /// * `raw_txs` - the raw Bitcoin transactions
+    fn verify_double_payment_report(
+        vault_id: &T::AccountId,
+        merkle_proofs: (Vec<u8>, Vec<u8>),
+        raw_txs: (Vec<u8>, Vec<u8>),
+    ) -> Result<(H256, H256Le, H256Le), DispatchError> {
+        let left_tx = parse_transaction(raw_txs.0.as_slice()).map_err(|_| Error::<T>::InvalidTransaction)?;
+        let right_tx = parse_transaction(raw_txs.1.as_slice()).map_err(|_| Error::<T>::InvalidTransaction)?;
+        let left_tx_id = left_tx.tx_id();
+        let right_tx_id = right_tx.tx_id();
+
+        // a transaction cannot double pay with itself
+        ensure!(left_tx_id != right_tx_id, Error::<T>::DuplicateTransaction);
+
+        for tx_id in &[left_tx_id, right_tx_id] {
             ensure!(
-                !Self::is_valid_merge_transaction(&payments, &op_returns, &vault.wallet),
-                Error::<T>::ValidMergeTransaction
+                !<TheftReports<T>>::get(tx_id).contains(vault_id),
+                Error::<T>::VaultAlreadyReported,
             );
+        }
+
+        ext::btc_relay::verify_transaction_inclusion::<T>(left_tx_id, merkle_proofs.0)?;
+        ext::btc_relay::verify_transaction_inclusion::<T>(right_tx_id, merkle_proofs.1)?;
+
+        ensure!(
+            !<PendingTheftReports<T>>::contains_key(vault_id),
+            Error::<T>::TheftReportPending
+        );
+
+        let vault = ext::vault_registry::get_active_vault_from_id::<T>(vault_id)?;
+        let left_wallet = Self::get_wallet_with_migration_targets(vault_id, &vault.wallet, &left_tx);
+        let right_wallet = Self::get_wallet_with_migration_targets(vault_id, &vault.wallet, &right_tx);
+        let (left_request_id, left_payments) =
+            Self::get_request_id_of_vault_transaction(vault_id, left_tx, &left_wallet)?;
+        let (right_request_id, right_payments) =
+            Self::get_request_id_of_vault_transaction(vault_id, right_tx, &right_wallet)?;
+        ensure!(left_request_id == right_request_id, Error::<T>::DifferentRequestIds);
+
+        let (_, btc_address) =
+            Self::get_request_payment(vault_id, &left_request_id).ok_or(Error::<T>::RequestNotFound)?;
+        for payments in &[left_payments, right_payments] {
+            ensure!(
+                payments.iter().any(|(_, address)| *address == btc_address),
+                Error::<T>::NoPaymentToRecipient
+            );
+        }
+
+        Ok((left_request_id, left_tx_id, right_tx_id))
+    }
+
+    /// Dry-run of `report_vault_theft`, returns whether the report would succeed or why not.
+    ///
+    /// # Arguments
+    ///
+    /// * `vault_id` - the account of the vault to check
+    /// * `merkle_proof` - the proof of tx inclusion
+    /// * `raw_tx` - the raw Bitcoin transaction
+    pub fn check_theft_report(vault_id: &T::AccountId, merkle_proof: Vec<u8>, raw_tx: Vec<u8>) -> TheftReportVerdict {
+        if !Self::is_call_enabled(b"report_vault_theft") {
+            return TheftReportVerdict::ReportingDisabled;
+        }
+        Self::verify_theft_report(vault_id, merkle_proof, raw_tx)
+            .map_or_else(Self::theft_report_verdict, |_| TheftReportVerdict::Theft)
+    }
+
+    /// Dry-run of `report_vault_double_payment`, returns whether the report would succeed or
+    /// why not.
+    ///
+    /// # Arguments
+    ///
+    /// * `vault_id` - the account of the vault to check
+    /// * `merkle_proofs` - the proofs of inclusion of both transactions
+    /// * `raw_txs` - the raw Bitcoin transactions
+    pub fn check_double_payment_report(
+        vault_id: &T::AccountId,
+        merkle_proofs: (Vec<u8>, Vec<u8>),
+        raw_txs: (Vec<u8>, Vec<u8>),
+    ) -> TheftReportVerdict {
+        if !Self::is_call_enabled(b"report_vault_double_payment") {
+            return TheftReportVerdict::ReportingDisabled;
+        }
+        Self::verify_double_payment_report(vault_id, merkle_proofs, raw_txs)
+            .map_or_else(Self::theft_report_verdict, |_| TheftReportVerdict::Theft)
+    }
+
+    /// Checks if the parachain is not shut down and the call of this module is not paused.
+    fn is_call_enabled(call: &[u8]) -> bool {
+        ext::security::ensure_parachain_status_not_shutdown::<T>().is_ok()
+            && ext::security::ensure_call_enabled::<T>(b"StakedRelayers", call).is_ok()
+    }
+
+    /// Returns the verdict on a theft report which failed with the given error.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - the error of the report
+    fn theft_report_verdict(error: DispatchError) -> TheftReportVerdict {
+        let verdicts: Vec<(DispatchError, TheftReportVerdict)> = sp_std::vec![
+            (
+                Error::<T>::InvalidTransaction.into(),
+                TheftReportVerdict::InvalidTransaction
+            ),
+            (
+                Error::<T>::VaultAlreadyReported.into(),
+                TheftReportVerdict::AlreadyReported
+            ),
+            (Error::<T>::TheftReportPending.into(), TheftReportVerdict::ReportPending),
+            (
+                Error::<T>::VaultNoInputToTransaction.into(),
+                TheftReportVerdict::NoVaultInput
+            ),
+            (
+                Error::<T>::ValidRedeemTransaction.into(),
+                TheftReportVerdict::ValidRedeem
+            ),
+            (
+                Error::<T>::ValidReplaceTransaction.into(),
+                TheftReportVerdict::ValidReplace
+            ),
+            (
+                Error::<T>::ValidRefundTransaction.into(),
+                TheftReportVerdict::ValidRefund
+            ),
+            (Error::<T>::ValidMergeTransaction.into(), TheftReportVerdict::ValidMerge),
+            (
+                Error::<T>::ValidFeeBumpTransaction.into(),
+                TheftReportVerdict::ValidFeeBump
+            ),
+            (Error::<T>::ValidBatchTransaction.into(), TheftReportVerdict::ValidBatch),
+            (
+                Error::<T>::DuplicateTransaction.into(),
+                TheftReportVerdict::DuplicateTransaction
+            ),
+            (Error::<T>::NoRequestId.into(), TheftReportVerdict::NoRequestId),
+            (
+                Error::<T>::DifferentRequestIds.into(),
+                TheftReportVerdict::DifferentRequestIds
+            ),
+            (Error::<T>::RequestNotFound.into(), TheftReportVerdict::RequestNotFound),
+            (
+                Error::<T>::NoPaymentToRecipient.into(),
+                TheftReportVerdict::NoPaymentToRecipient
+            ),
+            (
+                vault_registry::Error::<T>::VaultNotFound.into(),
+                TheftReportVerdict::VaultNotActive
+            ),
+        ];
+        if let Some((_, verdict)) = verdicts.into_iter().find(|(verdict_error, _)| *verdict_error == error) {
+            return verdict;
+        }
+
+        // the inclusion proof is the only part of a report checked by the BTC-Relay
+        let btc_relay_index = <<T as frame_system::Config>::PalletInfo as PalletInfo>::index::<btc_relay::Pallet<T>>();
+        match error {
+            DispatchError::Module { index, .. } if Some(index as usize) == btc_relay_index => {
+                TheftReportVerdict::InvalidProof
+            }
+            _ => TheftReportVerdict::Other,
+        }
+    }
+
+    /// Pays the reporter of a theft its share of the collateral confiscated from the vault,
+    /// capped at `MaxTheftReportBounty`. The bounty is taken from the liquidation vault.
+    ///
+    /// # Arguments
+    ///
+    /// * `reporter` - account which reported the theft
+    /// * `vault_id` - account of the liquidated vault
+    /// * `confiscated_collateral` - collateral slashed to the liquidation vault
+    fn pay_theft_report_bounty(
+        reporter: &T::AccountId,
+        vault_id: &T::AccountId,
+        confiscated_collateral: Collateral<T>,
+    ) -> DispatchResult {
+        let bounty = ext::fee::collateral_for::<T>(confiscated_collateral, Self::theft_report_bounty())?
+            .min(Self::max_theft_report_bounty());
+        if bounty.is_zero() {
+            return Ok(());
+        }
+
+        ext::vault_registry::transfer_funds::<T>(
+            CurrencySource::LiquidationVault,
+            CurrencySource::FreeBalance(reporter.clone()),
+            bounty,
+        )?;
+
+        Self::deposit_event(<Event<T>>::PayTheftReportBounty(
+            reporter.clone(),
+            vault_id.clone(),
+            bounty,
+        ));
+        Ok(())
+    }
+
+    /// Returns the type of maintenance the transaction performs if it only moves funds between
+    /// the vault's addresses, or `None` if funds leave the wallet. The wallet only contains the
+    /// target of a migration if the vault declared it for an address the transaction spends
+    /// from. OP_RETURN outputs may tag the transaction, but must not burn value.
+    ///
+    /// # Arguments
+    ///
+    /// * `vault_id` - the vault
+    /// * `payments` - all payment outputs extracted from tx
+    /// * `op_returns` - all op_return outputs extracted from tx
+    /// * `wallet` - vault btc addresses, including the targets of pending migrations
+    pub(crate) fn get_maintenance_transaction_type(
+        vault_id: &T::AccountId,
+        payments: &[(i64, BtcAddress)],
+        op_returns: &[(i64, Vec<u8>)],
+        wallet: &Wallet,
+    ) -> Option<MaintenanceTransaction> {
+        if op_returns.iter().any(|(value, _)| *value > 0) {
+            // burning funds moves them out of the wallet
+            return None;
+        }
+
+        if !payments.iter().all(|(_value, address)| wallet.has_btc_address(address)) {
+            return None;
+        }
+
+        match Self::extract_request_id(op_returns) {
+            // a fee bump for a request may pay the recipient nothing, the request is
+            // fulfilled by the transaction it bumps
+            Some(request_id) if Self::references_vault_request(vault_id, &request_id) => {
+                Some(MaintenanceTransaction::FeeBump)
+            }
+            // any other tag is only a label of the merge
+            _ => Some(MaintenanceTransaction::Merge),
+        }
+    }
+
+    /// Checks if the request id referenced by a transaction belongs to a redeem, replace or
+    /// refund request of the vault, or to a batch of such requests.
+    ///
+    /// # Arguments
+    ///
+    /// * `vault_id` - the vault
+    /// * `request_id` - the referenced request id
+    fn references_vault_request(vault_id: &T::AccountId, request_id: &H256) -> bool {
+        match ext::btc_relay::get_batch_payment::<T>(request_id) {
+            Some(batch) => batch
+                .iter()
+                .all(|request_id| Self::get_request_payment(vault_id, request_id).is_some()),
+            None => Self::get_request_payment(vault_id, request_id).is_some(),
+        }
+    }
+
+    /// Checks if the vault is doing a valid merge transaction to move funds between
+    /// addresses, e.g. to consolidate outputs or to migrate to a declared target.
+    ///
+    /// # Arguments
+    ///
+    /// * `vault_id` - the vault
+    /// * `payments` - all payment outputs extracted from tx
+    /// * `op_returns` - all op_return outputs extracted from tx
+    /// * `wallet` - vault btc addresses
+    pub(crate) fn is_valid_merge_transaction(
+        vault_id: &T::AccountId,
+        payments: &[(i64, BtcAddress)],
+        op_returns: &[(i64, Vec<u8>)],
+        wallet: &Wallet,
+    ) -> bool {
+        Self::get_maintenance_transaction_type(vault_id, payments, op_returns, wallet)
+            == Some(MaintenanceTransaction::Merge)
+    }
+
+    /// Checks if the vault is sending a valid request transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_value` - amount of btc as specified in the request
+    /// * `request_address` - recipient btc address
+    /// * `payments` - all payment outputs extracted from tx
+    /// * `wallet` - vault btc addresses
+    pub(crate) fn is_valid_request_transaction(
+        request_value: Wrapped<T>,
+        request_address: BtcAddress,
+        payments: &[(i64, BtcAddress)],
+        wallet: &Wallet,
+    ) -> bool {
+        let request_value = match TryInto::<u64>::try_into(request_value).map_err(|_e| Error::<T>::TryIntoIntError) {
+            Ok(value) => value as i64,
+            Err(_) => return false,
+        };
+
+        // check all outputs, vault cannot pay to unknown recipients
+        for (value, address) in payments {
+            if *address == request_address {
+                if *value < request_value {
+                    // insufficient payment to recipient
+                    return false;
+                }
+            } else if !wallet.has_btc_address(&address) {
+                // payment to unknown address
+                return false;
+            }
+        }
+
+        // tx has sufficient payment to recipient and
+        // all refunds are to wallet addresses
+        true
+    }
+
+    /// Check if a vault transaction is invalid. Returns `Ok` if invalid or `Err` otherwise.
+    /// This method should be callable over RPC for a staked-relayer client to check validity.
+    ///
+    /// # Arguments
+    ///
+    /// `vault_id`: the vault.
+    /// `raw_tx`: the BTC transaction by the vault.
+    pub fn is_transaction_invalid(vault_id: &T::AccountId, raw_tx: Vec<u8>) -> DispatchResult {
+        let tx = parse_transaction(raw_tx.as_slice()).map_err(|_| Error::<T>::InvalidTransaction)?;
+        Self::_is_parsed_transaction_invalid(vault_id, tx)
+    }
+
+    /// Checks if any input of the transaction spends from one of the wallet's addresses, or
+    /// from the target of a wallet migration the vault declared.
+    ///
+    /// # Arguments
+    ///
+    /// * `vault_id` - the vault
+    /// * `tx` - the transaction
+    /// * `wallet` - vault btc addresses
+    fn has_input_from_vault(vault_id: &T::AccountId, tx: &Transaction, wallet: &Wallet) -> bool {
+        Self::has_input_from(tx, |address| {
+            wallet.has_btc_address(address) || ext::vault_registry::is_reserved_by_vault::<T>(vault_id, address)
+        })
+    }
+
+    /// Checks if any input of the transaction spends from an address matching the predicate.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - the transaction
+    /// * `predicate` - matches the addresses to look for
+    fn has_input_from<F: Fn(&BtcAddress) -> bool>(tx: &Transaction, predicate: F) -> bool {
+        // match every address form the input could spend from, otherwise a vault could
+        // register e.g. a P2WPKH-P2SH address and spend from it without being detected
+        tx.inputs.iter().any(|input| {
+            input
+                .extract_addresses()
+                .map_or(false, |addresses| addresses.iter().any(&predicate))
+        })
+    }
+
+    /// Returns a copy of the vault's wallet which additionally contains the targets of the
+    /// pending wallet migrations whose source address the transaction spends from.
+    ///
+    /// # Arguments
+    ///
+    /// * `vault_id` - the vault
+    /// * `wallet` - vault btc addresses
+    /// * `tx` - the transaction
+    fn get_wallet_with_migration_targets(vault_id: &T::AccountId, wallet: &Wallet, tx: &Transaction) -> Wallet {
+        let mut wallet = wallet.clone();
+        for migration in ext::vault_registry::get_pending_wallet_migrations::<T>(vault_id) {
+            if Self::has_input_from(tx, |address| *address == migration.from) {
+                wallet.add_btc_address(migration.to);
+            }
+        }
+        wallet
+    }
+
+    /// Returns the request id referenced in the OP_RETURN output of a transaction, if the
+    /// transaction has exactly one OP_RETURN output which does not burn value and holds at
+    /// least 32 bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `op_returns` - all op_return outputs extracted from tx
+    fn extract_request_id(op_returns: &[(i64, Vec<u8>)]) -> Option<H256> {
+        if op_returns.len() != 1 || op_returns[0].0 > 0 || op_returns[0].1.len() < 32 {
+            return None;
+        }
+        // op_return can be up to 83 bytes so slice first 32
+        Some(H256::from_slice(&op_returns[0].1[..32]))
+    }
+
+    /// Returns the request id referenced by a transaction spending from the vault's wallet,
+    /// together with the payment outputs of the transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `vault_id` - the vault
+    /// * `tx` - the transaction
+    /// * `wallet` - vault btc addresses
+    fn get_request_id_of_vault_transaction(
+        vault_id: &T::AccountId,
+        tx: Transaction,
+        wallet: &Wallet,
+    ) -> Result<(H256, Vec<(i64, BtcAddress)>), DispatchError> {
+        ensure!(
+            Self::has_input_from_vault(vault_id, &tx, wallet),
+            Error::<T>::VaultNoInputToTransaction
+        );
+        let (payments, op_returns) = ext::btc_relay::extract_outputs::<T>(tx)?;
+        let request_id = Self::extract_request_id(&op_returns).ok_or(Error::<T>::NoRequestId)?;
+        // maintenance transactions keep the funds in the wallet, so they cannot pay a request twice
+        match Self::get_maintenance_transaction_type(vault_id, &payments, &op_returns, wallet) {
+            Some(MaintenanceTransaction::FeeBump) => return Err(Error::<T>::ValidFeeBumpTransaction.into()),
+            Some(MaintenanceTransaction::Merge) => return Err(Error::<T>::ValidMergeTransaction.into()),
+            None => (),
+        }
+        Ok((request_id, payments))
+    }
+
+    /// Checks if the request was settled by a transaction other than `tx_id`, in which case
+    /// `tx_id` cannot be a legitimate payment for the request.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_id` - the request referenced by the transaction
+    /// * `tx_id` - the id of the transaction
+    fn is_settled_by_other_transaction(request_id: &H256, tx_id: H256Le) -> bool {
+        matches!(ext::btc_relay::get_settling_transaction::<T>(request_id), Some(settling_tx_id) if settling_tx_id != tx_id)
+    }
+
+    /// Returns the amount and recipient of a redeem, replace or refund request which the vault
+    /// has to pay, or `None` if there is no such request of the vault.
+    ///
+    /// # Arguments
+    ///
+    /// * `vault_id` - the paying vault
+    /// * `request_id` - the id of the request
+    fn get_request_payment(vault_id: &T::AccountId, request_id: &H256) -> Option<(Wrapped<T>, BtcAddress)> {
+        if let Ok(req) = ext::redeem::get_open_or_completed_redeem_request_from_id::<T>(request_id) {
+            return Some((req.amount_btc, req.btc_address)).filter(|_| req.vault == *vault_id);
+        }
+        if let Ok(req) = ext::replace::get_open_or_completed_replace_request::<T>(request_id) {
+            return Some((req.amount, req.btc_address)).filter(|_| req.old_vault == *vault_id);
+        }
+        if let Ok(req) = ext::refund::get_open_or_completed_refund_request_from_id::<T>(request_id) {
+            return Some((req.amount_wrapped, req.btc_address)).filter(|_| req.vault == *vault_id);
+        }
+        None
+    }
+
+    /// Checks if the vault is validly paying a batch of its requests. The payment for the
+    /// i-th request is the i-th payment output, any further outputs must go to the wallet.
+    ///
+    /// # Arguments
+    ///
+    /// * `requests` - amount and recipient of each paid request
+    /// * `payments` - all payment outputs extracted from tx
+    /// * `wallet` - vault btc addresses
+    pub(crate) fn is_valid_batch_transaction(
+        requests: &[(Wrapped<T>, BtcAddress)],
+        payments: &[(i64, BtcAddress)],
+        wallet: &Wallet,
+    ) -> bool {
+        if payments.len() < requests.len() {
+            return false;
+        }
+
+        let (request_payments, other_payments) = payments.split_at(requests.len());
+        let pays_requests =
+            requests
+                .iter()
+                .zip(request_payments.iter())
+                .all(|((request_value, request_address), (value, address))| {
+                    match TryInto::<u64>::try_into(*request_value) {
+                        Ok(request_value) => address == request_address && *value >= request_value as i64,
+                        Err(_) => false,
+                    }
+                });
+
+        pays_requests
+            && other_payments
+                .iter()
+                .all(|(_value, address)| wallet.has_btc_address(address))
+    }
+
+    /// Check if a vault transaction is invalid. Returns `Ok` if invalid or `Err` otherwise.
+    pub fn _is_parsed_transaction_invalid(vault_id: &T::AccountId, tx: Transaction) -> DispatchResult {
+        let vault = ext::vault_registry::get_active_vault_from_id::<T>(vault_id)?;
+        let tx_id = tx.tx_id();
+
+        // check if vault's btc address features in an input of the transaction
+        ensure!(
+            Self::has_input_from_vault(vault_id, &tx, &vault.wallet),
+            // since the transaction does not have any inputs that correspond
+            // to any of the vault's registered BTC addresses, return Err
+            Error::<T>::VaultNoInputToTransaction
+        );
+
+        // funds may also move to the targets of migrations the vault declared in advance
+        let wallet = Self::get_wallet_with_migration_targets(vault_id, &vault.wallet, &tx);
+
+        // Vaults are required to move funds for redeem and replace operations.
+        // Each transaction MUST feature at least two or three outputs as follows:
+        // * recipient: the recipient of the redeem / replace
+        // * op_return: the associated ID encoded in the OP_RETURN
+        // * vault: any "spare change" the vault is transferring
+
+        // should only err if there are too many outputs
+        if let Ok((payments, op_returns)) = ext::btc_relay::extract_outputs::<T>(tx) {
+            // check if the transaction only moves funds between the vault's addresses
+            match Self::get_maintenance_transaction_type(vault_id, &payments, &op_returns, &wallet) {
+                Some(MaintenanceTransaction::FeeBump) => return Err(Error::<T>::ValidFeeBumpTransaction.into()),
+                Some(MaintenanceTransaction::Merge) => return Err(Error::<T>::ValidMergeTransaction.into()),
+                None => (),
+            }
 
             // we only expect one op_return output, the op_return output should not burn value, and
             // the request_id is expected to be 32 bytes
-            if op_returns.len() != 1 || op_returns[0].0 > 0 || op_returns[0].1.len() < 32 {
+            let request_id = match Self::extract_request_id(&op_returns) {
+                Some(request_id) => request_id,
+                None => return Ok(()),
+            };
+
+            // batched payments commit to the ids of all requests they pay
+            if let Some(batch) = ext::btc_relay::get_batch_payment::<T>(&request_id) {
+                // once a request is settled, any other payment for it moves funds out of the vault
+                if batch
+                    .iter()
+                    .any(|request_id| Self::is_settled_by_other_transaction(request_id, tx_id))
+                {
+                    return Ok(());
+                }
+                let requests = match batch
+                    .iter()
+                    .map(|request_id| Self::get_request_payment(vault_id, request_id))
+                    .collect::<Option<Vec<_>>>()
+                {
+                    Some(requests) => requests,
+                    // the batch pays requests of other vaults
+                    None => return Ok(()),
+                };
+                ensure!(
+                    !Self::is_valid_batch_transaction(&requests, &payments, &wallet),
+                    Error::<T>::ValidBatchTransaction
+                );
                 return Ok(());
             }
 
-            // op_return can be up to 83 bytes so slice first 32
-            let request_id = H256::from_slice(&op_returns[0].1[..32]);
+            // once a request is settled, any other payment for it moves funds out of the vault
+            if Self::is_settled_by_other_transaction(&request_id, tx_id) {
+                return Ok(());
+            }
 
             // redeem requests
             if let Ok(req) = ext::redeem::get_open_or_completed_redeem_request_from_id::<T>(&request_id) {
                 ensure!(
-                    !Self::is_valid_request_transaction(req.amount_btc, req.btc_address, &payments, &vault.wallet,),
+                    !Self::is_valid_request_transaction(req.amount_btc, req.btc_address, &payments, &wallet,),
                     Error::<T>::ValidRedeemTransaction
                 );
             };
@@ -356,7 +1937,7 @@ impl<T: Config> Module<T> {
             // replace requests
             if let Ok(req) = ext::replace::get_open_or_completed_replace_request::<T>(&request_id) {
                 ensure!(
-                    !Self::is_valid_request_transaction(req.amount, req.btc_address, &payments, &vault.wallet,),
+                    !Self::is_valid_request_transaction(req.amount, req.btc_address, &payments, &wallet,),
                     Error::<T>::ValidReplaceTransaction
                 );
             };
@@ -364,7 +1945,7 @@ impl<T: Config> Module<T> {
             // refund requests
             if let Ok(req) = ext::refund::get_open_or_completed_refund_request_from_id::<T>(&request_id) {
                 ensure!(
-                    !Self::is_valid_request_transaction(req.amount_wrapped, req.btc_address, &payments, &vault.wallet,),
+                    !Self::is_valid_request_transaction(req.amount_wrapped, req.btc_address, &payments, &wallet,),
                     Error::<T>::ValidRefundTransaction
                 );
             };
@@ -374,12 +1955,79 @@ impl<T: Config> Module<T> {
     }
 }
 
+impl<T: Config> security::PausableCalls for Module<T> {
+    fn pausable_calls(module: &[u8]) -> Option<&'static [&'static [u8]]> {
+        if module == b"StakedRelayers" {
+            Some(PAUSABLE_CALLS)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: Config> btc_relay::OnStaleBlockHeader<T::AccountId> for Module<T> {
+    /// Decreases the SLA of the relayer which submitted a block header of a removed stale fork.
+    fn on_stale_block_header(relayer: &T::AccountId) -> Weight {
+        if let Err(err) = Self::update_relayer_sla(relayer, ext::sla::RelayerEvent::StaleBlock) {
+            log::warn!(
+                "Failed to update the SLA of stale block submitter {:?}: {:?}",
+                relayer,
+                err
+            );
+        }
+        // the bond check and the SLA update of both relayer reward pools
+        T::DbWeight::get().reads_writes(12, 8)
+    }
+}
+
 decl_event!(
     pub enum Event<T>
     where
         AccountId = <T as frame_system::Config>::AccountId,
+        BlockNumber = <T as frame_system::Config>::BlockNumber,
+        Collateral = Collateral<T>,
     {
         VaultTheft(AccountId, H256Le),
+        /// vault_id, request_id, first tx_id, second tx_id
+        VaultDoublePayment(AccountId, H256, H256Le, H256Le),
+        /// vault_id, reason the report in a batch was skipped
+        SkipTheftReport(AccountId, TheftReportVerdict),
+        /// vault_id, reporter_id, reason the report is false
+        FalseTheftReport(AccountId, AccountId, TheftReportVerdict),
+        /// reporter_id, vault_id, bounty
+        PayTheftReportBounty(AccountId, AccountId, Collateral),
+        /// relayer_id, block_hash, deposit
+        LockHeaderDeposit(AccountId, H256Le, Collateral),
+        /// relayer_id, block_hash, deposit
+        RefundHeaderDeposit(AccountId, H256Le, Collateral),
+        /// relayer_id, block_hash, deposit
+        ForfeitHeaderDeposit(AccountId, H256Le, Collateral),
+        /// relayer_id, stake
+        RegisterStakedRelayer(AccountId, Collateral),
+        /// relayer_id, stake, unlock_height
+        DeregisterStakedRelayer(AccountId, Collateral, BlockNumber),
+        /// relayer_id, stake
+        WithdrawUnbondedStake(AccountId, Collateral),
+        /// relayer_id, slashed_amount
+        SlashStakedRelayer(AccountId, Collateral),
+        /// proposal_id, proposer, block_hash, error
+        ProposeBlockError(u64, AccountId, H256Le, ErrorCode),
+        /// proposal_id, relayer_id, approve
+        VoteOnBlockError(u64, AccountId, bool),
+        /// proposal_id
+        AcceptBlockErrorProposal(u64),
+        /// proposal_id
+        RejectBlockErrorProposal(u64),
+        /// vault_id, reporter_id, end of the challenge period
+        OpenTheftChallenge(AccountId, AccountId, BlockNumber),
+        /// vault_id, context
+        DisputeTheftReport(AccountId, Vec<u8>),
+        /// vault_id, reporter_id
+        ConfirmTheftReport(AccountId, AccountId),
+        /// vault_id, reporter_id, forfeited bond
+        RejectTheftReport(AccountId, AccountId, Collateral),
+        /// vault_id, op_return of the batch
+        DeclareBatchPayment(AccountId, H256),
     }
 );
 
@@ -397,9 +2045,63 @@ decl_error! {
         ValidRefundTransaction,
         /// Valid merge transaction
         ValidMergeTransaction,
+        /// Valid fee bump transaction
+        ValidFeeBumpTransaction,
+        /// Valid batch transaction
+        ValidBatchTransaction,
+        /// Batch contains a request which is not a redeem, replace or refund request of the vault
+        InvalidBatchPayment,
         /// Failed to parse transaction
         InvalidTransaction,
         /// Unable to convert value
         TryIntoIntError,
+        /// Both transactions are the same
+        DuplicateTransaction,
+        /// Transaction does not reference a request id
+        NoRequestId,
+        /// Transactions reference different request ids
+        DifferentRequestIds,
+        /// No redeem, replace or refund request of the vault with this id
+        RequestNotFound,
+        /// Transaction does not pay the recipient of the request
+        NoPaymentToRecipient,
+        /// Too many thefts reported in a single call
+        TooManyTheftReports,
+        /// Relayer is already registered
+        AlreadyRegistered,
+        /// Relayer is not registered
+        NotRegistered,
+        /// Stake is below the minimum stake
+        InsufficientStake,
+        /// Relayer has unbonding stake which is not withdrawn
+        UnbondingStakeNotWithdrawn,
+        /// Relayer has no unbonding stake
+        NoUnbondingStake,
+        /// Unbonding period has not expired
+        UnbondingPeriodNotExpired,
+        /// Only `NoDataBTCRelay` and `InvalidBTCRelay` can be proposed
+        InvalidErrorCode,
+        /// A proposal for this block and error is already open
+        ProposalAlreadyOpen,
+        /// Proposal not found or already decided
+        ProposalNotFound,
+        /// Relayer already voted on this proposal
+        AlreadyVoted,
+        /// Voting period has not expired
+        VotingPeriodNotExpired,
+        /// The vault has a theft report which is not decided yet
+        TheftReportPending,
+        /// No pending theft report for this vault
+        TheftReportNotFound,
+        /// The theft report is already disputed
+        TheftReportAlreadyDisputed,
+        /// The challenge period of the theft report has ended
+        ChallengePeriodExpired,
+        /// The dispute context exceeds `MAX_DISPUTE_CONTEXT_LENGTH`
+        DisputeContextTooLong,
+        /// Arithmetic overflow
+        ArithmeticOverflow,
+        /// Arithmetic underflow
+        ArithmeticUnderflow,
     }
 }
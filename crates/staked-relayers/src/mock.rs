@@ -1,15 +1,20 @@
 use crate as staked_relayers;
-use crate::{Config, Error};
+use crate::{
+    header_source::{HeaderSource, HeaderSourceError},
+    Config, Error,
+};
+use bitcoin::types::{H256Le, RawBlockHeader};
 use frame_support::{parameter_types, traits::StorageMapShim, PalletId};
 use mocktopus::mocking::clear_mocks;
 use sp_arithmetic::{FixedI128, FixedPointNumber, FixedU128};
 use sp_core::H256;
 use sp_runtime::{
-    testing::{Header, TestXt},
-    traits::{BlakeTwo256, IdentityLookup},
+    testing::{Header, TestSignature, TestXt, UintAuthorityId},
+    traits::{BlakeTwo256, Extrinsic as ExtrinsicT, IdentityLookup},
 };
+use std::cell::RefCell;
 
-type TestExtrinsic = TestXt<Call, ()>;
+pub(crate) type TestExtrinsic = TestXt<Call, ()>;
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
 
@@ -183,6 +188,8 @@ impl pallet_timestamp::Config for Test {
 
 impl security::Config for Test {
     type Event = TestEvent;
+    type OnRecovery = ();
+    type PausableCalls = ();
 }
 
 parameter_types! {
@@ -197,6 +204,75 @@ where
     type Extrinsic = TestExtrinsic;
 }
 
+impl frame_system::offchain::SigningTypes for Test {
+    type Public = UintAuthorityId;
+    type Signature = TestSignature;
+}
+
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Test
+where
+    Call: From<LocalCall>,
+{
+    fn create_transaction<C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+        call: Call,
+        _public: UintAuthorityId,
+        _account: AccountId,
+        nonce: u64,
+    ) -> Option<(Call, <TestExtrinsic as ExtrinsicT>::SignaturePayload)> {
+        Some((call, (nonce, ())))
+    }
+}
+
+pub struct TestAuthId;
+
+impl frame_system::offchain::AppCrypto<UintAuthorityId, TestSignature> for TestAuthId {
+    type RuntimeAppPublic = UintAuthorityId;
+    type GenericSignature = TestSignature;
+    type GenericPublic = UintAuthorityId;
+}
+
+thread_local! {
+    /// Main chain of the stand-in header source, indexed by block height.
+    pub static SOURCE_CHAIN: RefCell<Vec<RawBlockHeader>> = RefCell::new(vec![]);
+}
+
+/// Serves block headers from [`SOURCE_CHAIN`] instead of an HTTP endpoint.
+pub struct TestHeaderSource;
+
+impl HeaderSource for TestHeaderSource {
+    fn get_best_block_height() -> Result<u32, HeaderSourceError> {
+        SOURCE_CHAIN.with(|chain| {
+            chain
+                .borrow()
+                .len()
+                .checked_sub(1)
+                .map(|height| height as u32)
+                .ok_or(HeaderSourceError::Disabled)
+        })
+    }
+
+    fn get_block_hash(block_height: u32) -> Result<H256Le, HeaderSourceError> {
+        SOURCE_CHAIN.with(|chain| {
+            chain
+                .borrow()
+                .get(block_height as usize)
+                .map(|header| header.hash())
+                .ok_or(HeaderSourceError::InvalidResponse)
+        })
+    }
+
+    fn get_raw_block_header(block_hash: H256Le) -> Result<RawBlockHeader, HeaderSourceError> {
+        SOURCE_CHAIN.with(|chain| {
+            chain
+                .borrow()
+                .iter()
+                .find(|header| header.hash() == block_hash)
+                .copied()
+                .ok_or(HeaderSourceError::InvalidResponse)
+        })
+    }
+}
+
 impl vault_registry::Config for Test {
     type PalletId = VaultPalletId;
     type Event = TestEvent;
@@ -249,6 +325,7 @@ impl refund::Config for Test {
 impl btc_relay::Config for Test {
     type Event = TestEvent;
     type WeightInfo = ();
+    type OnStaleBlock = ();
 }
 
 impl redeem::Config for Test {
@@ -271,11 +348,14 @@ impl nomination::Config for Test {
 impl Config for Test {
     type Event = TestEvent;
     type WeightInfo = ();
+    type AuthorityId = TestAuthId;
+    type HeaderSource = TestHeaderSource;
 }
 
 pub type TestEvent = Event;
 pub type TestError = Error<Test>;
 pub type RedeemError = redeem::Error<Test>;
+pub type CollateralError = currency::Error<Test, currency::Collateral>;
 
 pub const ALICE: AccountId = 1;
 pub const BOB: AccountId = 2;
@@ -342,6 +422,7 @@ where
     T: FnOnce(),
 {
     clear_mocks();
+    SOURCE_CHAIN.with(|chain| chain.borrow_mut().clear());
     ExtBuilder::build().execute_with(|| {
         System::set_block_number(1);
         Security::set_active_block_number(1);
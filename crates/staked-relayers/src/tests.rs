@@ -1,18 +1,33 @@
 extern crate hex;
-use crate::{ext, mock::*};
+use crate::{
+    ext,
+    header_source::{EsploraHeaderSource, HeaderSource, HeaderSourceError, ESPLORA_URL_KEY},
+    mock::*,
+    types::{MaintenanceTransaction, PendingTheftReport, StakedRelayer, TheftReportVerdict},
+};
 use bitcoin::{
     formatter::Formattable,
-    types::{H256Le, RawBlockHeader, TransactionBuilder, TransactionInputBuilder, TransactionOutput},
+    parser::parse_transaction,
+    types::{H256Le, RawBlockHeader, Transaction, TransactionBuilder, TransactionInputBuilder, TransactionOutput},
 };
-use btc_relay::{BtcAddress, BtcPublicKey, Error as BtcRelayError};
-use frame_support::{assert_err, assert_ok};
+use btc_relay::{BtcAddress, BtcPublicKey, Error as BtcRelayError, RichBlockHeader};
+use codec::Decode;
+use frame_support::{assert_err, assert_ok, dispatch::DispatchResult, StorageDoubleMap, StorageMap, StorageValue};
 use mocktopus::mocking::*;
 use redeem::types::{RedeemRequest, RedeemRequestStatus};
 use replace::types::{ReplaceRequest, ReplaceRequestStatus};
-use sp_arithmetic::FixedI128;
-use sp_core::{H160, H256};
+use security::{ErrorCode, StatusChangeOrigin, StatusCode};
+use sp_arithmetic::{FixedI128, FixedPointNumber, FixedU128};
+use sp_core::{
+    offchain::{
+        testing::{PendingRequest, TestOffchainExt, TestTransactionPoolExt},
+        OffchainExt, StorageKind, TransactionPoolExt,
+    },
+    H160, H256,
+};
+use sp_runtime::testing::UintAuthorityId;
 use std::{convert::TryInto, str::FromStr};
-use vault_registry::{Vault, VaultStatus, Wallet};
+use vault_registry::{types::CurrencySource, Vault, VaultStatus, Wallet, WalletMigration};
 
 type Event = crate::Event<Test>;
 
@@ -51,7 +66,7 @@ fn test_report_vault_passes_with_vault_transaction() {
         ext::vault_registry::get_active_vault_from_id::<Test>
             .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(vault, Some(btc_address)))));
         ext::btc_relay::verify_transaction_inclusion::<Test>.mock_safe(move |_, _| MockResult::Return(Ok(())));
-        ext::vault_registry::liquidate_theft_vault::<Test>.mock_safe(|_| MockResult::Return(Ok(())));
+        ext::vault_registry::liquidate_theft_vault::<Test>.mock_safe(|_| MockResult::Return(Ok(0)));
 
         assert_ok!(StakedRelayers::report_vault_theft(
             Origin::signed(ALICE),
@@ -89,6 +104,29 @@ fn test_report_vault_fails_with_non_vault_transaction() {
     })
 }
 
+#[test]
+fn test_is_transaction_invalid_checks_inputs_from_migration_targets() {
+    run_test(|| {
+        let raw_tx = "0100000001c15041a06deb6b3818b022fac558da4ce2097f0860c8f642105bbad9d29be02a010000006c493046022100cfd2a2d332b29adce119c55a9fadd3c073332024b7e272513e51623ca15993480221009b482d7f7b4d479aff62bdcdaea54667737d56f8d4d63dd03ec3ef651ed9a25401210325f8b039a11861659c9bf03f43fc4ea055f3a71cd60c7b1fd474ab578f9977faffffffff0290d94000000000001976a9148ed243a7be26080a1a8cf96b53270665f1b8dd2388ac4083086b000000001976a9147e7d94d0ddc21d83bfbcfc7798e4547edf0832aa88ac00000000";
+
+        let vault = CAROL;
+
+        let btc_address = BtcAddress::P2PKH(H160::from_slice(&[
+            125, 125, 148, 208, 221, 194, 29, 131, 191, 188, 252, 119, 152, 228, 84, 126, 223, 8, 50, 170,
+        ]));
+
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(vault, Some(btc_address)))));
+        // the input spends from the target of an expired migration, which is not registered
+        ext::vault_registry::is_reserved_by_vault::<Test>.mock_safe(|_, _| MockResult::Return(true));
+
+        assert_ok!(StakedRelayers::is_transaction_invalid(
+            &CAROL,
+            hex::decode(&raw_tx).unwrap()
+        ));
+    })
+}
+
 #[test]
 fn test_report_vault_succeeds_with_segwit_transaction() {
     run_test(|| {
@@ -102,7 +140,7 @@ fn test_report_vault_succeeds_with_segwit_transaction() {
         ext::vault_registry::get_active_vault_from_id::<Test>
             .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(vault, Some(btc_address)))));
         ext::btc_relay::verify_transaction_inclusion::<Test>.mock_safe(move |_, _| MockResult::Return(Ok(())));
-        ext::vault_registry::liquidate_theft_vault::<Test>.mock_safe(|_| MockResult::Return(Ok(())));
+        ext::vault_registry::liquidate_theft_vault::<Test>.mock_safe(|_| MockResult::Return(Ok(0)));
 
         assert_ok!(StakedRelayers::report_vault_theft(
             Origin::signed(ALICE),
@@ -113,222 +151,174 @@ fn test_report_vault_succeeds_with_segwit_transaction() {
     })
 }
 
-#[test]
-fn test_report_vault_theft_succeeds() {
-    run_test(|| {
-        let relayer = Origin::signed(ALICE);
+fn sample_vault_address() -> BtcAddress {
+    BtcAddress::P2PKH(H160::from_slice(&[
+        126, 125, 148, 208, 221, 194, 29, 131, 191, 188, 252, 119, 152, 228, 84, 126, 223, 8, 50, 170,
+    ]))
+}
 
-        ext::btc_relay::verify_transaction_inclusion::<Test>.mock_safe(move |_, _| MockResult::Return(Ok(())));
-        StakedRelayers::_is_parsed_transaction_invalid.mock_safe(move |_, _| MockResult::Return(Ok(())));
-        ext::vault_registry::liquidate_theft_vault::<Test>.mock_safe(move |_| MockResult::Return(Ok(())));
+/// Transaction spending from `sample_vault_address` with the given outputs.
+fn sample_vault_transaction(outputs: Vec<TransactionOutput>) -> Transaction {
+    let raw_tx = "0100000001c15041a06deb6b3818b022fac558da4ce2097f0860c8f642105bbad9d29be02a010000006c493046022100cfd2a2d332b29adce119c55a9fadd3c073332024b7e272513e51623ca15993480221009b482d7f7b4d479aff62bdcdaea54667737d56f8d4d63dd03ec3ef651ed9a25401210325f8b039a11861659c9bf03f43fc4ea055f3a71cd60c7b1fd474ab578f9977faffffffff0290d94000000000001976a9148ed243a7be26080a1a8cf96b53270665f1b8dd2388ac4083086b000000001976a9147e7d94d0ddc21d83bfbcfc7798e4547edf0832aa88ac00000000";
+    let vault_input = parse_transaction(&hex::decode(raw_tx).unwrap()).unwrap().inputs[0].clone();
 
-        let raw_proof = hex::decode("00000020ecf348128755dbeea5deb8eddf64566d9d4e59bc65d485000000000000000000901f0d92a66ee7dcefd02fa282ca63ce85288bab628253da31ef259b24abe8a0470a385a45960018e8d672f8a90a00000d0bdabada1fb6e3cef7f5c6e234621e3230a2f54efc1cba0b16375d9980ecbc023cbef3ba8d8632ea220927ec8f95190b30769eb35d87618f210382c9445f192504074f56951b772efa43b89320d9c430b0d156b93b7a1ff316471e715151a0619a39392657f25289eb713168818bd5b37476f1bc59b166deaa736d8a58756f9d7ce2aef46d8004c5fe3293d883838f87b5f1da03839878895b71530e9ff89338bb6d4578b3c3135ff3e8671f9a64d43b22e14c2893e8271cecd420f11d2359307403bb1f3128885b3912336045269ef909d64576b93e816fa522c8c027fe408700dd4bdee0254c069ccb728d3516fe1e27578b31d70695e3e35483da448f3a951273e018de7f2a8f657064b013c6ede75c74bbd7f98fdae1c2ac6789ee7b21a791aa29d60e89fff2d1d2b1ada50aa9f59f403823c8c58bb092dc58dc09b28158ca15447da9c3bedb0b160f3fe1668d5a27716e27661bcb75ddbf3468f5c76b7bed1004c6b4df4da2ce80b831a7c260b515e6355e1c306373d2233e8de6fda3674ed95d17a01a1f64b27ba88c3676024fbf8d5dd962ffc4d5e9f3b1700763ab88047f7d0000").unwrap();
-        let tx_bytes = hex::decode("0100000001c8cc2b56525e734ff63a13bc6ad06a9e5664df8c67632253a8e36017aee3ee40000000009000483045022100ad0851c69dd756b45190b5a8e97cb4ac3c2b0fa2f2aae23aed6ca97ab33bf88302200b248593abc1259512793e7dea61036c601775ebb23640a0120b0dba2c34b79001455141042f90074d7a5bf30c72cf3a8dfd1381bdbd30407010e878f3a11269d5f74a58788505cdca22ea6eab7cfb40dc0e07aba200424ab0d79122a653ad0c7ec9896bdf51aefeffffff0120f40e00000000001976a9141d30342095961d951d306845ef98ac08474b36a088aca7270400").unwrap();
+    let mut builder = TransactionBuilder::new();
+    builder.with_version(1).add_input(vault_input);
+    for output in outputs {
+        builder.add_output(output);
+    }
+    builder.build()
+}
 
-        assert_ok!(StakedRelayers::report_vault_theft(relayer, BOB, raw_proof, tx_bytes,));
-        // check that the event has been emitted
-        assert!(System::events()
-            .iter()
-            .any(|a| matches!(a.event, TestEvent::staked_relayers(Event::VaultTheft(id, _)) if id == BOB)));
-    })
+/// Transaction spending from `sample_vault_address` which references the request.
+fn sample_vault_payment(value: i64, request_id: H256) -> Transaction {
+    let recipient_address = BtcAddress::P2PKH(H160::from_str(&"5f69790b72c98041330644bbd50f2ebb5d073c36").unwrap());
+
+    sample_vault_transaction(vec![
+        TransactionOutput::payment(value, &recipient_address),
+        TransactionOutput::op_return(0, request_id.as_bytes()),
+    ])
+}
+
+/// Mocks a completed redeem request of `vault` for 100 satoshi to the recipient of `sample_vault_payment`.
+fn mock_completed_redeem_request(vault: AccountId) {
+    let recipient_address = BtcAddress::P2PKH(H160::from_str(&"5f69790b72c98041330644bbd50f2ebb5d073c36").unwrap());
+    ext::redeem::get_open_or_completed_redeem_request_from_id::<Test>.mock_safe(move |_| {
+        MockResult::Return(Ok(RedeemRequest {
+            period: 0,
+            vault,
+            opentime: 0,
+            fee: 0,
+            amount_btc: 100,
+            premium: 0,
+            redeemer: ALICE,
+            btc_address: recipient_address,
+            btc_height: 0,
+            status: RedeemRequestStatus::Completed,
+            transfer_fee_btc: 0,
+        }))
+    });
+}
+
+fn report_vault_double_payment(left: &Transaction, right: &Transaction) -> DispatchResult {
+    StakedRelayers::report_vault_double_payment(
+        Origin::signed(ALICE),
+        CAROL,
+        (vec![0u8; 32], vec![0u8; 32]),
+        (left.format(), right.format()),
+    )
 }
 
 #[test]
-fn test_is_valid_merge_transaction_fails() {
+fn test_report_vault_double_payment_succeeds() {
     run_test(|| {
-        let vault = BOB;
-        ext::vault_registry::get_active_vault_from_id::<Test>
-            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(vault, None))));
-
-        let address1 = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+        let request_id = H256::from_low_u64_be(1);
+        let left = sample_vault_payment(100, request_id);
+        let right = sample_vault_payment(200, request_id);
 
-        let address2 = BtcAddress::P2PKH(H160::from_str(&"5f69790b72c98041330644bbd50f2ebb5d073c36").unwrap());
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(CAROL, Some(sample_vault_address())))));
+        ext::btc_relay::verify_transaction_inclusion::<Test>.mock_safe(move |_, _| MockResult::Return(Ok(())));
+        ext::vault_registry::liquidate_theft_vault::<Test>.mock_safe(|_| MockResult::Return(Ok(0)));
+        mock_completed_redeem_request(CAROL);
 
-        assert_eq!(
-            StakedRelayers::is_valid_merge_transaction(&[(100, address1)], &[], &Wallet::new(dummy_public_key())),
-            false,
-            "payment to unknown recipient"
-        );
+        assert_ok!(report_vault_double_payment(&left, &right));
+        assert!(StakedRelayers::theft_report(left.tx_id()).contains(&CAROL));
+        assert!(StakedRelayers::theft_report(right.tx_id()).contains(&CAROL));
+        assert!(System::events().iter().any(|a| matches!(
+            a.event,
+            TestEvent::staked_relayers(Event::VaultDoublePayment(CAROL, id, _, _)) if id == request_id
+        )));
 
-        assert_eq!(
-            StakedRelayers::is_valid_merge_transaction(
-                &[(100, address2)],
-                &[(0, vec![])],
-                &Wallet::new(dummy_public_key())
-            ),
-            false,
-            "migration should not have op_returns"
+        assert_err!(
+            report_vault_double_payment(&left, &right),
+            TestError::VaultAlreadyReported
         );
     })
 }
 
 #[test]
-fn test_is_valid_merge_transaction_succeeds() {
+fn test_report_vault_double_payment_fails_with_same_transaction() {
     run_test(|| {
-        let vault = BOB;
-        ext::vault_registry::get_active_vault_from_id::<Test>
-            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(vault, None))));
-
-        let address = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
-
-        let mut wallet = Wallet::new(dummy_public_key());
-        wallet.add_btc_address(address);
-
-        assert_eq!(
-            StakedRelayers::is_valid_merge_transaction(&[(100, address)], &[], &wallet),
-            true
+        let transaction = sample_vault_payment(100, H256::from_low_u64_be(1));
+        assert_err!(
+            report_vault_double_payment(&transaction, &transaction),
+            TestError::DuplicateTransaction
         );
     })
 }
 
 #[test]
-fn test_is_valid_request_transaction_fails() {
+fn test_report_vault_double_payment_fails_with_different_requests() {
     run_test(|| {
-        let vault = BOB;
-        ext::vault_registry::get_active_vault_from_id::<Test>
-            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(vault, None))));
-
-        let address1 = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
-
-        let address2 = BtcAddress::P2PKH(H160::from_str(&"5f69790b72c98041330644bbd50f2ebb5d073c36").unwrap());
-
-        let mut wallet = Wallet::new(dummy_public_key());
-        wallet.add_btc_address(address2);
-
-        let actual_value: i32 = 50;
+        let left = sample_vault_payment(100, H256::from_low_u64_be(1));
+        let right = sample_vault_payment(100, H256::from_low_u64_be(2));
 
-        let request_value = 100;
-        let request_address = address1;
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(CAROL, Some(sample_vault_address())))));
+        ext::btc_relay::verify_transaction_inclusion::<Test>.mock_safe(move |_, _| MockResult::Return(Ok(())));
 
-        assert_eq!(
-            StakedRelayers::is_valid_request_transaction(
-                request_value,
-                request_address,
-                &[(actual_value.try_into().unwrap(), address1)],
-                &wallet
-            ),
-            false
+        assert_err!(
+            report_vault_double_payment(&left, &right),
+            TestError::DifferentRequestIds
         );
     })
 }
 
 #[test]
-fn test_is_valid_request_transaction_succeeds() {
+fn test_report_vault_double_payment_fails_for_request_of_other_vault() {
     run_test(|| {
-        let recipient_address = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
-
-        let vault_address = BtcAddress::P2PKH(H160::from_str(&"5f69790b72c98041330644bbd50f2ebb5d073c36").unwrap());
-
-        let request_value = 100;
-        let change_value = 50;
+        let request_id = H256::from_low_u64_be(1);
+        let left = sample_vault_payment(100, request_id);
+        let right = sample_vault_payment(200, request_id);
 
-        let mut wallet = Wallet::new(dummy_public_key());
-        wallet.add_btc_address(vault_address);
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(CAROL, Some(sample_vault_address())))));
+        ext::btc_relay::verify_transaction_inclusion::<Test>.mock_safe(move |_, _| MockResult::Return(Ok(())));
+        mock_completed_redeem_request(BOB);
 
-        assert_eq!(
-            StakedRelayers::is_valid_request_transaction(
-                request_value,
-                recipient_address,
-                &[
-                    (request_value.try_into().unwrap(), recipient_address),
-                    (change_value.try_into().unwrap(), vault_address)
-                ],
-                &wallet
-            ),
-            true
-        );
+        assert_err!(report_vault_double_payment(&left, &right), TestError::RequestNotFound);
     })
 }
 
 #[test]
-fn test_is_transaction_invalid_fails_with_valid_merge_transaction() {
+fn test_report_vault_double_payment_fails_without_payment_to_recipient() {
     run_test(|| {
-        let address = BtcAddress::P2PKH(H160::from_slice(&[
-            126, 125, 148, 208, 221, 194, 29, 131, 191, 188, 252, 119, 152, 228, 84, 126, 223, 8, 50, 170,
-        ]));
+        let request_id = H256::from_low_u64_be(1);
+        let left = sample_vault_payment(100, request_id);
+        let other_address = BtcAddress::P2PKH(H160::from_low_u64_be(42));
+        let right = sample_vault_transaction(vec![
+            TransactionOutput::payment(100, &other_address),
+            TransactionOutput::op_return(0, request_id.as_bytes()),
+        ]);
 
-        let mut wallet = Wallet::new(dummy_public_key());
-        wallet.add_btc_address(address);
-
-        ext::vault_registry::get_active_vault_from_id::<Test>.mock_safe(move |_| {
-            MockResult::Return(Ok(Vault {
-                id: BOB,
-                to_be_replaced_tokens: 0,
-                replace_collateral: 0,
-                to_be_issued_tokens: 0,
-                issued_tokens: 0,
-                to_be_redeemed_tokens: 0,
-                backing_collateral: 0,
-                wallet: wallet.clone(),
-                banned_until: None,
-                status: VaultStatus::Active(true),
-                ..Default::default()
-            }))
-        });
-
-        let transaction = TransactionBuilder::new()
-            .with_version(1)
-            .add_input(
-                TransactionInputBuilder::new()
-                    .with_coinbase(false)
-                    .with_sequence(4294967295)
-                    .with_previous_index(1)
-                    .with_previous_hash(H256Le::from_bytes_le(&[
-                        193, 80, 65, 160, 109, 235, 107, 56, 24, 176, 34, 250, 197, 88, 218, 76, 226, 9, 127, 8, 96,
-                        200, 246, 66, 16, 91, 186, 217, 210, 155, 224, 42,
-                    ]))
-                    .with_script(&[
-                        73, 48, 70, 2, 33, 0, 207, 210, 162, 211, 50, 178, 154, 220, 225, 25, 197, 90, 159, 173, 211,
-                        192, 115, 51, 32, 36, 183, 226, 114, 81, 62, 81, 98, 60, 161, 89, 147, 72, 2, 33, 0, 155, 72,
-                        45, 127, 123, 77, 71, 154, 255, 98, 189, 205, 174, 165, 70, 103, 115, 125, 86, 248, 212, 214,
-                        61, 208, 62, 195, 239, 101, 30, 217, 162, 84, 1, 33, 3, 37, 248, 176, 57, 161, 24, 97, 101,
-                        156, 155, 240, 63, 67, 252, 78, 160, 85, 243, 167, 28, 214, 12, 123, 31, 212, 116, 171, 87,
-                        143, 153, 119, 250,
-                    ])
-                    .build(),
-            )
-            .add_output(TransactionOutput::payment(100, &address))
-            .build();
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(CAROL, Some(sample_vault_address())))));
+        ext::btc_relay::verify_transaction_inclusion::<Test>.mock_safe(move |_, _| MockResult::Return(Ok(())));
+        mock_completed_redeem_request(CAROL);
 
         assert_err!(
-            StakedRelayers::is_transaction_invalid(&BOB, transaction.format()),
-            TestError::ValidMergeTransaction
+            report_vault_double_payment(&left, &right),
+            TestError::NoPaymentToRecipient
         );
     })
 }
 
 #[test]
-fn test_is_transaction_invalid_fails_with_valid_request_or_redeem() {
+fn test_is_transaction_invalid_for_second_payment_of_settled_request() {
     run_test(|| {
-        let vault_address = BtcAddress::P2WPKHv0(H160::from_slice(&[
-            164, 180, 202, 72, 222, 11, 63, 255, 193, 84, 4, 161, 172, 220, 141, 186, 174, 34, 105, 85,
-        ]));
-
-        let mut wallet = Wallet::new(dummy_public_key());
-        wallet.add_btc_address(vault_address);
-
+        let request_id = H256::from_low_u64_be(1);
+        let settling_tx = sample_vault_payment(100, request_id);
+        let settling_tx_id = settling_tx.tx_id();
+        let other_tx = sample_vault_payment(200, request_id);
         let recipient_address = BtcAddress::P2PKH(H160::from_str(&"5f69790b72c98041330644bbd50f2ebb5d073c36").unwrap());
 
-        ext::vault_registry::get_active_vault_from_id::<Test>.mock_safe(move |_| {
-            MockResult::Return(Ok(Vault {
-                id: BOB,
-                to_be_replaced_tokens: 0,
-                to_be_issued_tokens: 0,
-                issued_tokens: 0,
-                to_be_redeemed_tokens: 0,
-                replace_collateral: 0,
-                backing_collateral: 0,
-                wallet: wallet.clone(),
-                banned_until: None,
-                status: VaultStatus::Active(true),
-                ..Default::default()
-            }))
-        });
-
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(CAROL, Some(sample_vault_address())))));
         ext::redeem::get_open_or_completed_redeem_request_from_id::<Test>.mock_safe(move |_| {
             MockResult::Return(Ok(RedeemRequest {
                 period: 0,
-                vault: BOB,
+                vault: CAROL,
                 opentime: 0,
                 fee: 0,
                 amount_btc: 100,
@@ -336,195 +326,1905 @@ fn test_is_transaction_invalid_fails_with_valid_request_or_redeem() {
                 redeemer: ALICE,
                 btc_address: recipient_address,
                 btc_height: 0,
-                status: RedeemRequestStatus::Pending,
+                status: RedeemRequestStatus::Completed,
                 transfer_fee_btc: 0,
             }))
         });
-
-        let transaction = TransactionBuilder::new()
-            .with_version(1)
-            .add_input(
-                TransactionInputBuilder::new()
-                    .with_coinbase(false)
-                    .with_previous_index(1)
-                    .with_previous_hash(H256Le::from_hex_le(
-                        "40d43a99926d43eb0e619bf0b3d83b4a31f60c176beecfb9d35bf45e54d0f742",
-                    ))
-                    .with_sequence(4294967295)
-                    .with_script(&[
-                        22, 0, 20, 164, 180, 202, 72, 222, 11, 63, 255, 193, 84, 4, 161, 172, 220, 141, 186, 174, 34,
-                        105, 85,
-                    ])
-                    .add_witness(&[
-                        48, 69, 2, 33, 0, 134, 4, 239, 143, 109, 138, 250, 137, 45, 238, 15, 49, 37, 155, 108, 224, 45,
-                        215, 12, 84, 92, 252, 254, 216, 20, 129, 121, 151, 24, 118, 197, 74, 2, 32, 118, 215, 113, 214,
-                        233, 27, 237, 33, 39, 131, 201, 176, 110, 13, 230, 0, 250, 178, 213, 24, 250, 214, 241, 90, 43,
-                        25, 29, 127, 189, 38, 42, 62, 1,
-                    ])
-                    .add_witness(&[
-                        3, 157, 37, 171, 121, 244, 31, 117, 206, 175, 136, 36, 17, 253, 65, 250, 103, 10, 76, 103, 44,
-                        35, 255, 175, 14, 54, 26, 150, 156, 222, 6, 146, 232,
-                    ])
-                    .build(),
-            )
-            .add_output(TransactionOutput::payment(100, &recipient_address))
-            .add_output(TransactionOutput::op_return(0, &H256::from_slice(&[0; 32]).as_bytes()))
-            .build();
+        ext::btc_relay::get_settling_transaction::<Test>.mock_safe(move |_| MockResult::Return(Some(settling_tx_id)));
 
         assert_err!(
-            StakedRelayers::is_transaction_invalid(&BOB, transaction.format()),
+            StakedRelayers::is_transaction_invalid(&CAROL, settling_tx.format()),
             TestError::ValidRedeemTransaction
         );
-
-        ext::redeem::get_open_or_completed_redeem_request_from_id::<Test>
-            .mock_safe(move |_| MockResult::Return(Err(RedeemError::RedeemIdNotFound.into())));
-
-        ext::replace::get_open_or_completed_replace_request::<Test>.mock_safe(move |_| {
-            MockResult::Return(Ok(ReplaceRequest {
-                period: 0,
-                old_vault: BOB,
-                amount: 100,
-                griefing_collateral: 0,
-                new_vault: ALICE,
-                collateral: 0,
-                accept_time: 1,
-                btc_address: recipient_address,
-                btc_height: 0,
-                status: ReplaceRequestStatus::Pending,
-            }))
-        });
-
-        assert_err!(
-            StakedRelayers::is_transaction_invalid(&BOB, transaction.format()),
-            TestError::ValidReplaceTransaction
-        );
+        // the request was already settled, so another payment for it is theft
+        assert_ok!(StakedRelayers::is_transaction_invalid(&CAROL, other_tx.format()));
     })
 }
 
-#[test]
-fn test_is_transaction_invalid_succeeds() {
-    run_test(|| {
-        let vault_address = BtcAddress::P2PKH(H160::from_slice(&[
-            126, 125, 148, 208, 221, 194, 29, 131, 191, 188, 252, 119, 152, 228, 84, 126, 223, 8, 50, 170,
-        ]));
+fn sample_batch_recipient(request_id: &H256) -> BtcAddress {
+    BtcAddress::P2PKH(H160::from_low_u64_be(request_id.to_low_u64_be()))
+}
 
-        let recipient_address = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+/// Mocks a redeem request of `vault` for 100 satoshi to `sample_batch_recipient` for every id.
+fn mock_batch_redeem_requests(vault: AccountId) {
+    ext::redeem::get_open_or_completed_redeem_request_from_id::<Test>.mock_safe(move |request_id| {
+        MockResult::Return(Ok(RedeemRequest {
+            period: 0,
+            vault,
+            opentime: 0,
+            fee: 0,
+            amount_btc: 100,
+            premium: 0,
+            redeemer: ALICE,
+            btc_address: sample_batch_recipient(request_id),
+            btc_height: 0,
+            status: RedeemRequestStatus::Pending,
+            transfer_fee_btc: 0,
+        }))
+    });
+}
 
+/// Transaction spending from `sample_vault_address` which pays the batch of requests.
+fn sample_vault_batch_payment(values: &[i64], request_ids: &[H256]) -> Transaction {
+    let op_return = btc_relay::Pallet::<Test>::compute_batch_op_return(request_ids).unwrap();
+    let mut outputs = values
+        .iter()
+        .zip(request_ids.iter())
+        .map(|(value, request_id)| TransactionOutput::payment(*value, &sample_batch_recipient(request_id)))
+        .collect::<Vec<_>>();
+    outputs.push(TransactionOutput::op_return(0, op_return.as_bytes()));
+    sample_vault_transaction(outputs)
+}
+
+#[test]
+fn test_report_vault_theft_fails_with_declared_batch_payment() {
+    run_test(|| {
+        let request_ids = vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)];
+        let transaction = sample_vault_batch_payment(&[100, 100], &request_ids);
+
+        mock_batch_redeem_requests(CAROL);
         ext::vault_registry::get_active_vault_from_id::<Test>
-            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(BOB, Some(vault_address)))));
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(CAROL, Some(sample_vault_address())))));
+        ext::btc_relay::verify_transaction_inclusion::<Test>.mock_safe(move |_, _| MockResult::Return(Ok(())));
 
-        let transaction = TransactionBuilder::new()
-            .with_version(1)
-            .add_input(
-                TransactionInputBuilder::new()
-                    .with_coinbase(false)
-                    .with_sequence(4294967295)
-                    .with_previous_index(1)
-                    .with_previous_hash(H256Le::from_bytes_le(&[
-                        193, 80, 65, 160, 109, 235, 107, 56, 24, 176, 34, 250, 197, 88, 218, 76, 226, 9, 127, 8, 96,
-                        200, 246, 66, 16, 91, 186, 217, 210, 155, 224, 42,
-                    ]))
-                    .with_script(&[
-                        73, 48, 70, 2, 33, 0, 207, 210, 162, 211, 50, 178, 154, 220, 225, 25, 197, 90, 159, 173, 211,
-                        192, 115, 51, 32, 36, 183, 226, 114, 81, 62, 81, 98, 60, 161, 89, 147, 72, 2, 33, 0, 155, 72,
-                        45, 127, 123, 77, 71, 154, 255, 98, 189, 205, 174, 165, 70, 103, 115, 125, 86, 248, 212, 214,
-                        61, 208, 62, 195, 239, 101, 30, 217, 162, 84, 1, 33, 3, 37, 248, 176, 57, 161, 24, 97, 101,
-                        156, 155, 240, 63, 67, 252, 78, 160, 85, 243, 167, 28, 214, 12, 123, 31, 212, 116, 171, 87,
-                        143, 153, 119, 250,
-                    ])
-                    .build(),
-            )
-            .add_output(TransactionOutput::payment(100, &recipient_address))
-            .build();
+        assert_ok!(StakedRelayers::declare_batch_payment(
+            Origin::signed(CAROL),
+            request_ids
+        ));
 
-        assert_ok!(StakedRelayers::is_transaction_invalid(&BOB, transaction.format()));
+        // an honest batch payment cannot be reported
+        assert_err!(
+            StakedRelayers::report_vault_theft(Origin::signed(ALICE), CAROL, vec![0u8; 32], transaction.format()),
+            TestError::ValidBatchTransaction
+        );
     })
 }
 
 #[test]
-fn test_is_transaction_invalid_fails_with_valid_merge_testnet_transaction() {
+fn test_is_transaction_invalid_succeeds_with_insufficient_batch_payment() {
     run_test(|| {
-        // bitcoin-cli -testnet getrawtransaction "3453e52ebab8ac96159d6b19114b492a05cce05a8fdfdaf5dea266ac10601ce4" 0
-        // "00000000000000398849cc9d67261ec2d5fea07db87ab66a8ea47bc05acfb194"
-        let raw_tx_hex = "0200000000010108ce8e8943edbbf09d070bb893e09c0de12c0cf3704fe8a9b0f8b8d1a4a7a4760000000017160014473ca3f4d726ce9c21af7cdc3fcc13264f681b04feffffff02b377413f0000000017a914fe5183ccb89d98beaa6908c7cf1bd109029482cf87142e1a00000000001976a914d0a46d39dafa3012c2a7ed4d82d644b428e4586b88ac02473044022069484377c6627ccca566d4c4ac2cb84d1b0662f5ffbd384815c5e98b072759fc022061de3b77b4543ef43bb969d3f97fbbbdcddc008438720e7026181d99c455b2410121034172c29d3da8279f71adda48db8281d65b794e73cf04ea91fac4293030f0fe91a3ee1c00";
-        let raw_tx = hex::decode(&raw_tx_hex).unwrap();
+        let request_ids = vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)];
 
-        // 2MsqorfMrsvXiVM8pD9bPWxGnccSWsj16XE (P2WPKH-P2SH)
-        let vault_btc_address_0 = BtcAddress::P2WPKHv0(H160::from_slice(
-            &hex::decode("473ca3f4d726ce9c21af7cdc3fcc13264f681b04").unwrap(),
+        mock_batch_redeem_requests(CAROL);
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(CAROL, Some(sample_vault_address())))));
+
+        assert_ok!(StakedRelayers::declare_batch_payment(
+            Origin::signed(CAROL),
+            request_ids.clone()
         ));
 
-        // 2NGRwGkzypA4fEz9m4KhA2ZBs7fTg3B7Zjo
-        let vault_btc_address_1 = BtcAddress::P2SH(H160::from_slice(
-            &hex::decode("fe5183ccb89d98beaa6908c7cf1bd109029482cf").unwrap(),
+        let transaction = sample_vault_batch_payment(&[100, 50], &request_ids);
+        assert_ok!(StakedRelayers::is_transaction_invalid(&CAROL, transaction.format()));
+
+        // an undeclared batch references no request
+        let other_request_ids = vec![H256::from_low_u64_be(3), H256::from_low_u64_be(4)];
+        let transaction = sample_vault_batch_payment(&[100, 100], &other_request_ids);
+        assert_ok!(StakedRelayers::is_transaction_invalid(&CAROL, transaction.format()));
+    })
+}
+
+#[test]
+fn test_declare_batch_payment_fails_with_request_of_other_vault() {
+    run_test(|| {
+        mock_batch_redeem_requests(BOB);
+
+        assert_err!(
+            StakedRelayers::declare_batch_payment(
+                Origin::signed(CAROL),
+                vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)]
+            ),
+            TestError::InvalidBatchPayment
+        );
+    })
+}
+
+#[test]
+fn test_declare_batch_payment_fails_with_too_many_requests() {
+    run_test(|| {
+        StakedRelayers::get_request_payment.mock_safe(|_, _| panic!("requests fetched before the length check"));
+        let request_ids = (0..=btc_relay::MAX_BATCH_PAYMENTS as u64)
+            .map(H256::from_low_u64_be)
+            .collect();
+
+        assert_err!(
+            StakedRelayers::declare_batch_payment(Origin::signed(CAROL), request_ids),
+            TestError::InvalidBatchPayment
+        );
+    })
+}
+
+#[test]
+fn test_report_vault_theft_succeeds() {
+    run_test(|| {
+        let relayer = Origin::signed(ALICE);
+
+        ext::btc_relay::verify_transaction_inclusion::<Test>.mock_safe(move |_, _| MockResult::Return(Ok(())));
+        StakedRelayers::_is_parsed_transaction_invalid.mock_safe(move |_, _| MockResult::Return(Ok(())));
+        ext::vault_registry::liquidate_theft_vault::<Test>.mock_safe(move |_| MockResult::Return(Ok(0)));
+
+        let raw_proof = hex::decode("00000020ecf348128755dbeea5deb8eddf64566d9d4e59bc65d485000000000000000000901f0d92a66ee7dcefd02fa282ca63ce85288bab628253da31ef259b24abe8a0470a385a45960018e8d672f8a90a00000d0bdabada1fb6e3cef7f5c6e234621e3230a2f54efc1cba0b16375d9980ecbc023cbef3ba8d8632ea220927ec8f95190b30769eb35d87618f210382c9445f192504074f56951b772efa43b89320d9c430b0d156b93b7a1ff316471e715151a0619a39392657f25289eb713168818bd5b37476f1bc59b166deaa736d8a58756f9d7ce2aef46d8004c5fe3293d883838f87b5f1da03839878895b71530e9ff89338bb6d4578b3c3135ff3e8671f9a64d43b22e14c2893e8271cecd420f11d2359307403bb1f3128885b3912336045269ef909d64576b93e816fa522c8c027fe408700dd4bdee0254c069ccb728d3516fe1e27578b31d70695e3e35483da448f3a951273e018de7f2a8f657064b013c6ede75c74bbd7f98fdae1c2ac6789ee7b21a791aa29d60e89fff2d1d2b1ada50aa9f59f403823c8c58bb092dc58dc09b28158ca15447da9c3bedb0b160f3fe1668d5a27716e27661bcb75ddbf3468f5c76b7bed1004c6b4df4da2ce80b831a7c260b515e6355e1c306373d2233e8de6fda3674ed95d17a01a1f64b27ba88c3676024fbf8d5dd962ffc4d5e9f3b1700763ab88047f7d0000").unwrap();
+        let tx_bytes = hex::decode("0100000001c8cc2b56525e734ff63a13bc6ad06a9e5664df8c67632253a8e36017aee3ee40000000009000483045022100ad0851c69dd756b45190b5a8e97cb4ac3c2b0fa2f2aae23aed6ca97ab33bf88302200b248593abc1259512793e7dea61036c601775ebb23640a0120b0dba2c34b79001455141042f90074d7a5bf30c72cf3a8dfd1381bdbd30407010e878f3a11269d5f74a58788505cdca22ea6eab7cfb40dc0e07aba200424ab0d79122a653ad0c7ec9896bdf51aefeffffff0120f40e00000000001976a9141d30342095961d951d306845ef98ac08474b36a088aca7270400").unwrap();
+
+        assert_ok!(StakedRelayers::report_vault_theft(relayer, BOB, raw_proof, tx_bytes,));
+        // check that the event has been emitted
+        assert!(System::events()
+            .iter()
+            .any(|a| matches!(a.event, TestEvent::staked_relayers(Event::VaultTheft(id, _)) if id == BOB)));
+    })
+}
+
+#[test]
+fn test_report_vault_theft_pays_capped_bounty() {
+    run_test(|| {
+        crate::TheftReportBounty::<Test>::put(FixedU128::checked_from_rational(10, 100).unwrap());
+        crate::MaxTheftReportBounty::<Test>::put(100);
+
+        ext::btc_relay::verify_transaction_inclusion::<Test>.mock_safe(move |_, _| MockResult::Return(Ok(())));
+        StakedRelayers::_is_parsed_transaction_invalid.mock_safe(move |_, _| MockResult::Return(Ok(())));
+        ext::vault_registry::liquidate_theft_vault::<Test>.mock_safe(move |_| MockResult::Return(Ok(2000)));
+        ext::vault_registry::transfer_funds::<Test>.mock_safe(|from, to, amount| {
+            assert!(matches!(from, CurrencySource::LiquidationVault));
+            assert!(matches!(to, CurrencySource::FreeBalance(ALICE)));
+            assert_eq!(amount, 100);
+            MockResult::Return(Ok(()))
+        });
+
+        let transaction = sample_vault_payment(100, H256::zero());
+        assert_ok!(StakedRelayers::report_vault_theft(
+            Origin::signed(ALICE),
+            BOB,
+            vec![0u8; 32],
+            transaction.format()
         ));
+        assert!(System::events().iter().any(|a| matches!(
+            a.event,
+            TestEvent::staked_relayers(Event::PayTheftReportBounty(ALICE, BOB, 100))
+        )));
+    })
+}
 
-        // mzY9pX6NA3cBmiC4umbBzf1NdwrmjS7MS8
-        let vault_btc_address_2 = BtcAddress::P2PKH(H160::from_slice(
-            &hex::decode("d0a46d39dafa3012c2a7ed4d82d644b428e4586b").unwrap(),
+#[test]
+fn test_report_vault_thefts_succeeds() {
+    run_test(|| {
+        ext::btc_relay::verify_transaction_inclusion::<Test>.mock_safe(move |_, _| MockResult::Return(Ok(())));
+        StakedRelayers::_is_parsed_transaction_invalid.mock_safe(move |_, _| MockResult::Return(Ok(())));
+        ext::vault_registry::liquidate_theft_vault::<Test>.mock_safe(move |_| MockResult::Return(Ok(0)));
+
+        let raw_tx = sample_vault_payment(100, H256::zero()).format();
+        let tx_id = parse_transaction(&raw_tx).unwrap().tx_id();
+        assert_ok!(StakedRelayers::report_vault_thefts(
+            Origin::signed(ALICE),
+            vec![(BOB, vec![0u8; 32], raw_tx.clone()), (CAROL, vec![0u8; 32], raw_tx)]
         ));
 
-        let mut wallet = Wallet::new(dummy_public_key());
-        wallet.add_btc_address(vault_btc_address_0);
-        wallet.add_btc_address(vault_btc_address_1);
-        wallet.add_btc_address(vault_btc_address_2);
+        assert!(StakedRelayers::theft_report(tx_id).contains(&BOB));
+        assert!(StakedRelayers::theft_report(tx_id).contains(&CAROL));
+        assert_eq!(
+            System::events()
+                .iter()
+                .filter(|a| matches!(a.event, TestEvent::staked_relayers(Event::VaultTheft(_, id)) if id == tx_id))
+                .count(),
+            2
+        );
+    })
+}
 
-        ext::vault_registry::get_active_vault_from_id::<Test>.mock_safe(move |_| {
-            MockResult::Return(Ok(Vault {
-                id: BOB,
-                to_be_replaced_tokens: 0,
-                replace_collateral: 0,
-                to_be_issued_tokens: 0,
-                issued_tokens: 0,
-                to_be_redeemed_tokens: 0,
-                backing_collateral: 0,
-                wallet: wallet.clone(),
-                banned_until: None,
-                status: VaultStatus::Active(true),
-                ..Default::default()
-            }))
+#[test]
+fn test_report_vault_thefts_skips_invalid_reports() {
+    run_test(|| {
+        ext::btc_relay::verify_transaction_inclusion::<Test>.mock_safe(move |_, _| MockResult::Return(Ok(())));
+        StakedRelayers::_is_parsed_transaction_invalid.mock_safe(move |vault_id, _| {
+            MockResult::Return(if *vault_id == CAROL {
+                Err(TestError::ValidRedeemTransaction.into())
+            } else {
+                Ok(())
+            })
         });
+        ext::vault_registry::liquidate_theft_vault::<Test>.mock_safe(move |_| MockResult::Return(Ok(0)));
+
+        let raw_tx = sample_vault_payment(100, H256::zero()).format();
+        let tx_id = parse_transaction(&raw_tx).unwrap().tx_id();
+        assert_ok!(StakedRelayers::report_vault_thefts(
+            Origin::signed(ALICE),
+            vec![
+                (CAROL, vec![0u8; 32], raw_tx.clone()),
+                (BOB, vec![0u8; 32], vec![0u8; 4]),
+                (BOB, vec![0u8; 32], raw_tx)
+            ]
+        ));
 
+        assert!(StakedRelayers::theft_report(tx_id).contains(&BOB));
+        assert!(!StakedRelayers::theft_report(tx_id).contains(&CAROL));
+        assert!(System::events().iter().any(|a| matches!(
+            a.event,
+            TestEvent::staked_relayers(Event::SkipTheftReport(CAROL, TheftReportVerdict::ValidRedeem))
+        )));
+        assert!(System::events().iter().any(|a| matches!(
+            a.event,
+            TestEvent::staked_relayers(Event::SkipTheftReport(BOB, TheftReportVerdict::InvalidTransaction))
+        )));
+    })
+}
+
+#[test]
+fn test_report_vault_theft_of_valid_transaction_decreases_bonded_reporter_sla() {
+    run_test(|| {
+        register_staked_relayer(ALICE, 150);
+        set_relayer_sla_change(ext::sla::RelayerEvent::FailedTheftReport, -1);
+        increase_relayer_sla(ALICE, 2);
+
+        ext::btc_relay::verify_transaction_inclusion::<Test>.mock_safe(move |_, _| MockResult::Return(Ok(())));
+        StakedRelayers::_is_parsed_transaction_invalid
+            .mock_safe(move |_, _| MockResult::Return(Err(TestError::ValidRedeemTransaction.into())));
+        ext::vault_registry::liquidate_theft_vault::<Test>.mock_safe(|_| panic!("vault liquidated for false report"));
+
+        // without a challenge period the report is rejected right away
+        let raw_tx = sample_vault_payment(100, H256::zero()).format();
+        assert_ok!(StakedRelayers::report_vault_theft(
+            Origin::signed(ALICE),
+            BOB,
+            vec![0u8; 32],
+            raw_tx.clone()
+        ));
+        assert_eq!(Sla::relayer_sla(ALICE), FixedI128::from(1));
+        assert!(System::events().iter().any(|a| matches!(
+            a.event,
+            TestEvent::staked_relayers(Event::FalseTheftReport(BOB, ALICE, TheftReportVerdict::ValidRedeem))
+        )));
+
+        // reporters which are not bonded have no SLA to decrease
         assert_err!(
-            StakedRelayers::is_transaction_invalid(&BOB, raw_tx),
-            TestError::ValidMergeTransaction
+            StakedRelayers::report_vault_theft(Origin::signed(CAROL), BOB, vec![0u8; 32], raw_tx),
+            TestError::ValidRedeemTransaction
         );
     })
 }
 
 #[test]
-fn test_is_transaction_invalid_succeeds_with_testnet_transaction() {
+fn test_report_vault_thefts_fails_with_too_many_reports() {
     run_test(|| {
-        // bitcoin-cli -testnet getrawtransaction "3453e52ebab8ac96159d6b19114b492a05cce05a8fdfdaf5dea266ac10601ce4" 0
-        // "00000000000000398849cc9d67261ec2d5fea07db87ab66a8ea47bc05acfb194"
-        let raw_tx_hex = "0200000000010108ce8e8943edbbf09d070bb893e09c0de12c0cf3704fe8a9b0f8b8d1a4a7a4760000000017160014473ca3f4d726ce9c21af7cdc3fcc13264f681b04feffffff02b377413f0000000017a914fe5183ccb89d98beaa6908c7cf1bd109029482cf87142e1a00000000001976a914d0a46d39dafa3012c2a7ed4d82d644b428e4586b88ac02473044022069484377c6627ccca566d4c4ac2cb84d1b0662f5ffbd384815c5e98b072759fc022061de3b77b4543ef43bb969d3f97fbbbdcddc008438720e7026181d99c455b2410121034172c29d3da8279f71adda48db8281d65b794e73cf04ea91fac4293030f0fe91a3ee1c00";
-        let raw_tx = hex::decode(&raw_tx_hex).unwrap();
+        let raw_tx = sample_vault_payment(100, H256::zero()).format();
+        let reports = vec![(BOB, vec![0u8; 32], raw_tx); crate::MAX_THEFT_REPORTS_PER_BATCH as usize + 1];
 
-        // 2MsqorfMrsvXiVM8pD9bPWxGnccSWsj16XE (P2WPKH-P2SH)
-        let btc_address = BtcAddress::P2WPKHv0(H160::from_slice(
-            &hex::decode("473ca3f4d726ce9c21af7cdc3fcc13264f681b04").unwrap(),
+        assert_err!(
+            StakedRelayers::report_vault_thefts(Origin::signed(ALICE), reports),
+            TestError::TooManyTheftReports
+        );
+    })
+}
+
+fn report_vault_theft_with_challenge_period() {
+    crate::TheftChallengePeriod::<Test>::put(10);
+    crate::TheftReportBond::<Test>::put(10);
+    crate::DisputeBond::<Test>::put(5);
+    crate::DisputeResolutionPeriod::<Test>::put(20);
+
+    ext::btc_relay::verify_transaction_inclusion::<Test>.mock_safe(move |_, _| MockResult::Return(Ok(())));
+    StakedRelayers::_is_parsed_transaction_invalid.mock_safe(move |_, _| MockResult::Return(Ok(())));
+    ext::vault_registry::freeze_vault::<Test>.mock_safe(|_| MockResult::Return(Ok(())));
+
+    let raw_tx = sample_vault_payment(100, H256::zero()).format();
+    assert_ok!(StakedRelayers::report_vault_theft(
+        Origin::signed(ALICE),
+        BOB,
+        vec![0u8; 32],
+        raw_tx
+    ));
+}
+
+#[test]
+fn test_report_vault_theft_with_challenge_period_freezes_vault() {
+    run_test(|| {
+        ext::vault_registry::liquidate_theft_vault::<Test>.mock_safe(|_| panic!("vault liquidated during challenge"));
+        report_vault_theft_with_challenge_period();
+
+        assert_eq!(CollateralCurrency::get_reserved_balance(&ALICE), 10);
+        assert_eq!(
+            StakedRelayers::pending_theft_report(BOB),
+            Some(PendingTheftReport {
+                reporter: ALICE,
+                tx_ids: vec![sample_vault_payment(100, H256::zero()).tx_id()],
+                bond: 10,
+                dispute_bond: 0,
+                end: 11,
+                disputed: false,
+            })
+        );
+        assert!(System::events().iter().any(|a| matches!(
+            a.event,
+            TestEvent::staked_relayers(Event::OpenTheftChallenge(BOB, ALICE, 11))
+        )));
+
+        let raw_tx = sample_vault_payment(200, H256::zero()).format();
+        assert_err!(
+            StakedRelayers::report_vault_theft(Origin::signed(CAROL), BOB, vec![0u8; 32], raw_tx),
+            TestError::TheftReportPending
+        );
+    })
+}
+
+#[test]
+fn test_decide_expired_theft_reports_liquidates_undisputed_vault() {
+    run_test(|| {
+        report_vault_theft_with_challenge_period();
+
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(|_| MockResult::Return(Ok(init_zero_vault(BOB, None))));
+        ext::vault_registry::liquidate_theft_vault::<Test>.mock_safe(|_| MockResult::Return(Ok(0)));
+
+        Security::set_active_block_number(10);
+        StakedRelayers::decide_expired_theft_reports();
+        assert!(StakedRelayers::pending_theft_report(BOB).is_some());
+
+        Security::set_active_block_number(11);
+        StakedRelayers::decide_expired_theft_reports();
+        assert_eq!(StakedRelayers::pending_theft_report(BOB), None);
+        assert_eq!(CollateralCurrency::get_reserved_balance(&ALICE), 0);
+        assert_eq!(CollateralCurrency::get_free_balance(&ALICE), ALICE_BALANCE);
+        assert!(System::events().iter().any(|a| matches!(
+            a.event,
+            TestEvent::staked_relayers(Event::ConfirmTheftReport(BOB, ALICE))
+        )));
+    })
+}
+
+#[test]
+fn test_dispute_theft_report_prevents_liquidation() {
+    run_test(|| {
+        ext::vault_registry::liquidate_theft_vault::<Test>.mock_safe(|_| panic!("disputed vault liquidated"));
+        report_vault_theft_with_challenge_period();
+
+        assert_ok!(StakedRelayers::dispute_theft_report(
+            Origin::signed(BOB),
+            b"consolidation".to_vec()
         ));
+        assert!(StakedRelayers::pending_theft_report(BOB).unwrap().disputed);
+        assert_eq!(StakedRelayers::pending_theft_report(BOB).unwrap().dispute_bond, 5);
+        assert_eq!(CollateralCurrency::get_reserved_balance(&BOB), 5);
+        assert_err!(
+            StakedRelayers::dispute_theft_report(Origin::signed(BOB), vec![]),
+            TestError::TheftReportAlreadyDisputed
+        );
+
+        Security::set_active_block_number(11);
+        StakedRelayers::decide_expired_theft_reports();
+        assert!(StakedRelayers::pending_theft_report(BOB).is_some());
+    })
+}
+
+#[test]
+fn test_decide_expired_theft_reports_confirms_undecided_dispute() {
+    run_test(|| {
+        report_vault_theft_with_challenge_period();
+        assert_ok!(StakedRelayers::dispute_theft_report(Origin::signed(BOB), vec![]));
+        assert_eq!(StakedRelayers::pending_theft_report(BOB).unwrap().end, 21);
 
         ext::vault_registry::get_active_vault_from_id::<Test>
-            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(BOB, Some(btc_address)))));
+            .mock_safe(|_| MockResult::Return(Ok(init_zero_vault(BOB, None))));
+        ext::vault_registry::liquidate_theft_vault::<Test>.mock_safe(|_| MockResult::Return(Ok(0)));
+
+        Security::set_active_block_number(20);
+        StakedRelayers::decide_expired_theft_reports();
+        assert!(StakedRelayers::pending_theft_report(BOB).is_some());
+
+        Security::set_active_block_number(21);
+        StakedRelayers::decide_expired_theft_reports();
+        assert_eq!(StakedRelayers::pending_theft_report(BOB), None);
+        assert_eq!(CollateralCurrency::get_reserved_balance(&ALICE), 0);
+        assert_eq!(CollateralCurrency::get_free_balance(&ALICE), ALICE_BALANCE + 5);
+        assert_eq!(CollateralCurrency::get_reserved_balance(&BOB), 0);
+        assert_eq!(CollateralCurrency::get_free_balance(&BOB), BOB_BALANCE - 5);
+        assert!(System::events().iter().any(|a| matches!(
+            a.event,
+            TestEvent::staked_relayers(Event::ConfirmTheftReport(BOB, ALICE))
+        )));
+    })
+}
 
-        assert_ok!(StakedRelayers::is_transaction_invalid(&BOB, raw_tx));
+#[test]
+fn test_dispute_theft_report_fails_without_dispute_bond() {
+    run_test(|| {
+        report_vault_theft_with_challenge_period();
+        crate::DisputeBond::<Test>::put(BOB_BALANCE + 1);
+
+        assert_err!(
+            StakedRelayers::dispute_theft_report(Origin::signed(BOB), vec![]),
+            CollateralError::InsufficientFreeBalance
+        );
+        assert!(!StakedRelayers::pending_theft_report(BOB).unwrap().disputed);
     })
 }
 
 #[test]
-fn test_store_block_header_and_update_sla_fails_with_invalid() {
+fn test_decide_expired_theft_reports_resets_cursor_without_pending_reports() {
     run_test(|| {
-        ext::btc_relay::store_block_header::<Test>
-            .mock_safe(|_, _| MockResult::Return(Err(BtcRelayError::<Test>::DiffTargetHeader.into())));
+        report_vault_theft_with_challenge_period();
+        assert_ok!(StakedRelayers::resolve_theft_report(Origin::root(), BOB, false));
+        assert_eq!(crate::NextTheftChallengeEnd::<Test>::get(), Some(11));
 
-        ext::sla::event_update_relayer_sla::<Test>.mock_safe(|_, _| {
-            panic!("Should not call sla update for invalid block");
-        });
+        StakedRelayers::decide_expired_theft_reports();
+        assert_eq!(crate::NextTheftChallengeEnd::<Test>::get(), None);
+    })
+}
+
+#[test]
+fn test_dispute_theft_report_fails_after_challenge_period() {
+    run_test(|| {
+        report_vault_theft_with_challenge_period();
 
+        Security::set_active_block_number(11);
         assert_err!(
-            StakedRelayers::store_block_header_and_update_sla(&0, RawBlockHeader::default()),
-            BtcRelayError::<Test>::DiffTargetHeader
+            StakedRelayers::dispute_theft_report(Origin::signed(BOB), vec![]),
+            TestError::ChallengePeriodExpired
+        );
+        assert_err!(
+            StakedRelayers::dispute_theft_report(Origin::signed(CAROL), vec![]),
+            TestError::TheftReportNotFound
         );
     })
 }
+
+#[test]
+fn test_resolve_theft_report_rejection_forfeits_bond() {
+    run_test(|| {
+        report_vault_theft_with_challenge_period();
+        assert_ok!(StakedRelayers::dispute_theft_report(Origin::signed(BOB), vec![]));
+
+        assert_ok!(StakedRelayers::resolve_theft_report(Origin::root(), BOB, false));
+        assert_eq!(StakedRelayers::pending_theft_report(BOB), None);
+        assert_eq!(CollateralCurrency::get_reserved_balance(&ALICE), 0);
+        assert_eq!(CollateralCurrency::get_free_balance(&ALICE), ALICE_BALANCE - 10);
+        assert_eq!(CollateralCurrency::get_reserved_balance(&BOB), 0);
+        assert_eq!(CollateralCurrency::get_free_balance(&BOB), BOB_BALANCE + 10);
+        assert!(System::events().iter().any(|a| matches!(
+            a.event,
+            TestEvent::staked_relayers(Event::RejectTheftReport(BOB, ALICE, 10))
+        )));
+    })
+}
+
+#[test]
+fn test_resolve_theft_report_rejection_decreases_reporter_sla() {
+    run_test(|| {
+        register_staked_relayer(ALICE, 150);
+        set_relayer_sla_change(ext::sla::RelayerEvent::FailedTheftReport, -1);
+        increase_relayer_sla(ALICE, 2);
+
+        report_vault_theft_with_challenge_period();
+        assert_ok!(StakedRelayers::resolve_theft_report(Origin::root(), BOB, false));
+        assert_eq!(Sla::relayer_sla(ALICE), FixedI128::from(1));
+    })
+}
+
+#[test]
+fn test_resolve_theft_report_rejection_allows_reporting_again() {
+    run_test(|| {
+        report_vault_theft_with_challenge_period();
+        let tx_id = sample_vault_payment(100, H256::zero()).tx_id();
+        assert!(StakedRelayers::theft_report(tx_id).contains(&BOB));
+
+        assert_ok!(StakedRelayers::resolve_theft_report(Origin::root(), BOB, false));
+        assert!(!crate::TheftReports::<Test>::contains_key(&tx_id));
+
+        let raw_tx = sample_vault_payment(100, H256::zero()).format();
+        assert_ok!(StakedRelayers::report_vault_theft(
+            Origin::signed(CAROL),
+            BOB,
+            vec![0u8; 32],
+            raw_tx
+        ));
+    })
+}
+
+#[test]
+fn test_check_theft_report_returns_reason() {
+    run_test(|| {
+        ext::btc_relay::verify_transaction_inclusion::<Test>.mock_safe(move |_, _| MockResult::Return(Ok(())));
+        StakedRelayers::_is_parsed_transaction_invalid
+            .mock_safe(move |_, _| MockResult::Return(Err(TestError::ValidMergeTransaction.into())));
+
+        let raw_tx = sample_vault_payment(100, H256::zero()).format();
+        let tx_id = parse_transaction(&raw_tx).unwrap().tx_id();
+
+        assert_eq!(
+            StakedRelayers::check_theft_report(&BOB, vec![0u8; 32], raw_tx.clone()),
+            TheftReportVerdict::ValidMerge
+        );
+        assert_eq!(
+            StakedRelayers::check_theft_report(&BOB, vec![0u8; 32], vec![0u8; 4]),
+            TheftReportVerdict::InvalidTransaction
+        );
+
+        StakedRelayers::_is_parsed_transaction_invalid.mock_safe(move |_, _| MockResult::Return(Ok(())));
+        assert_eq!(
+            StakedRelayers::check_theft_report(&BOB, vec![0u8; 32], raw_tx.clone()),
+            TheftReportVerdict::Theft
+        );
+
+        ext::btc_relay::verify_transaction_inclusion::<Test>
+            .mock_safe(|_, _| MockResult::Return(Err(BtcRelayError::<Test>::BlockNotFound.into())));
+        assert_eq!(
+            StakedRelayers::check_theft_report(&BOB, vec![0u8; 32], raw_tx.clone()),
+            TheftReportVerdict::InvalidProof
+        );
+
+        crate::TheftReports::<Test>::mutate(&tx_id, |reports| {
+            reports.insert(BOB);
+        });
+        assert_eq!(
+            StakedRelayers::check_theft_report(&BOB, vec![0u8; 32], raw_tx.clone()),
+            TheftReportVerdict::AlreadyReported
+        );
+
+        Security::set_status(StatusCode::Shutdown);
+        assert_eq!(
+            StakedRelayers::check_theft_report(&BOB, vec![0u8; 32], raw_tx),
+            TheftReportVerdict::ReportingDisabled
+        );
+    })
+}
+
+#[test]
+fn test_check_double_payment_report_returns_reason() {
+    run_test(|| {
+        let request_id = H256::from_low_u64_be(1);
+        let left = sample_vault_payment(100, request_id);
+        let right = sample_vault_payment(200, request_id);
+        let check = |left: &Transaction, right: &Transaction| {
+            StakedRelayers::check_double_payment_report(
+                &CAROL,
+                (vec![0u8; 32], vec![0u8; 32]),
+                (left.format(), right.format()),
+            )
+        };
+
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(CAROL, Some(sample_vault_address())))));
+        ext::btc_relay::verify_transaction_inclusion::<Test>.mock_safe(move |_, _| MockResult::Return(Ok(())));
+
+        assert_eq!(check(&left, &left), TheftReportVerdict::DuplicateTransaction);
+        assert_eq!(check(&left, &right), TheftReportVerdict::RequestNotFound);
+
+        mock_completed_redeem_request(CAROL);
+        assert_eq!(check(&left, &right), TheftReportVerdict::Theft);
+        // the dry-run does not report the vault
+        assert!(StakedRelayers::theft_report(left.tx_id()).is_empty());
+    })
+}
+
+#[test]
+fn test_is_valid_merge_transaction_fails() {
+    run_test(|| {
+        let vault = BOB;
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(vault, None))));
+
+        let address1 = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+
+        let address2 = BtcAddress::P2PKH(H160::from_str(&"5f69790b72c98041330644bbd50f2ebb5d073c36").unwrap());
+
+        assert_eq!(
+            StakedRelayers::is_valid_merge_transaction(
+                &vault,
+                &[(100, address1)],
+                &[],
+                &Wallet::new(dummy_public_key())
+            ),
+            false,
+            "payment to unknown recipient"
+        );
+
+        let mut wallet = Wallet::new(dummy_public_key());
+        wallet.add_btc_address(address2);
+        assert_eq!(
+            StakedRelayers::is_valid_merge_transaction(&vault, &[(100, address2)], &[(1, vec![])], &wallet),
+            false,
+            "op_returns should not burn value"
+        );
+    })
+}
+
+#[test]
+fn test_is_valid_merge_transaction_succeeds() {
+    run_test(|| {
+        let vault = BOB;
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(vault, None))));
+
+        let address = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+
+        let mut wallet = Wallet::new(dummy_public_key());
+        wallet.add_btc_address(address);
+
+        assert_eq!(
+            StakedRelayers::is_valid_merge_transaction(&vault, &[(100, address)], &[], &wallet),
+            true
+        );
+    })
+}
+
+#[test]
+fn test_get_maintenance_transaction_type() {
+    run_test(|| {
+        let address1 = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+        let address2 = BtcAddress::P2PKH(H160::from_str(&"5f69790b72c98041330644bbd50f2ebb5d073c36").unwrap());
+        let request_id = H256::from_low_u64_be(1);
+
+        let mut wallet = Wallet::new(dummy_public_key());
+        wallet.add_btc_address(address1);
+        wallet.add_btc_address(address2);
+
+        assert_eq!(
+            StakedRelayers::get_maintenance_transaction_type(
+                &CAROL,
+                &[(100, address1)],
+                &[(0, b"tag".to_vec())],
+                &wallet
+            ),
+            Some(MaintenanceTransaction::Merge)
+        );
+        assert_eq!(
+            StakedRelayers::get_maintenance_transaction_type(&CAROL, &[(50, address1), (50, address2)], &[], &wallet),
+            Some(MaintenanceTransaction::Merge)
+        );
+        assert_eq!(
+            StakedRelayers::get_maintenance_transaction_type(
+                &CAROL,
+                &[(100, address1), (50, sample_vault_address())],
+                &[],
+                &wallet
+            ),
+            None
+        );
+
+        // the referenced request is not a request of the vault
+        let fee_bump_outputs = [(0, request_id.as_bytes().to_vec())];
+        assert_eq!(
+            StakedRelayers::get_maintenance_transaction_type(&CAROL, &[(100, address1)], &fee_bump_outputs, &wallet),
+            Some(MaintenanceTransaction::Merge)
+        );
+
+        mock_completed_redeem_request(CAROL);
+        assert_eq!(
+            StakedRelayers::get_maintenance_transaction_type(&CAROL, &[(100, address1)], &fee_bump_outputs, &wallet),
+            Some(MaintenanceTransaction::FeeBump)
+        );
+        assert_eq!(
+            StakedRelayers::get_maintenance_transaction_type(&BOB, &[(100, address1)], &fee_bump_outputs, &wallet),
+            Some(MaintenanceTransaction::Merge)
+        );
+    })
+}
+
+#[test]
+fn test_is_transaction_invalid_fails_with_fee_bump_of_settled_request() {
+    run_test(|| {
+        let request_id = H256::from_low_u64_be(1);
+        let settling_tx_id = sample_vault_payment(100, request_id).tx_id();
+        // child which spends the change of the settling transaction back to the vault
+        let fee_bump_tx = sample_vault_transaction(vec![
+            TransactionOutput::payment(100, &sample_vault_address()),
+            TransactionOutput::op_return(0, request_id.as_bytes()),
+        ]);
+
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(CAROL, Some(sample_vault_address())))));
+        ext::btc_relay::get_settling_transaction::<Test>.mock_safe(move |_| MockResult::Return(Some(settling_tx_id)));
+        mock_completed_redeem_request(CAROL);
+
+        assert_err!(
+            StakedRelayers::is_transaction_invalid(&CAROL, fee_bump_tx.format()),
+            TestError::ValidFeeBumpTransaction
+        );
+    })
+}
+
+#[test]
+fn test_is_transaction_invalid_fails_with_tagged_consolidation() {
+    run_test(|| {
+        let consolidation_tx = sample_vault_transaction(vec![
+            TransactionOutput::payment(100, &sample_vault_address()),
+            TransactionOutput::op_return(0, b"consolidate"),
+        ]);
+
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(CAROL, Some(sample_vault_address())))));
+
+        assert_err!(
+            StakedRelayers::is_transaction_invalid(&CAROL, consolidation_tx.format()),
+            TestError::ValidMergeTransaction
+        );
+    })
+}
+
+#[test]
+fn test_is_transaction_invalid_succeeds_with_change_to_unregistered_address() {
+    run_test(|| {
+        let unregistered_address =
+            BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+        // looks like a consolidation, but the funds move to an address the vault did not register
+        let transaction = sample_vault_transaction(vec![
+            TransactionOutput::payment(100, &unregistered_address),
+            TransactionOutput::op_return(0, b"consolidate"),
+        ]);
+
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(CAROL, Some(sample_vault_address())))));
+
+        assert_ok!(StakedRelayers::is_transaction_invalid(&CAROL, transaction.format()));
+    })
+}
+
+#[test]
+fn test_is_transaction_invalid_fails_with_declared_wallet_migration() {
+    run_test(|| {
+        let migration_address = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+        let transaction = sample_vault_transaction(vec![TransactionOutput::payment(100, &migration_address)]);
+
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(CAROL, Some(sample_vault_address())))));
+        ext::vault_registry::get_pending_wallet_migrations::<Test>.mock_safe(move |_| {
+            MockResult::Return(vec![WalletMigration {
+                from: sample_vault_address(),
+                to: migration_address,
+                expiry: 10,
+            }])
+        });
+
+        assert_err!(
+            StakedRelayers::is_transaction_invalid(&CAROL, transaction.format()),
+            TestError::ValidMergeTransaction
+        );
+    })
+}
+
+#[test]
+fn test_is_transaction_invalid_succeeds_with_migration_declared_for_other_address() {
+    run_test(|| {
+        let other_address = BtcAddress::P2PKH(H160::from_str(&"5f69790b72c98041330644bbd50f2ebb5d073c36").unwrap());
+        let migration_address = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+        let transaction = sample_vault_transaction(vec![TransactionOutput::payment(100, &migration_address)]);
+
+        ext::vault_registry::get_active_vault_from_id::<Test>.mock_safe(move |_| {
+            let mut vault = init_zero_vault(CAROL, Some(sample_vault_address()));
+            vault.wallet.add_btc_address(other_address);
+            MockResult::Return(Ok(vault))
+        });
+        // the transaction does not spend from the address the migration was declared for
+        ext::vault_registry::get_pending_wallet_migrations::<Test>.mock_safe(move |_| {
+            MockResult::Return(vec![WalletMigration {
+                from: other_address,
+                to: migration_address,
+                expiry: 10,
+            }])
+        });
+
+        assert_ok!(StakedRelayers::is_transaction_invalid(&CAROL, transaction.format()));
+    })
+}
+
+#[test]
+fn test_is_transaction_invalid_succeeds_with_tagged_migration_without_declaration() {
+    run_test(|| {
+        let migration_address = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+        // a tag does not declare the migration
+        let transaction = sample_vault_transaction(vec![
+            TransactionOutput::payment(100, &migration_address),
+            TransactionOutput::op_return(0, b"migrate"),
+        ]);
+
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(CAROL, Some(sample_vault_address())))));
+        ext::vault_registry::get_pending_wallet_migrations::<Test>.mock_safe(|_| MockResult::Return(vec![]));
+
+        assert_ok!(StakedRelayers::is_transaction_invalid(&CAROL, transaction.format()));
+    })
+}
+
+#[test]
+fn test_report_vault_double_payment_fails_with_fee_bump() {
+    run_test(|| {
+        let request_id = H256::from_low_u64_be(1);
+        let payment = sample_vault_payment(100, request_id);
+        let fee_bump = sample_vault_transaction(vec![
+            TransactionOutput::payment(100, &sample_vault_address()),
+            TransactionOutput::op_return(0, request_id.as_bytes()),
+        ]);
+
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(CAROL, Some(sample_vault_address())))));
+        ext::btc_relay::verify_transaction_inclusion::<Test>.mock_safe(move |_, _| MockResult::Return(Ok(())));
+        mock_completed_redeem_request(CAROL);
+
+        assert_err!(
+            report_vault_double_payment(&payment, &fee_bump),
+            TestError::ValidFeeBumpTransaction
+        );
+    })
+}
+
+#[test]
+fn test_is_valid_request_transaction_fails() {
+    run_test(|| {
+        let vault = BOB;
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(vault, None))));
+
+        let address1 = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+
+        let address2 = BtcAddress::P2PKH(H160::from_str(&"5f69790b72c98041330644bbd50f2ebb5d073c36").unwrap());
+
+        let mut wallet = Wallet::new(dummy_public_key());
+        wallet.add_btc_address(address2);
+
+        let actual_value: i32 = 50;
+
+        let request_value = 100;
+        let request_address = address1;
+
+        assert_eq!(
+            StakedRelayers::is_valid_request_transaction(
+                request_value,
+                request_address,
+                &[(actual_value.try_into().unwrap(), address1)],
+                &wallet
+            ),
+            false
+        );
+    })
+}
+
+#[test]
+fn test_is_valid_request_transaction_succeeds() {
+    run_test(|| {
+        let recipient_address = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+
+        let vault_address = BtcAddress::P2PKH(H160::from_str(&"5f69790b72c98041330644bbd50f2ebb5d073c36").unwrap());
+
+        let request_value = 100;
+        let change_value = 50;
+
+        let mut wallet = Wallet::new(dummy_public_key());
+        wallet.add_btc_address(vault_address);
+
+        assert_eq!(
+            StakedRelayers::is_valid_request_transaction(
+                request_value,
+                recipient_address,
+                &[
+                    (request_value.try_into().unwrap(), recipient_address),
+                    (change_value.try_into().unwrap(), vault_address)
+                ],
+                &wallet
+            ),
+            true
+        );
+    })
+}
+
+#[test]
+fn test_is_transaction_invalid_fails_with_valid_merge_transaction() {
+    run_test(|| {
+        let address = BtcAddress::P2PKH(H160::from_slice(&[
+            126, 125, 148, 208, 221, 194, 29, 131, 191, 188, 252, 119, 152, 228, 84, 126, 223, 8, 50, 170,
+        ]));
+
+        let mut wallet = Wallet::new(dummy_public_key());
+        wallet.add_btc_address(address);
+
+        ext::vault_registry::get_active_vault_from_id::<Test>.mock_safe(move |_| {
+            MockResult::Return(Ok(Vault {
+                id: BOB,
+                to_be_replaced_tokens: 0,
+                replace_collateral: 0,
+                to_be_issued_tokens: 0,
+                issued_tokens: 0,
+                to_be_redeemed_tokens: 0,
+                backing_collateral: 0,
+                wallet: wallet.clone(),
+                banned_until: None,
+                status: VaultStatus::Active(true),
+                ..Default::default()
+            }))
+        });
+
+        let transaction = TransactionBuilder::new()
+            .with_version(1)
+            .add_input(
+                TransactionInputBuilder::new()
+                    .with_coinbase(false)
+                    .with_sequence(4294967295)
+                    .with_previous_index(1)
+                    .with_previous_hash(H256Le::from_bytes_le(&[
+                        193, 80, 65, 160, 109, 235, 107, 56, 24, 176, 34, 250, 197, 88, 218, 76, 226, 9, 127, 8, 96,
+                        200, 246, 66, 16, 91, 186, 217, 210, 155, 224, 42,
+                    ]))
+                    .with_script(&[
+                        73, 48, 70, 2, 33, 0, 207, 210, 162, 211, 50, 178, 154, 220, 225, 25, 197, 90, 159, 173, 211,
+                        192, 115, 51, 32, 36, 183, 226, 114, 81, 62, 81, 98, 60, 161, 89, 147, 72, 2, 33, 0, 155, 72,
+                        45, 127, 123, 77, 71, 154, 255, 98, 189, 205, 174, 165, 70, 103, 115, 125, 86, 248, 212, 214,
+                        61, 208, 62, 195, 239, 101, 30, 217, 162, 84, 1, 33, 3, 37, 248, 176, 57, 161, 24, 97, 101,
+                        156, 155, 240, 63, 67, 252, 78, 160, 85, 243, 167, 28, 214, 12, 123, 31, 212, 116, 171, 87,
+                        143, 153, 119, 250,
+                    ])
+                    .build(),
+            )
+            .add_output(TransactionOutput::payment(100, &address))
+            .build();
+
+        assert_err!(
+            StakedRelayers::is_transaction_invalid(&BOB, transaction.format()),
+            TestError::ValidMergeTransaction
+        );
+    })
+}
+
+#[test]
+fn test_is_transaction_invalid_fails_with_valid_request_or_redeem() {
+    run_test(|| {
+        let vault_address = BtcAddress::P2WPKHv0(H160::from_slice(&[
+            164, 180, 202, 72, 222, 11, 63, 255, 193, 84, 4, 161, 172, 220, 141, 186, 174, 34, 105, 85,
+        ]));
+
+        let mut wallet = Wallet::new(dummy_public_key());
+        wallet.add_btc_address(vault_address);
+
+        let recipient_address = BtcAddress::P2PKH(H160::from_str(&"5f69790b72c98041330644bbd50f2ebb5d073c36").unwrap());
+
+        ext::vault_registry::get_active_vault_from_id::<Test>.mock_safe(move |_| {
+            MockResult::Return(Ok(Vault {
+                id: BOB,
+                to_be_replaced_tokens: 0,
+                to_be_issued_tokens: 0,
+                issued_tokens: 0,
+                to_be_redeemed_tokens: 0,
+                replace_collateral: 0,
+                backing_collateral: 0,
+                wallet: wallet.clone(),
+                banned_until: None,
+                status: VaultStatus::Active(true),
+                ..Default::default()
+            }))
+        });
+
+        ext::redeem::get_open_or_completed_redeem_request_from_id::<Test>.mock_safe(move |_| {
+            MockResult::Return(Ok(RedeemRequest {
+                period: 0,
+                vault: BOB,
+                opentime: 0,
+                fee: 0,
+                amount_btc: 100,
+                premium: 0,
+                redeemer: ALICE,
+                btc_address: recipient_address,
+                btc_height: 0,
+                status: RedeemRequestStatus::Pending,
+                transfer_fee_btc: 0,
+            }))
+        });
+
+        let transaction = TransactionBuilder::new()
+            .with_version(1)
+            .add_input(
+                TransactionInputBuilder::new()
+                    .with_coinbase(false)
+                    .with_previous_index(1)
+                    .with_previous_hash(H256Le::from_hex_le(
+                        "40d43a99926d43eb0e619bf0b3d83b4a31f60c176beecfb9d35bf45e54d0f742",
+                    ))
+                    .with_sequence(4294967295)
+                    .with_script(&[
+                        22, 0, 20, 164, 180, 202, 72, 222, 11, 63, 255, 193, 84, 4, 161, 172, 220, 141, 186, 174, 34,
+                        105, 85,
+                    ])
+                    .add_witness(&[
+                        48, 69, 2, 33, 0, 134, 4, 239, 143, 109, 138, 250, 137, 45, 238, 15, 49, 37, 155, 108, 224, 45,
+                        215, 12, 84, 92, 252, 254, 216, 20, 129, 121, 151, 24, 118, 197, 74, 2, 32, 118, 215, 113, 214,
+                        233, 27, 237, 33, 39, 131, 201, 176, 110, 13, 230, 0, 250, 178, 213, 24, 250, 214, 241, 90, 43,
+                        25, 29, 127, 189, 38, 42, 62, 1,
+                    ])
+                    .add_witness(&[
+                        3, 157, 37, 171, 121, 244, 31, 117, 206, 175, 136, 36, 17, 253, 65, 250, 103, 10, 76, 103, 44,
+                        35, 255, 175, 14, 54, 26, 150, 156, 222, 6, 146, 232,
+                    ])
+                    .build(),
+            )
+            .add_output(TransactionOutput::payment(100, &recipient_address))
+            .add_output(TransactionOutput::op_return(0, &H256::from_slice(&[0; 32]).as_bytes()))
+            .build();
+
+        assert_err!(
+            StakedRelayers::is_transaction_invalid(&BOB, transaction.format()),
+            TestError::ValidRedeemTransaction
+        );
+
+        ext::redeem::get_open_or_completed_redeem_request_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Err(RedeemError::RedeemIdNotFound.into())));
+
+        ext::replace::get_open_or_completed_replace_request::<Test>.mock_safe(move |_| {
+            MockResult::Return(Ok(ReplaceRequest {
+                period: 0,
+                old_vault: BOB,
+                amount: 100,
+                griefing_collateral: 0,
+                new_vault: ALICE,
+                collateral: 0,
+                accept_time: 1,
+                btc_address: recipient_address,
+                btc_height: 0,
+                status: ReplaceRequestStatus::Pending,
+            }))
+        });
+
+        assert_err!(
+            StakedRelayers::is_transaction_invalid(&BOB, transaction.format()),
+            TestError::ValidReplaceTransaction
+        );
+    })
+}
+
+#[test]
+fn test_is_transaction_invalid_succeeds() {
+    run_test(|| {
+        let vault_address = BtcAddress::P2PKH(H160::from_slice(&[
+            126, 125, 148, 208, 221, 194, 29, 131, 191, 188, 252, 119, 152, 228, 84, 126, 223, 8, 50, 170,
+        ]));
+
+        let recipient_address = BtcAddress::P2PKH(H160::from_str(&"66c7060feb882664ae62ffad0051fe843e318e85").unwrap());
+
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(BOB, Some(vault_address)))));
+
+        let transaction = TransactionBuilder::new()
+            .with_version(1)
+            .add_input(
+                TransactionInputBuilder::new()
+                    .with_coinbase(false)
+                    .with_sequence(4294967295)
+                    .with_previous_index(1)
+                    .with_previous_hash(H256Le::from_bytes_le(&[
+                        193, 80, 65, 160, 109, 235, 107, 56, 24, 176, 34, 250, 197, 88, 218, 76, 226, 9, 127, 8, 96,
+                        200, 246, 66, 16, 91, 186, 217, 210, 155, 224, 42,
+                    ]))
+                    .with_script(&[
+                        73, 48, 70, 2, 33, 0, 207, 210, 162, 211, 50, 178, 154, 220, 225, 25, 197, 90, 159, 173, 211,
+                        192, 115, 51, 32, 36, 183, 226, 114, 81, 62, 81, 98, 60, 161, 89, 147, 72, 2, 33, 0, 155, 72,
+                        45, 127, 123, 77, 71, 154, 255, 98, 189, 205, 174, 165, 70, 103, 115, 125, 86, 248, 212, 214,
+                        61, 208, 62, 195, 239, 101, 30, 217, 162, 84, 1, 33, 3, 37, 248, 176, 57, 161, 24, 97, 101,
+                        156, 155, 240, 63, 67, 252, 78, 160, 85, 243, 167, 28, 214, 12, 123, 31, 212, 116, 171, 87,
+                        143, 153, 119, 250,
+                    ])
+                    .build(),
+            )
+            .add_output(TransactionOutput::payment(100, &recipient_address))
+            .build();
+
+        assert_ok!(StakedRelayers::is_transaction_invalid(&BOB, transaction.format()));
+    })
+}
+
+#[test]
+fn test_is_transaction_invalid_fails_with_valid_merge_testnet_transaction() {
+    run_test(|| {
+        // bitcoin-cli -testnet getrawtransaction "3453e52ebab8ac96159d6b19114b492a05cce05a8fdfdaf5dea266ac10601ce4" 0
+        // "00000000000000398849cc9d67261ec2d5fea07db87ab66a8ea47bc05acfb194"
+        let raw_tx_hex = "0200000000010108ce8e8943edbbf09d070bb893e09c0de12c0cf3704fe8a9b0f8b8d1a4a7a4760000000017160014473ca3f4d726ce9c21af7cdc3fcc13264f681b04feffffff02b377413f0000000017a914fe5183ccb89d98beaa6908c7cf1bd109029482cf87142e1a00000000001976a914d0a46d39dafa3012c2a7ed4d82d644b428e4586b88ac02473044022069484377c6627ccca566d4c4ac2cb84d1b0662f5ffbd384815c5e98b072759fc022061de3b77b4543ef43bb969d3f97fbbbdcddc008438720e7026181d99c455b2410121034172c29d3da8279f71adda48db8281d65b794e73cf04ea91fac4293030f0fe91a3ee1c00";
+        let raw_tx = hex::decode(&raw_tx_hex).unwrap();
+
+        // 2MsqorfMrsvXiVM8pD9bPWxGnccSWsj16XE (P2WPKH-P2SH)
+        let vault_btc_address_0 = BtcAddress::P2WPKHv0(H160::from_slice(
+            &hex::decode("473ca3f4d726ce9c21af7cdc3fcc13264f681b04").unwrap(),
+        ));
+
+        // 2NGRwGkzypA4fEz9m4KhA2ZBs7fTg3B7Zjo
+        let vault_btc_address_1 = BtcAddress::P2SH(H160::from_slice(
+            &hex::decode("fe5183ccb89d98beaa6908c7cf1bd109029482cf").unwrap(),
+        ));
+
+        // mzY9pX6NA3cBmiC4umbBzf1NdwrmjS7MS8
+        let vault_btc_address_2 = BtcAddress::P2PKH(H160::from_slice(
+            &hex::decode("d0a46d39dafa3012c2a7ed4d82d644b428e4586b").unwrap(),
+        ));
+
+        let mut wallet = Wallet::new(dummy_public_key());
+        wallet.add_btc_address(vault_btc_address_0);
+        wallet.add_btc_address(vault_btc_address_1);
+        wallet.add_btc_address(vault_btc_address_2);
+
+        ext::vault_registry::get_active_vault_from_id::<Test>.mock_safe(move |_| {
+            MockResult::Return(Ok(Vault {
+                id: BOB,
+                to_be_replaced_tokens: 0,
+                replace_collateral: 0,
+                to_be_issued_tokens: 0,
+                issued_tokens: 0,
+                to_be_redeemed_tokens: 0,
+                backing_collateral: 0,
+                wallet: wallet.clone(),
+                banned_until: None,
+                status: VaultStatus::Active(true),
+                ..Default::default()
+            }))
+        });
+
+        assert_err!(
+            StakedRelayers::is_transaction_invalid(&BOB, raw_tx),
+            TestError::ValidMergeTransaction
+        );
+    })
+}
+
+#[test]
+fn test_is_transaction_invalid_succeeds_with_testnet_transaction() {
+    run_test(|| {
+        // bitcoin-cli -testnet getrawtransaction "3453e52ebab8ac96159d6b19114b492a05cce05a8fdfdaf5dea266ac10601ce4" 0
+        // "00000000000000398849cc9d67261ec2d5fea07db87ab66a8ea47bc05acfb194"
+        let raw_tx_hex = "0200000000010108ce8e8943edbbf09d070bb893e09c0de12c0cf3704fe8a9b0f8b8d1a4a7a4760000000017160014473ca3f4d726ce9c21af7cdc3fcc13264f681b04feffffff02b377413f0000000017a914fe5183ccb89d98beaa6908c7cf1bd109029482cf87142e1a00000000001976a914d0a46d39dafa3012c2a7ed4d82d644b428e4586b88ac02473044022069484377c6627ccca566d4c4ac2cb84d1b0662f5ffbd384815c5e98b072759fc022061de3b77b4543ef43bb969d3f97fbbbdcddc008438720e7026181d99c455b2410121034172c29d3da8279f71adda48db8281d65b794e73cf04ea91fac4293030f0fe91a3ee1c00";
+        let raw_tx = hex::decode(&raw_tx_hex).unwrap();
+
+        // 2MsqorfMrsvXiVM8pD9bPWxGnccSWsj16XE (P2WPKH-P2SH)
+        let btc_address = BtcAddress::P2WPKHv0(H160::from_slice(
+            &hex::decode("473ca3f4d726ce9c21af7cdc3fcc13264f681b04").unwrap(),
+        ));
+
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(BOB, Some(btc_address)))));
+
+        assert_ok!(StakedRelayers::is_transaction_invalid(&BOB, raw_tx));
+    })
+}
+
+#[test]
+fn test_is_transaction_invalid_succeeds_with_nested_segwit_address() {
+    run_test(|| {
+        // bitcoin-cli -testnet getrawtransaction "3453e52ebab8ac96159d6b19114b492a05cce05a8fdfdaf5dea266ac10601ce4" 0
+        let raw_tx_hex = "0200000000010108ce8e8943edbbf09d070bb893e09c0de12c0cf3704fe8a9b0f8b8d1a4a7a4760000000017160014473ca3f4d726ce9c21af7cdc3fcc13264f681b04feffffff02b377413f0000000017a914fe5183ccb89d98beaa6908c7cf1bd109029482cf87142e1a00000000001976a914d0a46d39dafa3012c2a7ed4d82d644b428e4586b88ac02473044022069484377c6627ccca566d4c4ac2cb84d1b0662f5ffbd384815c5e98b072759fc022061de3b77b4543ef43bb969d3f97fbbbdcddc008438720e7026181d99c455b2410121034172c29d3da8279f71adda48db8281d65b794e73cf04ea91fac4293030f0fe91a3ee1c00";
+        let raw_tx = hex::decode(&raw_tx_hex).unwrap();
+
+        // the vault registered the nested address instead of the inner witness program
+        // 2MsqorfMrsvXiVM8pD9bPWxGnccSWsj16XE (P2WPKH-P2SH)
+        let btc_address = BtcAddress::P2SH(H160::from_slice(
+            &hex::decode("068a6a2ec6be7d6e7aac1657445154c52db0cef8").unwrap(),
+        ));
+
+        ext::vault_registry::get_active_vault_from_id::<Test>
+            .mock_safe(move |_| MockResult::Return(Ok(init_zero_vault(BOB, Some(btc_address)))));
+
+        assert_ok!(StakedRelayers::is_transaction_invalid(&BOB, raw_tx));
+    })
+}
+
+#[test]
+fn test_store_block_header_and_update_sla_fails_with_invalid() {
+    run_test(|| {
+        ext::btc_relay::store_block_header::<Test>
+            .mock_safe(|_, _| MockResult::Return(Err(BtcRelayError::<Test>::DiffTargetHeader.into())));
+
+        ext::sla::event_update_relayer_sla::<Test>.mock_safe(|_, _| {
+            panic!("Should not call sla update for invalid block");
+        });
+
+        assert_err!(
+            StakedRelayers::store_block_header_and_update_sla(&0, RawBlockHeader::default()),
+            BtcRelayError::<Test>::DiffTargetHeader
+        );
+    })
+}
+
+#[test]
+fn test_store_block_header_and_update_sla_decreases_sla_for_outdated_block() {
+    run_test(|| {
+        register_staked_relayer(ALICE, 150);
+        set_relayer_sla_change(ext::sla::RelayerEvent::OutdatedBlock, -1);
+        increase_relayer_sla(ALICE, 2);
+
+        ext::btc_relay::store_block_header::<Test>
+            .mock_safe(|_, _| MockResult::Return(Err(BtcRelayError::<Test>::OutdatedBlock.into())));
+
+        assert_ok!(StakedRelayers::store_block_header_and_update_sla(
+            &ALICE,
+            RawBlockHeader::default()
+        ));
+        assert_eq!(Sla::relayer_sla(ALICE), FixedI128::from(1));
+
+        // relayers which are not bonded have no SLA to decrease
+        assert_err!(
+            StakedRelayers::store_block_header_and_update_sla(&BOB, RawBlockHeader::default()),
+            BtcRelayError::<Test>::OutdatedBlock
+        );
+    })
+}
+
+#[test]
+fn test_on_stale_block_header_decreases_sla() {
+    run_test(|| {
+        register_staked_relayer(ALICE, 150);
+        set_relayer_sla_change(ext::sla::RelayerEvent::StaleBlock, -1);
+        increase_relayer_sla(ALICE, 2);
+
+        <StakedRelayers as btc_relay::OnStaleBlockHeader<AccountId>>::on_stale_block_header(&ALICE);
+        assert_eq!(Sla::relayer_sla(ALICE), FixedI128::from(1));
+
+        // relayers which are not bonded have no SLA to decrease
+        <StakedRelayers as btc_relay::OnStaleBlockHeader<AccountId>>::on_stale_block_header(&BOB);
+        assert_eq!(Sla::relayer_sla(BOB), FixedI128::from(0));
+    })
+}
+
+fn set_relayer_sla_change(event: ext::sla::RelayerEvent, value: i128) {
+    assert_ok!(Sla::set_relayer_sla(Origin::root(), event, FixedI128::from(value)));
+}
+
+fn increase_relayer_sla(relayer: AccountId, value: i128) {
+    set_relayer_sla_change(ext::sla::RelayerEvent::StoreBlock, value);
+    assert_ok!(Sla::event_update_relayer_sla(
+        &relayer,
+        ext::sla::RelayerEvent::StoreBlock
+    ));
+}
+
+fn sample_rich_block_header(block_hash: H256Le, block_height: u32) -> RichBlockHeader<AccountId, BlockNumber> {
+    RichBlockHeader {
+        block_hash,
+        block_header: Default::default(),
+        block_height,
+        chain_ref: 0,
+        account_id: ALICE,
+        para_height: 1,
+    }
+}
+
+fn store_block_header_with_deposit(raw_block_header: RawBlockHeader, block_height: u32) {
+    crate::HeaderDeposit::<Test>::put(10);
+    ext::btc_relay::store_block_header::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+    ext::btc_relay::get_block_header_from_hash::<Test>
+        .mock_safe(move |block_hash| MockResult::Return(Ok(sample_rich_block_header(block_hash, block_height))));
+    ext::sla::event_update_relayer_sla::<Test>.mock_safe(|_, _| {
+        panic!("Should not call sla update before the block is stable");
+    });
+
+    assert_ok!(StakedRelayers::store_block_header_and_update_sla(
+        &ALICE,
+        raw_block_header
+    ));
+}
+
+#[test]
+fn test_store_block_header_locks_deposit() {
+    run_test(|| {
+        let raw_block_header = RawBlockHeader::from_bytes(&[1; 80]).unwrap();
+        store_block_header_with_deposit(raw_block_header, 5);
+
+        assert_eq!(CollateralCurrency::get_reserved_balance(&ALICE), 10);
+        assert_eq!(
+            crate::HeaderDeposits::<Test>::get(5, raw_block_header.hash()),
+            Some((ALICE, 10))
+        );
+        assert!(System::events().iter().any(|a| matches!(
+            a.event,
+            TestEvent::staked_relayers(Event::LockHeaderDeposit(ALICE, _, 10))
+        )));
+    })
+}
+
+#[test]
+fn test_settle_header_deposits_waits_for_confirmations() {
+    run_test(|| {
+        let raw_block_header = RawBlockHeader::from_bytes(&[1; 80]).unwrap();
+        store_block_header_with_deposit(raw_block_header, 5);
+
+        ext::btc_relay::get_best_block_height::<Test>.mock_safe(|| MockResult::Return(10));
+        ext::btc_relay::get_stable_transaction_confirmations::<Test>.mock_safe(|| MockResult::Return(6));
+        StakedRelayers::settle_header_deposits();
+
+        assert_eq!(CollateralCurrency::get_reserved_balance(&ALICE), 10);
+        assert!(crate::HeaderDeposits::<Test>::get(5, raw_block_header.hash()).is_some());
+    })
+}
+
+#[test]
+fn test_settle_header_deposits_refunds_main_chain_header() {
+    run_test(|| {
+        let raw_block_header = RawBlockHeader::from_bytes(&[1; 80]).unwrap();
+        store_block_header_with_deposit(raw_block_header, 5);
+
+        ext::btc_relay::get_best_block_height::<Test>.mock_safe(|| MockResult::Return(11));
+        ext::btc_relay::get_stable_transaction_confirmations::<Test>.mock_safe(|| MockResult::Return(6));
+        ext::btc_relay::get_main_chain_block_header::<Test>.mock_safe(move |block_height| {
+            MockResult::Return(Ok(sample_rich_block_header(raw_block_header.hash(), block_height)))
+        });
+        ext::sla::event_update_relayer_sla::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        StakedRelayers::settle_header_deposits();
+
+        assert_eq!(CollateralCurrency::get_reserved_balance(&ALICE), 0);
+        assert_eq!(CollateralCurrency::get_free_balance(&ALICE), ALICE_BALANCE);
+        assert_eq!(crate::HeaderDeposits::<Test>::get(5, raw_block_header.hash()), None);
+        assert!(System::events().iter().any(|a| matches!(
+            a.event,
+            TestEvent::staked_relayers(Event::RefundHeaderDeposit(ALICE, _, 10))
+        )));
+    })
+}
+
+#[test]
+fn test_settle_header_deposits_forfeits_fork_header() {
+    run_test(|| {
+        let raw_block_header = RawBlockHeader::from_bytes(&[1; 80]).unwrap();
+        store_block_header_with_deposit(raw_block_header, 5);
+
+        ext::btc_relay::get_best_block_height::<Test>.mock_safe(|| MockResult::Return(11));
+        ext::btc_relay::get_stable_transaction_confirmations::<Test>.mock_safe(|| MockResult::Return(6));
+        ext::btc_relay::get_main_chain_block_header::<Test>
+            .mock_safe(|block_height| MockResult::Return(Ok(sample_rich_block_header(H256Le::zero(), block_height))));
+        let total_supply = CollateralCurrency::get_total_supply();
+        StakedRelayers::settle_header_deposits();
+
+        assert_eq!(CollateralCurrency::get_reserved_balance(&ALICE), 0);
+        assert_eq!(CollateralCurrency::get_free_balance(&ALICE), ALICE_BALANCE - 10);
+        // the deposit is burned rather than paid to the relayers
+        assert_eq!(
+            CollateralCurrency::get_free_balance(&ext::fee::fee_pool_account_id::<Test>()),
+            0
+        );
+        assert_eq!(CollateralCurrency::get_total_supply(), total_supply - 10);
+        assert!(System::events().iter().any(|a| matches!(
+            a.event,
+            TestEvent::staked_relayers(Event::ForfeitHeaderDeposit(ALICE, _, 10))
+        )));
+    })
+}
+
+#[test]
+fn test_settle_header_deposits_releases_deposit_on_error() {
+    run_test(|| {
+        let raw_block_header = RawBlockHeader::from_bytes(&[1; 80]).unwrap();
+        store_block_header_with_deposit(raw_block_header, 5);
+
+        ext::btc_relay::get_best_block_height::<Test>.mock_safe(|| MockResult::Return(11));
+        ext::btc_relay::get_stable_transaction_confirmations::<Test>.mock_safe(|| MockResult::Return(6));
+        ext::btc_relay::get_main_chain_block_header::<Test>
+            .mock_safe(|block_height| MockResult::Return(Ok(sample_rich_block_header(H256Le::zero(), block_height))));
+        ext::collateral::burn_collateral::<Test>
+            .mock_safe(|_, _| MockResult::Return(Err(TestError::NotRegistered.into())));
+        StakedRelayers::settle_header_deposits();
+
+        assert_eq!(crate::HeaderDeposits::<Test>::get(5, raw_block_header.hash()), None);
+        assert_eq!(CollateralCurrency::get_reserved_balance(&ALICE), 0);
+        assert_eq!(CollateralCurrency::get_free_balance(&ALICE), ALICE_BALANCE);
+    })
+}
+
+fn register_staked_relayer(relayer: AccountId, stake: u128) {
+    crate::MinimumStake::<Test>::put(100);
+    crate::UnbondingPeriod::<Test>::put(10);
+    assert_ok!(StakedRelayers::register_staked_relayer(Origin::signed(relayer), stake));
+}
+
+#[test]
+fn test_register_staked_relayer_succeeds() {
+    run_test(|| {
+        register_staked_relayer(ALICE, 150);
+
+        assert_eq!(CollateralCurrency::get_reserved_balance(&ALICE), 150);
+        assert_eq!(
+            StakedRelayers::staked_relayer(ALICE),
+            Some(StakedRelayer { stake: 150, height: 1 })
+        );
+        assert!(System::events().iter().any(|a| matches!(
+            a.event,
+            TestEvent::staked_relayers(Event::RegisterStakedRelayer(ALICE, 150))
+        )));
+
+        assert_err!(
+            StakedRelayers::register_staked_relayer(Origin::signed(ALICE), 150),
+            TestError::AlreadyRegistered
+        );
+    })
+}
+
+#[test]
+fn test_register_staked_relayer_fails_with_insufficient_stake() {
+    run_test(|| {
+        crate::MinimumStake::<Test>::put(100);
+        assert_err!(
+            StakedRelayers::register_staked_relayer(Origin::signed(ALICE), 99),
+            TestError::InsufficientStake
+        );
+    })
+}
+
+#[test]
+fn test_withdraw_unbonded_stake_after_unbonding_period() {
+    run_test(|| {
+        register_staked_relayer(ALICE, 150);
+        assert_ok!(StakedRelayers::deregister_staked_relayer(Origin::signed(ALICE)));
+
+        assert_eq!(StakedRelayers::staked_relayer(ALICE), None);
+        assert_eq!(StakedRelayers::unbonding_stake(ALICE), Some((11, 150)));
+        assert_err!(
+            StakedRelayers::register_staked_relayer(Origin::signed(ALICE), 150),
+            TestError::UnbondingStakeNotWithdrawn
+        );
+
+        Security::set_active_block_number(10);
+        assert_err!(
+            StakedRelayers::withdraw_unbonded_stake(Origin::signed(ALICE)),
+            TestError::UnbondingPeriodNotExpired
+        );
+
+        Security::set_active_block_number(11);
+        assert_ok!(StakedRelayers::withdraw_unbonded_stake(Origin::signed(ALICE)));
+        assert_eq!(CollateralCurrency::get_reserved_balance(&ALICE), 0);
+        assert_eq!(CollateralCurrency::get_free_balance(&ALICE), ALICE_BALANCE);
+        assert_eq!(StakedRelayers::unbonding_stake(ALICE), None);
+    })
+}
+
+#[test]
+fn test_slash_staked_relayer_distributes_stake() {
+    run_test(|| {
+        register_staked_relayer(ALICE, 150);
+
+        assert_err!(
+            StakedRelayers::slash_staked_relayer(Origin::signed(BOB), ALICE, 20),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(StakedRelayers::slash_staked_relayer(Origin::root(), ALICE, 20));
+
+        assert_eq!(CollateralCurrency::get_reserved_balance(&ALICE), 130);
+        assert_eq!(StakedRelayers::staked_relayer(ALICE).unwrap().stake, 130);
+        assert_eq!(
+            CollateralCurrency::get_free_balance(&ext::fee::fee_pool_account_id::<Test>()),
+            20
+        );
+        assert!(System::events().iter().any(|a| matches!(
+            a.event,
+            TestEvent::staked_relayers(Event::SlashStakedRelayer(ALICE, 20))
+        )));
+    })
+}
+
+#[test]
+fn test_slash_staked_relayer_below_minimum_deregisters() {
+    run_test(|| {
+        register_staked_relayer(ALICE, 150);
+        assert_ok!(StakedRelayers::slash_staked_relayer(Origin::root(), ALICE, 60));
+
+        assert_eq!(StakedRelayers::staked_relayer(ALICE), None);
+        assert_eq!(StakedRelayers::unbonding_stake(ALICE), Some((11, 90)));
+
+        // unbonding stake can still be slashed, but no more than is left
+        assert_ok!(StakedRelayers::slash_staked_relayer(Origin::root(), ALICE, 1000));
+        assert_eq!(StakedRelayers::unbonding_stake(ALICE), Some((11, 0)));
+        assert_eq!(CollateralCurrency::get_reserved_balance(&ALICE), 0);
+        assert_eq!(CollateralCurrency::get_free_balance(&ALICE), ALICE_BALANCE - 150);
+    })
+}
+
+#[test]
+fn test_store_block_header_only_rewards_staked_relayers() {
+    run_test(|| {
+        ext::btc_relay::store_block_header::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+        ext::sla::event_update_relayer_sla::<Test>.mock_safe(|_, _| {
+            panic!("Should not call sla update for unbonded relayer");
+        });
+        assert_ok!(StakedRelayers::store_block_header_and_update_sla(
+            &ALICE,
+            RawBlockHeader::default()
+        ));
+
+        register_staked_relayer(ALICE, 150);
+        ext::sla::event_update_relayer_sla::<Test>.mock_safe(|relayer, _| {
+            assert_eq!(*relayer, ALICE);
+            MockResult::Return(Ok(()))
+        });
+        assert_ok!(StakedRelayers::store_block_header_and_update_sla(
+            &ALICE,
+            RawBlockHeader::default()
+        ));
+    })
+}
+
+#[test]
+fn test_on_runtime_upgrade_resets_sla_of_unbonded_relayers() {
+    use crate::{types::Version, StorageVersion};
+    use frame_support::traits::OnRuntimeUpgrade;
+
+    run_test(|| {
+        register_staked_relayer(ALICE, 150);
+        increase_relayer_sla(ALICE, 2);
+        increase_relayer_sla(BOB, 2);
+        StorageVersion::put(Version::V0);
+
+        StakedRelayers::on_runtime_upgrade();
+
+        assert_eq!(Sla::relayer_sla(ALICE), FixedI128::from(2));
+        assert_eq!(Sla::relayer_sla(BOB), FixedI128::from(0));
+        assert!(StakedRelayers::storage_version() == Version::V1);
+    })
+}
+
+fn setup_block_error_vote(block_hash: H256Le) {
+    register_staked_relayer(ALICE, 100);
+    register_staked_relayer(BOB, 100);
+    register_staked_relayer(CAROL, 100);
+    crate::VoteThreshold::<Test>::put(FixedU128::checked_from_rational(2, 3).unwrap());
+    crate::VotingPeriod::<Test>::put(10);
+    crate::RejectedProposalSlash::<Test>::put(10);
+
+    ext::btc_relay::get_block_header_from_hash::<Test>
+        .mock_safe(move |block_hash| MockResult::Return(Ok(sample_rich_block_header(block_hash, 5))));
+    ext::btc_relay::get_main_chain_block_header::<Test>
+        .mock_safe(move |block_height| MockResult::Return(Ok(sample_rich_block_header(block_hash, block_height))));
+    ext::btc_relay::flag_block_error::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+}
+
+#[test]
+fn test_propose_block_error_fails_for_unbonded_relayer() {
+    run_test(|| {
+        assert_err!(
+            StakedRelayers::propose_block_error(Origin::signed(ALICE), H256Le::zero(), ErrorCode::NoDataBTCRelay),
+            TestError::NotRegistered
+        );
+    })
+}
+
+#[test]
+fn test_propose_block_error_fails_with_invalid_error_code() {
+    run_test(|| {
+        setup_block_error_vote(H256Le::zero());
+        assert_err!(
+            StakedRelayers::propose_block_error(Origin::signed(ALICE), H256Le::zero(), ErrorCode::OracleOffline),
+            TestError::InvalidErrorCode
+        );
+    })
+}
+
+#[test]
+fn test_block_error_proposal_accepted_by_stake_majority() {
+    run_test(|| {
+        let block_hash = H256Le::from_bytes_le(&[1; 32]);
+        setup_block_error_vote(block_hash);
+        ext::btc_relay::flag_block_error::<Test>.mock_safe(move |hash, error| {
+            assert_eq!(hash, block_hash);
+            assert_eq!(error, ErrorCode::NoDataBTCRelay);
+            MockResult::Return(Ok(()))
+        });
+        ext::sla::event_update_relayer_sla::<Test>.mock_safe(|relayer, event| {
+            assert_eq!(*relayer, ALICE);
+            assert_eq!(event, ext::sla::RelayerEvent::CorrectBlockErrorProposal);
+            MockResult::Return(Ok(()))
+        });
+
+        assert_ok!(StakedRelayers::propose_block_error(
+            Origin::signed(ALICE),
+            block_hash,
+            ErrorCode::NoDataBTCRelay
+        ));
+        assert_err!(
+            StakedRelayers::propose_block_error(Origin::signed(BOB), block_hash, ErrorCode::NoDataBTCRelay),
+            TestError::ProposalAlreadyOpen
+        );
+        assert_err!(
+            StakedRelayers::vote_on_block_error(Origin::signed(ALICE), 1, true),
+            TestError::AlreadyVoted
+        );
+        assert!(Security::get_errors().is_empty());
+
+        assert_ok!(StakedRelayers::vote_on_block_error(Origin::signed(BOB), 1, true));
+        assert_eq!(StakedRelayers::block_error_proposal(1), None);
+        assert!(Security::get_errors().contains(&ErrorCode::NoDataBTCRelay));
+        assert_eq!(Security::get_parachain_status(), StatusCode::Error);
+        assert!(Security::get_status_history()
+            .iter()
+            .all(|change| change.origin == StatusChangeOrigin::Signed(ALICE)));
+        assert!(System::events()
+            .iter()
+            .any(|a| matches!(a.event, TestEvent::staked_relayers(Event::AcceptBlockErrorProposal(1)))));
+    })
+}
+
+#[test]
+fn test_block_error_proposal_requires_minimum_voters() {
+    run_test(|| {
+        let block_hash = H256Le::from_bytes_le(&[1; 32]);
+        setup_block_error_vote(block_hash);
+        // the proposer holds more than the threshold on its own
+        register_staked_relayer(DAVE, 700);
+        ext::sla::event_update_relayer_sla::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+
+        assert_ok!(StakedRelayers::propose_block_error(
+            Origin::signed(DAVE),
+            block_hash,
+            ErrorCode::NoDataBTCRelay
+        ));
+        assert!(StakedRelayers::block_error_proposal(1).is_some());
+        assert!(Security::get_errors().is_empty());
+
+        assert_ok!(StakedRelayers::vote_on_block_error(Origin::signed(ALICE), 1, true));
+        assert_eq!(StakedRelayers::block_error_proposal(1), None);
+        assert!(Security::get_errors().contains(&ErrorCode::NoDataBTCRelay));
+    })
+}
+
+#[test]
+fn test_block_error_proposal_on_fork_does_not_raise_error() {
+    run_test(|| {
+        let block_hash = H256Le::from_bytes_le(&[1; 32]);
+        setup_block_error_vote(H256Le::zero());
+        ext::sla::event_update_relayer_sla::<Test>.mock_safe(|_, _| MockResult::Return(Ok(())));
+
+        assert_ok!(StakedRelayers::propose_block_error(
+            Origin::signed(ALICE),
+            block_hash,
+            ErrorCode::InvalidBTCRelay
+        ));
+        assert_ok!(StakedRelayers::vote_on_block_error(Origin::signed(BOB), 1, true));
+
+        assert!(Security::get_errors().is_empty());
+        assert_eq!(Security::get_parachain_status(), StatusCode::Running);
+    })
+}
+
+#[test]
+fn test_block_error_proposal_rejected_slashes_proposer() {
+    run_test(|| {
+        let block_hash = H256Le::from_bytes_le(&[1; 32]);
+        setup_block_error_vote(block_hash);
+        ext::btc_relay::flag_block_error::<Test>.mock_safe(|_, _| {
+            panic!("Should not flag rejected block error");
+        });
+
+        assert_ok!(StakedRelayers::propose_block_error(
+            Origin::signed(ALICE),
+            block_hash,
+            ErrorCode::NoDataBTCRelay
+        ));
+        assert_ok!(StakedRelayers::vote_on_block_error(Origin::signed(BOB), 1, false));
+        assert!(StakedRelayers::block_error_proposal(1).is_some());
+
+        assert_ok!(StakedRelayers::vote_on_block_error(Origin::signed(CAROL), 1, false));
+        assert_eq!(StakedRelayers::block_error_proposal(1), None);
+        assert_eq!(CollateralCurrency::get_reserved_balance(&ALICE), 90);
+        // the remaining stake is below the minimum, so the proposer is deregistered
+        assert_eq!(StakedRelayers::staked_relayer(ALICE), None);
+        assert_eq!(StakedRelayers::total_stake(), 200);
+        assert!(Security::get_errors().is_empty());
+        assert!(System::events()
+            .iter()
+            .any(|a| matches!(a.event, TestEvent::staked_relayers(Event::RejectBlockErrorProposal(1)))));
+    })
+}
+
+#[test]
+fn test_close_block_error_proposal_after_voting_period() {
+    run_test(|| {
+        let block_hash = H256Le::from_bytes_le(&[1; 32]);
+        setup_block_error_vote(block_hash);
+
+        assert_ok!(StakedRelayers::propose_block_error(
+            Origin::signed(ALICE),
+            block_hash,
+            ErrorCode::NoDataBTCRelay
+        ));
+        assert_err!(
+            StakedRelayers::close_block_error_proposal(Origin::signed(DAVE), 1),
+            TestError::VotingPeriodNotExpired
+        );
+
+        Security::set_active_block_number(11);
+        assert_ok!(StakedRelayers::close_block_error_proposal(Origin::signed(DAVE), 1));
+        assert_eq!(StakedRelayers::block_error_proposal(1), None);
+        assert_eq!(CollateralCurrency::get_reserved_balance(&ALICE), 90);
+
+        // the block can be proposed again
+        assert_ok!(StakedRelayers::propose_block_error(
+            Origin::signed(BOB),
+            block_hash,
+            ErrorCode::NoDataBTCRelay
+        ));
+        assert!(StakedRelayers::block_error_proposal(2).is_some());
+    })
+}
+
+fn sample_source_chain(length: u8) -> Vec<RawBlockHeader> {
+    (0..length)
+        .map(|i| RawBlockHeader::from_bytes(&[i; 80]).unwrap())
+        .collect()
+}
+
+fn run_offchain_test<T>(test: T) -> Vec<TestExtrinsic>
+where
+    T: FnOnce(),
+{
+    run_offchain_test_at(&[1], test)
+}
+
+/// Runs the off-chain worker at each of the given parachain block numbers
+fn run_offchain_test_at<T>(block_numbers: &[u64], test: T) -> Vec<TestExtrinsic>
+where
+    T: FnOnce(),
+{
+    let mut externalities = ExtBuilder::build();
+    let (offchain, _) = TestOffchainExt::new();
+    let (pool, pool_state) = TestTransactionPoolExt::new();
+    externalities.register_extension(OffchainExt::new(offchain));
+    externalities.register_extension(TransactionPoolExt::new(pool));
+
+    clear_mocks();
+    SOURCE_CHAIN.with(|chain| chain.borrow_mut().clear());
+    UintAuthorityId::set_all_keys(vec![ALICE]);
+    externalities.execute_with(|| {
+        test();
+        for block_number in block_numbers {
+            System::set_block_number(*block_number);
+            StakedRelayers::_offchain_worker(*block_number);
+        }
+    });
+
+    let transactions = pool_state.read().transactions.clone();
+    transactions
+        .iter()
+        .map(|tx| TestExtrinsic::decode(&mut &**tx).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_offchain_worker_submits_next_block_header() {
+    let source_chain = sample_source_chain(3);
+    let relayed: Vec<H256Le> = source_chain[..1].iter().map(|header| header.hash()).collect();
+
+    let transactions = run_offchain_test(|| {
+        SOURCE_CHAIN.with(|chain| *chain.borrow_mut() = source_chain.clone());
+        ext::btc_relay::get_best_block_height::<Test>.mock_safe(|| MockResult::Return(0));
+        ext::btc_relay::block_header_exists::<Test>.mock_safe(move |hash| MockResult::Return(relayed.contains(&hash)));
+    });
+
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].signature, Some((0, ())));
+    assert_eq!(
+        transactions[0].call,
+        Call::StakedRelayers(crate::Call::store_block_header(source_chain[1]))
+    );
+}
+
+#[test]
+fn test_offchain_worker_resubmits_pending_block_header_after_interval() {
+    let source_chain = sample_source_chain(3);
+    let relayed: Vec<H256Le> = source_chain[..1].iter().map(|header| header.hash()).collect();
+    let resubmit_at = 1 + crate::HEADER_RESUBMIT_INTERVAL as u64;
+
+    let transactions = run_offchain_test_at(&[1, 2, resubmit_at - 1, resubmit_at], || {
+        SOURCE_CHAIN.with(|chain| *chain.borrow_mut() = source_chain.clone());
+        ext::btc_relay::get_best_block_height::<Test>.mock_safe(|| MockResult::Return(0));
+        ext::btc_relay::block_header_exists::<Test>.mock_safe(move |hash| MockResult::Return(relayed.contains(&hash)));
+    });
+
+    // the header is not submitted again while the first submission may still be pending
+    assert_eq!(transactions.len(), 2);
+    for transaction in transactions {
+        assert_eq!(
+            transaction.call,
+            Call::StakedRelayers(crate::Call::store_block_header(source_chain[1]))
+        );
+    }
+}
+
+#[test]
+fn test_offchain_worker_follows_source_reorg() {
+    let source_chain = sample_source_chain(4);
+    // the relay stored a different block at height 2
+    let relayed: Vec<H256Le> = source_chain[..2].iter().map(|header| header.hash()).collect();
+
+    let transactions = run_offchain_test(|| {
+        SOURCE_CHAIN.with(|chain| *chain.borrow_mut() = source_chain.clone());
+        ext::btc_relay::get_best_block_height::<Test>.mock_safe(|| MockResult::Return(2));
+        ext::btc_relay::block_header_exists::<Test>.mock_safe(move |hash| MockResult::Return(relayed.contains(&hash)));
+    });
+
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(
+        transactions[0].call,
+        Call::StakedRelayers(crate::Call::store_block_header(source_chain[2]))
+    );
+}
+
+#[test]
+fn test_offchain_worker_does_nothing_when_in_sync() {
+    let source_chain = sample_source_chain(3);
+    let relayed: Vec<H256Le> = source_chain.iter().map(|header| header.hash()).collect();
+
+    let transactions = run_offchain_test(|| {
+        SOURCE_CHAIN.with(|chain| *chain.borrow_mut() = source_chain.clone());
+        ext::btc_relay::get_best_block_height::<Test>.mock_safe(|| MockResult::Return(2));
+        ext::btc_relay::block_header_exists::<Test>.mock_safe(move |hash| MockResult::Return(relayed.contains(&hash)));
+    });
+
+    assert!(transactions.is_empty());
+}
+
+#[test]
+fn test_offchain_worker_does_nothing_without_header_source() {
+    let transactions = run_offchain_test(|| {
+        ext::btc_relay::get_best_block_height::<Test>.mock_safe(|| MockResult::Return(0));
+    });
+
+    assert!(transactions.is_empty());
+}
+
+#[test]
+fn test_esplora_header_source_fetches_block_header() {
+    let mut externalities = ExtBuilder::build();
+    let (offchain, state) = TestOffchainExt::new();
+    externalities.register_extension(OffchainExt::new(offchain));
+
+    let raw_block_header = RawBlockHeader::from_hex(sample_block_header_hex()).unwrap();
+    let block_hash = H256Le::from_hex_be("000000000000000000103d2b2321e7c536fea628da61321a12ecca73d0560a6c");
+    assert_eq!(raw_block_header.hash(), block_hash);
+
+    {
+        let mut state = state.write();
+        state.expect_request(PendingRequest {
+            method: "GET".into(),
+            uri: "http://localhost:3002/blocks/tip/height".into(),
+            response: Some(b"633041".to_vec()),
+            sent: true,
+            ..Default::default()
+        });
+        state.expect_request(PendingRequest {
+            method: "GET".into(),
+            uri: "http://localhost:3002/block-height/633041".into(),
+            response: Some(block_hash.to_hex_be().into_bytes()),
+            sent: true,
+            ..Default::default()
+        });
+        state.expect_request(PendingRequest {
+            method: "GET".into(),
+            uri: format!("http://localhost:3002/block/{}/header", block_hash.to_hex_be()),
+            response: Some(format!("{}\n", sample_block_header_hex()).into_bytes()),
+            sent: true,
+            ..Default::default()
+        });
+    }
+
+    externalities.execute_with(|| {
+        assert_eq!(
+            EsploraHeaderSource::get_best_block_height(),
+            Err(HeaderSourceError::Disabled)
+        );
+
+        sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, ESPLORA_URL_KEY, b"http://localhost:3002");
+        assert_eq!(EsploraHeaderSource::get_best_block_height(), Ok(633041));
+        assert_eq!(EsploraHeaderSource::get_block_hash(633041), Ok(block_hash));
+        assert_eq!(
+            EsploraHeaderSource::get_raw_block_header(block_hash),
+            Ok(raw_block_header)
+        );
+    });
+}
+
+fn sample_block_header_hex() -> &'static str {
+    // bitcoin block 633041 (000000000000000000103d2b2321e7c536fea628da61321a12ecca73d0560a6c)
+    "000000205d72ff4bfdee01d66174f69de4e8910f97f2e7468650010000000000000000003378a1c3db549897983c906df8f88c06ee99bc82aef76341f6f961c0cbb0dda93913d95e357f1417c7b25968"
+}
@@ -1,6 +1,10 @@
+use bitcoin::types::H256Le;
 use codec::{Decode, Encode};
 use frame_support::traits::Currency;
-use sp_std::fmt::Debug;
+use security::ErrorCode;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_std::{collections::btree_set::BTreeSet, fmt::Debug, vec::Vec};
 
 pub(crate) type Collateral<T> = <<T as currency::Config<currency::Collateral>>::Currency as Currency<
     <T as frame_system::Config>::AccountId,
@@ -9,6 +13,17 @@ pub(crate) type Collateral<T> = <<T as currency::Config<currency::Collateral>>::
 pub(crate) type Wrapped<T> =
     <<T as currency::Config<currency::Wrapped>>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
+pub(crate) type UnsignedFixedPoint<T> = <T as fee::Config>::UnsignedFixedPoint;
+
+/// Storage version.
+#[derive(Encode, Decode, Eq, PartialEq)]
+pub enum Version {
+    /// Initial version.
+    V0,
+    /// Only bonded relayers earn relayer rewards.
+    V1,
+}
+
 /// Bonded participant which can suggest and vote on proposals.
 #[derive(Encode, Decode, Default, Clone, PartialEq, Debug)]
 pub struct StakedRelayer<Balance, BlockNumber> {
@@ -17,3 +32,113 @@ pub struct StakedRelayer<Balance, BlockNumber> {
     // the height at which the participant bonded
     pub height: BlockNumber,
 }
+
+/// Proposal by a staked relayer to flag an error on a Bitcoin block, decided by a stake-weighted vote.
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+pub struct BlockErrorProposal<AccountId: Ord, BlockNumber> {
+    // the relayer which made the proposal
+    pub proposer: AccountId,
+    // hash of the flagged block
+    pub block_hash: H256Le,
+    // error to flag, either `NoDataBTCRelay` or `InvalidBTCRelay`
+    pub error: ErrorCode,
+    // the height at which the proposal was made
+    pub start: BlockNumber,
+    // relayers which voted for the proposal
+    pub ayes: BTreeSet<AccountId>,
+    // relayers which voted against the proposal
+    pub nays: BTreeSet<AccountId>,
+}
+
+/// Theft report whose vault is frozen until the challenge period ends or governance decides.
+#[derive(Encode, Decode, Default, Clone, PartialEq, Debug)]
+pub struct PendingTheftReport<AccountId, BlockNumber, Balance> {
+    // the account which reported the theft
+    pub reporter: AccountId,
+    // the reported transactions, which may be reported again if the report is rejected
+    pub tx_ids: Vec<H256Le>,
+    // collateral locked by the reporter, forfeited to the vault if the report is rejected
+    pub bond: Balance,
+    // collateral locked by the vault when disputing, forfeited to the reporter if the theft
+    // is confirmed
+    pub dispute_bond: Balance,
+    // the height at which the vault is liquidated unless the report is disputed, or at which
+    // the disputed report is confirmed unless governance decided it
+    pub end: BlockNumber,
+    // whether the vault disputed the report, which leaves the decision to governance
+    pub disputed: bool,
+}
+
+/// Transactions by which a vault maintains its wallet without moving funds out of it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MaintenanceTransaction {
+    /// Replacement (RBF) or child (CPFP) of a request payment which references a request of the vault
+    FeeBump,
+    /// Moves funds between the registered addresses of the vault, e.g. to consolidate outputs, or
+    /// to the target of a wallet migration the vault declared with `declare_wallet_migration`
+    /// before its expiry. Funds can only leave the registered addresses with such a declared
+    /// intent. Since no value leaves the wallet, an OP_RETURN output is accepted as a label and
+    /// carries no meaning.
+    Merge,
+}
+
+/// Outcome of a dry-run theft report, with the reason if the report would fail.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum TheftReportVerdict {
+    /// The report would succeed
+    Theft,
+    /// Theft reports are paused or the parachain is shut down
+    ReportingDisabled,
+    /// The transaction cannot be parsed
+    InvalidTransaction,
+    /// The transaction is already reported for the vault
+    AlreadyReported,
+    /// The vault has a theft report which is not decided yet
+    ReportPending,
+    /// The inclusion proof is invalid or the transaction is not confirmed yet
+    InvalidProof,
+    /// The vault does not exist or is liquidated
+    VaultNotActive,
+    /// The transaction does not spend from the vault's wallet
+    NoVaultInput,
+    /// The transaction validly pays a redeem request
+    ValidRedeem,
+    /// The transaction validly pays a replace request
+    ValidReplace,
+    /// The transaction validly pays a refund request
+    ValidRefund,
+    /// The transaction moves funds between the vault's addresses
+    ValidMerge,
+    /// The transaction bumps the fee of a request payment
+    ValidFeeBump,
+    /// The transaction validly pays a declared batch of requests
+    ValidBatch,
+    /// Both transactions of a double payment report are the same
+    DuplicateTransaction,
+    /// The transaction does not reference a request id
+    NoRequestId,
+    /// The transactions reference different request ids
+    DifferentRequestIds,
+    /// No redeem, replace or refund request of the vault with the referenced id
+    RequestNotFound,
+    /// The transaction does not pay the recipient of the request
+    NoPaymentToRecipient,
+    /// Any other error
+    Other,
+}
+
+impl TheftReportVerdict {
+    /// Whether the reported transaction is a valid vault transaction, i.e. the report is false.
+    pub fn is_valid_transaction(&self) -> bool {
+        matches!(
+            self,
+            TheftReportVerdict::ValidRedeem
+                | TheftReportVerdict::ValidReplace
+                | TheftReportVerdict::ValidRefund
+                | TheftReportVerdict::ValidMerge
+                | TheftReportVerdict::ValidFeeBump
+                | TheftReportVerdict::ValidBatch
+        )
+    }
+}
@@ -8,11 +8,12 @@ use module_exchange_rate_oracle_rpc_runtime_api::BalanceWrapper;
 use sp_std::vec::Vec;
 
 sp_api::decl_runtime_apis! {
-    pub trait VaultRegistryApi<AccountId, Wrapped, Collateral, UnsignedFixedPoint> where
+    pub trait VaultRegistryApi<AccountId, Wrapped, Collateral, UnsignedFixedPoint, WalletMigration> where
         AccountId: Codec,
         Wrapped: Codec,
         Collateral: Codec,
-        UnsignedFixedPoint: Codec
+        UnsignedFixedPoint: Codec,
+        WalletMigration: Codec
     {
         /// Get the total collateralization of the system
         fn get_total_collateralization() -> Result<UnsignedFixedPoint, DispatchError>;
@@ -49,5 +50,8 @@ sp_api::decl_runtime_apis! {
         /// Get the amount of collateral required for the given vault to be at the
         /// current SecureCollateralThreshold with the current exchange rate
         fn get_required_collateral_for_vault(vault_id: AccountId) -> Result<BalanceWrapper<Collateral>, DispatchError>;
+
+        /// Get the wallet migrations declared by the vault that have not expired
+        fn get_pending_wallet_migrations(vault_id: AccountId) -> Vec<WalletMigration>;
     }
 }
@@ -16,7 +16,7 @@ use sp_runtime::{
 use std::sync::Arc;
 
 #[rpc]
-pub trait VaultRegistryApi<BlockHash, AccountId, Wrapped, Collateral, UnsignedFixedPoint>
+pub trait VaultRegistryApi<BlockHash, AccountId, Wrapped, Collateral, UnsignedFixedPoint, WalletMigration>
 where
     Wrapped: Codec + MaybeDisplay + MaybeFromStr,
     Collateral: Codec + MaybeDisplay + MaybeFromStr,
@@ -94,6 +94,13 @@ where
         vault_id: AccountId,
         at: Option<BlockHash>,
     ) -> JsonRpcResult<BalanceWrapper<Collateral>>;
+
+    #[rpc(name = "vaultRegistry_getPendingWalletMigrations")]
+    fn get_pending_wallet_migrations(
+        &self,
+        vault_id: AccountId,
+        at: Option<BlockHash>,
+    ) -> JsonRpcResult<Vec<WalletMigration>>;
 }
 
 /// A struct that implements the [`VaultRegistryApi`].
@@ -146,17 +153,18 @@ fn handle_response<T, E: std::fmt::Debug>(
     )
 }
 
-impl<C, Block, AccountId, Wrapped, Collateral, UnsignedFixedPoint>
-    VaultRegistryApi<<Block as BlockT>::Hash, AccountId, Wrapped, Collateral, UnsignedFixedPoint>
+impl<C, Block, AccountId, Wrapped, Collateral, UnsignedFixedPoint, WalletMigration>
+    VaultRegistryApi<<Block as BlockT>::Hash, AccountId, Wrapped, Collateral, UnsignedFixedPoint, WalletMigration>
     for VaultRegistry<C, Block>
 where
     Block: BlockT,
     C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
-    C::Api: VaultRegistryRuntimeApi<Block, AccountId, Wrapped, Collateral, UnsignedFixedPoint>,
+    C::Api: VaultRegistryRuntimeApi<Block, AccountId, Wrapped, Collateral, UnsignedFixedPoint, WalletMigration>,
     AccountId: Codec,
     Wrapped: Codec + MaybeDisplay + MaybeFromStr,
     Collateral: Codec + MaybeDisplay + MaybeFromStr,
     UnsignedFixedPoint: Codec + MaybeDisplay + MaybeFromStr,
+    WalletMigration: Codec,
 {
     fn get_total_collateralization(&self, at: Option<<Block as BlockT>::Hash>) -> JsonRpcResult<UnsignedFixedPoint> {
         let api = self.client.runtime_api();
@@ -318,4 +326,19 @@ where
             },
         )
     }
+
+    fn get_pending_wallet_migrations(
+        &self,
+        vault_id: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> JsonRpcResult<Vec<WalletMigration>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+        api.get_pending_wallet_migrations(&at, vault_id).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(Error::RuntimeError.into()),
+            message: "Unable to get pending wallet migrations.".into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
 }
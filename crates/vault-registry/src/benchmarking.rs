@@ -4,6 +4,7 @@ use exchange_rate_oracle::Pallet as ExchangeRateOracle;
 use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite};
 use frame_support::traits::Currency;
 use frame_system::RawOrigin;
+use sp_core::H160;
 use sp_std::prelude::*;
 
 fn dummy_public_key() -> BtcPublicKey {
@@ -59,6 +60,14 @@ benchmarks! {
         VaultRegistry::<T>::_register_vault(&origin, 1234u32.into(), dummy_public_key()).unwrap();
     }: _(RawOrigin::Signed(origin), BtcAddress::default())
 
+    declare_wallet_migration {
+        let origin: T::AccountId = account("Origin", 0, 0);
+        make_free_balance_be::<T>(&origin, (1u32 << 31).into());
+        VaultRegistry::<T>::_register_vault(&origin, 1234u32.into(), dummy_public_key()).unwrap();
+        VaultRegistry::<T>::insert_vault_deposit_address(&origin, BtcAddress::default()).unwrap();
+        MaxWalletMigrationPeriod::<T>::put::<T::BlockNumber>(100u32.into());
+    }: _(RawOrigin::Signed(origin), BtcAddress::default(), BtcAddress::P2SH(H160::zero()), 100u32.into())
+
     accept_new_issues {
         let origin: T::AccountId = account("Origin", 0, 0);
         make_free_balance_be::<T>(&origin, (1u32 << 31).into());
@@ -11,6 +11,7 @@ pub trait WeightInfo {
     fn withdraw_collateral() -> Weight;
     fn update_public_key() -> Weight;
     fn register_address() -> Weight;
+    fn declare_wallet_migration() -> Weight;
     fn accept_new_issues() -> Weight;
     fn report_undercollateralized_vault() -> Weight;
 }
@@ -41,6 +42,12 @@ impl crate::WeightInfo for () {
             .saturating_add(DbWeight::get().reads(2 as Weight))
             .saturating_add(DbWeight::get().writes(2 as Weight))
     }
+    // ESTIMATE: not measured by the benchmark CLI, regenerate this file to replace it
+    fn declare_wallet_migration() -> Weight {
+        (52_000_000 as Weight)
+            .saturating_add(DbWeight::get().reads(5 as Weight))
+            .saturating_add(DbWeight::get().writes(2 as Weight))
+    }
     fn accept_new_issues() -> Weight {
         (48_000_000 as Weight)
             .saturating_add(DbWeight::get().reads(1 as Weight))
@@ -79,11 +79,33 @@ pub(crate) mod oracle {
 #[cfg_attr(test, mockable)]
 pub(crate) mod security {
     use frame_support::dispatch::DispatchResult;
+    use security::ErrorCode;
+    use sp_std::collections::btree_set::BTreeSet;
 
     pub fn ensure_parachain_status_not_shutdown<T: security::Config>() -> DispatchResult {
         <security::Pallet<T>>::ensure_parachain_status_not_shutdown()
     }
 
+    pub fn ensure_parachain_does_not_have_errors<T: security::Config>(error_codes: &[ErrorCode]) -> DispatchResult {
+        <security::Pallet<T>>::ensure_parachain_does_not_have_errors(error_codes)
+    }
+
+    pub fn get_errors<T: security::Config>() -> BTreeSet<ErrorCode> {
+        <security::Pallet<T>>::get_errors()
+    }
+
+    pub fn insert_error<T: security::Config>(error_code: ErrorCode) {
+        <security::Pallet<T>>::insert_error(error_code)
+    }
+
+    pub fn recover_from_system_undercollateralized<T: security::Config>() {
+        <security::Pallet<T>>::recover_from_system_undercollateralized()
+    }
+
+    pub fn ensure_call_enabled<T: security::Config>(module: &[u8], call: &[u8]) -> DispatchResult {
+        <security::Pallet<T>>::ensure_call_enabled(module, call)
+    }
+
     pub fn active_block_number<T: security::Config>() -> T::BlockNumber {
         <security::Pallet<T>>::active_block_number()
     }
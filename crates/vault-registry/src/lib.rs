@@ -36,7 +36,7 @@ use crate::types::{
 #[doc(inline)]
 pub use crate::{
     slash::{Slashable, TryDepositCollateral, TryWithdrawCollateral},
-    types::{BtcPublicKey, CurrencySource, DefaultVault, SystemVault, Vault, VaultStatus, Wallet},
+    types::{BtcPublicKey, CurrencySource, DefaultVault, SystemVault, Vault, VaultStatus, Wallet, WalletMigration},
 };
 use codec::{Decode, Encode, EncodeLike};
 use frame_support::{
@@ -49,6 +49,7 @@ use frame_system::{
     ensure_signed,
     offchain::{SendTransactionTypes, SubmitTransaction},
 };
+use security::ErrorCode;
 use sp_arithmetic::{traits::*, FixedPointNumber};
 use sp_core::{H256, U256};
 #[cfg(feature = "std")]
@@ -62,6 +63,9 @@ use sp_std::{
 // value taken from https://github.com/substrate-developer-hub/recipes/blob/master/pallets/ocw-demo/src/lib.rs
 pub const UNSIGNED_TXS_PRIORITY: u64 = 100;
 
+/// Calls which root may pause
+pub const PAUSABLE_CALLS: &[&[u8]] = &[b"register_vault", b"deposit_collateral", b"declare_wallet_migration"];
+
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
 #[cfg_attr(feature = "std", derive(Debug))]
 pub struct RegisterRequest<AccountId, DateTime> {
@@ -122,6 +126,23 @@ pub mod pallet {
 
     #[pallet::hooks]
     impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(n: T::BlockNumber) -> Weight {
+            Self::begin_block(n);
+            // the issued tokens, the collateral, the exchange rate, the threshold and the errors
+            // are read, the error and the status history written
+            T::DbWeight::get().reads_writes(11, 4)
+        }
+
+        fn on_runtime_upgrade() -> Weight {
+            if Self::storage_version() == Version::V3 {
+                return T::DbWeight::get().reads(1);
+            }
+            // allow migrations for as long as vaults have to pay a request
+            MaxWalletMigrationPeriod::<T>::put(PunishmentDelay::<T>::get());
+            StorageVersion::<T>::put(Version::V3);
+            T::DbWeight::get().reads_writes(2, 2)
+        }
+
         fn offchain_worker(n: T::BlockNumber) {
             log::info!("Off-chain worker started on block {:?}", n);
             Self::_offchain_worker();
@@ -180,6 +201,7 @@ pub mod pallet {
             public_key: BtcPublicKey,
         ) -> DispatchResultWithPostInfo {
             ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+            ext::security::ensure_call_enabled::<T>(b"VaultRegistry", b"register_vault")?;
             Self::_register_vault(&ensure_signed(origin)?, collateral, public_key)?;
             Ok(().into())
         }
@@ -198,6 +220,7 @@ pub mod pallet {
             let sender = ensure_signed(origin)?;
 
             ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+            ext::security::ensure_call_enabled::<T>(b"VaultRegistry", b"deposit_collateral")?;
 
             Self::try_deposit_collateral(&sender, amount)?;
 
@@ -234,6 +257,7 @@ pub mod pallet {
         ) -> DispatchResultWithPostInfo {
             let sender = ensure_signed(origin)?;
             ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+            ext::security::ensure_parachain_does_not_have_errors::<T>(&[ErrorCode::SystemUndercollateralized])?;
 
             Self::try_withdraw_collateral(&sender, amount)?;
 
@@ -268,6 +292,39 @@ pub mod pallet {
             Ok(().into())
         }
 
+        /// Declares that the vault intends to move the funds held by one of its registered
+        /// addresses to another address. Until the expiry, transactions spending from `from`
+        /// may pay `to` without being reported as theft. The target is reserved to the vault,
+        /// so that spending the migrated funds is checked like spending from its wallet, but
+        /// the vault must register it to keep paying it after the expiry. A new declaration
+        /// for the same source address replaces the previous one.
+        ///
+        /// # Arguments
+        /// * `from` - the registered address the funds are moved from
+        /// * `to` - the address the funds are moved to
+        /// * `expiry` - the active block number until which the migration is accepted
+        ///
+        /// # Errors
+        /// * `VaultNotFound` - if no active vault exists for the origin account
+        /// * `UnknownMigrationSource` - if `from` is not registered to the vault
+        /// * `ReservedDepositAddress` - if `to` is registered to another vault
+        /// * `InvalidMigrationExpiry` - if the expiry has passed or exceeds the `MaxWalletMigrationPeriod`
+        #[pallet::weight(<T as Config>::WeightInfo::declare_wallet_migration())]
+        #[transactional]
+        pub fn declare_wallet_migration(
+            origin: OriginFor<T>,
+            from: BtcAddress,
+            to: BtcAddress,
+            expiry: T::BlockNumber,
+        ) -> DispatchResultWithPostInfo {
+            let account_id = ensure_signed(origin)?;
+            ext::security::ensure_parachain_status_not_shutdown::<T>()?;
+            ext::security::ensure_call_enabled::<T>(b"VaultRegistry", b"declare_wallet_migration")?;
+            Self::_declare_wallet_migration(&account_id, from, to, expiry)?;
+            Self::deposit_event(Event::<T>::DeclareWalletMigration(account_id, from, to, expiry));
+            Ok(().into())
+        }
+
         /// Configures whether or not the vault accepts new issues.
         ///
         /// # Arguments
@@ -318,6 +375,8 @@ pub mod pallet {
         UpdatePublicKey(T::AccountId, BtcPublicKey),
         /// vault_id, new address
         RegisterAddress(T::AccountId, BtcAddress),
+        /// vault_id, from address, to address, expiry
+        DeclareWalletMigration(T::AccountId, BtcAddress, BtcAddress, T::BlockNumber),
         /// vault_id, additional to-be-issued tokens
         IncreaseToBeIssuedTokens(T::AccountId, Wrapped<T>),
         /// vault_id, decrease in to-be-issued tokens
@@ -356,6 +415,10 @@ pub mod pallet {
         ),
         /// vault_id, banned_until
         BanVault(T::AccountId, T::BlockNumber),
+        /// vault_id
+        FreezeVault(T::AccountId),
+        /// vault_id
+        UnfreezeVault(T::AccountId),
     }
 
     #[pallet::error]
@@ -368,6 +431,8 @@ pub mod pallet {
         InsufficientTokensCommitted,
         /// Action not allowed on banned vault.
         VaultBanned,
+        /// Action not allowed on a vault with a pending theft report.
+        VaultFrozen,
         /// The provided collateral was insufficient - it must be above ``MinimumCollateralVault``.
         InsufficientVaultCollateralAmount,
         /// Returned if a vault tries to register while already being registered
@@ -380,6 +445,10 @@ pub mod pallet {
         VaultNotBelowLiquidationThreshold,
         /// Deposit address could not be generated with the given public key.
         InvalidPublicKey,
+        /// The address funds are migrated from is not registered to the vault.
+        UnknownMigrationSource,
+        /// The migration expiry has passed or exceeds the ``MaxWalletMigrationPeriod``.
+        InvalidMigrationExpiry,
 
         // Errors used exclusively in RPC functions
         /// Collateralization is infinite if no tokens are issued
@@ -463,6 +532,27 @@ pub mod pallet {
     pub(super) type ReservedAddresses<T: Config> =
         StorageMap<_, Blake2_128Concat, BtcAddress, T::AccountId, ValueQuery>;
 
+    /// Number of blocks a Vault may declare a wallet migration for.
+    #[pallet::storage]
+    #[pallet::getter(fn max_wallet_migration_period)]
+    pub(super) type MaxWalletMigrationPeriod<T: Config> = StorageValue<_, T::BlockNumber, ValueQuery>;
+
+    /// Vaults with a pending theft report, which can neither issue tokens nor withdraw collateral.
+    #[pallet::storage]
+    pub(super) type FrozenVaults<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, (), OptionQuery>;
+
+    /// Wallet migrations declared by Vaults, keyed by the Vault and the address funds are moved from.
+    #[pallet::storage]
+    pub(super) type WalletMigrations<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        Blake2_128Concat,
+        BtcAddress,
+        WalletMigration<T::BlockNumber>,
+        OptionQuery,
+    >;
+
     /// Total collateral used for collateral tokens issued by active vaults, excluding the liquidation vault
     #[pallet::storage]
     pub(super) type TotalUserVaultCollateralCollateral<T: Config> = StorageValue<_, Collateral<T>, ValueQuery>;
@@ -472,7 +562,7 @@ pub mod pallet {
         Version::V0
     }
 
-    /// Build storage at V1 (requires default 0).
+    /// Build storage at V3 (requires default 0).
     #[pallet::storage]
     #[pallet::getter(fn storage_version)]
     pub(super) type StorageVersion<T: Config> = StorageValue<_, Version, ValueQuery, DefaultForStorageVersion>;
@@ -484,6 +574,7 @@ pub mod pallet {
         pub secure_collateral_threshold: UnsignedFixedPoint<T>,
         pub premium_redeem_threshold: UnsignedFixedPoint<T>,
         pub liquidation_collateral_threshold: UnsignedFixedPoint<T>,
+        pub max_wallet_migration_period: T::BlockNumber,
     }
 
     #[cfg(feature = "std")]
@@ -495,6 +586,7 @@ pub mod pallet {
                 secure_collateral_threshold: Default::default(),
                 premium_redeem_threshold: Default::default(),
                 liquidation_collateral_threshold: Default::default(),
+                max_wallet_migration_period: Default::default(),
             }
         }
     }
@@ -507,7 +599,8 @@ pub mod pallet {
             SecureCollateralThreshold::<T>::put(self.secure_collateral_threshold);
             PremiumRedeemThreshold::<T>::put(self.premium_redeem_threshold);
             LiquidationCollateralThreshold::<T>::put(self.liquidation_collateral_threshold);
-            StorageVersion::<T>::put(Version::V1);
+            MaxWalletMigrationPeriod::<T>::put(self.max_wallet_migration_period);
+            StorageVersion::<T>::put(Version::V3);
             LiquidationVaultAccountId::<T>::put::<T::AccountId>(T::PalletId::get().into_account());
         }
     }
@@ -515,6 +608,21 @@ pub mod pallet {
 
 #[cfg_attr(test, mockable)]
 impl<T: Config> Pallet<T> {
+    fn begin_block(_height: T::BlockNumber) {
+        // the collateralization is unknown while the oracle is offline
+        let is_undercollateralized = match Self::is_system_undercollateralized() {
+            Ok(is_undercollateralized) => is_undercollateralized,
+            Err(_) => return,
+        };
+        let has_error = ext::security::get_errors::<T>().contains(&ErrorCode::SystemUndercollateralized);
+        if is_undercollateralized && !has_error {
+            // only the operations which lower the collateralization are restricted
+            ext::security::insert_error::<T>(ErrorCode::SystemUndercollateralized);
+        } else if !is_undercollateralized && has_error {
+            ext::security::recover_from_system_undercollateralized::<T>();
+        }
+    }
+
     fn _offchain_worker() {
         for vault in Self::undercollateralized_vaults() {
             log::info!("Reporting vault {:?}", vault);
@@ -631,6 +739,8 @@ impl<T: Config> Pallet<T> {
         amount: Collateral<T>,
     ) -> Result<bool, DispatchError> {
         let vault = Self::get_vault_from_id(vault_id)?;
+        // the collateral may still be confiscated for theft
+        Self::ensure_not_frozen(vault_id)?;
 
         let new_collateral = match vault.backing_collateral.checked_sub(&amount) {
             Some(x) => x,
@@ -708,6 +818,7 @@ impl<T: Config> Pallet<T> {
     /// * `tokens` - the amount of tokens to be reserved
     pub fn try_increase_to_be_issued_tokens(vault_id: &T::AccountId, tokens: Wrapped<T>) -> Result<(), DispatchError> {
         let mut vault = Self::get_active_rich_vault_from_id(&vault_id)?;
+        Self::ensure_not_frozen(vault_id)?;
 
         let issuable_tokens = vault.issuable_tokens()?;
         ensure!(issuable_tokens >= tokens, Error::<T>::ExceedingVaultLimit);
@@ -1235,6 +1346,30 @@ impl<T: Config> Pallet<T> {
         vault.ensure_not_banned()
     }
 
+    /// Freezes an active vault until `unfreeze_vault` is called, it can then neither issue
+    /// tokens nor withdraw collateral.
+    pub fn freeze_vault(vault_id: &T::AccountId) -> DispatchResult {
+        Self::get_active_vault_from_id(vault_id)?;
+        FrozenVaults::<T>::insert(vault_id, ());
+        Self::deposit_event(Event::<T>::FreezeVault(vault_id.clone()));
+        Ok(())
+    }
+
+    pub fn unfreeze_vault(vault_id: &T::AccountId) {
+        if FrozenVaults::<T>::take(vault_id).is_some() {
+            Self::deposit_event(Event::<T>::UnfreezeVault(vault_id.clone()));
+        }
+    }
+
+    pub fn is_vault_frozen(vault_id: &T::AccountId) -> bool {
+        FrozenVaults::<T>::contains_key(vault_id)
+    }
+
+    pub fn ensure_not_frozen(vault_id: &T::AccountId) -> DispatchResult {
+        ensure!(!Self::is_vault_frozen(vault_id), Error::<T>::VaultFrozen);
+        Ok(())
+    }
+
     /// Threshold checks
     pub fn is_vault_below_secure_threshold(vault_id: &T::AccountId) -> Result<bool, DispatchError> {
         Self::is_vault_below_threshold(&vault_id, SecureCollateralThreshold::<T>::get())
@@ -1320,6 +1455,14 @@ impl<T: Config> Pallet<T> {
         Self::get_collateralization(raw_collateral_in_wrapped, raw_issued_tokens)
     }
 
+    /// Checks if the total collateralization of the system is below the liquidation threshold.
+    pub fn is_system_undercollateralized() -> Result<bool, DispatchError> {
+        if Self::get_total_issued_tokens(true)?.is_zero() {
+            return Ok(false);
+        }
+        Ok(Self::get_total_collateralization()? < Self::liquidation_collateral_threshold())
+    }
+
     /// Get the first available vault with sufficient collateral to fulfil an issue request
     /// with the specified amount of issued tokens.
     pub fn get_first_vault_with_sufficient_collateral(amount: Wrapped<T>) -> Result<T::AccountId, DispatchError> {
@@ -1667,8 +1810,9 @@ impl<T: Config> Pallet<T> {
     }
 
     pub fn insert_vault_deposit_address(vault_id: &T::AccountId, btc_address: BtcAddress) -> DispatchResult {
+        // the target of a declared migration is reserved to the vault before it is registered
         ensure!(
-            !ReservedAddresses::<T>::contains_key(&btc_address),
+            !ReservedAddresses::<T>::contains_key(&btc_address) || Self::is_reserved_by_vault(vault_id, &btc_address),
             Error::<T>::ReservedDepositAddress
         );
         let mut vault = Self::get_active_rich_vault_from_id(vault_id)?;
@@ -1677,6 +1821,55 @@ impl<T: Config> Pallet<T> {
         Ok(())
     }
 
+    pub fn _declare_wallet_migration(
+        vault_id: &T::AccountId,
+        from: BtcAddress,
+        to: BtcAddress,
+        expiry: T::BlockNumber,
+    ) -> DispatchResult {
+        let vault = Self::get_active_vault_from_id(vault_id)?;
+        ensure!(vault.wallet.has_btc_address(&from), Error::<T>::UnknownMigrationSource);
+        // moving funds to another vault must go through replace
+        ensure!(
+            !ReservedAddresses::<T>::contains_key(&to) || ReservedAddresses::<T>::get(&to) == *vault_id,
+            Error::<T>::ReservedDepositAddress
+        );
+
+        let height = ext::security::active_block_number::<T>();
+        ensure!(
+            expiry > height && expiry <= height.saturating_add(Self::max_wallet_migration_period()),
+            Error::<T>::InvalidMigrationExpiry
+        );
+
+        // drop expired declarations, they are no longer considered by theft detection
+        let expired: Vec<BtcAddress> = WalletMigrations::<T>::iter_prefix(vault_id)
+            .filter(|(_, migration)| migration.expiry < height)
+            .map(|(address, _)| address)
+            .collect();
+        for address in expired {
+            WalletMigrations::<T>::remove(vault_id, address);
+        }
+
+        // funds moved to the target remain attributed to the vault once the migration expired
+        ReservedAddresses::<T>::insert(to, vault_id);
+
+        WalletMigrations::<T>::insert(vault_id, from, WalletMigration { from, to, expiry });
+        Ok(())
+    }
+
+    /// Checks if the address is registered to the vault or is the target of one of its migrations.
+    pub fn is_reserved_by_vault(vault_id: &T::AccountId, btc_address: &BtcAddress) -> bool {
+        ReservedAddresses::<T>::contains_key(btc_address) && ReservedAddresses::<T>::get(btc_address) == *vault_id
+    }
+
+    /// Get the wallet migrations declared by the vault that have not expired.
+    pub fn get_pending_wallet_migrations(vault_id: &T::AccountId) -> Vec<WalletMigration<T::BlockNumber>> {
+        let height = ext::security::active_block_number::<T>();
+        WalletMigrations::<T>::iter_prefix_values(vault_id)
+            .filter(|migration| migration.expiry >= height)
+            .collect()
+    }
+
     pub fn new_vault_deposit_address(vault_id: &T::AccountId, secure_id: H256) -> Result<BtcAddress, DispatchError> {
         let mut vault = Self::get_active_rich_vault_from_id(vault_id)?;
         let btc_address = vault.new_deposit_address(secure_id)?;
@@ -1684,6 +1877,16 @@ impl<T: Config> Pallet<T> {
     }
 }
 
+impl<T: Config> security::PausableCalls for Pallet<T> {
+    fn pausable_calls(module: &[u8]) -> Option<&'static [&'static [u8]]> {
+        if module == b"VaultRegistry" {
+            Some(PAUSABLE_CALLS)
+        } else {
+            None
+        }
+    }
+}
+
 trait CheckedMulIntRoundedUp {
     /// Like checked_mul_int, but this version rounds the result up instead of down.
     fn checked_mul_int_rounded_up<N: TryFrom<u128> + TryInto<u128>>(self, n: N) -> Option<N>;
@@ -207,6 +207,8 @@ where
 
 impl security::Config for Test {
     type Event = TestEvent;
+    type OnRecovery = ();
+    type PausableCalls = ();
 }
 
 impl sla::Config for Test {
@@ -254,6 +256,7 @@ impl ExtBuilder {
             secure_collateral_threshold: FixedU128::one(),
             premium_redeem_threshold: FixedU128::one(),
             liquidation_collateral_threshold: FixedU128::one(),
+            max_wallet_migration_period: 100,
         }
         .assimilate_storage(&mut storage)
         .unwrap();
@@ -6,12 +6,13 @@ use crate::{
         OTHER_ID, RICH_COLLATERAL, RICH_ID,
     },
     types::{BtcAddress, Collateral, Wrapped},
-    BtcPublicKey, CurrencySource, DispatchError, Error, UpdatableVault, Vault, VaultStatus, Wallet, H256,
+    BtcPublicKey, CurrencySource, DispatchError, Error, UpdatableVault, Vault, VaultStatus, Wallet, WalletMigration,
+    H256,
 };
 use codec::Decode;
 use frame_support::{assert_err, assert_noop, assert_ok, traits::OnInitialize};
 use mocktopus::mocking::*;
-use security::Pallet as Security;
+use security::{ErrorCode, Pallet as Security, StatusCode};
 use sp_arithmetic::{traits::One, FixedPointNumber, FixedU128};
 use sp_core::U256;
 use sp_runtime::{
@@ -171,6 +172,18 @@ fn withdraw_collateral_succeeds() {
     });
 }
 
+#[test]
+fn withdraw_collateral_fails_when_system_undercollateralized() {
+    run_test(|| {
+        let id = create_sample_vault();
+        Security::<Test>::insert_error(ErrorCode::SystemUndercollateralized);
+        assert_noop!(
+            VaultRegistry::withdraw_collateral(Origin::signed(id), 50),
+            SecurityError::ParachainUndercollateralizedError
+        );
+    })
+}
+
 #[test]
 fn withdraw_collateral_fails_when_vault_does_not_exist() {
     run_test(|| {
@@ -179,6 +192,23 @@ fn withdraw_collateral_fails_when_vault_does_not_exist() {
     })
 }
 
+#[test]
+fn withdraw_collateral_fails_when_vault_is_frozen() {
+    run_test(|| {
+        let id = create_sample_vault();
+        assert_ok!(VaultRegistry::freeze_vault(&id));
+        assert_emitted!(Event::FreezeVault(id));
+        assert_noop!(
+            VaultRegistry::withdraw_collateral(Origin::signed(id), 50),
+            TestError::VaultFrozen
+        );
+
+        VaultRegistry::unfreeze_vault(&id);
+        assert_emitted!(Event::UnfreezeVault(id));
+        assert_ok!(VaultRegistry::withdraw_collateral(Origin::signed(id), 50));
+    })
+}
+
 #[test]
 fn withdraw_collateral_fails_when_not_enough_collateral() {
     run_test(|| {
@@ -188,6 +218,19 @@ fn withdraw_collateral_fails_when_not_enough_collateral() {
     })
 }
 
+#[test]
+fn try_increase_to_be_issued_tokens_fails_when_vault_is_frozen() {
+    run_test(|| {
+        let id = create_sample_vault();
+        assert_ok!(VaultRegistry::freeze_vault(&id));
+        assert_eq!(VaultRegistry::get_issuable_tokens_from_vault(id), Ok(0));
+        assert_noop!(
+            VaultRegistry::try_increase_to_be_issued_tokens(&id, 50),
+            TestError::VaultFrozen
+        );
+    });
+}
+
 #[test]
 fn try_increase_to_be_issued_tokens_succeeds() {
     run_test(|| {
@@ -1252,6 +1295,35 @@ fn get_total_collateralization_with_tokens_issued() {
     })
 }
 
+#[test]
+fn begin_block_reports_and_recovers_from_system_undercollateralization() {
+    run_test(|| {
+        let issue_tokens: u128 = DEFAULT_COLLATERAL / 10 / 2; // = 5
+        let _id = create_sample_vault_and_issue_tokens(issue_tokens);
+
+        // collateralization is 200%
+        VaultRegistry::set_liquidation_collateral_threshold(FixedU128::checked_from_rational(250, 100).unwrap());
+        VaultRegistry::begin_block(0);
+        assert_eq!(Security::<Test>::get_parachain_status(), StatusCode::Running);
+        assert!(Security::<Test>::get_errors().contains(&ErrorCode::SystemUndercollateralized));
+        // operations which do not lower the collateralization, e.g. nomination, are unaffected
+        assert_ok!(Security::<Test>::ensure_parachain_status_running());
+
+        VaultRegistry::set_liquidation_collateral_threshold(FixedU128::checked_from_rational(150, 100).unwrap());
+        VaultRegistry::begin_block(0);
+        assert_eq!(Security::<Test>::get_parachain_status(), StatusCode::Running);
+        assert!(Security::<Test>::get_errors().is_empty());
+    })
+}
+
+#[test]
+fn system_not_undercollateralized_without_issued_tokens() {
+    run_test(|| {
+        create_sample_vault();
+        assert_eq!(VaultRegistry::is_system_undercollateralized(), Ok(false));
+    })
+}
+
 // #[test]
 // fn wallet_add_btc_address_succeeds() {
 //     run_test(|| {
@@ -1290,6 +1362,150 @@ fn wallet_has_btc_address_succeeds() {
     });
 }
 
+#[test]
+fn declare_wallet_migration_succeeds() {
+    run_test(|| {
+        let id = create_sample_vault();
+        let from = BtcAddress::random();
+        let to = BtcAddress::random();
+        assert_ok!(VaultRegistry::register_address(Origin::signed(id), from));
+
+        Security::<Test>::set_active_block_number(10);
+        assert_ok!(VaultRegistry::declare_wallet_migration(
+            Origin::signed(id),
+            from,
+            to,
+            20
+        ));
+        assert_emitted!(Event::DeclareWalletMigration(id, from, to, 20));
+        assert_eq!(
+            VaultRegistry::get_pending_wallet_migrations(&id),
+            vec![WalletMigration { from, to, expiry: 20 }]
+        );
+        // the target is reserved to the vault, but not registered
+        assert!(!VaultRegistry::get_active_vault_from_id(&id)
+            .unwrap()
+            .wallet
+            .has_btc_address(&to));
+        assert!(VaultRegistry::is_reserved_by_vault(&id, &to));
+        let other_id = create_vault(OTHER_ID);
+        assert_noop!(
+            VaultRegistry::register_address(Origin::signed(other_id), to),
+            TestError::ReservedDepositAddress
+        );
+
+        // the migration is accepted up to and including the expiry
+        Security::<Test>::set_active_block_number(20);
+        assert_eq!(VaultRegistry::get_pending_wallet_migrations(&id).len(), 1);
+        Security::<Test>::set_active_block_number(21);
+        assert_eq!(VaultRegistry::get_pending_wallet_migrations(&id), vec![]);
+
+        // the vault can register the target to keep using it
+        assert_ok!(VaultRegistry::register_address(Origin::signed(id), to));
+    });
+}
+
+#[test]
+fn declare_wallet_migration_replaces_previous_declaration() {
+    run_test(|| {
+        let id = create_sample_vault();
+        let from = BtcAddress::random();
+        let to = BtcAddress::random();
+        assert_ok!(VaultRegistry::register_address(Origin::signed(id), from));
+
+        assert_ok!(VaultRegistry::declare_wallet_migration(
+            Origin::signed(id),
+            from,
+            BtcAddress::random(),
+            20
+        ));
+        assert_ok!(VaultRegistry::declare_wallet_migration(
+            Origin::signed(id),
+            from,
+            to,
+            30
+        ));
+        assert_eq!(
+            VaultRegistry::get_pending_wallet_migrations(&id),
+            vec![WalletMigration { from, to, expiry: 30 }]
+        );
+    });
+}
+
+#[test]
+fn declare_wallet_migration_fails_with_unregistered_source() {
+    run_test(|| {
+        let id = create_sample_vault();
+        assert_noop!(
+            VaultRegistry::declare_wallet_migration(Origin::signed(id), BtcAddress::random(), BtcAddress::random(), 20),
+            TestError::UnknownMigrationSource
+        );
+    });
+}
+
+#[test]
+fn declare_wallet_migration_fails_with_address_of_other_vault() {
+    run_test(|| {
+        let id = create_sample_vault();
+        let other_id = create_vault(OTHER_ID);
+        let from = BtcAddress::random();
+        let to = BtcAddress::random();
+        assert_ok!(VaultRegistry::register_address(Origin::signed(id), from));
+        assert_ok!(VaultRegistry::register_address(Origin::signed(other_id), to));
+
+        assert_noop!(
+            VaultRegistry::declare_wallet_migration(Origin::signed(id), from, to, 20),
+            TestError::ReservedDepositAddress
+        );
+    });
+}
+
+#[test]
+fn declare_wallet_migration_fails_with_invalid_expiry() {
+    run_test(|| {
+        let id = create_sample_vault();
+        let from = BtcAddress::random();
+        assert_ok!(VaultRegistry::register_address(Origin::signed(id), from));
+
+        Security::<Test>::set_active_block_number(10);
+        assert_noop!(
+            VaultRegistry::declare_wallet_migration(Origin::signed(id), from, BtcAddress::random(), 10),
+            TestError::InvalidMigrationExpiry
+        );
+        // the mock limits the migration period to 100 blocks
+        assert_noop!(
+            VaultRegistry::declare_wallet_migration(Origin::signed(id), from, BtcAddress::random(), 111),
+            TestError::InvalidMigrationExpiry
+        );
+        assert_ok!(VaultRegistry::declare_wallet_migration(
+            Origin::signed(id),
+            from,
+            BtcAddress::random(),
+            110
+        ));
+    });
+}
+
+#[test]
+fn on_runtime_upgrade_sets_max_wallet_migration_period() {
+    use frame_support::traits::OnRuntimeUpgrade;
+
+    run_test(|| {
+        crate::StorageVersion::<Test>::put(crate::types::Version::V2);
+        crate::MaxWalletMigrationPeriod::<Test>::kill();
+        crate::PunishmentDelay::<Test>::put(20);
+
+        VaultRegistry::on_runtime_upgrade();
+        assert_eq!(VaultRegistry::max_wallet_migration_period(), 20);
+        assert!(VaultRegistry::storage_version() == crate::types::Version::V3);
+
+        // the period is not overwritten
+        crate::MaxWalletMigrationPeriod::<Test>::put(30);
+        VaultRegistry::on_runtime_upgrade();
+        assert_eq!(VaultRegistry::max_wallet_migration_period(), 30);
+    });
+}
+
 // #[test]
 // fn update_btc_address_fails_with_btc_address_taken() {
 //     run_test(|| {
@@ -24,6 +24,8 @@ pub enum Version {
     V1,
     /// added replace_collateral to vault, changed vaultStatus enum
     V2,
+    /// MaxWalletMigrationPeriod
+    V3,
 }
 
 #[derive(Debug, PartialEq)]
@@ -120,6 +122,19 @@ impl Wallet {
     }
 }
 
+/// Declared intent of a vault to move the funds held by one of its addresses to another
+/// address, e.g. when rotating keys.
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug, serde::Serialize, serde::Deserialize))]
+pub struct WalletMigration<BlockNumber> {
+    /// Registered address of the vault the funds are moved from
+    pub from: BtcAddress,
+    /// Address the funds are moved to, reserved to the vault but only registered by the vault
+    pub to: BtcAddress,
+    /// Active block number until which transactions performing the migration are accepted
+    pub expiry: BlockNumber,
+}
+
 #[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
 pub enum VaultStatus {
     /// Vault is active - bool=true indicates that the vault accepts new issue requests
@@ -392,8 +407,8 @@ impl<T: Config> RichVault<T> {
     }
 
     pub fn issuable_tokens(&self) -> Result<Wrapped<T>, DispatchError> {
-        // unable to issue additional tokens when banned
-        if self.is_banned() {
+        // unable to issue additional tokens when banned or frozen
+        if self.is_banned() || Pallet::<T>::is_vault_frozen(&self.data.id) {
             return Ok(0u32.into());
         }
 
@@ -6,7 +6,8 @@
 #![warn(missing_docs)]
 
 use btc_parachain_runtime::{
-    opaque::Block, AccountId, Balance, BlockNumber, Index, IssueRequest, RedeemRequest, RefundRequest, ReplaceRequest,
+    bitcoin::types::H256Le, opaque::Block, AccountId, Balance, BlockNumber, DeploymentState, Index, IssueRequest,
+    RedeemRequest, RefundRequest, ReplaceRequest, RichBlockHeader, StatusChange, TheftReportVerdict, WalletMigration,
 };
 pub use sc_rpc_api::DenyUnsafe;
 use sp_api::ProvideRuntimeApi;
@@ -37,9 +38,24 @@ where
     C: Send + Sync + 'static,
     C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
     C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+    C::Api: module_btc_relay_rpc::BtcRelayRuntimeApi<
+        Block,
+        H256Le,
+        H256,
+        RichBlockHeader<AccountId, BlockNumber>,
+        BlockNumber,
+        DeploymentState,
+    >,
     C::Api: module_exchange_rate_oracle_rpc::ExchangeRateOracleRuntimeApi<Block, Balance, Balance>,
-    C::Api: module_staked_relayers_rpc::StakedRelayersRuntimeApi<Block, AccountId>,
-    C::Api: module_vault_registry_rpc::VaultRegistryRuntimeApi<Block, AccountId, Balance, Balance, FixedU128>,
+    C::Api: module_staked_relayers_rpc::StakedRelayersRuntimeApi<Block, AccountId, TheftReportVerdict>,
+    C::Api: module_vault_registry_rpc::VaultRegistryRuntimeApi<
+        Block,
+        AccountId,
+        Balance,
+        Balance,
+        FixedU128,
+        WalletMigration<BlockNumber>,
+    >,
     C::Api: module_issue_rpc::IssueRuntimeApi<
         Block,
         AccountId,
@@ -59,14 +75,17 @@ where
         H256,
         ReplaceRequest<AccountId, BlockNumber, Balance, Balance>,
     >,
+    C::Api: module_security_rpc::SecurityRuntimeApi<Block, StatusChange<AccountId, BlockNumber>>,
     C::Api: BlockBuilder<Block>,
     P: TransactionPool + 'static,
 {
+    use module_btc_relay_rpc::{BtcRelay, BtcRelayApi};
     use module_exchange_rate_oracle_rpc::{ExchangeRateOracle, ExchangeRateOracleApi};
     use module_issue_rpc::{Issue, IssueApi};
     use module_redeem_rpc::{Redeem, RedeemApi};
     use module_refund_rpc::{Refund, RefundApi};
     use module_replace_rpc::{Replace, ReplaceApi};
+    use module_security_rpc::{Security, SecurityApi};
     use module_staked_relayers_rpc::{StakedRelayers, StakedRelayersApi};
     use module_vault_registry_rpc::{VaultRegistry, VaultRegistryApi};
     use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
@@ -89,6 +108,8 @@ where
         client.clone(),
     )));
 
+    io.extend_with(BtcRelayApi::to_delegate(BtcRelay::new(client.clone())));
+
     io.extend_with(ExchangeRateOracleApi::to_delegate(ExchangeRateOracle::new(
         client.clone(),
     )));
@@ -103,7 +124,9 @@ where
 
     io.extend_with(RefundApi::to_delegate(Refund::new(client.clone())));
 
-    io.extend_with(ReplaceApi::to_delegate(Replace::new(client)));
+    io.extend_with(ReplaceApi::to_delegate(Replace::new(client.clone())));
+
+    io.extend_with(SecurityApi::to_delegate(Security::new(client)));
 
     io
 }
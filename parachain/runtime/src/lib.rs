@@ -47,7 +47,8 @@ pub use sp_runtime::BuildStorage;
 pub use sp_runtime::{Perbill, Permill};
 
 // PolkaBTC exports
-pub use btc_relay::{bitcoin, Call as RelayCall, TARGET_SPACING};
+use btc_relay::bitcoin::types::H256Le;
+pub use btc_relay::{bitcoin, Call as RelayCall, DeploymentState, RichBlockHeader, TARGET_SPACING};
 pub use module_exchange_rate_oracle_rpc_runtime_api::BalanceWrapper;
 
 // XCM imports
@@ -647,6 +648,7 @@ impl pallet_balances::Config<pallet_balances::Instance2> for Runtime {
 impl btc_relay::Config for Runtime {
     type Event = Event;
     type WeightInfo = ();
+    type OnStaleBlock = StakedRelayers;
 }
 
 parameter_types! {
@@ -697,21 +699,29 @@ impl reward::Config<reward::WrappedRelayer> for Runtime {
     type SignedFixedPoint = FixedI128;
 }
 
+pub use security::StatusChange;
+
 impl security::Config for Runtime {
     type Event = Event;
+    type OnRecovery = BTCRelay;
+    type PausableCalls = (Issue, Replace, Nomination, VaultRegistry, StakedRelayers);
 }
 
-pub use staked_relayers::RawEvent as StakedRelayersEvent;
+pub use staked_relayers::{types::TheftReportVerdict, RawEvent as StakedRelayersEvent};
 
 impl staked_relayers::Config for Runtime {
     type Event = Event;
     type WeightInfo = ();
+    type AuthorityId = staked_relayers::crypto::RelayerAuthId;
+    type HeaderSource = staked_relayers::header_source::EsploraHeaderSource;
 }
 
 parameter_types! {
     pub const VaultPalletId: PalletId = PalletId(*b"mod/vreg");
 }
 
+pub use vault_registry::WalletMigration;
+
 impl vault_registry::Config for Runtime {
     type PalletId = VaultPalletId;
     type Event = Event;
@@ -729,6 +739,46 @@ where
     type Extrinsic = UncheckedExtrinsic;
 }
 
+impl frame_system::offchain::SigningTypes for Runtime {
+    type Public = <Signature as Verify>::Signer;
+    type Signature = Signature;
+}
+
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Runtime
+where
+    Call: From<LocalCall>,
+{
+    fn create_transaction<C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+        call: Call,
+        public: <Signature as Verify>::Signer,
+        account: AccountId,
+        nonce: Index,
+    ) -> Option<(
+        Call,
+        <UncheckedExtrinsic as sp_runtime::traits::Extrinsic>::SignaturePayload,
+    )> {
+        // the transaction is valid for at most half the block hash count
+        let period = BlockHashCount::get()
+            .checked_next_power_of_two()
+            .map(|c| c / 2)
+            .unwrap_or(2) as u64;
+        let current_block = (System::block_number() as u64).saturating_sub(1);
+        let extra: SignedExtra = (
+            frame_system::CheckSpecVersion::<Runtime>::new(),
+            frame_system::CheckTxVersion::<Runtime>::new(),
+            frame_system::CheckGenesis::<Runtime>::new(),
+            frame_system::CheckEra::<Runtime>::from(generic::Era::mortal(period, current_block)),
+            frame_system::CheckNonce::<Runtime>::from(nonce),
+            frame_system::CheckWeight::<Runtime>::new(),
+            pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+        );
+        let raw_payload = SignedPayload::new(call, extra).ok()?;
+        let signature = codec::Encode::using_encoded(&raw_payload, |payload| C::sign(payload, public))?;
+        let (call, extra, _) = raw_payload.deconstruct();
+        Some((call, (account, signature, extra)))
+    }
+}
+
 impl exchange_rate_oracle::Config for Runtime {
     type Event = Event;
     type UnsignedFixedPoint = FixedU128;
@@ -834,7 +884,7 @@ macro_rules! construct_polkabtc_runtime {
 
                 // Operational
                 Security: security::{Pallet, Call, Storage, Event<T>},
-                StakedRelayers: staked_relayers::{Pallet, Call, Storage, Event<T>},
+                StakedRelayers: staked_relayers::{Pallet, Call, Config<T>, Storage, Event<T>},
                 VaultRegistry: vault_registry::{Pallet, Call, Config<T>, Storage, Event<T>, ValidateUnsigned},
                 ExchangeRateOracle: exchange_rate_oracle::{Pallet, Call, Config<T>, Storage, Event<T>},
                 Issue: issue::{Pallet, Call, Config<T>, Storage, Event<T>},
@@ -897,6 +947,8 @@ pub type SignedExtra = (
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
 /// Extrinsic type that has already been checked.
 pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, Call, SignedExtra>;
+/// The payload being signed in transactions.
+pub type SignedPayload = generic::SignedPayload<Call, SignedExtra>;
 /// Executive: handles dispatch to the various modules.
 pub type Executive =
     frame_executive::Executive<Runtime, Block, frame_system::ChainContext<Runtime>, Runtime, AllPallets>;
@@ -1078,6 +1130,55 @@ impl_runtime_apis! {
         }
     }
 
+    impl module_btc_relay_rpc_runtime_api::BtcRelayApi<
+        Block,
+        H256Le,
+        H256,
+        RichBlockHeader<AccountId, BlockNumber>,
+        BlockNumber,
+        DeploymentState,
+    > for Runtime {
+        fn get_best_block() -> H256Le {
+            BTCRelay::get_best_block()
+        }
+
+        fn get_best_block_height() -> u32 {
+            BTCRelay::get_best_block_height()
+        }
+
+        fn get_block_header(block_hash: H256Le) -> Result<RichBlockHeader<AccountId, BlockNumber>, DispatchError> {
+            BTCRelay::get_block_header_from_hash(block_hash)
+        }
+
+        fn get_block_header_at_height(block_height: u32) -> Result<RichBlockHeader<AccountId, BlockNumber>, DispatchError> {
+            BTCRelay::get_main_chain_block_header(block_height)
+        }
+
+        fn get_fork_tips() -> Vec<(H256Le, u32)> {
+            BTCRelay::get_fork_tips()
+        }
+
+        fn get_bitcoin_confirmations() -> u32 {
+            BTCRelay::bitcoin_confirmations()
+        }
+
+        fn get_parachain_confirmations() -> BlockNumber {
+            BTCRelay::parachain_confirmations()
+        }
+
+        fn verify_transaction_inclusion(tx_id: H256Le, raw_merkle_proof: Vec<u8>, confirmations: Option<u32>) -> DispatchResult {
+            BTCRelay::_verify_transaction_inclusion(tx_id, raw_merkle_proof, confirmations)
+        }
+
+        fn get_consumed_output(tx_id: H256Le, vout: u32) -> Option<H256> {
+            BTCRelay::consumed_output((tx_id, vout))
+        }
+
+        fn get_deployment_state(deployment_id: u32) -> Result<DeploymentState, DispatchError> {
+            BTCRelay::get_deployment_state(deployment_id)
+        }
+    }
+
     impl module_exchange_rate_oracle_rpc_runtime_api::ExchangeRateOracleApi<
         Block,
         Balance,
@@ -1097,10 +1198,23 @@ impl_runtime_apis! {
     impl module_staked_relayers_rpc_runtime_api::StakedRelayersApi<
         Block,
         AccountId,
+        TheftReportVerdict,
     > for Runtime {
         fn is_transaction_invalid(vault_id: AccountId, raw_tx: Vec<u8>) -> DispatchResult {
             StakedRelayers::is_transaction_invalid(&vault_id, raw_tx)
         }
+
+        fn check_theft_report(vault_id: AccountId, merkle_proof: Vec<u8>, raw_tx: Vec<u8>) -> TheftReportVerdict {
+            StakedRelayers::check_theft_report(&vault_id, merkle_proof, raw_tx)
+        }
+
+        fn check_double_payment_report(
+            vault_id: AccountId,
+            merkle_proofs: (Vec<u8>, Vec<u8>),
+            raw_txs: (Vec<u8>, Vec<u8>),
+        ) -> TheftReportVerdict {
+            StakedRelayers::check_double_payment_report(&vault_id, merkle_proofs, raw_txs)
+        }
     }
 
     impl module_vault_registry_rpc_runtime_api::VaultRegistryApi<
@@ -1108,7 +1222,8 @@ impl_runtime_apis! {
         AccountId,
         Balance,
         Balance,
-        FixedU128
+        FixedU128,
+        WalletMigration<BlockNumber>
     > for Runtime {
         fn get_total_collateralization() -> Result<FixedU128, DispatchError> {
             VaultRegistry::get_total_collateralization()
@@ -1159,6 +1274,10 @@ impl_runtime_apis! {
             let result = VaultRegistry::get_required_collateral_for_vault(vault_id)?;
             Ok(BalanceWrapper{amount:result})
         }
+
+        fn get_pending_wallet_migrations(vault_id: AccountId) -> Vec<WalletMigration<BlockNumber>> {
+            VaultRegistry::get_pending_wallet_migrations(&vault_id)
+        }
     }
 
     impl module_issue_rpc_runtime_api::IssueApi<
@@ -1224,6 +1343,15 @@ impl_runtime_apis! {
             Replace::get_replace_requests_for_new_vault(account_id)
         }
     }
+
+    impl module_security_rpc_runtime_api::SecurityApi<
+        Block,
+        StatusChange<AccountId, BlockNumber>
+    > for Runtime {
+        fn get_status_history() -> Vec<StatusChange<AccountId, BlockNumber>> {
+            Security::get_status_history()
+        }
+    }
 }
 
 #[cfg(feature = "cumulus-polkadot")]
@@ -116,6 +116,8 @@ pub type SlaCall = sla::Call<Runtime>;
 
 pub type StakedRelayersCall = staked_relayers::Call<Runtime>;
 pub type StakedRelayersPallet = staked_relayers::Pallet<Runtime>;
+pub type StakedRelayersError = staked_relayers::Error<Runtime>;
+pub type StakedRelayersEvent = staked_relayers::Event<Runtime>;
 
 pub type SystemModule = frame_system::Pallet<Runtime>;
 
@@ -692,7 +694,7 @@ impl TransactionGenerator {
         self
     }
     pub fn mine(&self) -> (H256Le, u32, Vec<u8>, Vec<u8>, Transaction) {
-        let mut height = 1;
+        let mut height = 0;
         let extra_confirmations = self.confirmations - 1;
 
         // initialize BTC Relay with one block
@@ -706,7 +708,10 @@ impl TransactionGenerator {
         let raw_init_block_header = RawBlockHeader::from_bytes(&init_block.header.try_format().unwrap())
             .expect("could not serialize block header");
 
-        match BTCRelayPallet::initialize(account_of(ALICE), raw_init_block_header, height) {
+        assert_ok!(
+            Call::BTCRelay(BTCRelayCall::set_checkpoint(height, init_block.header.hash().unwrap())).dispatch(root())
+        );
+        match BTCRelayPallet::initialize(account_of(ALICE), vec![raw_init_block_header], height) {
             Ok(_) => {}
             Err(e) if e == BTCRelayError::AlreadyInitialized.into() => {}
             _ => panic!("Failed to initialize btc relay"),
@@ -837,7 +842,8 @@ impl ExtBuilder {
 
         exchange_rate_oracle::GenesisConfig::<Runtime> {
             authorized_oracles: vec![(account_of(BOB), BOB.to_vec())],
-            max_delay: 3600000, // one hour
+            max_delay: 3600000,             // one hour
+            max_btc_tx_fees_delay: 3600000, // one hour
         }
         .assimilate_storage(&mut storage)
         .unwrap();
@@ -848,6 +854,12 @@ impl ExtBuilder {
             disable_difficulty_check: false,
             disable_inclusion_check: false,
             disable_op_return_check: false,
+            pruning_window: 0,
+            max_forks: 0,
+            stale_fork_depth: 0,
+            checkpoints: vec![],
+            max_reorg_depth: 0,
+            deployments: vec![],
         }
         .assimilate_storage(&mut storage)
         .unwrap();
@@ -858,6 +870,7 @@ impl ExtBuilder {
             secure_collateral_threshold: FixedU128::checked_from_rational(150, 100).unwrap(),
             premium_redeem_threshold: FixedU128::checked_from_rational(135, 100).unwrap(),
             liquidation_collateral_threshold: FixedU128::checked_from_rational(110, 100).unwrap(),
+            max_wallet_migration_period: 100,
         }
         .assimilate_storage(&mut storage)
         .unwrap();
@@ -909,6 +922,28 @@ impl ExtBuilder {
             relayer_target_sla: FixedI128::from(100),
             relayer_store_block: FixedI128::from(1),
             relayer_theft_report: FixedI128::from(1),
+            relayer_correct_block_error_proposal: FixedI128::from(1),
+            relayer_stale_block: FixedI128::from(-2),
+            relayer_outdated_block: FixedI128::from(-1),
+            relayer_failed_theft_report: FixedI128::from(-10),
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+
+        staked_relayers::GenesisConfig::<Runtime> {
+            header_deposit: 0,
+            minimum_stake: 100,
+            unbonding_period: 10,
+            vote_threshold: FixedU128::checked_from_rational(2, 3).unwrap(),
+            voting_period: 10,
+            minimum_voters: 2,
+            rejected_proposal_slash: 10,
+            theft_report_bounty: FixedU128::checked_from_rational(10, 100).unwrap(),
+            max_theft_report_bounty: 1000,
+            theft_challenge_period: 0,
+            theft_report_bond: 0,
+            dispute_bond: 0,
+            dispute_resolution_period: 10,
         }
         .assimilate_storage(&mut storage)
         .unwrap();
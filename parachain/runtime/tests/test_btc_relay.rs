@@ -15,12 +15,22 @@ fn integration_test_submit_block_headers_and_verify_transaction_inclusion() {
         SecurityPallet::set_active_block_number(1);
 
         // store all block headers. parachain_genesis is the first block
-        // known in the parachain. Any block before will be rejected
-        let parachain_genesis_height = test_data[0].height;
+        // known in the parachain. Any block before will be rejected. The
+        // relay must be anchored at the start of a difficulty adjustment
+        // period. The fetch script downloads test data from the start of a
+        // period, which is relayed at its real heights; older test data is
+        // shifted to the start of its period
+        let height_offset = test_data[0].height % 2016;
+        let parachain_genesis_height = test_data[0].height - height_offset;
         let parachain_genesis_header = test_data[0].get_raw_header();
 
+        assert_ok!(Call::BTCRelay(BTCRelayCall::set_checkpoint(
+            parachain_genesis_height,
+            test_data[0].get_block_hash()
+        ))
+        .dispatch(root()));
         assert_ok!(Call::StakedRelayers(StakedRelayersCall::initialize(
-            parachain_genesis_header,
+            vec![parachain_genesis_header],
             parachain_genesis_height
         ))
         .dispatch(origin_of(account_of(ALICE))));
@@ -30,7 +40,11 @@ fn integration_test_submit_block_headers_and_verify_transaction_inclusion() {
                     .dispatch(origin_of(account_of(ALICE)))
             );
 
-            assert_store_main_chain_header_event(block.height, block.get_block_hash(), account_of(ALICE));
+            assert_store_main_chain_header_event(
+                block.height - height_offset,
+                block.get_block_hash(),
+                account_of(ALICE),
+            );
         }
         SecurityPallet::set_active_block_number(1 + CONFIRMATIONS);
         // verify all transaction
@@ -39,7 +53,7 @@ fn integration_test_submit_block_headers_and_verify_transaction_inclusion() {
             for tx in &block.test_txs {
                 let txid = tx.get_txid();
                 let raw_merkle_proof = tx.get_raw_merkle_proof();
-                if block.height <= current_height - CONFIRMATIONS + 1 {
+                if block.height - height_offset <= current_height - CONFIRMATIONS + 1 {
                     assert_ok!(Call::BTCRelay(BTCRelayCall::verify_transaction_inclusion(
                         txid,
                         raw_merkle_proof,
@@ -31,6 +31,12 @@ fn test_vault_theft(submit_by_relayer: bool) {
             vault_btc_address
         ));
 
+        // only bonded relayers earn relayer rewards
+        assert_ok!(Call::StakedRelayers(StakedRelayersCall::register_staked_relayer(
+            StakedRelayersPallet::minimum_stake()
+        ))
+        .dispatch(origin_of(account_of(ALICE))));
+
         let initial_sla = SlaPallet::relayer_sla(account_of(ALICE));
 
         let (_tx_id, _height, proof, raw_tx, _) = TransactionGenerator::new()
@@ -79,6 +85,178 @@ fn integration_test_report_vault_theft_by_non_relayer() {
     test_vault_theft(false);
 }
 
+/// Issues tokens with the vault and mines a transaction which spends from its wallet,
+/// returning the merkle proof and raw transaction to report.
+fn setup_vault_theft(issued_tokens: u128) -> (Vec<u8>, Vec<u8>) {
+    let vault_btc_address = BtcAddress::P2SH(H160([
+        215, 255, 109, 96, 235, 244, 10, 155, 24, 134, 172, 206, 6, 101, 59, 162, 34, 77, 143, 234,
+    ]));
+
+    SecurityPallet::set_active_block_number(1);
+    assert_ok!(ExchangeRateOraclePallet::_set_exchange_rate(FixedU128::one()));
+    force_issue_tokens(ALICE, VAULT, 1000000, issued_tokens);
+    assert_ok!(VaultRegistryPallet::insert_vault_deposit_address(
+        &account_of(VAULT),
+        vault_btc_address
+    ));
+
+    let (_tx_id, _height, proof, raw_tx, _) = TransactionGenerator::new()
+        .with_address(BtcAddress::P2SH(H160([1; 20])))
+        .with_amount(100)
+        .with_confirmations(7)
+        .mine();
+
+    SecurityPallet::set_active_block_number(1000);
+    (proof, raw_tx)
+}
+
+fn assert_theft_report_bounty_event(reporter: [u8; 32], bounty: u128) {
+    assert!(SystemModule::events().iter().any(|record| matches!(
+        &record.event,
+        Event::staked_relayers(StakedRelayersEvent::PayTheftReportBounty(reporter_id, vault_id, amount))
+            if reporter_id == &account_of(reporter) && vault_id == &account_of(VAULT) && *amount == bounty
+    )));
+}
+
+#[test]
+fn integration_test_report_vault_theft_pays_bounty() {
+    ExtBuilder::build().execute_with(|| {
+        let (proof, raw_tx) = setup_vault_theft(1000);
+        let free_balance = CollateralPallet::get_free_balance(&account_of(CAROL));
+
+        assert_ok!(
+            Call::StakedRelayers(StakedRelayersCall::report_vault_theft(account_of(VAULT), proof, raw_tx))
+                .dispatch(origin_of(account_of(CAROL)))
+        );
+
+        // 1500 collateral is confiscated for 1000 tokens at the 150% secure threshold, 10% of it is paid out
+        let bounty = 150;
+        assert_eq!(
+            CollateralPallet::get_free_balance(&account_of(CAROL)),
+            free_balance + bounty
+        );
+        assert_eq!(CoreVaultData::liquidation_vault().backing_collateral, 1500 - bounty);
+        assert_theft_report_bounty_event(CAROL, bounty);
+    });
+}
+
+#[test]
+fn integration_test_report_vault_theft_bounty_is_capped() {
+    ExtBuilder::build().execute_with(|| {
+        let (proof, raw_tx) = setup_vault_theft(10000);
+        let free_balance = CollateralPallet::get_free_balance(&account_of(CAROL));
+
+        assert_ok!(
+            Call::StakedRelayers(StakedRelayersCall::report_vault_theft(account_of(VAULT), proof, raw_tx))
+                .dispatch(origin_of(account_of(CAROL)))
+        );
+
+        // 10% of the 15000 confiscated collateral exceeds the maximum bounty
+        let bounty = StakedRelayersPallet::max_theft_report_bounty();
+        assert_eq!(bounty, 1000);
+        assert_eq!(
+            CollateralPallet::get_free_balance(&account_of(CAROL)),
+            free_balance + bounty
+        );
+        assert_eq!(CoreVaultData::liquidation_vault().backing_collateral, 15000 - bounty);
+        assert_theft_report_bounty_event(CAROL, bounty);
+    });
+}
+
+#[test]
+fn integration_test_report_vault_theft_fails_with_declared_wallet_migration() {
+    ExtBuilder::build().execute_with(|| {
+        let vault_btc_address = BtcAddress::P2SH(H160([
+            215, 255, 109, 96, 235, 244, 10, 155, 24, 134, 172, 206, 6, 101, 59, 162, 34, 77, 143, 234,
+        ]));
+        let migration_btc_address = BtcAddress::P2SH(H160([1; 20]));
+
+        SecurityPallet::set_active_block_number(1);
+        assert_ok!(ExchangeRateOraclePallet::_set_exchange_rate(FixedU128::one()));
+        force_issue_tokens(ALICE, VAULT, 1000000, 1000);
+        assert_ok!(VaultRegistryPallet::insert_vault_deposit_address(
+            &account_of(VAULT),
+            vault_btc_address
+        ));
+        assert_ok!(Call::VaultRegistry(VaultRegistryCall::declare_wallet_migration(
+            vault_btc_address,
+            migration_btc_address,
+            100
+        ))
+        .dispatch(origin_of(account_of(VAULT))));
+
+        let (_tx_id, _height, proof, raw_tx, _) = TransactionGenerator::new()
+            .with_address(migration_btc_address)
+            .with_amount(100)
+            .with_op_return(None)
+            .with_confirmations(7)
+            .mine();
+
+        SecurityPallet::set_active_block_number(50);
+        assert_noop!(
+            Call::StakedRelayers(StakedRelayersCall::report_vault_theft(
+                account_of(VAULT),
+                proof.clone(),
+                raw_tx.clone()
+            ))
+            .dispatch(origin_of(account_of(CAROL))),
+            StakedRelayersError::ValidMergeTransaction
+        );
+
+        // once the migration expired, moving funds to the unregistered target is theft
+        SecurityPallet::set_active_block_number(101);
+        assert_ok!(
+            Call::StakedRelayers(StakedRelayersCall::report_vault_theft(account_of(VAULT), proof, raw_tx))
+                .dispatch(origin_of(account_of(CAROL)))
+        );
+    });
+}
+
+#[test]
+fn integration_test_unbonded_relayer_earns_no_sla() {
+    ExtBuilder::build().execute_with(|| {
+        SecurityPallet::set_active_block_number(1);
+
+        TransactionGenerator::new()
+            .with_address(BtcAddress::P2SH(H160([1; 20])))
+            .with_amount(100)
+            .with_confirmations(7)
+            .with_relayer(Some(CAROL))
+            .mine();
+
+        assert_eq!(SlaPallet::relayer_sla(account_of(CAROL)), FixedI128::zero());
+    });
+}
+
+#[test]
+fn integration_test_deregistered_relayer_withdraws_stake_after_unbonding() {
+    ExtBuilder::build().execute_with(|| {
+        SecurityPallet::set_active_block_number(1);
+        let stake = StakedRelayersPallet::minimum_stake();
+        let free_balance = CollateralPallet::get_free_balance(&account_of(CAROL));
+
+        assert_ok!(Call::StakedRelayers(StakedRelayersCall::register_staked_relayer(stake))
+            .dispatch(origin_of(account_of(CAROL))));
+        assert_eq!(
+            CollateralPallet::get_free_balance(&account_of(CAROL)),
+            free_balance - stake
+        );
+
+        assert_ok!(Call::StakedRelayers(StakedRelayersCall::deregister_staked_relayer())
+            .dispatch(origin_of(account_of(CAROL))));
+        assert_noop!(
+            Call::StakedRelayers(StakedRelayersCall::withdraw_unbonded_stake()).dispatch(origin_of(account_of(CAROL))),
+            StakedRelayersError::UnbondingPeriodNotExpired
+        );
+
+        SecurityPallet::set_active_block_number(1 + StakedRelayersPallet::unbonding_period());
+        assert_ok!(
+            Call::StakedRelayers(StakedRelayersCall::withdraw_unbonded_stake()).dispatch(origin_of(account_of(CAROL)))
+        );
+        assert_eq!(CollateralPallet::get_free_balance(&account_of(CAROL)), free_balance);
+    });
+}
+
 #[test]
 fn test_staked_relayer_parachain_status_check_fails() {
     ExtBuilder::build().execute_with(|| {
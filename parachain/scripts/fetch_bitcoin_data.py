@@ -7,6 +7,8 @@ DIRNAME = os.path.dirname(__file__)
 TESTDATA_DIR = os.path.join(DIRNAME, "..", "runtime", "tests", "data")
 TESTDATA_FILE = os.path.join(TESTDATA_DIR, "bitcoin-testdata.json")
 BASE_URL = "https://blockstream.info/api"
+# BTC-Relay is anchored at the first block of a difficulty adjustment period
+DIFFICULTY_ADJUSTMENT_INTERVAL = 2016
 
 def query(uri):
     url = BASE_URL + uri
@@ -63,10 +65,10 @@ def get_raw_merkle_proof(txid):
     uri = "/tx/{}/merkleblock-proof".format(txid)
     return query_binary(uri)
 
-def get_testdata(number, tip_height):
+def get_testdata(number, start_height):
     # query number of blocks
     blocks = []
-    for i in range(tip_height - number, tip_height):
+    for i in range(start_height, start_height + number):
         blockhash = get_block_hash(i)
         print("Getting block at height {} with hash {}".format(i, blockhash))
         raw_header = get_raw_header(blockhash)
@@ -108,23 +110,16 @@ def read_testdata():
 
 def main():
     max_num_blocks = 100
-    number_blocks = max_num_blocks
     # get current tip of Bitcoin blockchain
     tip_height = get_tip_height()
+    # start at the latest difficulty adjustment period with max_num_blocks blocks
+    start_height = (tip_height - max_num_blocks) // DIFFICULTY_ADJUSTMENT_INTERVAL * DIFFICULTY_ADJUSTMENT_INTERVAL
     blocks = read_testdata()
-    if blocks:
-        if blocks[-1]['height'] == tip_height:
-            print("Latest blocks already downloaded")
-            return
-        else:
-            ## download new blocks
-            delta = tip_height - blocks[-1]["height"]
-            number_blocks = delta if delta <= max_num_blocks else max_num_blocks
-
-    new_blocks = get_testdata(number_blocks, tip_height)
-    blocks = blocks + new_blocks
-    # only store max_num_blocks
-    blocks = blocks[-max_num_blocks:]
+    if blocks and blocks[0]['height'] == start_height:
+        print("Latest blocks already downloaded")
+        return
+
+    blocks = get_testdata(max_num_blocks, start_height)
     overwrite_testdata(blocks)
 
 if __name__ == "__main__":
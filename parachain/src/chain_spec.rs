@@ -1,12 +1,20 @@
 use btc_parachain_runtime::{
     AccountId, BTCRelayConfig, BlockNumber, CollateralConfig, ExchangeRateOracleConfig, FeeConfig, GenesisConfig,
-    IssueConfig, NominationConfig, RedeemConfig, RefundConfig, ReplaceConfig, Signature, SlaConfig, SudoConfig,
-    SystemConfig, VaultRegistryConfig, WrappedConfig, DAYS, HOURS, MILLISECS_PER_BLOCK, TARGET_SPACING, WASM_BINARY,
+    IssueConfig, NominationConfig, RedeemConfig, RefundConfig, ReplaceConfig, Signature, SlaConfig,
+    StakedRelayersConfig, SudoConfig, SystemConfig, VaultRegistryConfig, WrappedConfig, DAYS, HOURS,
+    MILLISECS_PER_BLOCK, TARGET_SPACING, WASM_BINARY,
 };
 
 use bitcoin::utils::{virtual_transaction_size, InputType, TransactionInputMetadata, TransactionOutputMetadata};
 const BITCOIN_SPACING_MS: u32 = TARGET_SPACING * 1000;
 const BLOCK_SPACING: BlockNumber = BITCOIN_SPACING_MS / MILLISECS_PER_BLOCK as BlockNumber;
+// retain roughly four weeks (two retarget periods) of Bitcoin block headers
+const PRUNING_WINDOW: u32 = 4032;
+// bound the number of forks relayers can keep alive, and remove them roughly a day after falling behind
+const MAX_FORKS: u32 = 32;
+const STALE_FORK_DEPTH: u32 = 144;
+// reorgs orphaning more blocks than are usually required as confirmations freeze the relay
+const MAX_REORG_DEPTH: u32 = 6;
 
 #[cfg(feature = "aura-grandpa")]
 use {btc_parachain_runtime::GrandpaConfig, sp_finality_grandpa::AuthorityId as GrandpaId};
@@ -385,7 +393,8 @@ fn testnet_genesis(
         pallet_balances_Instance2: WrappedConfig { balances: vec![] },
         exchange_rate_oracle: ExchangeRateOracleConfig {
             authorized_oracles,
-            max_delay: 3600000, // one hour
+            max_delay: 3600000,             // one hour
+            max_btc_tx_fees_delay: 3600000, // one hour
         },
         btc_relay: BTCRelayConfig {
             bitcoin_confirmations,
@@ -393,6 +402,13 @@ fn testnet_genesis(
             disable_difficulty_check: true,
             disable_inclusion_check: false,
             disable_op_return_check: false,
+            pruning_window: PRUNING_WINDOW,
+            max_forks: MAX_FORKS,
+            stale_fork_depth: STALE_FORK_DEPTH,
+            // relayers may only initialize from a checkpoint approved by root
+            checkpoints: vec![],
+            max_reorg_depth: MAX_REORG_DEPTH,
+            deployments: vec![],
         },
         issue: IssueConfig { issue_period: DAYS },
         redeem: RedeemConfig {
@@ -421,6 +437,7 @@ fn testnet_genesis(
             secure_collateral_threshold: FixedU128::checked_from_rational(150, 100).unwrap(), // 150%
             premium_redeem_threshold: FixedU128::checked_from_rational(135, 100).unwrap(),    // 135%
             liquidation_collateral_threshold: FixedU128::checked_from_rational(110, 100).unwrap(), // 110%
+            max_wallet_migration_period: DAYS,
         },
         fee: FeeConfig {
             issue_fee: FixedU128::checked_from_rational(5, 1000).unwrap(), // 0.5%
@@ -447,10 +464,29 @@ fn testnet_genesis(
             relayer_target_sla: FixedI128::from(100),
             relayer_store_block: FixedI128::from(1),
             relayer_theft_report: FixedI128::from(1),
+            relayer_correct_block_error_proposal: FixedI128::from(1),
+            relayer_stale_block: FixedI128::from(-2),
+            relayer_outdated_block: FixedI128::from(-1),
+            relayer_failed_theft_report: FixedI128::from(-10),
         },
         refund: RefundConfig {
             refund_btc_dust_value: 1000,
         },
+        staked_relayers: StakedRelayersConfig {
+            header_deposit: 100_000_000,      // 0.01 DOT
+            minimum_stake: 1_000_000_000_000, // 100 DOT
+            unbonding_period: 7 * DAYS,
+            vote_threshold: FixedU128::checked_from_rational(2, 3).unwrap(), // 2/3 of the stake
+            voting_period: DAYS,
+            minimum_voters: 2,
+            rejected_proposal_slash: 10_000_000_000, // 1 DOT
+            theft_report_bounty: FixedU128::checked_from_rational(5, 100).unwrap(), // 5%
+            max_theft_report_bounty: 1_000_000_000_000, // 100 DOT
+            theft_challenge_period: DAYS,
+            theft_report_bond: 100_000_000_000, // 10 DOT
+            dispute_bond: 100_000_000_000, // 10 DOT
+            dispute_resolution_period: 7 * DAYS,
+        },
         nomination: NominationConfig {
             is_nomination_enabled: false,
             get_operator_unbonding_period: 24 * HOURS,